@@ -1,8 +1,7 @@
 use pax_engine::api::*;
 use pax_engine::*;
 use pax_std::primitives::*;
-use pax_std::components::{Stacker};
-use pax_std::types::{StackerDirection};
+use pax_std::types::GridTrack;
 
 
 #[pax]
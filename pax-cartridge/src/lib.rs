@@ -2,7 +2,7 @@ use std::any::Any;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
-use pax_runtime::{ComponentInstance, ExpressionContext, InstantiationArgs};
+use pax_runtime::{ComponentInstance, ExpressionContext, InstanceNode, InstantiationArgs};
 use piet_common::RenderContext;
 use std::rc::Rc;
 
@@ -23,6 +23,10 @@ pub fn instantiate_main_component() -> Rc<ComponentInstance> {
     unreachable!("{}", PLACEHOLDER_ERROR)
 }
 
+pub fn instantiate_test_harness(_viewport_size: (f64, f64)) -> pax_runtime::testing::TestHarness {
+    unreachable!("{}", PLACEHOLDER_ERROR)
+}
+
 pub struct DefinitionToInstanceTraverser {}
 
 impl DefinitionToInstanceTraverser {
@@ -37,4 +41,15 @@ impl DefinitionToInstanceTraverser {
     pub fn get_manifest(&self) -> &pax_manifest::PaxManifest {
         unreachable!("{}", PLACEHOLDER_ERROR)
     }
+
+    pub fn build_component_template(
+        &self,
+        _type_id: &pax_manifest::TypeId,
+    ) -> Vec<Rc<dyn InstanceNode>> {
+        unreachable!("{}", PLACEHOLDER_ERROR)
+    }
+
+    pub fn set_component_template(&mut self, _template: pax_manifest::ComponentTemplate) {
+        unreachable!("{}", PLACEHOLDER_ERROR)
+    }
 }
\ No newline at end of file
@@ -95,6 +95,8 @@ public class TextElement {
     public var textStyle: TextStyle
     public var depth: UInt?
     public var style_link: TextStyle?
+    public var opacity: Double = 1.0
+    public var blur: Double = 0.0
     
     public init(id_chain: [UInt64], clipping_ids: [[UInt64]], content: String, transform: [Float], size_x: Float, size_y: Float, textStyle: TextStyle, depth: UInt?, style_link: TextStyle?) {
         self.id_chain = id_chain
@@ -142,6 +144,11 @@ public class TextElement {
             self.style_link?.applyPatch(from: styleLinkBuffer)
         }
     }
+
+    public func applyPatch(patch: EffectsUpdatePatch) {
+        self.opacity = patch.opacity
+        self.blur = patch.blur
+    }
 }
 
 public enum TextAlignHorizontal {
@@ -195,6 +202,63 @@ public func toAlignment(horizontalAlignment: TextAlignHorizontal, verticalAlignm
 }
 
 
+public class DropdownElement {
+    public var id_chain: [UInt64]
+    public var clipping_ids: [[UInt64]]
+    public var options: [String]
+    public var selected_id: Int
+    public var transform: [Float]
+    public var size_x: Float
+    public var size_y: Float
+    public var textStyle: TextStyle
+    public var opacity: Double = 1.0
+    public var blur: Double = 0.0
+
+    public init(id_chain: [UInt64], clipping_ids: [[UInt64]], options: [String], selected_id: Int, transform: [Float], size_x: Float, size_y: Float, textStyle: TextStyle) {
+        self.id_chain = id_chain
+        self.clipping_ids = clipping_ids
+        self.options = options
+        self.selected_id = selected_id
+        self.transform = transform
+        self.size_x = size_x
+        self.size_y = size_y
+        self.textStyle = textStyle
+    }
+
+    public static func makeDefault(id_chain: [UInt64], clipping_ids: [[UInt64]]) -> DropdownElement {
+        let defaultTextStyle = TextStyle(font: PaxFont.makeDefault(), fill: Color(.black), alignmentMultiline: .leading, alignment: .leading, font_size: 13.0, underline: false)
+        return DropdownElement(id_chain: id_chain, clipping_ids: clipping_ids, options: [], selected_id: 0, transform: [1,0,0,1,0,0], size_x: 0.0, size_y: 0.0, textStyle: defaultTextStyle)
+    }
+
+    public func applyPatch(patch: DropdownUpdatePatch) {
+        //no-op to ID, as it is primary key
+
+        if let options = patch.options {
+            self.options = options
+        }
+        if let selected_id = patch.selected_id {
+            self.selected_id = selected_id
+        }
+        if let transform = patch.transform {
+            self.transform = transform
+        }
+        if let size_x = patch.size_x {
+            self.size_x = size_x
+        }
+        if let size_y = patch.size_y {
+            self.size_y = size_y
+        }
+        if let styleBuffer = patch.style {
+            self.textStyle.applyPatch(from: styleBuffer)
+        }
+    }
+
+    public func applyPatch(patch: EffectsUpdatePatch) {
+        self.opacity = patch.opacity
+        self.blur = patch.blur
+    }
+}
+
 /// A patch representing an image load request from a given id_chain
 public class ImageLoadPatch {
     public var id_chain: [UInt64]
@@ -302,6 +366,50 @@ public class TextUpdatePatch {
     }
 }
 
+public class DropdownUpdatePatch {
+    public var id_chain: [UInt64]
+    public var options: [String]?
+    public var selected_id: Int?
+    public var transform: [Float]?
+    public var size_x: Float?
+    public var size_y: Float?
+    public var style: TextStyleMessage?
+
+    public init(fb: FlxbReference) {
+        self.id_chain = fb["id_chain"]!.asVector!.makeIterator().map({ fb in
+            fb.asUInt64!
+        })
+        self.options = fb["options"]?.asVector?.makeIterator().map({ fb in
+            fb.asString!
+        })
+        self.selected_id = fb["selected_id"]?.asUInt.map { Int($0) }
+        self.transform = fb["transform"]?.asVector?.makeIterator().map({ fb in
+            fb.asFloat!
+        })
+        self.size_x = fb["size_x"]?.asFloat
+        self.size_y = fb["size_y"]?.asFloat
+
+        if let styleBuffer = fb["style"], !styleBuffer.isNull {
+            self.style = TextStyleMessage(styleBuffer)
+        }
+    }
+}
+
+/// The opacity and blur of a native element, composed from those of its ancestors
+public class EffectsUpdatePatch {
+    public var id_chain: [UInt64]
+    public var opacity: Double
+    public var blur: Double
+
+    public init(fb: FlxbReference) {
+        self.id_chain = fb["id_chain"]!.asVector!.makeIterator().map({ fb in
+            fb.asUInt64!
+        })
+        self.opacity = fb["opacity"]!.asDouble!
+        self.blur = fb["blur"]!.asDouble!
+    }
+}
+
 ///// A patch containing optional fields, representing an update action for the NativeElement of the given id_chain
 //public class TextUpdatePatch {
 //    public var id_chain: [UInt64]
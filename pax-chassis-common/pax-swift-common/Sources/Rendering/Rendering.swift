@@ -7,6 +7,7 @@ public struct NativeRenderingLayer: View {
 
     @ObservedObject var textElements : TextElements = TextElements.singleton
     @ObservedObject var frameElements : FrameElements = FrameElements.singleton
+    @ObservedObject var dropdownElements : DropdownElements = DropdownElements.singleton
 
     public func getClippingMask(clippingIds: [[UInt64]]) -> some View {
 
@@ -68,6 +69,8 @@ public struct NativeRenderingLayer: View {
                         .position(x: CGFloat(textElement.size_x / 2.0), y: CGFloat(textElement.size_y / 2.0))
                         .transformEffect(transform)
                         .textSelection(.enabled)
+                        .opacity(textElement.opacity)
+                        .blur(radius: CGFloat(textElement.blur))
 
 //
 //            if !textElement.clipping_ids.isEmpty {
@@ -79,11 +82,45 @@ public struct NativeRenderingLayer: View {
         textView
     }
 
+    @ViewBuilder
+    public func getPositionedDropdown(dropdownElement: DropdownElement) -> some View {
+        let transform = CGAffineTransform.init(
+                a: CGFloat(dropdownElement.transform[0]),
+                b: CGFloat(dropdownElement.transform[1]),
+                c: CGFloat(dropdownElement.transform[2]),
+                d: CGFloat(dropdownElement.transform[3]),
+                tx: CGFloat(dropdownElement.transform[4]),
+                ty: CGFloat(dropdownElement.transform[5])
+        )
+        // The selection is owned by the engine: picking an option only reports it, and the
+        // picker reflects the change once `selected_id` comes back in a `DropdownUpdate`
+        let selection = Binding<Int>(
+            get: { dropdownElement.selected_id },
+            set: { selected_id in self.dropdownElements.onChange?(dropdownElement.id_chain, selected_id) }
+        )
+        Picker("", selection: selection) {
+            ForEach(Array(dropdownElement.options.enumerated()), id: \.offset) { index, option in
+                Text(option).tag(index)
+            }
+        }
+                .labelsHidden()
+                .foregroundColor(dropdownElement.textStyle.fill)
+                .font(dropdownElement.textStyle.font.getFont(size: dropdownElement.textStyle.font_size))
+                .frame(width: CGFloat(dropdownElement.size_x), height: CGFloat(dropdownElement.size_y))
+                .position(x: CGFloat(dropdownElement.size_x / 2.0), y: CGFloat(dropdownElement.size_y / 2.0))
+                .transformEffect(transform)
+                .opacity(dropdownElement.opacity)
+                .blur(radius: CGFloat(dropdownElement.blur))
+    }
+
     public var body: some View {
         ZStack{
             ForEach(Array(self.textElements.elements.values), id: \.id_chain) { textElement in
                 getPositionedTextGroup(textElement: textElement)
             }
+            ForEach(Array(self.dropdownElements.elements.values), id: \.id_chain) { dropdownElement in
+                getPositionedDropdown(dropdownElement: dropdownElement)
+            }
         }
     }
 }
@@ -101,6 +138,23 @@ public class TextElements: ObservableObject {
     }
 }
 
+public class DropdownElements: ObservableObject {
+    public static let singleton : DropdownElements = DropdownElements()
+
+    @Published public var elements : [[UInt64]: DropdownElement] = [:]
+
+    /// Called with the `id_chain` of a dropdown and the index of the option the user picked;
+    /// set by the chassis to forward a `FormDropdownChange` interrupt to the engine
+    public var onChange : (([UInt64], Int) -> Void)? = nil
+
+    public func add(element: DropdownElement) {
+        self.elements[element.id_chain] = element
+    }
+    public func remove(id: [UInt64]) {
+        self.elements.removeValue(forKey: id)
+    }
+}
+
 public class FrameElements: ObservableObject {
     public static let singleton : FrameElements = FrameElements()
 
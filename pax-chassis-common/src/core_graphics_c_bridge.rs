@@ -6,7 +6,8 @@ use std::ffi::c_void;
 
 use std::mem::{transmute, ManuallyDrop};
 
-use core_graphics::context::CGContext;
+use core_graphics::base::CGFloat;
+use core_graphics::context::{CGBlendMode, CGContext};
 use pax_runtime::api::math::Point2;
 use piet_coregraphics::CoreGraphicsContext;
 
@@ -21,7 +22,16 @@ use pax_runtime::{ExpressionTable, PaxEngine, Renderer};
 //Note that any types exposed by pax_message must ALSO be added to `PaxCartridge.h`
 //in order to be visible to Swift
 pub use pax_message::*;
-use pax_runtime::api::{Click, ModifierKey, MouseButton, MouseEventArgs, RenderContext};
+use pax_runtime::api::{
+    BlendMode, Click, DropdownChange, ImageError, ModifierKey, MouseButton, MouseEventArgs,
+    RenderContext,
+};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    // Not bound by `core_graphics`
+    fn CGContextSetAlpha(c: core_graphics::sys::CGContextRef, alpha: CGFloat);
+}
 
 /// Container data structure for PaxEngine, aggregated to support passing across C bridge
 #[repr(C)] //Exposed to Swift via PaxCartridge.h
@@ -39,6 +49,7 @@ pub extern "C" fn pax_init() -> *mut PaxEngineContainer {
     let main_component_instance = definition_to_instance_traverser.get_main_component();
     let expression_table = ExpressionTable {
         table: pax_cartridge::instantiate_expression_table(),
+        theme: Default::default(),
     };
 
     //Initialize a ManuallyDrop-contained PaxEngine, so that a pointer to that
@@ -132,7 +143,30 @@ pub extern "C" fn pax_interrupt(
                 todo!();
             }
             ImageLoadInterruptArgs::Data(_) => {}
+            ImageLoadInterruptArgs::Error(args) => {
+                if let Some(node) = engine.get_expanded_node(args.id_chain[0]) {
+                    node.dispatch_image_error(
+                        ImageError {
+                            path: args.path,
+                            message: args.message,
+                        },
+                        engine.runtime_context.globals(),
+                        &engine.runtime_context,
+                    );
+                }
+            }
         },
+        NativeInterrupt::FormDropdownChange(args) => {
+            if let Some(node) = engine.get_expanded_node(args.id_chain[0]) {
+                node.dispatch_dropdown_change(
+                    DropdownChange {
+                        selected_id: args.selected_id,
+                    },
+                    engine.runtime_context.globals(),
+                    &engine.runtime_context,
+                );
+            }
+        }
         _ => {}
     }
 
@@ -158,6 +192,14 @@ pub extern "C" fn pax_tick(
 
     (*engine).set_viewport_size((width as f64, height as f64));
     render_context.add_context("0", CoreGraphicsContext::new_y_up(ctx, height as f64, None));
+    let cg_context_ref = cgContext as core_graphics::sys::CGContextRef;
+    render_context.set_alpha_hook("0", move |alpha| unsafe {
+        CGContextSetAlpha(cg_context_ref, alpha)
+    });
+    let blend_context = unsafe { CGContext::from_existing_context_ptr(cg_context_ref) };
+    render_context.set_blend_hook("0", move |mode| {
+        blend_context.set_blend_mode(to_cg_blend_mode(mode))
+    });
 
     let messages = (*engine).tick();
     engine.render(&mut render_context as &mut dyn RenderContext);
@@ -186,6 +228,27 @@ pub extern "C" fn pax_tick(
     queue_container
 }
 
+fn to_cg_blend_mode(mode: BlendMode) -> CGBlendMode {
+    match mode {
+        BlendMode::Normal => CGBlendMode::Normal,
+        BlendMode::Multiply => CGBlendMode::Multiply,
+        BlendMode::Screen => CGBlendMode::Screen,
+        BlendMode::Overlay => CGBlendMode::Overlay,
+        BlendMode::Darken => CGBlendMode::Darken,
+        BlendMode::Lighten => CGBlendMode::Lighten,
+        BlendMode::ColorDodge => CGBlendMode::ColorDodge,
+        BlendMode::ColorBurn => CGBlendMode::ColorBurn,
+        BlendMode::HardLight => CGBlendMode::HardLight,
+        BlendMode::SoftLight => CGBlendMode::SoftLight,
+        BlendMode::Difference => CGBlendMode::Difference,
+        BlendMode::Exclusion => CGBlendMode::Exclusion,
+        BlendMode::Hue => CGBlendMode::Hue,
+        BlendMode::Saturation => CGBlendMode::Saturation,
+        BlendMode::Color => CGBlendMode::Color,
+        BlendMode::Luminosity => CGBlendMode::Luminosity,
+    }
+}
+
 /// Required manual cleanup callback from Swift after reading a frame's message queue.
 /// If this is not called after `pax_tick` is invoked, we will have a memory leak.
 #[no_mangle] //Exposed to Swift via PaxCartridge.h
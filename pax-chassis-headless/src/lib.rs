@@ -0,0 +1,318 @@
+//! Headless chassis: drives a Pax cartridge against offscreen bitmaps, so that frames can be
+//! inspected or written to PNG without a browser or an Apple device -- e.g. for visual
+//! regression tests running in Linux CI.
+
+mod native;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+
+use pax_message::{ImagePatch, NativeMessage};
+use pax_runtime::api::{ImageError, RenderContext as _};
+use pax_runtime::{ExpressionTable, PaxEngine, Renderer};
+use piet::{ImageFormat, RenderContext};
+use piet_common::{Device, Piet};
+
+pub use native::NativeElementRasterizer;
+
+/// Decoded RGBA8 pixels of an image requested by an `Image` primitive
+struct LoadedImage {
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+pub struct PaxChassisHeadless {
+    engine: PaxEngine,
+    viewport_size: (usize, usize),
+    native_elements: NativeElementRasterizer,
+    image_map: HashMap<String, LoadedImage>,
+    assets_dir: PathBuf,
+}
+
+impl PaxChassisHeadless {
+    /// Instantiates the main component of the attached cartridge into an engine
+    /// with a viewport of `width` x `height` pixels
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut definition_to_instance_traverser =
+            pax_cartridge::DefinitionToInstanceTraverser::new();
+        let main_component_instance = definition_to_instance_traverser.get_main_component();
+        let expression_table = ExpressionTable {
+            table: pax_cartridge::instantiate_expression_table(),
+            theme: Default::default(),
+        };
+        let engine = PaxEngine::new(
+            main_component_instance,
+            expression_table,
+            (width as f64, height as f64),
+        );
+        Self::new_with_engine(engine, width, height)
+    }
+
+    /// Wraps an already-instantiated engine, e.g. one built around a hand-assembled component tree
+    pub fn new_with_engine(engine: PaxEngine, width: usize, height: usize) -> Self {
+        let mut engine = engine;
+        engine.set_viewport_size((width as f64, height as f64));
+        Self {
+            engine,
+            viewport_size: (width, height),
+            native_elements: NativeElementRasterizer::default(),
+            image_map: HashMap::new(),
+            assets_dir: PathBuf::from("."),
+        }
+    }
+
+    /// Directory against which image paths (e.g. `assets/images/pax-logo.png`) are resolved.
+    /// Defaults to the current working directory.  Base64 `data:` URLs of PNGs are decoded as they are.
+    pub fn set_assets_dir(&mut self, assets_dir: impl Into<PathBuf>) {
+        self.assets_dir = assets_dir.into();
+    }
+
+    pub fn engine(&self) -> &PaxEngine {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut PaxEngine {
+        &mut self.engine
+    }
+
+    pub fn set_viewport_size(&mut self, width: usize, height: usize) {
+        self.viewport_size = (width, height);
+        self.engine.set_viewport_size((width as f64, height as f64));
+    }
+
+    /// Advances the engine by one frame, applying the resulting native messages to the
+    /// headless native element and image state.  The messages are returned for inspection.
+    pub fn tick(&mut self) -> Vec<NativeMessage> {
+        let messages = self.engine.tick();
+        for message in messages.iter() {
+            if let NativeMessage::ImageLoad(patch) = message {
+                self.load_image(patch);
+            }
+            self.native_elements.handle_message(message);
+        }
+        messages
+    }
+
+    /// Renders the current state of the engine, compositing every occlusion layer
+    /// (canvas content followed by the native elements occluded by it) into a single frame
+    pub fn render(&mut self) -> Result<Frame, piet::Error> {
+        let (width, height) = self.viewport_size;
+        let layer_count = self.layer_count();
+
+        // A `Device` hands out a single bitmap target at a time, so each layer gets its own
+        let mut devices = (0..layer_count)
+            .map(|_| Device::new())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut targets = devices
+            .iter_mut()
+            .map(|device| device.bitmap_target(width, height, 1.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        {
+            let mut renderer: Renderer<Piet> = Renderer::new();
+            for (layer, target) in targets.iter_mut().enumerate() {
+                renderer.add_context(&layer.to_string(), target.render_context());
+            }
+            for (path, image) in self.image_map.iter() {
+                renderer.load_image(path, &image.pixels, image.width, image.height);
+            }
+
+            self.engine.render(&mut renderer);
+
+            for layer in 0..layer_count {
+                let rc = renderer.backends.get_mut(&layer.to_string()).unwrap();
+                self.native_elements.render_layer(layer as u32, rc);
+                rc.finish()?;
+            }
+        }
+
+        let mut frame = Frame::new(width, height);
+        for target in targets.iter_mut() {
+            let layer = target.to_image_buf(ImageFormat::RgbaPremul)?;
+            frame.composite_premultiplied(layer.raw_pixels());
+        }
+        frame.unpremultiply();
+        Ok(frame)
+    }
+
+    /// Ticks `frames` times, then renders and writes the last frame to `path` as a PNG
+    pub fn render_to_png(
+        &mut self,
+        frames: usize,
+        path: impl AsRef<Path>,
+    ) -> Result<(), piet::Error> {
+        for _ in 0..frames {
+            self.tick();
+        }
+        self.render()?.save_png(path)
+    }
+
+    /// One canvas layer exists per occlusion level computed during the last tick
+    fn layer_count(&self) -> usize {
+        self.engine
+            .runtime_context
+            .z_index_node_cache
+            .iter()
+            .map(|node| *node.occlusion_id.borrow() as usize + 1)
+            .max()
+            .unwrap_or(1)
+    }
+
+    fn load_image(&mut self, patch: &ImagePatch) {
+        let Some(path) = &patch.path else {
+            return;
+        };
+        let decoded = match path.strip_prefix("data:") {
+            Some(data_url) => decode_data_url(data_url),
+            None => File::open(self.assets_dir.join(path))
+                .map_err(Into::into)
+                .and_then(decode_png),
+        };
+        match decoded {
+            Ok(image) => {
+                self.image_map.insert(path.clone(), image);
+            }
+            Err(err) => {
+                log::warn!("failed to load image {}: {}", path, err);
+                if let Some(node) = self.engine.get_expanded_node(patch.id_chain[0]) {
+                    node.dispatch_image_error(
+                        ImageError {
+                            path: path.clone(),
+                            message: err.to_string(),
+                        },
+                        self.engine.runtime_context.globals(),
+                        &self.engine.runtime_context,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// A rendered frame of straight (non-premultiplied) RGBA8 pixels, row-major
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    /// The RGBA value of the pixel at (`x`, `y`), counted from the top-left corner
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * self.width + x) * 4;
+        [
+            self.pixels[offset],
+            self.pixels[offset + 1],
+            self.pixels[offset + 2],
+            self.pixels[offset + 3],
+        ]
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), piet::Error> {
+        let file = BufWriter::new(File::create(path).map_err(Into::<Box<_>>::into)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .map_err(Into::<Box<_>>::into)?
+            .write_image_data(&self.pixels)
+            .map_err(Into::<Box<_>>::into)?;
+        Ok(())
+    }
+
+    /// Draws a premultiplied RGBA layer on top of this (still premultiplied) frame
+    fn composite_premultiplied(&mut self, layer: &[u8]) {
+        for (dst, src) in self.pixels.chunks_exact_mut(4).zip(layer.chunks_exact(4)) {
+            let inverse_src_alpha = 255 - src[3] as u32;
+            for (dst_channel, src_channel) in dst.iter_mut().zip(src) {
+                *dst_channel = (*src_channel as u32
+                    + (*dst_channel as u32 * inverse_src_alpha + 127) / 255)
+                    .min(255) as u8;
+            }
+        }
+    }
+
+    fn unpremultiply(&mut self) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u32;
+            if alpha != 0 && alpha != 255 {
+                for channel in pixel[..3].iter_mut() {
+                    *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Decodes the part of a `data:` URL after the scheme, e.g. `image/png;base64,iVBORw0KGgo...`
+fn decode_data_url(data_url: &str) -> Result<LoadedImage, Box<dyn Error>> {
+    let (media_type, data) = data_url.split_once(',').ok_or("data URL has no data")?;
+    if !media_type.ends_with(";base64") {
+        return Err("only base64-encoded data URLs are supported".into());
+    }
+    decode_png(decode_base64(data)?.as_slice())
+}
+
+fn decode_base64(encoded: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for c in encoded.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => return Err(format!("invalid base64 character {:?}", c as char).into()),
+        };
+        // Only the bits not yet emitted as bytes (fewer than 8) need keeping
+        bits = ((bits << 6) | value as u32) & 0x3fff;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+fn decode_png(reader: impl Read) -> Result<LoadedImage, Box<dyn Error>> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    let bytes = &buf[..info.buffer_size()];
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => bytes.to_vec(),
+        png::ColorType::Rgb => bytes
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => bytes
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => bytes.iter().flat_map(|g| [*g, *g, *g, 255]).collect(),
+        png::ColorType::Indexed => unreachable!("palettes are expanded while decoding"),
+    };
+    Ok(LoadedImage {
+        pixels,
+        width: info.width as usize,
+        height: info.height as usize,
+    })
+}
@@ -0,0 +1,473 @@
+//! A minimal rasterizer for native elements (`Text`, `Button`, `Textbox`, `Checkbox`, `Dropdown`).
+//! Other chassis hand these elements to the host platform (e.g. DOM nodes on Web); here they are
+//! drawn straight into the offscreen layer they are occluded by, approximating the platform defaults.
+
+use std::collections::HashMap;
+
+use kurbo::{Affine, BezPath, Point, Rect, RoundedRect};
+use pax_message::{
+    AnyCreatePatch, ButtonPatch, CheckboxPatch, ColorMessage, DropdownPatch, FontPatch,
+    FontStyleMessage, FontWeightMessage, NativeMessage, TextAlignHorizontalMessage,
+    TextAlignVerticalMessage, TextPatch, TextStyleMessage, TextboxPatch,
+};
+use piet::{
+    Color, FontFamily, FontStyle, FontWeight, RenderContext, Text, TextAlignment, TextAttribute,
+    TextLayout, TextLayoutBuilder,
+};
+
+const DEFAULT_FONT_SIZE: f64 = 20.0;
+const TEXTBOX_PADDING: f64 = 4.0;
+const CONTROL_BORDER_RADIUS: f64 = 4.0;
+const CONTROL_BACKGROUND: Color = Color::rgb8(0xEF, 0xEF, 0xEF);
+const CONTROL_STROKE: Color = Color::rgb8(0x76, 0x76, 0x76);
+const CHECKBOX_CHECKED_BACKGROUND: Color = Color::rgb8(0x00, 0x75, 0xFF);
+const DROPDOWN_ARROW_WIDTH: f64 = 20.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum NativeElementKind {
+    Text,
+    Button,
+    Textbox,
+    Checkbox,
+    Dropdown,
+}
+
+/// Last known state of a single native element, accumulated from its create / update patches
+struct NativeElement {
+    kind: NativeElementKind,
+    z_index: u32,
+    transform: Affine,
+    size: (f64, f64),
+    content: String,
+    style: TextStyleMessage,
+    background: Option<ColorMessage>,
+    stroke_color: Option<ColorMessage>,
+    stroke_width: Option<f64>,
+    border_radius: Option<f64>,
+    checked: bool,
+    options: Vec<String>,
+    selected_id: u32,
+}
+
+impl NativeElement {
+    fn new(kind: NativeElementKind, z_index: u32) -> Self {
+        Self {
+            kind,
+            z_index,
+            transform: Affine::IDENTITY,
+            size: (0.0, 0.0),
+            content: String::new(),
+            style: TextStyleMessage::default(),
+            background: None,
+            stroke_color: None,
+            stroke_width: None,
+            border_radius: None,
+            checked: false,
+            options: Vec::new(),
+            selected_id: 0,
+        }
+    }
+
+    fn apply_layout(
+        &mut self,
+        transform: &Option<Vec<f64>>,
+        size_x: Option<f64>,
+        size_y: Option<f64>,
+    ) {
+        if let Some(coeffs) = transform {
+            if let Ok(coeffs) = <[f64; 6]>::try_from(coeffs.as_slice()) {
+                self.transform = Affine::new(coeffs);
+            }
+        }
+        if let Some(size_x) = size_x {
+            self.size.0 = size_x;
+        }
+        if let Some(size_y) = size_y {
+            self.size.1 = size_y;
+        }
+    }
+}
+
+/// Tracks the native elements created by the engine and draws them on request
+#[derive(Default)]
+pub struct NativeElementRasterizer {
+    elements: HashMap<Vec<u32>, NativeElement>,
+}
+
+impl NativeElementRasterizer {
+    /// Folds a message emitted by `PaxEngine::tick` into the tracked element state.
+    /// Messages unrelated to the supported native elements are ignored.
+    pub fn handle_message(&mut self, message: &NativeMessage) {
+        match message {
+            NativeMessage::TextCreate(patch) => self.create(patch, NativeElementKind::Text),
+            NativeMessage::ButtonCreate(patch) => self.create(patch, NativeElementKind::Button),
+            NativeMessage::TextboxCreate(patch) => self.create(patch, NativeElementKind::Textbox),
+            NativeMessage::CheckboxCreate(patch) => self.create(patch, NativeElementKind::Checkbox),
+            NativeMessage::DropdownCreate(patch) => self.create(patch, NativeElementKind::Dropdown),
+            NativeMessage::TextUpdate(patch) => self.update_text(patch),
+            NativeMessage::ButtonUpdate(patch) => self.update_button(patch),
+            NativeMessage::TextboxUpdate(patch) => self.update_textbox(patch),
+            NativeMessage::CheckboxUpdate(patch) => self.update_checkbox(patch),
+            NativeMessage::DropdownUpdate(patch) => self.update_dropdown(patch),
+            NativeMessage::TextDelete(id_chain)
+            | NativeMessage::ButtonDelete(id_chain)
+            | NativeMessage::TextboxDelete(id_chain)
+            | NativeMessage::CheckboxDelete(id_chain)
+            | NativeMessage::DropdownDelete(id_chain) => {
+                self.elements.remove(id_chain);
+            }
+            NativeMessage::OcclusionUpdate(patch) => {
+                if let Some(element) = self.elements.get_mut(&patch.id_chain) {
+                    element.z_index = patch.z_index;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws every element occluded by the layer `z_index`, i.e. on top of the canvas content of that layer
+    pub fn render_layer<R: RenderContext>(&self, z_index: u32, rc: &mut R) {
+        // Sort by id_chain so that overlapping elements are always drawn in the same order
+        let mut elements: Vec<_> = self
+            .elements
+            .iter()
+            .filter(|(_, element)| element.z_index == z_index)
+            .collect();
+        elements.sort_by_key(|(id_chain, _)| *id_chain);
+
+        for (_, element) in elements {
+            rc.with_save(|rc| {
+                rc.transform(element.transform);
+                match element.kind {
+                    NativeElementKind::Text => draw_text(rc, element),
+                    NativeElementKind::Button => draw_button(rc, element),
+                    NativeElementKind::Textbox => draw_textbox(rc, element),
+                    NativeElementKind::Checkbox => draw_checkbox(rc, element),
+                    NativeElementKind::Dropdown => draw_dropdown(rc, element),
+                }
+                Ok(())
+            })
+            .expect("failed to save piet state");
+        }
+    }
+
+    fn create(&mut self, patch: &AnyCreatePatch, kind: NativeElementKind) {
+        self.elements.insert(
+            patch.id_chain.clone(),
+            NativeElement::new(kind, patch.z_index),
+        );
+    }
+
+    fn update_text(&mut self, patch: &TextPatch) {
+        let Some(element) = self.elements.get_mut(&patch.id_chain) else {
+            return;
+        };
+        element.apply_layout(&patch.transform, patch.size_x, patch.size_y);
+        if let Some(content) = &patch.content {
+            element.content = content.clone();
+        }
+        if let Some(style) = &patch.style {
+            element.style = style.clone();
+        }
+    }
+
+    fn update_button(&mut self, patch: &ButtonPatch) {
+        let Some(element) = self.elements.get_mut(&patch.id_chain) else {
+            return;
+        };
+        element.apply_layout(&patch.transform, patch.size_x, patch.size_y);
+        if let Some(content) = &patch.content {
+            element.content = content.clone();
+        }
+        if let Some(style) = &patch.style {
+            element.style = style.clone();
+        }
+    }
+
+    fn update_textbox(&mut self, patch: &TextboxPatch) {
+        let Some(element) = self.elements.get_mut(&patch.id_chain) else {
+            return;
+        };
+        element.apply_layout(&patch.transform, patch.size_x, patch.size_y);
+        if let Some(text) = &patch.text {
+            element.content = text.clone();
+        }
+        if let Some(style) = &patch.style {
+            element.style = style.clone();
+        }
+        if let Some(background) = &patch.background {
+            element.background = Some(background.clone());
+        }
+        if let Some(stroke_color) = &patch.stroke_color {
+            element.stroke_color = Some(stroke_color.clone());
+        }
+        if let Some(stroke_width) = patch.stroke_width {
+            element.stroke_width = Some(stroke_width);
+        }
+        if let Some(border_radius) = patch.border_radius {
+            element.border_radius = Some(border_radius);
+        }
+    }
+
+    fn update_checkbox(&mut self, patch: &CheckboxPatch) {
+        let Some(element) = self.elements.get_mut(&patch.id_chain) else {
+            return;
+        };
+        element.apply_layout(&patch.transform, patch.size_x, patch.size_y);
+        if let Some(checked) = patch.checked {
+            element.checked = checked;
+        }
+    }
+
+    fn update_dropdown(&mut self, patch: &DropdownPatch) {
+        let Some(element) = self.elements.get_mut(&patch.id_chain) else {
+            return;
+        };
+        element.apply_layout(&patch.transform, patch.size_x, patch.size_y);
+        if let Some(options) = &patch.options {
+            element.options = options.clone();
+        }
+        if let Some(selected_id) = patch.selected_id {
+            element.selected_id = selected_id;
+        }
+        if let Some(style) = &patch.style {
+            element.style = style.clone();
+        }
+    }
+}
+
+fn draw_text<R: RenderContext>(rc: &mut R, element: &NativeElement) {
+    let bounds = Rect::new(0.0, 0.0, element.size.0, element.size.1);
+    draw_text_in_bounds(
+        rc,
+        &element.content,
+        &element.style,
+        bounds,
+        TextAlignHorizontalMessage::Left,
+        TextAlignVerticalMessage::Top,
+    );
+}
+
+fn draw_button<R: RenderContext>(rc: &mut R, element: &NativeElement) {
+    let rect = RoundedRect::new(
+        0.0,
+        0.0,
+        element.size.0,
+        element.size.1,
+        CONTROL_BORDER_RADIUS,
+    );
+    rc.fill(rect, &CONTROL_BACKGROUND);
+    rc.stroke(rect, &CONTROL_STROKE, 1.0);
+
+    let bounds = Rect::new(0.0, 0.0, element.size.0, element.size.1);
+    draw_text_in_bounds(
+        rc,
+        &element.content,
+        &element.style,
+        bounds,
+        TextAlignHorizontalMessage::Center,
+        TextAlignVerticalMessage::Center,
+    );
+}
+
+fn draw_textbox<R: RenderContext>(rc: &mut R, element: &NativeElement) {
+    let rect = RoundedRect::new(
+        0.0,
+        0.0,
+        element.size.0,
+        element.size.1,
+        element.border_radius.unwrap_or(0.0),
+    );
+    let background = element
+        .background
+        .as_ref()
+        .map(to_piet_color)
+        .unwrap_or(Color::WHITE);
+    rc.fill(rect, &background);
+    let stroke_width = element.stroke_width.unwrap_or(1.0);
+    if stroke_width > 0.0 {
+        let stroke_color = element
+            .stroke_color
+            .as_ref()
+            .map(to_piet_color)
+            .unwrap_or(CONTROL_STROKE);
+        rc.stroke(rect, &stroke_color, stroke_width);
+    }
+
+    let bounds = Rect::new(0.0, 0.0, element.size.0, element.size.1).inset(-TEXTBOX_PADDING);
+    rc.save().expect("failed to save piet state");
+    rc.clip(bounds);
+    draw_text_in_bounds(
+        rc,
+        &element.content,
+        &element.style,
+        bounds,
+        TextAlignHorizontalMessage::Left,
+        TextAlignVerticalMessage::Center,
+    );
+    rc.restore().expect("failed to restore piet state");
+}
+
+fn draw_checkbox<R: RenderContext>(rc: &mut R, element: &NativeElement) {
+    let side = element.size.0.min(element.size.1);
+    let rect = RoundedRect::new(0.0, 0.0, side, side, side * 0.15);
+    if element.checked {
+        rc.fill(rect, &CHECKBOX_CHECKED_BACKGROUND);
+        let mut check = BezPath::new();
+        check.move_to(Point::new(side * 0.22, side * 0.52));
+        check.line_to(Point::new(side * 0.42, side * 0.72));
+        check.line_to(Point::new(side * 0.78, side * 0.3));
+        rc.stroke(check, &Color::WHITE, (side * 0.12).max(1.0));
+    } else {
+        rc.fill(rect, &Color::WHITE);
+        rc.stroke(rect, &CONTROL_STROKE, 1.0);
+    }
+}
+
+fn draw_dropdown<R: RenderContext>(rc: &mut R, element: &NativeElement) {
+    let rect = RoundedRect::new(
+        0.0,
+        0.0,
+        element.size.0,
+        element.size.1,
+        CONTROL_BORDER_RADIUS,
+    );
+    rc.fill(rect, &Color::WHITE);
+    rc.stroke(rect, &CONTROL_STROKE, 1.0);
+
+    let arrow_x = element.size.0 - DROPDOWN_ARROW_WIDTH / 2.0;
+    let arrow_y = element.size.1 / 2.0;
+    let mut arrow = BezPath::new();
+    arrow.move_to(Point::new(arrow_x - 4.0, arrow_y - 2.0));
+    arrow.line_to(Point::new(arrow_x, arrow_y + 2.0));
+    arrow.line_to(Point::new(arrow_x + 4.0, arrow_y - 2.0));
+    rc.stroke(arrow, &CONTROL_STROKE, 1.5);
+
+    let selected = element
+        .options
+        .get(element.selected_id as usize)
+        .map(String::as_str)
+        .unwrap_or("");
+    let bounds = Rect::new(
+        TEXTBOX_PADDING,
+        0.0,
+        element.size.0 - DROPDOWN_ARROW_WIDTH,
+        element.size.1,
+    );
+    rc.save().expect("failed to save piet state");
+    rc.clip(bounds);
+    draw_text_in_bounds(
+        rc,
+        selected,
+        &element.style,
+        bounds,
+        TextAlignHorizontalMessage::Left,
+        TextAlignVerticalMessage::Center,
+    );
+    rc.restore().expect("failed to restore piet state");
+}
+
+/// Lays out `content` with `style` inside `bounds`, falling back to the provided alignments when
+/// the style doesn't specify them (mirroring the defaults of the respective platform controls)
+fn draw_text_in_bounds<R: RenderContext>(
+    rc: &mut R,
+    content: &str,
+    style: &TextStyleMessage,
+    bounds: Rect,
+    default_align_horizontal: TextAlignHorizontalMessage,
+    default_align_vertical: TextAlignVerticalMessage,
+) {
+    if content.is_empty() {
+        return;
+    }
+
+    let font_family = font_family_from_patch(rc, style.font.as_ref());
+    let (font_weight, font_style) = match &style.font {
+        Some(FontPatch::System(font)) => (font.weight.clone(), font.style.clone()),
+        Some(FontPatch::Web(font)) => (font.weight.clone(), font.style.clone()),
+        Some(FontPatch::Local(font)) => (font.weight.clone(), font.style.clone()),
+        None => (None, None),
+    };
+    let alignment = match style
+        .align_multiline
+        .as_ref()
+        .or(style.align_horizontal.as_ref())
+        .unwrap_or(&default_align_horizontal)
+    {
+        TextAlignHorizontalMessage::Left => TextAlignment::Start,
+        TextAlignHorizontalMessage::Center => TextAlignment::Center,
+        TextAlignHorizontalMessage::Right => TextAlignment::End,
+    };
+
+    let mut builder = rc
+        .text()
+        .new_text_layout(content.to_string())
+        .font(font_family, style.font_size.unwrap_or(DEFAULT_FONT_SIZE))
+        .text_color(
+            style
+                .fill
+                .as_ref()
+                .map(to_piet_color)
+                .unwrap_or(Color::BLACK),
+        )
+        .alignment(alignment)
+        .max_width(bounds.width().max(0.0));
+    if let Some(weight) = font_weight {
+        builder = builder.default_attribute(TextAttribute::Weight(to_piet_font_weight(&weight)));
+    }
+    if let Some(FontStyleMessage::Italic | FontStyleMessage::Oblique) = font_style {
+        builder = builder.default_attribute(TextAttribute::Style(FontStyle::Italic));
+    }
+    if style.underline == Some(true) {
+        builder = builder.default_attribute(TextAttribute::Underline(true));
+    }
+    let Ok(layout) = builder.build() else {
+        log::warn!("failed to lay out text: {}", content);
+        return;
+    };
+
+    let free_height = bounds.height() - layout.size().height;
+    let offset_y = match style
+        .align_vertical
+        .as_ref()
+        .unwrap_or(&default_align_vertical)
+    {
+        TextAlignVerticalMessage::Top => 0.0,
+        TextAlignVerticalMessage::Center => free_height / 2.0,
+        TextAlignVerticalMessage::Bottom => free_height,
+    };
+    rc.draw_text(&layout, Point::new(bounds.x0, bounds.y0 + offset_y));
+}
+
+fn font_family_from_patch<R: RenderContext>(rc: &mut R, font: Option<&FontPatch>) -> FontFamily {
+    let family = match font {
+        Some(FontPatch::System(font)) => font.family.as_ref(),
+        Some(FontPatch::Web(font)) => font.family.as_ref(),
+        Some(FontPatch::Local(font)) => font.family.as_ref(),
+        None => None,
+    };
+    family
+        .and_then(|family| rc.text().font_family(family))
+        .unwrap_or(FontFamily::SANS_SERIF)
+}
+
+fn to_piet_font_weight(weight: &FontWeightMessage) -> FontWeight {
+    match weight {
+        FontWeightMessage::Thin => FontWeight::THIN,
+        FontWeightMessage::ExtraLight => FontWeight::EXTRA_LIGHT,
+        FontWeightMessage::Light => FontWeight::LIGHT,
+        FontWeightMessage::Normal => FontWeight::REGULAR,
+        FontWeightMessage::Medium => FontWeight::MEDIUM,
+        FontWeightMessage::SemiBold => FontWeight::SEMI_BOLD,
+        FontWeightMessage::Bold => FontWeight::BOLD,
+        FontWeightMessage::ExtraBold => FontWeight::EXTRA_BOLD,
+        FontWeightMessage::Black => FontWeight::BLACK,
+    }
+}
+
+fn to_piet_color(color: &ColorMessage) -> Color {
+    match color {
+        ColorMessage::Rgba([r, g, b, a]) => Color::rgba(*r, *g, *b, *a),
+        ColorMessage::Rgb([r, g, b]) => Color::rgb(*r, *g, *b),
+    }
+}
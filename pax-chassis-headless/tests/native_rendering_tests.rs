@@ -0,0 +1,100 @@
+use pax_chassis_headless::NativeElementRasterizer;
+use pax_message::{AnyCreatePatch, ButtonPatch, DropdownPatch, NativeMessage, OcclusionPatch};
+use piet::{ImageFormat, RenderContext};
+use piet_common::Device;
+
+const SIZE: usize = 40;
+
+fn render_layer(rasterizer: &NativeElementRasterizer, z_index: u32) -> Vec<u8> {
+    let mut device = Device::new().unwrap();
+    let mut target = device.bitmap_target(SIZE, SIZE, 1.0).unwrap();
+    {
+        let mut rc = target.render_context();
+        rasterizer.render_layer(z_index, &mut rc);
+        rc.finish().unwrap();
+    }
+    target
+        .to_image_buf(ImageFormat::RgbaPremul)
+        .unwrap()
+        .raw_pixels()
+        .to_vec()
+}
+
+fn alpha_at(pixels: &[u8], x: usize, y: usize) -> u8 {
+    pixels[(y * SIZE + x) * 4 + 3]
+}
+
+fn button_messages() -> Vec<NativeMessage> {
+    vec![
+        NativeMessage::ButtonCreate(AnyCreatePatch {
+            id_chain: vec![7],
+            clipping_ids: vec![],
+            scroller_ids: vec![],
+            z_index: 0,
+        }),
+        NativeMessage::ButtonUpdate(ButtonPatch {
+            id_chain: vec![7],
+            transform: Some(vec![1.0, 0.0, 0.0, 1.0, 10.0, 10.0]),
+            size_x: Some(20.0),
+            size_y: Some(20.0),
+            content: Some("OK".to_string()),
+            ..Default::default()
+        }),
+    ]
+}
+
+#[test]
+fn test_button_is_drawn_within_its_bounds() {
+    let mut rasterizer = NativeElementRasterizer::default();
+    for message in button_messages().iter() {
+        rasterizer.handle_message(message);
+    }
+
+    let pixels = render_layer(&rasterizer, 0);
+    assert_eq!(alpha_at(&pixels, 20, 20), 255);
+    assert_eq!(alpha_at(&pixels, 2, 2), 0);
+    assert_eq!(alpha_at(&pixels, 35, 35), 0);
+}
+
+#[test]
+fn test_occlusion_update_and_delete() {
+    let mut rasterizer = NativeElementRasterizer::default();
+    for message in button_messages().iter() {
+        rasterizer.handle_message(message);
+    }
+    rasterizer.handle_message(&NativeMessage::OcclusionUpdate(OcclusionPatch {
+        id_chain: vec![7],
+        z_index: 1,
+    }));
+    assert_eq!(alpha_at(&render_layer(&rasterizer, 0), 20, 20), 0);
+    assert_eq!(alpha_at(&render_layer(&rasterizer, 1), 20, 20), 255);
+
+    rasterizer.handle_message(&NativeMessage::ButtonDelete(vec![7]));
+    assert_eq!(alpha_at(&render_layer(&rasterizer, 1), 20, 20), 0);
+}
+
+#[test]
+fn test_dropdown_is_drawn_until_deleted() {
+    let mut rasterizer = NativeElementRasterizer::default();
+    rasterizer.handle_message(&NativeMessage::DropdownCreate(AnyCreatePatch {
+        id_chain: vec![3],
+        clipping_ids: vec![],
+        scroller_ids: vec![],
+        z_index: 0,
+    }));
+    rasterizer.handle_message(&NativeMessage::DropdownUpdate(DropdownPatch {
+        id_chain: vec![3],
+        transform: Some(vec![1.0, 0.0, 0.0, 1.0, 5.0, 5.0]),
+        size_x: Some(30.0),
+        size_y: Some(20.0),
+        options: Some(vec!["One".to_string(), "Two".to_string()]),
+        selected_id: Some(1),
+        ..Default::default()
+    }));
+    let pixels = render_layer(&rasterizer, 0);
+    assert_eq!(alpha_at(&pixels, 20, 15), 255);
+    assert_eq!(alpha_at(&pixels, 2, 2), 0);
+
+    rasterizer.handle_message(&NativeMessage::DropdownDelete(vec![3]));
+    assert_eq!(alpha_at(&render_layer(&rasterizer, 0), 20, 15), 0);
+}
@@ -125,6 +125,7 @@ struct PaxViewIos: View {
 
         @ObservedObject var textElements = TextElements.singleton
         @ObservedObject var frameElements = FrameElements.singleton
+        @ObservedObject var dropdownElements = DropdownElements.singleton
         private var displayLink: CADisplayLink?
 
         override init(frame: CGRect) {
@@ -231,6 +232,42 @@ struct PaxViewIos: View {
             frameElements.remove(id: patch.id_chain)
         }
 
+        func handleDropdownCreate(patch: AnyCreatePatch) {
+            dropdownElements.onChange = { id_chain, selected_id in
+                let json = String(format: "{\"FormDropdownChange\": {\"id_chain\": [%@], \"selected_id\": %d} }", id_chain.map { String($0) }.joined(separator: ","), selected_id)
+                let buffer = try! FlexBufferBuilder.fromJSON(json)
+
+                //Send `FormDropdownChange` interrupt
+                buffer.data.withUnsafeBytes({ptr in
+                    var ffi_container = InterruptBuffer( data_ptr: ptr.baseAddress!, length: UInt64(ptr.count) )
+                    withUnsafePointer(to: &ffi_container) {ffi_container_ptr in
+                        pax_interrupt(PaxEngineContainer.paxEngineContainer!, ffi_container_ptr)
+                    }
+                })
+            }
+            dropdownElements.add(element: DropdownElement.makeDefault(id_chain: patch.id_chain, clipping_ids: patch.clipping_ids))
+        }
+
+        func handleDropdownUpdate(patch: DropdownUpdatePatch) {
+            dropdownElements.elements[patch.id_chain]?.applyPatch(patch: patch)
+            dropdownElements.objectWillChange.send()
+        }
+
+        func handleDropdownDelete(patch: AnyDeletePatch) {
+            dropdownElements.remove(id: patch.id_chain)
+        }
+
+        func handleEffectsUpdate(patch: EffectsUpdatePatch) {
+            if let textElement = textElements.elements[patch.id_chain] {
+                textElement.applyPatch(patch: patch)
+                textElements.objectWillChange.send()
+            }
+            if let dropdownElement = dropdownElements.elements[patch.id_chain] {
+                dropdownElement.applyPatch(patch: patch)
+                dropdownElements.objectWillChange.send()
+            }
+        }
+
         func printAllFilesInBundle() {
             let bundleURL = Bundle.main.bundleURL
 
@@ -362,6 +399,26 @@ struct PaxViewIos: View {
                     handleImageLoad(patch: ImageLoadPatch(fb: imageLoadMessage!))
                 }
 
+                let dropdownCreateMessage = message["DropdownCreate"]
+                if dropdownCreateMessage != nil {
+                    handleDropdownCreate(patch: AnyCreatePatch(fb: dropdownCreateMessage!))
+                }
+
+                let dropdownUpdateMessage = message["DropdownUpdate"]
+                if dropdownUpdateMessage != nil {
+                    handleDropdownUpdate(patch: DropdownUpdatePatch(fb: dropdownUpdateMessage!))
+                }
+
+                let dropdownDeleteMessage = message["DropdownDelete"]
+                if dropdownDeleteMessage != nil {
+                    handleDropdownDelete(patch: AnyDeletePatch(fb: dropdownDeleteMessage!))
+                }
+
+                let effectsUpdateMessage = message["EffectsUpdate"]
+                if effectsUpdateMessage != nil {
+                    handleEffectsUpdate(patch: EffectsUpdatePatch(fb: effectsUpdateMessage!))
+                }
+
                 //^ Add new message-receive handlers here ^
             })
 
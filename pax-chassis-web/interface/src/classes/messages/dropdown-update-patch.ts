@@ -0,0 +1,45 @@
+import {ObjectManager} from "../../pools/object-manager";
+import { TEXT_STYLE } from "../../pools/supported-objects";
+import { TextStyle } from "../text";
+
+export class DropdownUpdatePatch {
+    public id_chain?: number[];
+    public size_x?: number;
+    public size_y?: number;
+    public transform?: number[];
+    public options?: string[];
+    public selected_id?: number;
+    public style?: TextStyle;
+
+    objectManager: ObjectManager;
+
+    constructor(objectManager: ObjectManager) {
+        this.objectManager = objectManager;
+    }
+
+    fromPatch(jsonMessage: any, registeredFontFaces: Set<string>) {
+        this.id_chain = jsonMessage["id_chain"];
+        this.size_x = jsonMessage["size_x"];
+        this.size_y = jsonMessage["size_y"];
+        this.transform = jsonMessage["transform"];
+        this.options = jsonMessage["options"];
+        this.selected_id = jsonMessage["selected_id"];
+        const styleMessage = jsonMessage["style"];
+
+        if (styleMessage) {
+            this.style = this.objectManager.getFromPool(TEXT_STYLE, this.objectManager);
+            this.style.build(styleMessage, registeredFontFaces)
+        }
+    }
+
+    cleanUp(){
+        this.id_chain = [];
+        this.size_x = 0;
+        this.size_y = 0;
+        this.transform = [];
+        this.options = undefined;
+        this.selected_id = undefined;
+        this.objectManager.returnToPool(TEXT_STYLE, this.style);
+        this.style = undefined;
+    }
+}
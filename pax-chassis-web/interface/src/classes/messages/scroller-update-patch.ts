@@ -7,6 +7,8 @@ export class ScrollerUpdatePatch {
     public transform? : number[];
     public scrollX? : boolean;
     public scrollY? : boolean;
+    public scrollOffsetX? : number;
+    public scrollOffsetY? : number;
     public subtreeDepth?: number;
 
     fromPatch(jsonMessage: any) {
@@ -18,6 +20,8 @@ export class ScrollerUpdatePatch {
         this.transform = jsonMessage["transform"];
         this.scrollX = jsonMessage["scroll_x"];
         this.scrollY = jsonMessage["scroll_y"];
+        this.scrollOffsetX = jsonMessage["scroll_offset_x"];
+        this.scrollOffsetY = jsonMessage["scroll_offset_y"];
         this.subtreeDepth = jsonMessage["subtree_depth"];
     }
 
@@ -30,6 +34,8 @@ export class ScrollerUpdatePatch {
         this.transform = [];
         this.scrollX = false;
         this.scrollY = false;
+        this.scrollOffsetX = undefined;
+        this.scrollOffsetY = undefined;
         this.subtreeDepth = 0;
     }
 }
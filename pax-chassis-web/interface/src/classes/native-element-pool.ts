@@ -12,12 +12,13 @@ import {ButtonUpdatePatch} from "./messages/button-update-patch";
 import {ImageLoadPatch} from "./messages/image-load-patch";
 import {OcclusionContext} from "./occlusion-context";
 import {ObjectManager} from "../pools/object-manager";
-import {INPUT, BUTTON, DIV, OBJECT, OCCLUSION_CONTEXT, SCROLLER} from "../pools/supported-objects";
+import {INPUT, BUTTON, DIV, OBJECT, OCCLUSION_CONTEXT, SCROLLER, SELECT} from "../pools/supported-objects";
 import {arrayToKey, packAffineCoeffsIntoMatrix3DString, readImageToByteBuffer} from "../utils/helpers";
 import {ColorGroup, TextStyle, getAlignItems, getJustifyContent, getTextAlign} from "./text";
 import type {PaxChassisWeb} from "../types/pax-chassis-web";
 import { CheckboxUpdatePatch } from "./messages/checkbox-update-patch";
 import { TextboxUpdatePatch } from "./messages/textbox-update-patch";
+import { DropdownUpdatePatch } from "./messages/dropdown-update-patch";
 
 export class NativeElementPool {
     private canvases: Map<string, HTMLCanvasElement>;
@@ -82,6 +83,8 @@ export class NativeElementPool {
                 const scrollEvent = this.objectManager.getFromPool(OBJECT);
                 const deltas: object = this.objectManager.getFromPool(OBJECT);
                 // @ts-ignore
+                deltas['id_chain'] = scroller.getIdChain();
+                // @ts-ignore
                 deltas['delta_x'] = deltaX;
                 // @ts-ignore
                 deltas['delta_y'] = deltaY;
@@ -108,6 +111,15 @@ export class NativeElementPool {
         }
     }
 
+    effectsUpdate(idChain: number[], opacity: number, blur: number) {
+        // @ts-ignore
+        let node = this.textNodes[idChain];
+        if (node){
+            node.style.opacity = opacity.toString();
+            node.style.filter = blur > 0 ? `blur(${blur}px)` : "";
+        }
+    }
+
     checkboxCreate(patch: AnyCreatePatch) {
         console.assert(patch.idChain != null);
         console.assert(patch.clippingIds != null);
@@ -184,6 +196,89 @@ export class NativeElementPool {
         }
     }
 
+    dropdownCreate(patch: AnyCreatePatch) {
+        console.assert(patch.idChain != null);
+        console.assert(patch.clippingIds != null);
+        console.assert(patch.scrollerIds != null);
+        console.assert(patch.zIndex != null);
+
+        const dropdown = this.objectManager.getFromPool(SELECT) as HTMLSelectElement;
+        dropdown.style.margin = "0";
+        dropdown.addEventListener("change", (_event) => {
+            let message = {
+                "FormDropdownChange": {
+                    "id_chain": patch.idChain!,
+                    "selected_id": dropdown.selectedIndex,
+                }
+            }
+            //Reset the selection (state changes only allowed through engine)
+            dropdown.selectedIndex = Number(dropdown.dataset.selectedId ?? 0);
+            this.chassis!.interrupt(JSON.stringify(message), undefined);
+        });
+
+        let runningChain: HTMLDivElement = this.objectManager.getFromPool(DIV);
+        runningChain.appendChild(dropdown);
+        runningChain.setAttribute("class", NATIVE_LEAF_CLASS)
+        runningChain.setAttribute("id_chain", String(patch.idChain));
+        let scroller_id;
+        if(patch.scrollerIds != null){
+            let length = patch.scrollerIds.length;
+            if(length != 0) {
+                scroller_id = patch.scrollerIds[length-1];
+            }
+        }
+        if(patch.idChain != undefined && patch.zIndex != undefined){
+            NativeElementPool.addNativeElement(runningChain, this.baseOcclusionContext,
+                this.scrollers, patch.idChain, scroller_id, patch.zIndex);
+        }
+        // @ts-ignore
+        this.textNodes[patch.idChain] = runningChain;
+    }
+
+    dropdownUpdate(patch: DropdownUpdatePatch) {
+        // @ts-ignore
+        let leaf = this.textNodes[patch.id_chain];
+        console.assert(leaf !== undefined);
+        let dropdown = leaf.firstChild;
+
+        applyTextTyle(dropdown, dropdown, patch.style);
+
+        if (patch.options != null) {
+            dropdown.innerHTML = "";
+            patch.options.forEach((label, i) => {
+                let option = document.createElement("option");
+                option.value = String(i);
+                option.textContent = label;
+                dropdown.appendChild(option);
+            });
+            dropdown.selectedIndex = Number(dropdown.dataset.selectedId ?? 0);
+        }
+        if (patch.selected_id != null) {
+            dropdown.dataset.selectedId = String(patch.selected_id);
+            dropdown.selectedIndex = patch.selected_id;
+        }
+        // Handle size_x and size_y
+        if (patch.size_x != null) {
+            dropdown.style.width = patch.size_x + "px";
+        }
+        if (patch.size_y != null) {
+            dropdown.style.height = patch.size_y + "px";
+        }
+        // Handle transform
+        if (patch.transform != null) {
+            leaf.style.transform = packAffineCoeffsIntoMatrix3DString(patch.transform);
+        }
+    }
+
+    dropdownDelete(id_chain: number[]) {
+        // @ts-ignore
+        let oldNode = this.textNodes[id_chain];
+        if (oldNode){
+            let parent = oldNode.parentElement;
+            parent.removeChild(oldNode);
+        }
+    }
+
     textboxCreate(patch: AnyCreatePatch) {
         const textbox = this.objectManager.getFromPool(INPUT) as HTMLInputElement;
         textbox.type = "text";
@@ -640,8 +735,26 @@ export class NativeElementPool {
 
         const BASE_PATH = getScriptBasePath('pax-chassis-web-interface.js');
 
-        let path = (BASE_PATH + patch.path!).replace("//", "/");
-        let image_data = await readImageToByteBuffer(path!)
+        // URLs with a scheme (`data:`, `blob:`, `https:` etc.) are fetched as they are; other paths are assets
+        let path = /^[a-z][a-z0-9+.-]*:/i.test(patch.path!)
+            ? patch.path!
+            : (BASE_PATH + patch.path!).replace("//", "/");
+        let image_data;
+        try {
+            image_data = await readImageToByteBuffer(path);
+        } catch (error) {
+            let message = {
+                "Image": {
+                    "Error": {
+                        "id_chain": patch.id_chain!,
+                        "path": patch.path!,
+                        "message": String(error),
+                    }
+                }
+            }
+            chassis.interrupt(JSON.stringify(message), []);
+            return
+        }
         let message = {
             "Image": {
                 "Data": {
@@ -43,6 +43,13 @@ export class ScrollManager {
         }
     }
 
+    // Moves the tracked position without reporting a delta, for when the engine moved the content
+    syncTo(scrollTop: number) {
+        this.lastScrollTop = scrollTop;
+        this.lastInterruptScrollTop = scrollTop;
+        this.predicting = false;
+    }
+
     getScrollDelta() {
         let ret;
         if (!this.predicting || this.stopped) {
@@ -64,6 +64,10 @@ export class Scroller {
         this.occlusionContext.build(this.container, idChain, chassis, canvasMap);
     }
 
+    getIdChain(){
+        return this.idChain;
+    }
+
     getTickScrollDelta(){
         return this.scrollManager?.getScrollDelta();
     }
@@ -142,6 +146,13 @@ export class Scroller {
                 // @ts-ignore
                 this.occlusionContext.updateNativeOverlays(this.sizeX, this.sizeY);
             }
+            if(msg.scrollOffsetX != null){
+                this.container.scrollLeft = msg.scrollOffsetX;
+            }
+            if(msg.scrollOffsetY != null){
+                this.container.scrollTop = msg.scrollOffsetY;
+                this.scrollManager?.syncTo(msg.scrollOffsetY);
+            }
             if(msg.sizeInnerPaneX != null || msg.sizeInnerPaneY != null){
                 // @ts-ignore
                 this.innerPane.style.width = String(this.sizeInnerPaneX)+'px';
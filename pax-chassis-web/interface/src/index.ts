@@ -6,6 +6,7 @@ import {
     ANY_CREATE_PATCH,
     BUTTON_UPDATE_PATCH,
     CHECKBOX_UPDATE_PATCH,
+    DROPDOWN_UPDATE_PATCH,
     FRAME_UPDATE_PATCH,
     IMAGE_LOAD_PATCH, OCCLUSION_UPDATE_PATCH, SCROLLER_UPDATE_PATCH,
     SUPPORTED_OBJECTS,
@@ -20,10 +21,12 @@ import {FrameUpdatePatch} from "./classes/messages/frame-update-patch";
 import {ImageLoadPatch} from "./classes/messages/image-load-patch";
 import {ScrollerUpdatePatch} from "./classes/messages/scroller-update-patch";
 import {setupEventListeners} from "./events/listeners";
+import {watchForTemplateUpdates} from "./utils/hot-reload";
 import "./styles/pax-web.css";
 import { OcclusionUpdatePatch } from "./classes/messages/occlusion-update-patch";
 import { ButtonUpdatePatch } from "./classes/messages/button-update-patch";
 import { TextboxUpdatePatch } from "./classes/messages/textbox-update-patch";
+import { DropdownUpdatePatch } from "./classes/messages/dropdown-update-patch";
 
 let objectManager = new ObjectManager(SUPPORTED_OBJECTS);
 let messages : any[];
@@ -105,6 +108,7 @@ function renderLoop (chassis: PaxChassisWeb, mount: Element, get_latest_memory:
         window.addEventListener('resize', resizeHandler);
         resizeHandler();//Fire once manually to init viewport size & occlusion context
         setupEventListeners(chassis);
+        watchForTemplateUpdates(chassis);
         initializedChassis = true;
     }
 
@@ -127,6 +131,9 @@ export function processMessages(messages: any[], chassis: PaxChassisWeb, objectM
             let patch: OcclusionUpdatePatch = objectManager.getFromPool(OCCLUSION_UPDATE_PATCH);
             patch.fromPatch(msg);
             nativePool.occlusionUpdate(patch);
+        } else if(unwrapped_msg["EffectsUpdate"]) {
+            let msg = unwrapped_msg["EffectsUpdate"]
+            nativePool.effectsUpdate(msg["id_chain"], msg["opacity"], msg["blur"]);
         } else if(unwrapped_msg["ButtonCreate"]) {
             let msg = unwrapped_msg["ButtonCreate"]
             let patch: AnyCreatePatch = objectManager.getFromPool(ANY_CREATE_PATCH);
@@ -153,6 +160,19 @@ export function processMessages(messages: any[], chassis: PaxChassisWeb, objectM
         }else if (unwrapped_msg["CheckboxDelete"]) {
             let msg = unwrapped_msg["CheckboxDelete"];
             nativePool.checkboxDelete(msg)
+        } else if(unwrapped_msg["DropdownCreate"]) {
+            let msg = unwrapped_msg["DropdownCreate"]
+            let patch: AnyCreatePatch = objectManager.getFromPool(ANY_CREATE_PATCH);
+            patch.fromPatch(msg);
+            nativePool.dropdownCreate(patch);
+        } else if (unwrapped_msg["DropdownUpdate"]){
+            let msg = unwrapped_msg["DropdownUpdate"]
+            let patch: DropdownUpdatePatch = objectManager.getFromPool(DROPDOWN_UPDATE_PATCH, objectManager);
+            patch.fromPatch(msg, nativePool.registeredFontFaces);
+            nativePool.dropdownUpdate(patch);
+        }else if (unwrapped_msg["DropdownDelete"]) {
+            let msg = unwrapped_msg["DropdownDelete"];
+            nativePool.dropdownDelete(msg)
         } else if(unwrapped_msg["TextboxCreate"]) {
             let msg = unwrapped_msg["TextboxCreate"]
             let patch: AnyCreatePatch = objectManager.getFromPool(ANY_CREATE_PATCH);
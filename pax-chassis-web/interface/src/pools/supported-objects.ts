@@ -13,12 +13,14 @@ import { CheckboxUpdatePatch } from "../classes/messages/checkbox-update-patch";
 import { OcclusionUpdatePatch } from "../classes/messages/occlusion-update-patch";
 import { ButtonUpdatePatch } from "../classes/messages/button-update-patch";
 import { TextboxUpdatePatch } from "../classes/messages/textbox-update-patch";
+import { DropdownUpdatePatch } from "../classes/messages/dropdown-update-patch";
 
 export const OBJECT = "Object";
 export const ARRAY = "Array";
 export const DIV = "DIV";
 export const INPUT = "Input";
 export const BUTTON = "Button";
+export const SELECT = "Select";
 export const CANVAS = "Canvas";
 export const ANY_CREATE_PATCH = "Any Create Patch";
 export const OCCLUSION_UPDATE_PATCH = "Occlusion Update Patch";
@@ -29,6 +31,7 @@ export const TEXT_UPDATE_PATCH = "Text Update Patch";
 export const CHECKBOX_UPDATE_PATCH = "Checkbox Update Patch";
 export const TEXTBOX_UPDATE_PATCH = "Textbox Update Patch";
 export const BUTTON_UPDATE_PATCH = "Button Update Patch";
+export const DROPDOWN_UPDATE_PATCH = "Dropdown Update Patch";
 
 export const LAYER = "LAYER";
 export const OCCLUSION_CONTEXT = "Occlusion Context";
@@ -67,6 +70,14 @@ export let SUPPORTED_OBJECTS = [{
             input.innerHTML= "";
         }
     },
+    {
+        name: SELECT,
+        factory: () => document.createElement("select"),
+        cleanUp: (select: HTMLSelectElement) => {
+            select.removeAttribute("style");
+            select.innerHTML= "";
+        }
+    },
     {
         name: ARRAY,
         factory: () => ([]),
@@ -137,6 +148,11 @@ export let SUPPORTED_OBJECTS = [{
         factory: (objectManager: ObjectManager) => new ButtonUpdatePatch(objectManager),
         cleanUp: (patch: CheckboxUpdatePatch) => {patch.cleanUp()},
     },
+    {
+        name: DROPDOWN_UPDATE_PATCH,
+        factory: (objectManager: ObjectManager) => new DropdownUpdatePatch(objectManager),
+        cleanUp: (patch: DropdownUpdatePatch) => {patch.cleanUp()},
+    },
     {
         name: IMAGE_LOAD_PATCH,
         factory: () => new ImageLoadPatch(),
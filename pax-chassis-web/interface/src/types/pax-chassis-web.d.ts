@@ -48,6 +48,10 @@ export class PaxChassisWeb {
 */
   deallocate(slice: MemorySlice): void;
 /**
+* @param {string} template_json
+*/
+  update_template(template_json: string): void;
+/**
 * @returns {MemorySlice}
 */
   tick(): MemorySlice;
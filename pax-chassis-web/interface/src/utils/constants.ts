@@ -7,3 +7,5 @@ export const NATIVE_CLIPPING_CLASS = "native-clipping";
 export const CLIP_PREFIX = "clip"
 export const BUTTON_CLASS = "button";
 export const BUTTON_TEXT_CONTAINER_CLASS = "button-text-container";
+export const HOT_RELOAD_ENDPOINT = "__pax/hot-reload";
+export const HOT_RELOAD_POLL_INTERVAL_MS = 500;
@@ -8,6 +8,9 @@ export function getStringIdFromClippingId(prefix: string, id_chain: number[]) {
 
 export async function readImageToByteBuffer(imagePath: string): Promise<{ pixels: Uint8ClampedArray, width: number, height: number }> {
     const response = await fetch(imagePath);
+    if (!response.ok) {
+        throw new Error(`${response.status} ${response.statusText}`);
+    }
     const blob = await response.blob();
     const img = await createImageBitmap(blob);
     const canvas = new OffscreenCanvas(img.width+1000, img.height);
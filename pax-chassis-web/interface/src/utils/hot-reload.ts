@@ -0,0 +1,31 @@
+import type {PaxChassisWeb} from "../types/pax-chassis-web";
+import {HOT_RELOAD_ENDPOINT, HOT_RELOAD_POLL_INTERVAL_MS} from "./constants";
+
+// Polls the `pax run --watch` dev server for reparsed component templates and swaps them into the
+// running chassis.  When the server had to rebuild the project instead, the page is reloaded.
+// Does nothing when served without `--watch`.
+export function watchForTemplateUpdates(chassis: PaxChassisWeb) {
+    let build: number | undefined = undefined;
+    let applied = 0;
+
+    let poll = async () => {
+        let response = await fetch(`${HOT_RELOAD_ENDPOINT}?since=${applied}`);
+        if (!response.ok) {
+            return;
+        }
+        let update: {build: number, templates: string[]} = await response.json();
+        if (build !== undefined && update.build !== build) {
+            window.location.reload();
+            return;
+        }
+        build = update.build;
+        update.templates.forEach((template) => {
+            chassis.update_template(template);
+        });
+        applied += update.templates.length;
+        setTimeout(start, HOT_RELOAD_POLL_INTERVAL_MS);
+    };
+    // Stop quietly once the dev server goes away
+    let start = () => poll().catch(() => {});
+    start();
+}
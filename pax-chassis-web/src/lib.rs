@@ -2,11 +2,17 @@
 
 use js_sys::Uint8Array;
 use log::Level;
+#[cfg(not(feature = "designtime"))]
+use pax_manifest::ComponentTemplate;
 use pax_message::ImageLoadInterruptArgs;
 use pax_runtime::api::math::Point2;
+use pax_runtime::api::BlendMode;
 use pax_runtime::api::ButtonClick;
 use pax_runtime::api::CheckboxChange;
+use pax_runtime::api::DropdownChange;
+use pax_runtime::api::ImageError;
 use pax_runtime::api::RenderContext;
+use pax_runtime::api::Scroll;
 use pax_runtime::api::TextInput;
 use pax_runtime::api::TextboxChange;
 use pax_runtime::api::TextboxInput;
@@ -50,7 +56,6 @@ pub fn wasm_memory() -> JsValue {
 pub struct PaxChassisWeb {
     drawing_contexts: Renderer<WebRenderContext<'static>>,
     engine: Rc<RefCell<PaxEngine>>,
-    #[cfg(feature = "designtime")]
     definition_to_instance_traverser: pax_cartridge::DefinitionToInstanceTraverser,
     #[cfg(feature = "designtime")]
     designtime_manager: Rc<RefCell<DesigntimeManager>>,
@@ -86,6 +91,7 @@ impl PaxChassisWeb {
         let main_component_instance = definition_to_instance_traverser.get_main_component();
         let expression_table = ExpressionTable {
             table: pax_cartridge::instantiate_expression_table(),
+            theme: Default::default(),
         };
 
         #[cfg(feature = "designtime")]
@@ -119,6 +125,7 @@ impl PaxChassisWeb {
             Self {
                 engine: engine_container,
                 drawing_contexts: Renderer::new(),
+                definition_to_instance_traverser,
             }
         }
     }
@@ -146,8 +153,16 @@ impl PaxChassisWeb {
         canvas.set_height(height as u32);
         let _ = context.scale(dpr, dpr);
 
+        let blend_context = context.clone();
+        let alpha_context = context.clone();
         let render_context = WebRenderContext::new(context, window.clone());
         self.drawing_contexts.add_context(&id, render_context);
+        self.drawing_contexts
+            .set_blend_hook(&id, move |mode: BlendMode| {
+                let _ = blend_context.set_global_composite_operation(mode.css_name());
+            });
+        self.drawing_contexts
+            .set_alpha_hook(&id, move |alpha| alpha_context.set_global_alpha(alpha));
     }
 
     pub fn send_viewport_update(&mut self, width: f64, height: f64) {
@@ -179,6 +194,21 @@ impl PaxChassisWeb {
                     );
                     false
                 }
+                ImageLoadInterruptArgs::Error(error_args) => {
+                    // The image may have been unmounted while it was loading
+                    if let Some(node) = engine.get_expanded_node(error_args.id_chain[0]) {
+                        node.dispatch_image_error(
+                            ImageError {
+                                path: error_args.path,
+                                message: error_args.message,
+                            },
+                            globals,
+                            &engine.runtime_context,
+                        )
+                    } else {
+                        false
+                    }
+                }
             },
             NativeInterrupt::FormButtonClick(args) => {
                 let node = engine
@@ -228,6 +258,18 @@ impl PaxChassisWeb {
                     &engine.runtime_context,
                 )
             }
+            NativeInterrupt::FormDropdownChange(args) => {
+                let node = engine
+                    .get_expanded_node(args.id_chain[0])
+                    .expect("dropdown node exists in engine");
+                node.dispatch_dropdown_change(
+                    DropdownChange {
+                        selected_id: args.selected_id,
+                    },
+                    globals,
+                    &engine.runtime_context,
+                )
+            }
 
             NativeInterrupt::AddedLayer(_args) => false,
             NativeInterrupt::Click(args) => {
@@ -252,7 +294,16 @@ impl PaxChassisWeb {
                     false
                 }
             }
-            NativeInterrupt::Scroll(_args) => false,
+            NativeInterrupt::Scroll(args) => {
+                let node = engine
+                    .get_expanded_node(args.id_chain[0])
+                    .expect("scroller node exists in engine");
+                node.scroll(Scroll {
+                    delta_x: args.delta_x,
+                    delta_y: args.delta_y,
+                });
+                false
+            }
             NativeInterrupt::Clap(args) => {
                 let prospective_hit = engine
                     .runtime_context
@@ -578,6 +629,21 @@ impl PaxChassisWeb {
         }
     }
 
+    /// Swaps in a component template reparsed by `pax run --watch` (a serialized
+    /// `ComponentTemplate`), keeping the state of the component's mounted instances
+    #[cfg(not(feature = "designtime"))]
+    pub fn update_template(&mut self, template_json: String) {
+        let template: ComponentTemplate = serde_json::from_str(&template_json)
+            .expect("Malformed template from `pax run --watch`");
+        let type_id = template.get_containing_component_type_id();
+        self.definition_to_instance_traverser
+            .set_component_template(template);
+        let traverser = &self.definition_to_instance_traverser;
+        self.engine
+            .borrow_mut()
+            .replace_component_template(&type_id, || traverser.build_component_template(&type_id));
+    }
+
     #[cfg(feature = "designtime")]
     pub fn update_userland_component(&mut self) {
         let current_manifest_version = self.designtime_manager.borrow().get_manifest_version();
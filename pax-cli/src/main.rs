@@ -6,7 +6,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{process, thread};
 
-use pax_compiler::{CreateContext, RunContext, RunTarget};
+use pax_compiler::{CreateContext, RunContext, RunTarget, TestContext};
 extern crate pax_language_server;
 
 mod http;
@@ -82,6 +82,10 @@ fn main() -> Result<(), Report> {
                 .arg( ARG_TARGET.clone() )
                 .arg( ARG_VERBOSE.clone() )
                 .arg( ARG_LIBDEV.clone() )
+                .arg(Arg::with_name("watch")
+                    .long("watch")
+                    .takes_value(false)
+                    .help("Hot reload the running app as its source changes.  Template-only changes are swapped in without losing state; other changes rebuild and reload the app.  Web only."))
         )
         .subcommand(
             App::new("build")
@@ -92,6 +96,17 @@ fn main() -> Result<(), Report> {
                 .arg( ARG_LIBDEV.clone() )
                 .arg( ARG_RELEASE.clone() )
         )
+        .subcommand(
+            App::new("test")
+                .about("Compiles the Pax project from the current working directory, then runs its `cargo test`s against the generated cartridge.  Tests can mount the main component with `pax_cartridge::instantiate_test_harness`.")
+                .arg( ARG_PATH.clone() )
+                .arg( ARG_VERBOSE.clone() )
+                .arg( ARG_LIBDEV.clone() )
+                .arg(Arg::with_name("filter")
+                    .help("Only run tests whose names contain this string; forwarded to the test binaries as with `cargo test`")
+                    .takes_value(true)
+                    .index(1))
+        )
         .subcommand(
             App::new("clean")
                 .arg( ARG_PATH.clone() )
@@ -165,12 +180,14 @@ fn perform_nominal_action(
             let path = args.value_of("path").unwrap().to_string(); //default value "."
             let verbose = args.is_present("verbose");
             let is_libdev_mode = args.is_present("libdev");
+            let should_watch = args.is_present("watch");
 
             let _ = pax_compiler::perform_build(&RunContext {
                 target: RunTarget::from(target.as_str()),
                 path,
                 verbose,
                 should_also_run: true,
+                should_watch,
                 is_libdev_mode,
                 process_child_ids,
                 is_release: false,
@@ -189,6 +206,7 @@ fn perform_nominal_action(
                 target: RunTarget::from(target.as_str()),
                 path,
                 should_also_run: false,
+                should_watch: false,
                 verbose,
                 is_libdev_mode,
                 process_child_ids,
@@ -197,6 +215,23 @@ fn perform_nominal_action(
 
             Ok(())
         }
+        ("test", Some(args)) => {
+            let path = args.value_of("path").unwrap().to_string(); //default value "."
+            let verbose = args.is_present("verbose");
+            let is_libdev_mode = args.is_present("libdev");
+            let cargo_test_args = args
+                .value_of("filter")
+                .map(|filter| vec![filter.to_string()])
+                .unwrap_or_default();
+
+            pax_compiler::perform_test(&TestContext {
+                path,
+                verbose,
+                is_libdev_mode,
+                process_child_ids,
+                cargo_test_args,
+            })
+        }
         ("clean", Some(args)) => {
             println!("🧹 Cleaning cached & temporary files...");
             let path = args.value_of("path").unwrap().to_string(); //default value "."
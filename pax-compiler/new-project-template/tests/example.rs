@@ -0,0 +1,17 @@
+//! Run with `pax-cli test`, which generates the cartridge these tests mount.
+
+use pax_std::primitives::Text;
+
+#[test]
+fn clicking_the_rectangle_updates_the_message() {
+    let mut harness = pax_cartridge::instantiate_test_harness((800.0, 600.0));
+    harness.tick(1);
+
+    harness.click(400.0, 300.0);
+    harness.tick(1);
+
+    let text = harness.get_nodes_by_id("text").remove(0);
+    text.with_properties(|text: &mut Text| {
+        assert_eq!(text.text.get().string, "1 clicks");
+    });
+}
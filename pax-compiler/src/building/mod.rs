@@ -19,12 +19,16 @@ use tar::Archive;
 
 use crate::{
     helpers::{copy_dir_recursively, ALL_PKGS, DIR_IGNORE_LIST_MACOS, PKG_DIR_NAME},
+    hot_reload::HotReloadState,
     RunContext, RunTarget,
 };
 
 use self::{apple::build_apple_chassis_with_cartridge, web::build_web_chassis_with_cartridge};
 
+pub use self::test::run_tests_with_cartridge;
+
 pub mod apple;
+pub mod test;
 pub mod web;
 
 #[cfg(feature = "designtime")]
@@ -37,6 +41,7 @@ pub fn build_chassis_with_cartridge(
     pax_dir: &PathBuf,
     ctx: &RunContext,
     process_child_ids: Arc<Mutex<Vec<u64>>>,
+    hot_reload: Option<Arc<Mutex<HotReloadState>>>,
 ) -> Result<Option<PathBuf>, eyre::Report> {
     let target: &RunTarget = &ctx.target;
     let target_str: &str = target.into();
@@ -78,7 +83,8 @@ pub fn build_chassis_with_cartridge(
             Ok(None)
         }
         RunTarget::Web => {
-            let fs = build_web_chassis_with_cartridge(ctx, &pax_dir, process_child_ids)?;
+            let fs =
+                build_web_chassis_with_cartridge(ctx, &pax_dir, process_child_ids, hot_reload)?;
             Ok(Some(fs))
         }
     }
@@ -132,11 +138,11 @@ pub fn update_type_id_prefixes_in_place(
 /// The packages in `.pax/pkg` are both where we write our codegen (into pax-cartridge)
 /// and where we build chassis and chassis-interfaces. (for example, running `wasm-pack` inside `.pax/pkg/pax-chassis-web`.
 /// This assumes that you are in the examples/src directory in the monorepo
-pub fn clone_all_to_pkg_dir(pax_dir: &PathBuf, pax_version: &Option<String>, ctx: &RunContext) {
+pub fn clone_all_to_pkg_dir(pax_dir: &PathBuf, pax_version: &Option<String>, is_libdev_mode: bool) {
     let dest_pkg_root = pax_dir.join(PKG_DIR_NAME);
     #[cfg(feature = "designtime")]
     {
-        if ctx.is_libdev_mode {
+        if is_libdev_mode {
             let pax_corp_root = if let Ok(specified_override) = std::env::var("PAX_CORP_ROOT") {
                 PathBuf::from(&specified_override)
             } else {
@@ -159,7 +165,7 @@ pub fn clone_all_to_pkg_dir(pax_dir: &PathBuf, pax_version: &Option<String>, ctx
     }
 
     for pkg in ALL_PKGS {
-        if ctx.is_libdev_mode {
+        if is_libdev_mode {
             //Copy all packages from monorepo root on every build.  this allows us to propagate changes
             //to a libdev build without "sticky caches."
             let pax_workspace_root =
@@ -0,0 +1,58 @@
+use crate::helpers::{wait_with_output, ALL_PKGS, ERR_SPAWN, PKG_DIR_NAME};
+use crate::{pre_exec_hook, TestContext};
+
+use color_eyre::eyre;
+use eyre::eyre;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+/// Runs `cargo test` in the userland project, with every Pax package -- most importantly the
+/// freshly generated `pax-cartridge` -- patched to its copy inside `.pax/pkg`.  The patch is
+/// passed on the command line rather than written to the userland `Cargo.toml`, so that the
+/// project is left untouched.
+pub fn run_tests_with_cartridge(
+    pax_dir: &PathBuf,
+    ctx: &TestContext,
+    process_child_ids: Arc<Mutex<Vec<u64>>>,
+) -> Result<(), eyre::Report> {
+    let pkg_dir = pax_dir.join(PKG_DIR_NAME);
+
+    let mut cmd = Command::new("cargo");
+    cmd.current_dir(&ctx.path)
+        .arg("test")
+        .env("PAX_DIR", pax_dir)
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit());
+
+    for pkg in ALL_PKGS {
+        cmd.arg("--config").arg(format!(
+            "patch.crates-io.{}.path={:?}",
+            pkg,
+            pkg_dir.join(pkg).to_str().unwrap()
+        ));
+    }
+
+    if !ctx.verbose {
+        cmd.arg("--quiet");
+    }
+
+    if !ctx.cargo_test_args.is_empty() {
+        cmd.arg("--").args(&ctx.cargo_test_args);
+    }
+
+    #[cfg(unix)]
+    unsafe {
+        cmd.pre_exec(pre_exec_hook);
+    }
+
+    let child = cmd.spawn().expect(ERR_SPAWN);
+    let output = wait_with_output(&process_child_ids, child);
+    if !output.status.success() {
+        return Err(eyre!("Tests failed."));
+    }
+
+    Ok(())
+}
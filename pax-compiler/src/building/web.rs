@@ -2,6 +2,7 @@ use crate::helpers::{
     wait_with_output, ASSETS_DIR_NAME, BUILD_DIR_NAME, DIR_IGNORE_LIST_WEB, ERR_SPAWN, PAX_BADGE,
     PKG_DIR_NAME, PUBLIC_DIR_NAME,
 };
+use crate::hot_reload::HotReloadState;
 use crate::{copy_dir_recursively, pre_exec_hook, RunContext, RunTarget};
 
 use color_eyre::eyre;
@@ -12,9 +13,10 @@ use std::process::Command;
 use std::sync::{Arc, Mutex};
 
 use actix_web::middleware::Logger;
-use actix_web::{App, HttpServer};
+use actix_web::{web, App, HttpResponse, HttpServer};
 use env_logger;
 use eyre::eyre;
+use pax_runtime::api::serde::Deserialize;
 use std::io::Write;
 use std::net::TcpListener;
 #[cfg(unix)]
@@ -26,6 +28,7 @@ pub fn build_web_chassis_with_cartridge(
     ctx: &RunContext,
     pax_dir: &PathBuf,
     process_child_ids: Arc<Mutex<Vec<u64>>>,
+    hot_reload: Option<Arc<Mutex<HotReloadState>>>,
 ) -> Result<PathBuf, eyre::Report> {
     let target: &RunTarget = &ctx.target;
     let target_str: &str = target.into();
@@ -122,7 +125,7 @@ pub fn build_web_chassis_with_cartridge(
     // Start local server if this is a `run` rather than a `build`
     if ctx.should_also_run {
         println!("{} 🐇 Running Pax Web...", *PAX_BADGE);
-        let _ = start_static_http_server(interface_path.join(PUBLIC_DIR_NAME));
+        let _ = start_static_http_server(interface_path.join(PUBLIC_DIR_NAME), hot_reload);
     } else {
         println!(
             "{} 🗂️ Done: {} build available at {}",
@@ -134,7 +137,35 @@ pub fn build_web_chassis_with_cartridge(
     Ok(build_src)
 }
 
-fn start_static_http_server(fs_path: PathBuf) -> std::io::Result<()> {
+/// Query of the running app, polling for changes while hot reloading
+#[derive(Deserialize)]
+#[serde(crate = "pax_runtime::api::serde")]
+struct HotReloadQuery {
+    /// Number of templates of the current build that the app has already applied
+    since: usize,
+}
+
+/// Serves the current build number and the templates reparsed since `since`; a 404 tells the
+/// app that this server isn't watching
+async fn serve_hot_reload(
+    state: web::Data<Option<Arc<Mutex<HotReloadState>>>>,
+    query: web::Query<HotReloadQuery>,
+) -> HttpResponse {
+    let Some(state) = state.get_ref() else {
+        return HttpResponse::NotFound().finish();
+    };
+    let state = state.lock().unwrap();
+    let templates = state.templates.get(query.since..).unwrap_or_default();
+    HttpResponse::Ok().json(serde_json::json!({
+        "build": state.build,
+        "templates": templates,
+    }))
+}
+
+fn start_static_http_server(
+    fs_path: PathBuf,
+    hot_reload: Option<Arc<Mutex<HotReloadState>>>,
+) -> std::io::Result<()> {
     // Initialize logging
 
     std::env::set_var("RUST_LOG", "actix_web=info");
@@ -157,10 +188,15 @@ fn start_static_http_server(fs_path: PathBuf) -> std::io::Result<()> {
                 let address_msg = format!("http://127.0.0.1:{}", port).blue();
                 let server_running_at_msg = format!("Server running at {}", address_msg).bold();
                 println!("{} 📠 {}", *PAX_BADGE, server_running_at_msg);
+                let hot_reload = web::Data::new(hot_reload.clone());
                 break HttpServer::new(move || {
-                    App::new().wrap(Logger::new("| %s | %U")).service(
-                        actix_files::Files::new("/*", fs_path.clone()).index_file("index.html"),
-                    )
+                    App::new()
+                        .wrap(Logger::new("| %s | %U"))
+                        .app_data(hot_reload.clone())
+                        .route("/__pax/hot-reload", web::get().to(serve_hot_reload))
+                        .service(
+                            actix_files::Files::new("/*", fs_path.clone()).index_file("index.html"),
+                        )
                 })
                 .bind(("127.0.0.1", port))
                 .expect("Error binding to address")
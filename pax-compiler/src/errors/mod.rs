@@ -75,7 +75,10 @@ impl fmt::Display for PaxTemplateError {
             TokenType::IfExpression => "Invalid 'if' expression.",
             TokenType::ForPredicate => "Invalid 'for' predicate.",
             TokenType::ForSource => "Invalid 'for' source.",
+            TokenType::ForKey => "Invalid 'for' key.",
             TokenType::SlotExpression => "Invalid slot expression.",
+            TokenType::MatchExpression => "Invalid 'match' expression.",
+            TokenType::MatchPattern => "Invalid 'match' pattern.",
             TokenType::EventId => "Invalid event ID.",
             TokenType::Handler => "Invalid handler.",
             TokenType::SettingKey => "Invalid setting key.",
@@ -1,8 +1,8 @@
 use pax_manifest::{
-    escape_identifier, ComponentDefinition, ComponentTemplate,
+    escape_identifier, ComponentDefinition, ComponentTemplate, ControlFlowMatchPatternDefinition,
     ControlFlowRepeatPredicateDefinition, ExpressionSpec, ExpressionSpecInvocation, HostCrateInfo,
-    PaxManifest, PropertyDefinition, PropertyDefinitionFlags, SettingElement, TemplateNodeId,
-    Token, TypeDefinition, TypeId, TypeTable, ValueDefinition,
+    PaxManifest, PaxType, PropertyDefinition, PropertyDefinitionFlags, SettingElement,
+    TemplateNodeId, Token, TypeDefinition, TypeId, TypeTable, ValueDefinition,
 };
 use std::any::Any;
 use std::collections::HashMap;
@@ -11,10 +11,15 @@ use std::slice::IterMut;
 
 use crate::errors::source_map::SourceMap;
 use crate::errors::PaxTemplateError;
+use crate::parsing::{PaxParser, Rule};
+use crate::type_checking::{check_paxel_type, infer_paxel_type, PaxelType};
 use color_eyre::eyre;
 use color_eyre::eyre::Report;
 use lazy_static::lazy_static;
 use pax_manifest::constants::COMMON_PROPERTIES_TYPE;
+use pax_manifest::selectors::SelectorSubject;
+use pax_runtime_api::EasingCurve;
+use pest::Parser;
 
 pub fn compile_all_expressions<'a>(
     manifest: &'a mut PaxManifest,
@@ -72,7 +77,10 @@ fn get_output_type_by_property_identifier(
     prop_defs: &Vec<PropertyDefinition>,
     property_identifier: &str,
 ) -> String {
-    let output_type = if let Some(common_match) = COMMON_PROPERTIES_TYPE
+    let output_type = if property_identifier == "class" {
+        // e.g. `class={self.is_active ? "active" : "inactive"}`, whitespace-separated class names
+        "String".to_string()
+    } else if let Some(common_match) = COMMON_PROPERTIES_TYPE
         .iter()
         .find(|cpt| cpt.0 == property_identifier)
     {
@@ -92,6 +100,34 @@ fn get_output_type_by_property_identifier(
     output_type
 }
 
+/// The type an expression bound to `property_identifier` must have, for type checking
+fn get_paxel_type_by_property_identifier(
+    ctx: &ExpressionCompilationContext,
+    prop_defs: &[PropertyDefinition],
+    property_identifier: &str,
+) -> PaxelType {
+    if property_identifier == "class" {
+        PaxelType::String
+    } else if let Some((_, type_path)) = COMMON_PROPERTIES_TYPE
+        .iter()
+        .find(|cpt| cpt.0 == property_identifier)
+    {
+        let type_id = if type_path.contains("::") {
+            TypeId::build_singleton(type_path, None)
+        } else {
+            TypeId::build_primitive(type_path)
+        };
+        PaxelType::of(&type_id, ctx.type_table)
+    } else if let Some(local_match) = prop_defs
+        .iter()
+        .find(|property_def| property_def.name == property_identifier)
+    {
+        PaxelType::of(&local_match.type_id, ctx.type_table)
+    } else {
+        PaxelType::Unknown
+    }
+}
+
 fn recurse_compile_literal_block<'a>(
     settings_pairs: &mut IterMut<SettingElement>,
     ctx: &mut ExpressionCompilationContext,
@@ -105,6 +141,21 @@ fn recurse_compile_literal_block<'a>(
                 // LiteralValue:       no need to compile literal values
                 // EventBindingTarget: event bindings are handled on a separate compiler pass; no-op here
                 ValueDefinition::LiteralValue(_) | ValueDefinition::EventBindingTarget(_) => {}
+                ValueDefinition::Timeline(keyframes) => {
+                    // Keyframes are literal values too; only their easing curves need checking
+                    for curve in keyframes.iter().filter_map(|k| k.curve.as_ref()) {
+                        if EasingCurve::from_name(&curve.token_value).is_none() {
+                            return Err(PaxTemplateError::new(
+                                Some(format!(
+                                    "Unknown easing curve `{}`; expected one of {}",
+                                    curve.token_value,
+                                    EasingCurve::NAMES.join(", ")
+                                )),
+                                curve.clone(),
+                            ));
+                        }
+                    }
+                }
                 ValueDefinition::Block(block) => {
                     let type_def = (current_property_definitions
                         .iter()
@@ -134,10 +185,20 @@ fn recurse_compile_literal_block<'a>(
                         &current_property_definitions,
                         &token.token_value,
                     );
+                    let (output_statement, invocations, paxel_type) =
+                        compile_paxel_to_ril(input.clone(), &ctx)?;
+                    check_paxel_type(
+                        input,
+                        &paxel_type,
+                        &get_paxel_type_by_property_identifier(
+                            ctx,
+                            &current_property_definitions,
+                            &token.token_value,
+                        ),
+                        &format!("`{}`", token.token_value),
+                    )?;
 
                     let id = ctx.vtable_uid_gen.next().unwrap();
-                    let (output_statement, invocations) =
-                        compile_paxel_to_ril(input.clone(), &ctx)?;
 
                     let mut whitespace_removed_input = input.clone().token_value;
                     whitespace_removed_input.retain(|c| !c.is_whitespace());
@@ -183,14 +244,20 @@ fn recurse_compile_literal_block<'a>(
                         .get_type_definition(ctx.type_table);
                         let output_type = type_def.type_id.clone();
 
-                        //Write this id back to the manifest, for downstream use by RIL component tree generator
-                        let mut manifest_id_insert: Option<usize> = Some(id);
-                        std::mem::swap(manifest_id, &mut manifest_id_insert);
-
                         //a single identifier binding is the same as an expression returning that identifier, `{self.some_identifier}`
                         //thus, we can compile it as PAXEL and make use of any shared logic, e.g. `self`/`this` handling
-                        let (output_statement, invocations) =
+                        let (output_statement, invocations, paxel_type) =
                             compile_paxel_to_ril(identifier.clone(), &ctx)?;
+                        check_paxel_type(
+                            identifier,
+                            &paxel_type,
+                            &PaxelType::of(&output_type, ctx.type_table),
+                            &format!("`{}`", token.token_value),
+                        )?;
+
+                        //Write this id back to the manifest, for downstream use by RIL component tree generator
+                        let mut manifest_id_insert: Option<usize> = Some(id);
+                        std::mem::swap(manifest_id, &mut manifest_id_insert);
 
                         let source_map_id = source_map.insert(identifier.clone());
                         let input_statement = source_map
@@ -224,6 +291,8 @@ fn recurse_compile_expressions<'a>(
     mut source_map: &mut SourceMap,
 ) -> eyre::Result<ExpressionCompilationContext<'a>, Report> {
     let mut incremented = false;
+    // For a `match`, the payloads bound by each of its arms, to be resolved by that arm's nodes
+    let mut match_arm_scopes: Vec<HashMap<String, PropertyDefinition>> = vec![];
 
     let cloned_settings_block = ctx.component_def.settings.clone();
     let mut active_node_def = ctx
@@ -231,9 +300,15 @@ fn recurse_compile_expressions<'a>(
         .get_node(&ctx.active_node_id.clone().unwrap())
         .unwrap()
         .clone();
-    let cloned_inline_settings = active_node_def.settings.clone();
+    let ancestors: Vec<SelectorSubject> = ctx
+        .template
+        .get_ancestors(&ctx.active_node_id.clone().unwrap())
+        .into_iter()
+        .map(SelectorSubject::of)
+        .collect();
     let mut merged_settings = PaxManifest::merge_inline_settings_with_settings_block(
-        &cloned_inline_settings,
+        &active_node_def,
+        &ancestors,
         &cloned_settings_block,
     );
     let mut cloned_control_flow_settings = active_node_def.control_flow_settings.clone();
@@ -257,9 +332,23 @@ fn recurse_compile_expressions<'a>(
             &mut inline_settings.iter_mut(),
             &mut ctx,
             property_def.clone(),
-            pascal_identifier,
+            pascal_identifier.clone(),
             &mut source_map,
         )?;
+
+        // A `class` bound to an expression switches at runtime between the blocks its values match
+        let mut class_bundles =
+            PaxManifest::build_class_bundles(&active_node_def, &ancestors, &cloned_settings_block);
+        for bundle in class_bundles.iter_mut() {
+            recurse_compile_literal_block(
+                &mut bundle.settings.iter_mut(),
+                &mut ctx,
+                property_def.clone(),
+                pascal_identifier.clone(),
+                &mut source_map,
+            )?;
+        }
+        active_node_def.class_bundles = (!class_bundles.is_empty()).then_some(class_bundles);
     } else if let Some(ref mut cfa) = cloned_control_flow_settings {
         //Handle attributes for control flow
         //Our purpose here is broadly twofold:
@@ -296,7 +385,7 @@ fn recurse_compile_expressions<'a>(
             } else if let Some(symbolic_binding) = &repeat_source_definition.symbolic_binding {
                 let inner_iterable_type_id = ctx
                     .resolve_symbol_as_prop_def(
-                        &symbolic_binding.token_value,
+                        &repeat_source_list_symbol(symbolic_binding)?,
                         symbolic_binding.clone(),
                     )?
                     .unwrap()
@@ -318,7 +407,7 @@ fn recurse_compile_expressions<'a>(
             //with the parser that we are only binding to a simple symbolic id, like `self.foo`.
             //This is because we are inferring the return type of this expression based on the declared-and-known
             //type of property `self.foo`
-            let (output_statement, invocations) = compile_paxel_to_ril(paxel.clone(), &ctx)?;
+            let (output_statement, invocations, _) = compile_paxel_to_ril(paxel.clone(), &ctx)?;
 
             //Figure out the return type for our datum — either `T` for `Property<Vec<T>>`, or `isize` for some range `j..k`
             //if repeat_source is a range, this is simply isize
@@ -328,7 +417,7 @@ fn recurse_compile_expressions<'a>(
             } else if let Some(symbolic_binding) = &repeat_source_definition.symbolic_binding {
                 let pd = ctx
                     .resolve_symbol_as_prop_def(
-                        &symbolic_binding.token_value,
+                        &repeat_source_list_symbol(symbolic_binding)?,
                         symbolic_binding.clone(),
                     )?
                     .ok_or::<eyre::Report>(PaxTemplateError::new(
@@ -365,6 +454,7 @@ fn recurse_compile_expressions<'a>(
                         flags: PropertyDefinitionFlags {
                             is_binding_repeat_i: false,
                             is_binding_repeat_elem: true,
+                            is_binding_match_arm: false,
                             is_repeat_source_range,
                             is_repeat_source_iterable,
                             is_property_wrapped: true,
@@ -388,6 +478,7 @@ fn recurse_compile_expressions<'a>(
                         flags: PropertyDefinitionFlags {
                             is_binding_repeat_elem: true,
                             is_binding_repeat_i: false,
+                            is_binding_match_arm: false,
                             is_repeat_source_range: is_repeat_source_range.clone(),
                             is_repeat_source_iterable: is_repeat_source_iterable.clone(),
                             is_property_wrapped: true,
@@ -400,6 +491,7 @@ fn recurse_compile_expressions<'a>(
                     i_property_definition.flags = PropertyDefinitionFlags {
                         is_binding_repeat_i: true,
                         is_binding_repeat_elem: false,
+                        is_binding_match_arm: false,
                         is_repeat_source_range,
                         is_repeat_source_iterable,
                         is_property_wrapped: true,
@@ -418,7 +510,8 @@ fn recurse_compile_expressions<'a>(
 
             // The return type for a repeat source expression will either be:
             //   1. isize, for ranges (including ranges with direct symbolic references as either operand, like `self.x..10`)
-            //   2. T for a direct symbolic reference to `self.x` for x : Property<Vec<T>>
+            //   2. T for a direct symbolic reference to `self.x` for x : Property<Vec<T>>, including
+            //      one passed through list methods that keep its elements, like `self.x.filter(...)`
             // Presumably, we could also support arbitrary expressions as a #3, but
             // we need some way to infer the return type, statically.  This may mean requiring
             // an explicit type declaration by the end-user, or perhaps we can hack something
@@ -445,10 +538,45 @@ fn recurse_compile_expressions<'a>(
                     is_repeat_source_iterable_expression: is_repeat_source_iterable,
                 },
             );
+
+            // Handle the `item.id` in `for item in self.items key item.id`, compiled with the
+            // predicate already on the scope_stack since it is evaluated once per element
+            if let Some(key_expression_paxel) = &cfa.repeat_key_expression_paxel {
+                let (output_statement, invocations, _) =
+                    compile_paxel_to_ril(key_expression_paxel.clone(), &ctx)?;
+                let id = ctx.vtable_uid_gen.next().unwrap();
+
+                cfa.repeat_key_expression_vtable_id = Some(id);
+
+                let mut whitespace_removed_input = key_expression_paxel.clone().token_value;
+                whitespace_removed_input.retain(|c| !c.is_whitespace());
+
+                let source_map_id = source_map.insert(key_expression_paxel.clone());
+                let input_statement =
+                    source_map.generate_mapped_string(whitespace_removed_input, source_map_id);
+
+                ctx.expression_specs.insert(
+                    id,
+                    ExpressionSpec {
+                        id,
+                        invocations,
+                        output_type: "RepeatKey".to_string(),
+                        output_statement,
+                        input_statement,
+                        is_repeat_source_iterable_expression: false,
+                    },
+                );
+            }
         } else if let Some(condition_expression_paxel) = &cfa.condition_expression_paxel {
             //Handle `if` boolean expression, e.g. the `num_clicks > 5` in `if num_clicks > 5 { ... }`
-            let (output_statement, invocations) =
+            let (output_statement, invocations, paxel_type) =
                 compile_paxel_to_ril(condition_expression_paxel.clone(), &ctx)?;
+            check_paxel_type(
+                condition_expression_paxel,
+                &paxel_type,
+                &PaxelType::Bool,
+                "An `if` condition",
+            )?;
             let id = ctx.vtable_uid_gen.next().unwrap();
 
             cfa.condition_expression_vtable_id = Some(id);
@@ -473,7 +601,7 @@ fn recurse_compile_expressions<'a>(
             );
         } else if let Some(slot_index_expression_paxel) = &cfa.slot_index_expression_paxel {
             //Handle `slot` index expression, e.g. the `i` in `slot(i)`
-            let (output_statement, invocations) =
+            let (output_statement, invocations, _) =
                 compile_paxel_to_ril(slot_index_expression_paxel.clone(), &ctx)?;
             let id = ctx.vtable_uid_gen.next().unwrap();
 
@@ -497,6 +625,199 @@ fn recurse_compile_expressions<'a>(
                     is_repeat_source_iterable_expression: false,
                 },
             );
+        } else if let Some(match_expression_paxel) = &cfa.match_expression_paxel {
+            //Handle `match` statement, e.g. the `self.mode` in `match self.mode { Mode::A => { ... } }`
+            //Its arms are compiled along with it, into a single expression evaluating to the active
+            //arm and that arm's destructured payloads, by binding name
+            let arm_patterns: Vec<ControlFlowMatchPatternDefinition> = ctx
+                .template
+                .get_children(&ctx.active_node_id.clone().unwrap())
+                .unwrap_or_default()
+                .iter()
+                .map(|arm_id| {
+                    let arm = ctx.template.get_node(arm_id).unwrap();
+                    arm.control_flow_settings
+                        .as_ref()
+                        .and_then(|cfs| cfs.match_arm_pattern.clone())
+                        .unwrap()
+                })
+                .collect();
+
+            let matched_prop_def_chain = ctx
+                .resolve_symbol_as_prop_def(
+                    &match_expression_paxel.token_value,
+                    match_expression_paxel.clone(),
+                )?
+                .ok_or::<eyre::Report>(PaxTemplateError::new(
+                    Some(format!(
+                        "Property not found: {}",
+                        match_expression_paxel.token_value
+                    )),
+                    match_expression_paxel.clone(),
+                ))?;
+            let matched_type_id = matched_prop_def_chain.last().unwrap().type_id.clone();
+            // An `Option`, matched by `Some(...)` and `None`, as in `if let Some(user) = self.user`
+            let option_inner_type_id = ctx.resolve_optional_inner_type_id(
+                &match_expression_paxel.token_value,
+                &matched_prop_def_chain,
+            );
+
+            let mut arms_ril = String::new();
+            for (i, pattern) in arm_patterns.iter().enumerate() {
+                let mut scope = HashMap::new();
+                let Some(variant) = &pattern.variant else {
+                    arms_ril += &format!("_ => MatchArm::new({}, vec![]),", i);
+                    match_arm_scopes.push(scope);
+                    continue;
+                };
+
+                // Payload types are recorded per variant, in order, on the enum's type definition
+                let variant_name = variant.token_value.rsplit("::").next().unwrap();
+                if option_inner_type_id.is_none() && !variant.token_value.contains("::") {
+                    return Err(PaxTemplateError::new(
+                        Some(format!(
+                            "`{}` matches an optional value, but `{}` is not optional",
+                            variant.token_value, match_expression_paxel.token_value
+                        )),
+                        variant.clone(),
+                    ));
+                }
+                if option_inner_type_id.is_some()
+                    && !matches!(variant.token_value.as_str(), "Some" | "None")
+                {
+                    return Err(PaxTemplateError::new(
+                        Some(format!(
+                            "`{}` can't match `{}`, which is optional; match it with `Some(...)` or `None`",
+                            variant.token_value, match_expression_paxel.token_value
+                        )),
+                        variant.clone(),
+                    ));
+                }
+                let payload_types: Vec<TypeId> = match &option_inner_type_id {
+                    Some(inner) if variant_name == "Some" => vec![inner.clone()],
+                    Some(_) => vec![],
+                    None => ctx
+                        .type_table
+                        .get(&matched_type_id)
+                        .map(|td| {
+                            td.property_definitions
+                                .iter()
+                                .filter(|pd| pd.flags.is_enum && pd.name == variant_name)
+                                .map(|pd| pd.type_id.clone())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                };
+                if pattern.bindings.len() != payload_types.len() && !pattern.bindings.is_empty() {
+                    return Err(PaxTemplateError::new(
+                        Some(format!(
+                            "{} has {} payload(s), but its pattern binds {}",
+                            variant.token_value,
+                            payload_types.len(),
+                            pattern.bindings.len()
+                        )),
+                        variant.clone(),
+                    ));
+                }
+
+                let mut bound = vec![];
+                for (binding, type_id) in pattern.bindings.iter().zip(payload_types.clone()) {
+                    if binding.token_value == "_" {
+                        continue;
+                    }
+                    // optional numbers and strings are matched as `Numeric`s and `StringBox`es, and
+                    // bound as the type they are declared with, like any other payload
+                    let value = if option_inner_type_id.is_some()
+                        && (ExpressionSpecInvocation::is_numeric(&type_id)
+                            || ExpressionSpecInvocation::is_primitive_string(&type_id))
+                    {
+                        format!(
+                            "{}::from({})",
+                            type_id.get_unique_identifier(),
+                            binding.token_value
+                        )
+                    } else {
+                        binding.token_value.clone()
+                    };
+                    bound.push(format!(
+                        "(\"{}\".to_string(), Rc::new(RefCell::new({})) as Rc<RefCell<dyn Any>>)",
+                        binding.token_value, value
+                    ));
+                    scope.insert(
+                        binding.token_value.clone(),
+                        PropertyDefinition {
+                            name: binding.token_value.clone(),
+                            type_id,
+                            flags: PropertyDefinitionFlags {
+                                is_binding_repeat_i: false,
+                                is_binding_repeat_elem: false,
+                                is_binding_match_arm: true,
+                                is_repeat_source_range: false,
+                                is_repeat_source_iterable: false,
+                                is_property_wrapped: true,
+                                is_enum: false,
+                            },
+                        },
+                    );
+                }
+                let destructuring = if pattern.bindings.is_empty() {
+                    // a variant named without bindings matches regardless of its payloads
+                    if payload_types.is_empty() {
+                        String::new()
+                    } else {
+                        "(..)".to_string()
+                    }
+                } else {
+                    let bindings: Vec<&str> = pattern
+                        .bindings
+                        .iter()
+                        .map(|binding| binding.token_value.as_str())
+                        .collect();
+                    format!("({})", bindings.join(", "))
+                };
+                arms_ril += &format!(
+                    "{}{} => MatchArm::new({}, vec![{}]),",
+                    variant.token_value,
+                    destructuring,
+                    i,
+                    bound.join(", ")
+                );
+                match_arm_scopes.push(scope);
+            }
+
+            let (matched_ril, invocations, _) =
+                compile_paxel_to_ril(match_expression_paxel.clone(), &ctx)?;
+            // Without a matching arm, nothing is mounted
+            let output_statement = format!(
+                "match {} {{ {} _ => MatchArm::default() }}",
+                matched_ril, arms_ril
+            );
+            let id = ctx.vtable_uid_gen.next().unwrap();
+
+            cfa.match_expression_vtable_id = Some(id);
+
+            let mut whitespace_removed_input = match_expression_paxel.clone().token_value;
+            whitespace_removed_input.retain(|c| !c.is_whitespace());
+
+            let source_map_id = source_map.insert(match_expression_paxel.clone());
+            let input_statement =
+                source_map.generate_mapped_string(whitespace_removed_input, source_map_id);
+
+            ctx.expression_specs.insert(
+                id,
+                ExpressionSpec {
+                    id,
+                    invocations,
+                    output_type: "MatchArm".to_string(),
+                    output_statement,
+                    input_statement,
+                    is_repeat_source_iterable_expression: false,
+                },
+            );
+        } else if cfa.match_arm_pattern.is_some() {
+            //An arm is compiled along with its `match`, which also resolves the payloads it binds
+        } else if cfa.is_else_branch {
+            //A bare `else` has no condition to compile; it is always active once reached
         } else {
             unreachable!("encountered invalid control flow definition")
         }
@@ -511,19 +832,28 @@ fn recurse_compile_expressions<'a>(
     std::mem::swap(&mut merged_settings, &mut active_node_def.settings);
 
     // Traverse descendent nodes and continue compiling expressions recursively
-    for id in ctx
+    for (i, id) in ctx
         .template
         .get_children(&ctx.active_node_id.clone().unwrap())
         .clone()
         .unwrap_or_default()
         .iter()
+        .enumerate()
     {
         // update active id to child for next level of recursion into tree
         let parent_id = ctx.active_node_id;
         ctx.active_node_id = Some(id.clone());
 
+        // the arms of a `match` each run in their own stack frame, holding the payloads they bind
+        let arm_scope = match_arm_scopes.get(i).cloned();
+        let is_match_arm = arm_scope.is_some();
+        ctx.scope_stack.extend(arm_scope);
+
         ctx = recurse_compile_expressions(ctx, source_map)?;
 
+        if is_match_arm {
+            ctx.scope_stack.pop();
+        }
         ctx.active_node_id = parent_id;
     }
 
@@ -545,6 +875,22 @@ fn resolve_symbol_as_invocation(
     //Handle built-ins, like $container
     if BUILTIN_MAP.contains_key(sym) {
         unimplemented!("Built-ins like $bounds are not yet supported")
+    } else if let Some(theme_token) = resolve_symbol_as_theme_token(sym, ctx) {
+        //Design tokens, like `theme.primary`, are looked up in the runtime's current theme
+        Ok(ExpressionSpecInvocation {
+            root_identifier: "theme".to_string(),
+            escaped_identifier: escape_identifier(sym.to_string()),
+            stack_offset: 0,
+            fully_qualified_properties_struct_type: String::new(),
+            fully_qualified_iterable_type: String::new(),
+            is_numeric: false,
+            is_bool: false,
+            is_string: false,
+            property_flags: PropertyDefinitionFlags::default(),
+            nested_symbol_tail_literal: String::new(),
+            is_nested_numeric: false,
+            theme_token: Some(theme_token),
+        })
     } else {
         let prop_def_chain = ctx
             .resolve_symbol_as_prop_def(&sym, token.clone())?
@@ -567,8 +913,7 @@ fn resolve_symbol_as_invocation(
         let split_symbols = clean_and_split_symbols(&sym);
         let escaped_identifier = escape_identifier(split_symbols.join("."));
 
-        let mut split_symbols = split_symbols.into_iter();
-        let root_identifier = split_symbols.next().unwrap().to_string();
+        let root_identifier = split_symbols[0].trim_end_matches('?').to_string();
         let root_prop_def = prop_def_chain.first().unwrap();
 
         let fully_qualified_properties_struct_type =
@@ -605,9 +950,27 @@ fn resolve_symbol_as_invocation(
         let property_flags = found_val.flags;
         let property_type = &root_prop_def.type_id;
 
+        // A field after a `?.`, as in `self.user?.name`, is read from within an `Option`, and so is the
+        // rest of the chain, which is `None` wherever a value along the way is
+        let option_inner_type_id = ctx.resolve_optional_inner_type_id(&sym, &prop_def_chain);
         let mut nested_symbol_tail_literal = "".to_string();
+        let mut closing_parens = "".to_string();
         prop_def_chain.iter().enumerate().for_each(|(i, elem)| {
             if i > 0 && i < prop_def_chain.len() {
+                if split_symbols[i - 1].ends_with('?') {
+                    // the rest of the chain is itself optional past another `?.`, or if it ends with an `Option`
+                    let is_rest_optional = split_symbols[i..split_symbols.len() - 1]
+                        .iter()
+                        .any(|s| s.ends_with('?'))
+                        || ctx
+                            .resolve_option_inner_type_id(&prop_def_chain.last().unwrap().type_id)
+                            .is_some();
+                    nested_symbol_tail_literal += &format!(
+                        ".as_ref().{}(|v| v",
+                        if is_rest_optional { "and_then" } else { "map" }
+                    );
+                    closing_parens += ")";
+                }
                 nested_symbol_tail_literal += &if elem.flags.is_property_wrapped {
                     format!(".{}.get()", elem.name)
                 } else {
@@ -616,8 +979,18 @@ fn resolve_symbol_as_invocation(
             }
         });
         if nested_symbol_tail_literal != "" {
-            nested_symbol_tail_literal += ".clone()"
+            nested_symbol_tail_literal += ".clone()";
+            nested_symbol_tail_literal += &closing_parens;
+        }
+        // Optional numbers and strings are handled as `Numeric`s and `StringBox`es, like other numbers and strings
+        if let Some(inner) = &option_inner_type_id {
+            if ExpressionSpecInvocation::is_numeric(inner) {
+                nested_symbol_tail_literal += ".map(Numeric::from)";
+            } else if ExpressionSpecInvocation::is_primitive_string(inner) {
+                nested_symbol_tail_literal += ".map(StringBox::from)";
+            }
         }
+        let is_nested_numeric = is_nested_numeric && option_inner_type_id.is_none();
 
         Ok(ExpressionSpecInvocation {
             root_identifier,
@@ -631,15 +1004,38 @@ fn resolve_symbol_as_invocation(
             property_flags,
             nested_symbol_tail_literal,
             is_nested_numeric,
+            theme_token: None,
         })
     }
 }
 
-/// Returns (RIL string, list of invocation specs for any symbols used)
+/// For a symbol like `theme.primary`, the name of the design token it refers to (`primary`),
+/// unless a property or binding named `theme` is in scope, which takes precedence
+pub(crate) fn resolve_symbol_as_theme_token(
+    sym: &str,
+    ctx: &ExpressionCompilationContext,
+) -> Option<String> {
+    let token = sym.strip_prefix("theme.")?;
+    if token.is_empty()
+        || token.contains('.')
+        || ctx
+            .scope_stack
+            .iter()
+            .any(|scope| scope.contains_key("theme"))
+    {
+        return None;
+    }
+    Some(token.to_string())
+}
+
+/// Returns (RIL string, list of invocation specs for any symbols used, inferred type), for
+/// callers to check the inferred type against whatever the expression is bound to
 fn compile_paxel_to_ril<'a>(
     paxel: Token,
     ctx: &ExpressionCompilationContext<'a>,
-) -> Result<(String, Vec<ExpressionSpecInvocation>), eyre::Report> {
+) -> Result<(String, Vec<ExpressionSpecInvocation>, PaxelType), eyre::Report> {
+    let paxel_type = infer_paxel_type(&paxel, ctx)?;
+
     //1. run Pratt parser; generate output RIL and collected symbolic_ids
     let (output_string, symbolic_ids) = crate::parsing::run_pratt_parser(&paxel.token_value);
 
@@ -658,8 +1054,38 @@ fn compile_paxel_to_ril<'a>(
         Err(e) => return Err(e),
     };
 
-    //3. return tuple of (RIL string,ExpressionSpecInvocations)
-    Ok((output_string, invocations))
+    //3. return tuple of (RIL string,ExpressionSpecInvocations,PaxelType)
+    Ok((output_string, invocations, paxel_type))
+}
+
+/// Methods of lists that keep their elements, and so their elements' type, and may end a `for` source
+const REPEAT_SOURCE_METHODS: [&str; 3] = ["filter", "sort", "sort_by"];
+
+/// The list property a `for` iterates over: `self.items` for `for i in self.items` as well as for
+/// `for i in self.items.filter(|i| i.visible)`, whose elements are also those of `self.items`
+fn repeat_source_list_symbol(source: &Token) -> Result<String, Report> {
+    let Ok(mut pairs) = PaxParser::parse(Rule::xo_method_chain, &source.token_value) else {
+        return Ok(source.token_value.clone());
+    };
+    let mut chain = pairs.next().unwrap().into_inner();
+    let receiver = chain.next().unwrap().into_inner().next().unwrap();
+    let methods: Vec<&str> = chain
+        .map(|method_call| method_call.into_inner().next().unwrap().as_str())
+        .collect();
+    if receiver.as_rule() != Rule::xo_symbol
+        || methods
+            .iter()
+            .any(|method| !REPEAT_SOURCE_METHODS.contains(method))
+    {
+        return Err(PaxTemplateError::new(
+            Some(format!(
+                "A `for` can only iterate over a list property, optionally passed through {}",
+                REPEAT_SOURCE_METHODS.join(", ")
+            )),
+            source.clone(),
+        ));
+    }
+    Ok(receiver.as_str().to_string())
 }
 
 pub struct ExpressionCompilationContext<'a> {
@@ -731,8 +1157,10 @@ impl<'a> ExpressionCompilationContext<'a> {
         let mut split_symbols = split_symbols.iter();
 
         let root_symbol = split_symbols.next().unwrap();
+        let mut is_optional = root_symbol.ends_with('?');
+        let root_symbol = root_symbol.trim_end_matches('?');
 
-        let root_symbol_pd = if BUILTIN_MAP.contains_key(root_symbol.as_str()) {
+        let root_symbol_pd = if BUILTIN_MAP.contains_key(root_symbol) {
             // resolve root symbol through builtin map
             None //FUTURE: support built-ins
         } else {
@@ -760,7 +1188,23 @@ impl<'a> ExpressionCompilationContext<'a> {
         if let Some(root_symbol_pd) = root_symbol_pd {
             let mut ret = vec![root_symbol_pd];
             for atomic_symbol in split_symbols {
-                let td = ret.last().unwrap().get_type_definition(self.type_table);
+                let last = ret.last().unwrap();
+                // past a `?.`, fields are those of the value within the `Option`
+                let td = if is_optional {
+                    self.resolve_option_inner_type_id(&last.type_id)
+                        .and_then(|inner| self.type_table.get(&inner))
+                        .ok_or::<eyre::Report>(PaxTemplateError::new(
+                            Some(format!(
+                                "`?.` can only follow an optional value, but `{}` is not optional, while evaluating `{}`.",
+                                last.name, symbol
+                            )),
+                            token.clone(),
+                        ))?
+                } else {
+                    last.get_type_definition(self.type_table)
+                };
+                is_optional = atomic_symbol.ends_with('?');
+                let atomic_symbol = atomic_symbol.trim_end_matches('?');
                 // return terminal nested symbol's PropertyDefinition, or root's if there are no nested symbols
                 let next_pd = td
                     .property_definitions
@@ -781,4 +1225,25 @@ impl<'a> ExpressionCompilationContext<'a> {
             Ok(None)
         }
     }
+
+    /// `T`, for a `type_id` of `Option<T>`
+    pub fn resolve_option_inner_type_id(&self, type_id: &TypeId) -> Option<TypeId> {
+        if !matches!(type_id.get_pax_type(), PaxType::Option { .. }) {
+            return None;
+        }
+        self.type_table.get(type_id)?.inner_iterable_type_id.clone()
+    }
+
+    /// The type of the value within the `Option` that `symbol` evaluates to, if it is optional,
+    /// given the `PropertyDefinition`s it resolves to: `User` for a `self.user` of type
+    /// `Option<User>`, and `String` for `self.user?.name`
+    pub fn resolve_optional_inner_type_id(
+        &self,
+        symbol: &str,
+        prop_def_chain: &[PropertyDefinition],
+    ) -> Option<TypeId> {
+        let type_id = &prop_def_chain.last()?.type_id;
+        self.resolve_option_inner_type_id(type_id)
+            .or_else(|| symbol.contains("?.").then(|| type_id.clone()))
+    }
 }
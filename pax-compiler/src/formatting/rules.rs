@@ -9,7 +9,7 @@ const INDENTATION: usize = 4;
 pub const PREFIX_OPERATORS: [Rule; 2] = [Rule::xo_neg, Rule::xo_bool_not];
 pub const DO_NOT_INSERT_TAB_MARKER: &str = "|-DO_NOT_INSERT_TAB-|";
 
-pub const INFIX_OPERATORS: [Rule; 16] = [
+pub const INFIX_OPERATORS: [Rule; 17] = [
     Rule::xo_add,
     Rule::xo_bool_and,
     Rule::xo_bool_or,
@@ -24,17 +24,21 @@ pub const INFIX_OPERATORS: [Rule; 16] = [
     Rule::xo_rel_lte,
     Rule::xo_rel_neq,
     Rule::xo_sub,
+    Rule::xo_null_coalesce,
     Rule::xo_tern_then,
     Rule::xo_tern_else,
 ];
 
-pub const PRIMARY_OPERANDS: [Rule; 8] = [
+pub const PRIMARY_OPERANDS: [Rule; 11] = [
+    Rule::xo_method_chain,
     Rule::expression_grouped,
     Rule::xo_enum_or_function_call,
+    Rule::xo_function_call,
     Rule::xo_object,
     Rule::xo_range,
     Rule::xo_tuple,
     Rule::xo_list,
+    Rule::xo_template_literal,
     Rule::xo_literal,
     Rule::xo_symbol,
 ];
@@ -88,6 +92,13 @@ fn get_formatting_rules(pest_rule: Rule) -> Vec<Box<dyn FormattingRule>> {
         Rule::attribute_event_binding => vec![Box::new(AttributeEventBindingDefaultRule)],
         Rule::settings_block_declaration => vec![Box::new(SettingsBlockDeclarationDefaultRule)],
         Rule::settings_event_binding => vec![Box::new(SettingsEventBindingDefaultRule)],
+        Rule::settings_timeline_declaration => {
+            vec![Box::new(SettingsTimelineDeclarationDefaultRule)]
+        }
+        Rule::settings_theme_declaration => vec![Box::new(SettingsThemeDeclarationDefaultRule)],
+        Rule::theme_token_declaration => vec![Box::new(ThemeTokenDeclarationDefaultRule)],
+        Rule::literal_timeline => vec![Box::new(LiteralTimelineDefaultRule)],
+        Rule::literal_keyframe => vec![Box::new(LiteralKeyframeDefaultRule)],
         Rule::selector_block => vec![Box::new(SelectorBlockDefaultRule)],
         Rule::literal_object | Rule::xo_object => vec![Box::new(ObjectDefaultRule)],
         Rule::settings_key_value_pair => vec![Box::new(SettingsKeyValuePairDefaultRule)],
@@ -98,13 +109,16 @@ fn get_formatting_rules(pest_rule: Rule) -> Vec<Box<dyn FormattingRule>> {
         Rule::literal_tuple | Rule::xo_tuple => {
             vec![Box::new(TupleMultiLineRule), Box::new(TupleDefaultRule)]
         }
-        Rule::literal_enum_value | Rule::xo_enum_or_function_call => vec![
+        Rule::literal_enum_value | Rule::xo_enum_or_function_call | Rule::xo_function_call => vec![
             Box::new(IdentifierCallMultiLineRule),
             Box::new(IdentifierCallDefaultRule),
         ],
         Rule::event_id => vec![Box::new(EventIdDefaultRule)],
+        Rule::xo_method_call => vec![Box::new(MethodCallDefaultRule)],
+        Rule::xo_lambda => vec![Box::new(LambdaDefaultRule)],
         Rule::literal_enum_args_list
         | Rule::xo_enum_or_function_args_list
+        | Rule::xo_function_args_list
         | Rule::literal_color => vec![
             Box::new(ArgsListMultiLineRule),
             Box::new(ArgsListDefaultRule),
@@ -119,17 +133,25 @@ fn get_formatting_rules(pest_rule: Rule) -> Vec<Box<dyn FormattingRule>> {
         }
         Rule::statement_for => vec![Box::new(StatementForDefaultRule)],
         Rule::statement_if => vec![Box::new(StatementIfDefaultRule)],
+        Rule::statement_if_let => vec![Box::new(StatementIfLetDefaultRule)],
+        Rule::statement_else => vec![Box::new(StatementElseDefaultRule)],
         Rule::statement_slot => vec![Box::new(StatementSlotDefaultRule)],
+        Rule::statement_match => vec![Box::new(StatementMatchDefaultRule)],
+        Rule::statement_match_arm => vec![Box::new(StatementMatchArmDefaultRule)],
         Rule::any_template_value | Rule::node_inner_content | Rule::settings_value => {
             vec![Box::new(WrapExpressionRule), Box::new(ForwardRule)]
         }
         Rule::root_tag_pair
         | Rule::xo_literal
         | Rule::literal_value
-        | Rule::statement_control_flow => vec![Box::new(ForwardRule)],
+        | Rule::statement_control_flow
+        | Rule::statement_for_key
+        | Rule::xo_method_chain
+        | Rule::xo_method_receiver => vec![Box::new(ForwardRule)],
+
+        Rule::selector | Rule::class_binding => vec![Box::new(CollapseWhitespaceRule)],
 
-        Rule::selector
-        | Rule::settings_key
+        Rule::settings_key
         | Rule::literal_number_with_unit
         | Rule::literal_number
         | Rule::literal_number_integer
@@ -145,8 +167,15 @@ fn get_formatting_rules(pest_rule: Rule) -> Vec<Box<dyn FormattingRule>> {
 
         Rule::identifier
         | Rule::pascal_identifier
+        | Rule::class
+        | Rule::class_list
+        | Rule::selector_compound
+        | Rule::selector_qualifier
         | Rule::statement_for_predicate_declaration
         | Rule::statement_for_source
+        | Rule::statement_match_pattern
+        | Rule::statement_match_variant
+        | Rule::statement_match_wildcard
         | Rule::comment
         | Rule::xo_neg
         | Rule::xo_bool_not
@@ -164,6 +193,7 @@ fn get_formatting_rules(pest_rule: Rule) -> Vec<Box<dyn FormattingRule>> {
         | Rule::xo_rel_lte
         | Rule::xo_rel_neq
         | Rule::xo_sub
+        | Rule::xo_null_coalesce
         | Rule::xo_tern_then
         | Rule::xo_tern_else
         | Rule::xo_range
@@ -182,9 +212,14 @@ fn get_formatting_rules(pest_rule: Rule) -> Vec<Box<dyn FormattingRule>> {
         | Rule::WHITESPACE
         | Rule::id
         | Rule::silent_comma
+        | Rule::xo_template_text
+        | Rule::xo_template_hole
+        | Rule::xo_template_expression
+        | Rule::xo_template_format
+        | Rule::xo_optional_chain
         | Rule::empty => vec![Box::new(IgnoreRule)],
 
-        Rule::string => vec![Box::new(DoNotIndentRule)],
+        Rule::string | Rule::xo_template_literal => vec![Box::new(DoNotIndentRule)],
 
         Rule::inner_tag_error
         | Rule::selector_block_error
@@ -378,7 +413,10 @@ impl FormattingRule for SettingsBlockDeclarationDefaultRule {
             if child.node_type == Rule::selector_block {
                 current = SettingType::Selector;
                 selectors.push_front(child.clone());
-            } else if child.node_type == Rule::settings_event_binding {
+            } else if child.node_type == Rule::settings_event_binding
+                || child.node_type == Rule::settings_timeline_declaration
+                || child.node_type == Rule::settings_theme_declaration
+            {
                 current = SettingType::Event;
                 handlers.push_front(child.clone());
             } else if child.node_type == Rule::comment {
@@ -452,6 +490,21 @@ impl FormattingRule for RemoveWhitespaceRule {
     }
 }
 
+/// Removes whitespace around `=` and collapses the rest to single spaces, which separate
+/// the classes of a `class_binding` and the compound selectors of a `selector`
+#[derive(Clone)]
+struct CollapseWhitespaceRule;
+
+impl FormattingRule for CollapseWhitespaceRule {
+    fn format(&self, node: Pair<Rule>, _children: Vec<Child>) -> String {
+        node.as_str()
+            .split('=')
+            .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("=")
+    }
+}
+
 #[derive(Clone)]
 struct SettingsKeyValuePairDefaultRule;
 
@@ -498,6 +551,75 @@ impl FormattingRule for crate::formatting::rules::SettingsEventBindingDefaultRul
     }
 }
 
+#[derive(Clone)]
+struct SettingsTimelineDeclarationDefaultRule;
+
+impl FormattingRule for SettingsTimelineDeclarationDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        "@timeline: ".to_string() + &children[0].formatted_node + ","
+    }
+}
+
+#[derive(Clone)]
+struct SettingsThemeDeclarationDefaultRule;
+
+impl FormattingRule for SettingsThemeDeclarationDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let tokens = children
+            .iter()
+            .map(|child| child.formatted_node.clone())
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!("@theme: {{\n{}\n}},", indent_every_line_of_string(tokens))
+    }
+}
+
+#[derive(Clone)]
+struct ThemeTokenDeclarationDefaultRule;
+
+impl FormattingRule for ThemeTokenDeclarationDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        format!(
+            "{}: {},",
+            children[0].formatted_node, children[1].formatted_node
+        )
+    }
+}
+
+#[derive(Clone)]
+struct LiteralTimelineDefaultRule;
+
+impl FormattingRule for LiteralTimelineDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let keyframes = children
+            .iter()
+            .map(|child| child.formatted_node.clone())
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!(
+            "@timeline {{\n{}\n}}",
+            indent_every_line_of_string(keyframes)
+        )
+    }
+}
+
+#[derive(Clone)]
+struct LiteralKeyframeDefaultRule;
+
+impl FormattingRule for LiteralKeyframeDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let mut formatted_node = children[0].formatted_node.clone() + ": ";
+        formatted_node.push_str(
+            &children[1..]
+                .iter()
+                .map(|child| child.formatted_node.clone())
+                .collect::<Vec<String>>()
+                .join(" "),
+        );
+        formatted_node + ","
+    }
+}
+
 #[derive(Clone)]
 struct ListMultiLineRule;
 
@@ -584,6 +706,7 @@ impl FormattingRule for IdentifierCallMultiLineRule {
                 formatted_node.push_str(&child.formatted_node);
             } else if child.node_type == Rule::literal_enum_args_list
                 || child.node_type == Rule::xo_enum_or_function_args_list
+                || child.node_type == Rule::xo_function_args_list
             {
                 formatted_node.push_str("(\n");
                 let indented_child = indent_every_line_of_string(child.formatted_node.clone());
@@ -609,6 +732,7 @@ impl FormattingRule for IdentifierCallDefaultRule {
                 formatted_node.push_str(&child.formatted_node);
             } else if child.node_type == Rule::literal_enum_args_list
                 || child.node_type == Rule::xo_enum_or_function_args_list
+                || child.node_type == Rule::xo_function_args_list
             {
                 formatted_node.push_str("(");
                 formatted_node.push_str(&child.formatted_node);
@@ -619,6 +743,30 @@ impl FormattingRule for IdentifierCallDefaultRule {
     }
 }
 
+#[derive(Clone)]
+struct MethodCallDefaultRule;
+
+impl FormattingRule for MethodCallDefaultRule {
+    fn format(&self, node: Pair<Rule>, children: Vec<Child>) -> String {
+        format!(".{}", IdentifierCallDefaultRule.format(node, children))
+    }
+}
+
+#[derive(Clone)]
+struct LambdaDefaultRule;
+
+impl FormattingRule for LambdaDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let (body, params) = children.split_last().unwrap();
+        let params = params
+            .iter()
+            .map(|param| param.formatted_node.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!("|{}| {}", params, body.formatted_node)
+    }
+}
+
 #[derive(Clone)]
 struct ArgsListMultiLineRule;
 
@@ -783,10 +931,20 @@ impl FormattingRule for StatementForDefaultRule {
         let mut formatted_node = String::new();
         let sfpd = children[0].formatted_node.clone();
         let sfs = children[1].formatted_node.clone();
-        let inner_nodes = children[2].formatted_node.clone();
+        let key = children
+            .iter()
+            .find(|child| child.node_type == Rule::statement_for_key)
+            .map(|child| format!(" key {}", child.formatted_node))
+            .unwrap_or_default();
+        let inner_nodes = children.last().unwrap().formatted_node.clone();
         let inner_nodes_indented = indent_every_line_of_string(inner_nodes);
-        formatted_node
-            .push_str(format!("for {} in {} {{\n{}\n}}", sfpd, sfs, inner_nodes_indented).as_str());
+        formatted_node.push_str(
+            format!(
+                "for {} in {}{} {{\n{}\n}}",
+                sfpd, sfs, key, inner_nodes_indented
+            )
+            .as_str(),
+        );
         formatted_node
     }
 }
@@ -801,6 +959,37 @@ impl FormattingRule for StatementIfDefaultRule {
         let inner_nodes = children[1].formatted_node.clone();
         let inner_nodes_indented = indent_every_line_of_string(inner_nodes);
         formatted_node.push_str(format!("if {} {{\n{}\n}}", exp, inner_nodes_indented).as_str());
+        if let Some(statement_else) = children.get(2) {
+            formatted_node.push_str(format!(" {}", statement_else.formatted_node).as_str());
+        }
+        formatted_node
+    }
+}
+
+#[derive(Clone)]
+struct StatementIfLetDefaultRule;
+
+impl FormattingRule for StatementIfLetDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let pattern = children[0].formatted_node.clone();
+        let symbol = children[1].formatted_node.clone();
+        format!("let {} = {}", pattern, symbol)
+    }
+}
+
+#[derive(Clone)]
+struct StatementElseDefaultRule;
+
+impl FormattingRule for StatementElseDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let mut formatted_node = String::new();
+        let body = children[0].clone();
+        if body.node_type == Rule::statement_if {
+            formatted_node.push_str(format!("else {}", body.formatted_node).as_str());
+        } else {
+            let inner_nodes_indented = indent_every_line_of_string(body.formatted_node);
+            formatted_node.push_str(format!("else {{\n{}\n}}", inner_nodes_indented).as_str());
+        }
         formatted_node
     }
 }
@@ -817,6 +1006,39 @@ impl FormattingRule for StatementSlotDefaultRule {
     }
 }
 
+#[derive(Clone)]
+struct StatementMatchDefaultRule;
+
+impl FormattingRule for StatementMatchDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let mut formatted_node = String::new();
+        let symbol = children[0].formatted_node.clone();
+        let arms = children[1..]
+            .iter()
+            .map(|child| format!("{},", child.formatted_node))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let arms_indented = indent_every_line_of_string(arms);
+        formatted_node.push_str(format!("match {} {{\n{}\n}}", symbol, arms_indented).as_str());
+        formatted_node
+    }
+}
+
+#[derive(Clone)]
+struct StatementMatchArmDefaultRule;
+
+impl FormattingRule for StatementMatchArmDefaultRule {
+    fn format(&self, _node: Pair<Rule>, children: Vec<Child>) -> String {
+        let mut formatted_node = String::new();
+        let pattern = children[0].formatted_node.clone();
+        let inner_nodes = children[1].formatted_node.clone();
+        let inner_nodes_indented = indent_every_line_of_string(inner_nodes);
+        formatted_node
+            .push_str(format!("{} => {{\n{}\n}}", pattern, inner_nodes_indented).as_str());
+        formatted_node
+    }
+}
+
 #[derive(Clone)]
 struct ForwardRule;
 
@@ -151,12 +151,14 @@ pub fn remove_path_from_pax_dependencies(full_path: &Path) {
 }
 
 pub fn update_pax_dependency_versions(doc: &mut Document, ctx_version: &str) {
-    if let Some(deps) = doc
-        .as_table_mut()
-        .entry("dependencies")
-        .or_insert_with(toml_edit::table)
-        .as_table_mut()
-    {
+    for section in ["dependencies", "dev-dependencies"] {
+        let Some(deps) = doc
+            .as_table_mut()
+            .get_mut(section)
+            .and_then(|item| item.as_table_mut())
+        else {
+            continue;
+        };
         let keys: Vec<String> = deps
             .iter()
             .filter_map(|(key, _)| {
@@ -0,0 +1,547 @@
+//! # Hot reloading for `pax run --watch`
+//!
+//! A watcher thread polls the project's `.rs` and `.pax` sources.  When a change is confined to the
+//! template of a component — a `.pax` file or an `#[inlined(...)]` template — only that template is
+//! reparsed and queued for the running app, which swaps it in without losing component state.
+//! Reparsed templates can rearrange nodes, change literal values and reuse expressions already
+//! compiled for that component; anything else (Rust code, `@settings`, new expressions or handlers)
+//! triggers a full rebuild, after which the app reloads.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use color_eyre::eyre;
+use eyre::eyre;
+use pax_manifest::{
+    ComponentTemplate, ControlFlowMatchPatternDefinition, ControlFlowRepeatPredicateDefinition,
+    PaxManifest, SettingElement, TemplateNodeId, Token, TypeId, ValueDefinition,
+};
+use pest::Parser;
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::helpers::PAX_BADGE;
+use crate::parsing::{
+    extract_errors, parse_template_from_component_definition_string, PaxParser, Rule,
+    TemplateNodeParseContext,
+};
+use crate::{perform_build, RunContext, RunTarget};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Shared between the watcher and the dev server, which serves it to the running app
+#[derive(Default)]
+pub struct HotReloadState {
+    /// Incremented by every full rebuild; the app reloads when it changes
+    pub build: usize,
+    /// Serialized `ComponentTemplate`s reparsed since the last build, in order
+    pub templates: Vec<String>,
+}
+
+/// Starts watching the project of `ctx` in the background, given the manifest it was built from
+pub fn start_watching(ctx: &RunContext, manifest: PaxManifest) -> Arc<Mutex<HotReloadState>> {
+    let state = Arc::new(Mutex::new(HotReloadState::default()));
+    let mut watcher = Watcher {
+        ctx: RunContext {
+            target: RunTarget::Web,
+            path: ctx.path.clone(),
+            verbose: ctx.verbose,
+            should_also_run: false,
+            should_watch: false,
+            is_libdev_mode: ctx.is_libdev_mode,
+            process_child_ids: Arc::clone(&ctx.process_child_ids),
+            is_release: ctx.is_release,
+        },
+        manifest,
+        sources: HashMap::new(),
+        state: Arc::clone(&state),
+    };
+    watcher.sources = watcher.read_sources();
+    println!("{} 👀 Watching for changes", *PAX_BADGE);
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        watcher.poll();
+    });
+    state
+}
+
+struct Watcher {
+    /// Context for rebuilds, which neither serve nor watch
+    ctx: RunContext,
+    manifest: PaxManifest,
+    /// Last seen modification time and contents of each source file
+    sources: HashMap<PathBuf, (SystemTime, String)>,
+    state: Arc<Mutex<HotReloadState>>,
+}
+
+/// How a changed source file can be brought into the running app
+enum Reload {
+    Templates(Vec<ComponentTemplate>),
+    Rebuild(String),
+}
+
+impl Watcher {
+    fn read_sources(&self) -> HashMap<PathBuf, (SystemTime, String)> {
+        let src = Path::new(&self.ctx.path).join("src");
+        WalkDir::new(src)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let extension = entry.path().extension().and_then(|e| e.to_str());
+                matches!(extension, Some("rs") | Some("pax"))
+            })
+            .filter_map(|entry| {
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                let contents = fs::read_to_string(entry.path()).ok()?;
+                Some((canonicalize(entry.path()), (modified, contents)))
+            })
+            .collect()
+    }
+
+    fn poll(&mut self) {
+        let sources = self.read_sources();
+        let mut changed: Vec<&PathBuf> = sources
+            .iter()
+            .filter(|(path, (modified, _))| {
+                self.sources.get(*path).map(|(m, _)| m) != Some(modified)
+            })
+            .map(|(path, _)| path)
+            .collect();
+        changed.extend(
+            self.sources
+                .keys()
+                .filter(|path| !sources.contains_key(*path)),
+        );
+        if changed.is_empty() {
+            return;
+        }
+
+        let mut templates = vec![];
+        let mut rebuild = None;
+        for path in changed {
+            let old = self
+                .sources
+                .get(path)
+                .map(|(_, contents)| contents.as_str());
+            let new = sources.get(path).map(|(_, contents)| contents.as_str());
+            match self.reload_for_change(path, old, new) {
+                Reload::Templates(reparsed) => templates.extend(reparsed),
+                Reload::Rebuild(reason) => {
+                    rebuild = Some(reason);
+                    break;
+                }
+            }
+        }
+        self.sources = sources;
+
+        if let Some(reason) = rebuild {
+            println!("{} 🔁 {}; rebuilding", *PAX_BADGE, reason);
+            match perform_build(&self.ctx) {
+                Ok((manifest, _)) => {
+                    self.manifest = manifest;
+                    let mut state = self.state.lock().unwrap();
+                    state.build += 1;
+                    state.templates.clear();
+                }
+                Err(e) => eprintln!("{} ❌ Rebuild failed: {}", *PAX_BADGE, e),
+            }
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        for template in templates {
+            println!(
+                "{} 🔥 Reloaded template of {}",
+                *PAX_BADGE,
+                template.get_containing_component_type_id()
+            );
+            let type_id = template.get_containing_component_type_id();
+            state
+                .templates
+                .push(serde_json::to_string(&template).unwrap());
+            // Later reparses and rebuilds start from this template
+            self.manifest.components.get_mut(&type_id).unwrap().template = Some(template);
+        }
+    }
+
+    fn reload_for_change(&self, path: &Path, old: Option<&str>, new: Option<&str>) -> Reload {
+        let (Some(old), Some(new)) = (old, new) else {
+            return Reload::Rebuild(format!("{} was added or removed", path.display()));
+        };
+        let components: Vec<&TypeId> = self
+            .manifest
+            .components
+            .values()
+            .filter(|component| {
+                component.template.as_ref().is_some_and(|template| {
+                    template.get_file_path().is_some_and(|file| {
+                        canonicalize(&Path::new(&self.ctx.path).join(file)) == path
+                    })
+                })
+            })
+            .map(|component| &component.type_id)
+            .collect();
+
+        let is_pax_file = path.extension().is_some_and(|e| e == "pax");
+        let (old_definitions, new_definitions) = if is_pax_file {
+            let Some(&type_id) = components.first() else {
+                return Reload::Templates(vec![]);
+            };
+            (
+                HashMap::from([(type_id.clone(), old.to_string())]),
+                HashMap::from([(type_id.clone(), new.to_string())]),
+            )
+        } else {
+            let (old_definitions, old_rust) = split_inlined_templates(old, &components);
+            let (new_definitions, new_rust) = split_inlined_templates(new, &components);
+            if old_rust != new_rust || old_definitions.len() != new_definitions.len() {
+                return Reload::Rebuild(format!("{} changed", path.display()));
+            }
+            (old_definitions, new_definitions)
+        };
+
+        let mut templates = vec![];
+        for (type_id, new_definition) in new_definitions {
+            let old_definition = old_definitions.get(&type_id);
+            if old_definition == Some(&new_definition) {
+                continue;
+            }
+            if old_definition.map(|d| non_template_source(d))
+                != Some(non_template_source(&new_definition))
+            {
+                return Reload::Rebuild(format!("@settings of {} changed", type_id));
+            }
+            match reparse_component_template(&self.manifest, &type_id, &new_definition) {
+                Ok(template) => templates.push(template),
+                Err(e) => return Reload::Rebuild(format!("{}", e)),
+            }
+        }
+        Reload::Templates(templates)
+    }
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Splits a Rust source file into the `#[inlined(...)]` Pax of each of `components` it defines,
+/// and the rest of the file
+fn split_inlined_templates(
+    source: &str,
+    components: &[&TypeId],
+) -> (HashMap<TypeId, String>, String) {
+    let struct_name = Regex::new(r"struct\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    let mut definitions = HashMap::new();
+    let mut rest = String::new();
+    let mut remaining = source;
+    while let Some(start) = remaining.find("#[inlined(") {
+        let contents_start = start + "#[inlined(".len();
+        let mut depth = 1;
+        let contents_end = remaining[contents_start..]
+            .char_indices()
+            .find(|(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .map(|(i, _)| contents_start + i)
+            .unwrap_or(remaining.len());
+        rest.push_str(&remaining[..contents_start]);
+        let pax = &remaining[contents_start..contents_end];
+        remaining = &remaining[contents_end..];
+
+        let pascal_identifier = struct_name
+            .captures(remaining)
+            .map(|captures| captures[1].to_string());
+        if let Some(type_id) = components
+            .iter()
+            .find(|type_id| type_id.get_pascal_identifier() == pascal_identifier)
+        {
+            definitions.insert((*type_id).clone(), pax.to_string());
+        }
+    }
+    rest.push_str(remaining);
+    (definitions, rest)
+}
+
+/// The parts of a component definition besides its template, i.e. its `@settings`
+fn non_template_source(pax: &str) -> Vec<String> {
+    PaxParser::parse(Rule::pax_component_definition, pax)
+        .map(|mut pairs| {
+            pairs
+                .next()
+                .unwrap()
+                .into_inner()
+                .filter(|pair| pair.as_rule() == Rule::settings_block_declaration)
+                .map(|pair| pair.as_str().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Where an expression sits in a template: the `for`s enclosing it (whose stack frames its compiled
+/// form indexes into), the type of its node, the property it is bound to, and its source
+type ExpressionSite = (Vec<String>, String, String, String);
+
+/// Reparses the template of the component `type_id` from `pax`, the source of its component
+/// definition, and binds the template's expressions to the ones already compiled for it in
+/// `manifest`.  Fails when the template uses components, expressions or handlers that the build
+/// behind `manifest` doesn't know about, in which case the project needs a rebuild.
+pub fn reparse_component_template(
+    manifest: &PaxManifest,
+    type_id: &TypeId,
+    pax: &str,
+) -> eyre::Result<ComponentTemplate> {
+    let component = manifest
+        .components
+        .get(type_id)
+        .ok_or_else(|| eyre!("{} is not in the manifest", type_id))?;
+    let old_template = component
+        .template
+        .as_ref()
+        .ok_or_else(|| eyre!("{} has no template", type_id))?;
+
+    let definition = PaxParser::parse(Rule::pax_component_definition, pax)
+        .map_err(|e| eyre!("template of {} failed to parse: {}", type_id, e))?
+        .next()
+        .unwrap();
+    if let Some(error) = extract_errors(definition.into_inner()).first() {
+        return Err(eyre!(
+            "template of {} failed to parse: {} at {}:{}",
+            type_id,
+            error.error_message,
+            error.start.0,
+            error.start.1
+        ));
+    }
+
+    // Tags resolve to the components known to this build, preferring those already in the template
+    let mut pascal_identifier_to_type_id_map: HashMap<String, TypeId> = manifest
+        .components
+        .keys()
+        .filter_map(|type_id| Some((type_id.get_pascal_identifier()?, type_id.clone())))
+        .collect();
+    for node in old_template.get_nodes() {
+        if let Some(pascal_identifier) = node.type_id.get_pascal_identifier() {
+            pascal_identifier_to_type_id_map.insert(pascal_identifier, node.type_id.clone());
+        }
+    }
+    let tag = Regex::new(r"<\s*([A-Z][A-Za-z0-9_]*)").unwrap();
+    for captures in tag.captures_iter(pax) {
+        if !pascal_identifier_to_type_id_map.contains_key(&captures[1]) {
+            return Err(eyre!("<{}> is new to {}", &captures[1], type_id));
+        }
+    }
+
+    let mut ctx = TemplateNodeParseContext {
+        template: ComponentTemplate::new(type_id.clone(), old_template.get_file_path()),
+        pascal_identifier_to_type_id_map,
+    };
+    parse_template_from_component_definition_string(&mut ctx, pax);
+    let mut template = ctx.template;
+
+    // As during compilation, nodes carry their settings merged with the component's `@settings`
+    let known_handlers = event_handlers(old_template);
+    template.merge_with_settings(&component.settings);
+    if let Some(handler) = event_handlers(&template).difference(&known_handlers).next() {
+        return Err(eyre!("handler `{}` is new to {}", handler, type_id));
+    }
+
+    let mut compiled = HashMap::new();
+    let mut old_template = old_template.clone();
+    let old_root = old_template.get_root();
+    visit_expressions(
+        &mut old_template,
+        old_root,
+        &mut vec![],
+        &mut |site, vtable_id| {
+            if let Some(vtable_id) = vtable_id {
+                compiled.insert(site, *vtable_id);
+            }
+        },
+    );
+    let mut missing = None;
+    let root = template.get_root();
+    visit_expressions(
+        &mut template,
+        root,
+        &mut vec![],
+        &mut |site, vtable_id| match compiled.get(&site) {
+            Some(id) => *vtable_id = Some(*id),
+            None => missing = Some(site.3),
+        },
+    );
+    if let Some(source) = missing {
+        return Err(eyre!("expression `{}` is new to {}", source, type_id));
+    }
+    Ok(template)
+}
+
+fn event_handlers(template: &ComponentTemplate) -> HashSet<String> {
+    template
+        .get_nodes()
+        .into_iter()
+        .flat_map(|node| node.settings.iter().flatten())
+        .filter_map(|setting| match setting {
+            SettingElement::Setting(_, ValueDefinition::EventBindingTarget(handler)) => {
+                Some(normalize(&handler.raw_value))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Source of an expression or handler without whitespace, which inlined templates (parsed from a
+/// stringified `TokenStream`) and their Rust source space differently
+fn normalize(source: &str) -> String {
+    source.split_whitespace().collect()
+}
+
+fn match_pattern_source(pattern: &ControlFlowMatchPatternDefinition) -> String {
+    match &pattern.variant {
+        Some(variant) => {
+            let bindings: Vec<String> = pattern
+                .bindings
+                .iter()
+                .map(|binding| normalize(&binding.raw_value))
+                .collect();
+            format!("{}({})", normalize(&variant.raw_value), bindings.join(", "))
+        }
+        None => "_".to_string(),
+    }
+}
+
+/// Visits the vtable id of each expression in the subtrees of `node_ids`, along with its site
+fn visit_expressions(
+    template: &mut ComponentTemplate,
+    node_ids: Vec<TemplateNodeId>,
+    scope: &mut Vec<String>,
+    visit: &mut impl FnMut(ExpressionSite, &mut Option<usize>),
+) {
+    let source = |token: &Token| normalize(&token.raw_value);
+    for id in node_ids {
+        let mut node = template.get_node(&id).unwrap().clone();
+        let mut inner_scope = None;
+        if let Some(cfs) = &mut node.control_flow_settings {
+            let site = |kind: &str, source: String| {
+                (scope.clone(), kind.to_string(), String::new(), source)
+            };
+            if let Some(condition) = &cfs.condition_expression_paxel {
+                visit(
+                    site("if", source(condition)),
+                    &mut cfs.condition_expression_vtable_id,
+                );
+            }
+            if let Some(index) = &cfs.slot_index_expression_paxel {
+                visit(
+                    site("slot", source(index)),
+                    &mut cfs.slot_index_expression_vtable_id,
+                );
+            }
+            let predicate = match &cfs.repeat_predicate_definition {
+                Some(ControlFlowRepeatPredicateDefinition::ElemId(elem)) => source(elem),
+                Some(ControlFlowRepeatPredicateDefinition::ElemIdIndexId(elem, index)) => {
+                    format!("({}, {})", source(elem), source(index))
+                }
+                None => String::new(),
+            };
+            if let Some(rsd) = &mut cfs.repeat_source_definition {
+                let repeat_source = rsd
+                    .range_expression_paxel
+                    .as_ref()
+                    .or(rsd.symbolic_binding.as_ref())
+                    .map(source)
+                    .unwrap_or_default();
+                visit(site("for", repeat_source.clone()), &mut rsd.vtable_id);
+                let for_scope = format!("for {} in {}", predicate, repeat_source);
+                if let Some(key) = &cfs.repeat_key_expression_paxel {
+                    // The key is evaluated per element, with the predicate in scope
+                    let mut key_scope = scope.clone();
+                    key_scope.push(for_scope.clone());
+                    visit(
+                        (key_scope, "for key".to_string(), String::new(), source(key)),
+                        &mut cfs.repeat_key_expression_vtable_id,
+                    );
+                }
+                inner_scope = Some(for_scope);
+            }
+            if let Some(matched) = &cfs.match_expression_paxel {
+                // A `match` compiles its arms' patterns into its own expression
+                let arms: Vec<String> = template
+                    .get_children(&id)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|arm_id| {
+                        let arm = template.get_node(arm_id)?.control_flow_settings.as_ref()?;
+                        Some(match_pattern_source(arm.match_arm_pattern.as_ref()?))
+                    })
+                    .collect();
+                let match_source = format!("{} {{ {} }}", source(matched), arms.join(", "));
+                visit(
+                    site("match", match_source),
+                    &mut cfs.match_expression_vtable_id,
+                );
+            }
+            if let Some(pattern) = &cfs.match_arm_pattern {
+                inner_scope = Some(format!("match arm {}", match_pattern_source(pattern)));
+            }
+        }
+        if let Some(settings) = &mut node.settings {
+            for setting in settings.iter_mut() {
+                if let SettingElement::Setting(key, value) = setting {
+                    // `id` and `class` name selectors rather than bind expressions
+                    if key.token_value != "id" && key.token_value != "class" {
+                        let node_type = node.type_id.get_unique_identifier();
+                        visit_value(scope, &node_type, key.token_value.clone(), value, visit);
+                    }
+                }
+            }
+        }
+        let children = template.get_children(&id).unwrap_or_default();
+        template.set_node(id, node);
+
+        let has_inner_scope = inner_scope.is_some();
+        scope.extend(inner_scope);
+        visit_expressions(template, children, scope, visit);
+        if has_inner_scope {
+            scope.pop();
+        }
+    }
+}
+
+fn visit_value(
+    scope: &[String],
+    node_type: &str,
+    path: String,
+    value: &mut ValueDefinition,
+    visit: &mut impl FnMut(ExpressionSite, &mut Option<usize>),
+) {
+    match value {
+        ValueDefinition::Expression(token, vtable_id)
+        | ValueDefinition::Identifier(token, vtable_id) => {
+            let site = (
+                scope.to_vec(),
+                node_type.to_string(),
+                path,
+                normalize(&token.raw_value),
+            );
+            visit(site, vtable_id);
+        }
+        ValueDefinition::Block(block) => {
+            for element in block.elements.iter_mut() {
+                if let SettingElement::Setting(key, value) = element {
+                    let path = format!("{}.{}", path, key.token_value);
+                    visit_value(scope, node_type, path, value, visit);
+                }
+            }
+        }
+        _ => {}
+    }
+}
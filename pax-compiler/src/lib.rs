@@ -16,8 +16,10 @@ pub mod errors;
 pub mod expressions;
 pub mod formatting;
 pub mod helpers;
+pub mod hot_reload;
 pub mod parsing;
 mod reexports;
+pub mod type_checking;
 
 use color_eyre::eyre;
 use color_eyre::eyre::Report;
@@ -33,7 +35,8 @@ use std::sync::{Arc, Mutex};
 use std::os::unix::process::CommandExt;
 
 use crate::building::{
-    build_chassis_with_cartridge, clone_all_to_pkg_dir, update_type_id_prefixes_in_place,
+    build_chassis_with_cartridge, clone_all_to_pkg_dir, run_tests_with_cartridge,
+    update_type_id_prefixes_in_place,
 };
 
 use crate::cartridge_generation::generate_and_overwrite_cartridge;
@@ -57,11 +60,22 @@ pub struct RunContext {
     pub path: String,
     pub verbose: bool,
     pub should_also_run: bool,
+    /// Whether to hot reload the running app as its source changes; only supported on Web
+    pub should_watch: bool,
     pub is_libdev_mode: bool,
     pub process_child_ids: Arc<Mutex<Vec<u64>>>,
     pub is_release: bool,
 }
 
+pub struct TestContext {
+    pub path: String,
+    pub verbose: bool,
+    pub is_libdev_mode: bool,
+    pub process_child_ids: Arc<Mutex<Vec<u64>>>,
+    /// Extra arguments forwarded to `cargo test`, e.g. a test name filter
+    pub cargo_test_args: Vec<String>,
+}
+
 #[derive(PartialEq)]
 pub enum RunTarget {
     #[allow(non_camel_case_types)]
@@ -99,30 +113,72 @@ pub fn perform_build(ctx: &RunContext) -> eyre::Result<(PaxManifest, Option<Path
         }
     }
 
+    let (manifest, pax_dir) = generate_cartridge(
+        &ctx.path,
+        ctx.is_libdev_mode,
+        Arc::clone(&ctx.process_child_ids),
+    )?;
+
+    // Watch before building, since running blocks on serving the app
+    let hot_reload = (ctx.should_watch && ctx.should_also_run && ctx.target == RunTarget::Web)
+        .then(|| hot_reload::start_watching(ctx, manifest.clone()));
+
+    //7. Build the appropriate `chassis` from source, with the patched `Cargo.toml`, Properties Coproduct, and Cartridge from above
+    println!("{} 🧱 Building cartridge with `cargo`", *PAX_BADGE);
+    let build_dir = build_chassis_with_cartridge(
+        &pax_dir,
+        &ctx,
+        Arc::clone(&ctx.process_child_ids),
+        hot_reload,
+    )?;
+    Ok((manifest, build_dir))
+}
+
+/// For the specified file path or current working directory, first compile Pax project,
+/// then run its `cargo test`s with the generated cartridge patched in, so that tests can
+/// mount the main component in a `TestHarness` via `pax_cartridge::instantiate_test_harness`
+pub fn perform_test(ctx: &TestContext) -> eyre::Result<(), Report> {
+    let (_manifest, pax_dir) = generate_cartridge(
+        &ctx.path,
+        ctx.is_libdev_mode,
+        Arc::clone(&ctx.process_child_ids),
+    )?;
+
+    println!("{} 🧪 Running tests with `cargo`", *PAX_BADGE);
+    run_tests_with_cartridge(&pax_dir, ctx, Arc::clone(&ctx.process_child_ids))
+}
+
+/// Parses the Pax project at `path`, compiles its expressions, and generates the
+/// cartridge into `.pax/pkg/pax-cartridge`.  Returns the manifest and the `.pax` directory.
+fn generate_cartridge(
+    path: &str,
+    is_libdev_mode: bool,
+    process_child_ids: Arc<Mutex<Vec<u64>>>,
+) -> eyre::Result<(PaxManifest, PathBuf), Report> {
     //First we clone dependencies into the .pax/pkg directory.  We must do this before running
     //the parser binary specifical for libdev in pax-example — see pax-example/Cargo.toml where
     //dependency paths are `.pax/pkg/*`.
-    let pax_dir = get_or_create_pax_directory(&ctx.path);
+    let pax_dir = get_or_create_pax_directory(path);
 
     //Inspect Cargo.lock to find declared pax lib versions.  Note that this is moot for
     //libdev, where we don't care about a crates.io version (and where `cargo metadata` won't work
     //on a cold-start monorepo clone.)
-    let pax_version = if ctx.is_libdev_mode {
+    let pax_version = if is_libdev_mode {
         None
     } else {
-        Some(get_version_of_whitelisted_packages(&ctx.path).unwrap())
+        Some(get_version_of_whitelisted_packages(path).unwrap())
     };
-    clone_all_to_pkg_dir(&pax_dir, &pax_version, &ctx);
+    clone_all_to_pkg_dir(&pax_dir, &pax_version, is_libdev_mode);
 
-    if ctx.is_libdev_mode {
-        let full_path = Path::new(&ctx.path);
+    if is_libdev_mode {
+        let full_path = Path::new(path);
         set_path_on_pax_dependencies(&full_path);
     }
 
     println!("{} 🛠️  Building parser binary with `cargo`...", *PAX_BADGE);
 
     // Run parser bin from host project with `--features parser`
-    let output = run_parser_binary(&ctx.path, Arc::clone(&ctx.process_child_ids));
+    let output = run_parser_binary(path, process_child_ids);
 
     // Forward stderr only
     std::io::stderr()
@@ -138,7 +194,7 @@ pub fn perform_build(ctx: &RunContext) -> eyre::Result<(PaxManifest, Option<Path
     let out = String::from_utf8(output.stdout).unwrap();
     let mut manifest: PaxManifest =
         serde_json::from_str(&out).expect(&format!("Malformed JSON from parser: {}", &out));
-    let host_cargo_toml_path = Path::new(&ctx.path).join("Cargo.toml");
+    let host_cargo_toml_path = Path::new(path).join("Cargo.toml");
     let host_crate_info = get_host_crate_info(&host_cargo_toml_path);
     update_type_id_prefixes_in_place(&mut manifest, &host_crate_info);
 
@@ -152,11 +208,7 @@ pub fn perform_build(ctx: &RunContext) -> eyre::Result<(PaxManifest, Option<Path
     let cartridge_path = generate_and_overwrite_cartridge(&pax_dir, &manifest, &host_crate_info);
     source_map.extract_ranges_from_generated_code(cartridge_path.to_str().unwrap());
 
-    //7. Build the appropriate `chassis` from source, with the patched `Cargo.toml`, Properties Coproduct, and Cartridge from above
-    println!("{} 🧱 Building cartridge with `cargo`", *PAX_BADGE);
-    let build_dir =
-        build_chassis_with_cartridge(&pax_dir, &ctx, Arc::clone(&ctx.process_child_ids))?;
-    Ok((manifest, build_dir))
+    Ok((manifest, pax_dir))
 }
 
 /// Clean all `.pax` temp files
@@ -5,10 +5,12 @@ use std::rc::Rc;
 use pax_manifest::{escape_identifier, ComponentTemplate, TemplateNodeId, TreeLocation, TypeId};
 
 use pax_manifest::{
-    get_primitive_type_table, ComponentDefinition, ControlFlowRepeatPredicateDefinition,
-    ControlFlowRepeatSourceDefinition, ControlFlowSettingsDefinition, LiteralBlockDefinition,
-    LocationInfo, PropertyDefinition, SettingElement, SettingsBlockElement, TemplateNodeDefinition,
-    Token, TokenType, TypeDefinition, TypeTable, ValueDefinition,
+    get_primitive_type_table, ComponentDefinition, ControlFlowMatchPatternDefinition,
+    ControlFlowRepeatPredicateDefinition, ControlFlowRepeatSourceDefinition,
+    ControlFlowSettingsDefinition, KeyframeDefinition, LiteralBlockDefinition, LocationInfo,
+    PropertyDefinition, SettingElement, SettingsBlockElement, TemplateNodeDefinition,
+    ThemeTokenDefinition, ThemeTokenKind, TimelineDefinition, Token, TokenType, TypeDefinition,
+    TypeTable, ValueDefinition,
 };
 
 extern crate pest;
@@ -18,17 +20,22 @@ use pest_derive::Parser;
 
 use pest::pratt_parser::{Assoc, Op, PrattParser};
 
+use pax_runtime_api::functions::{get_builtin_function, ValueKind};
+
 #[derive(Parser)]
 #[grammar = "pax.pest"]
 pub struct PaxParser;
 
-/// Returns (RIL output string, `symbolic id`s found during parse)
-/// where a `symbolic id` may be something like `self.num_clicks` or `i`
-pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<String>) {
+/// Where generated cartridges find the functions PAXEL calls by name, e.g. `clamp(...)`
+const BUILTIN_FUNCTIONS_PATH: &str = "pax_runtime::api::functions";
+
+/// The Pratt parser for PAXEL expressions, shared by compilation to RIL and type checking
+pub fn paxel_pratt_parser() -> PrattParser<Rule> {
     // Operator precedence is declared via the ordering here:
-    let pratt = PrattParser::new()
+    PrattParser::new()
         .op(Op::infix(Rule::xo_tern_then, Assoc::Left)
             | Op::infix(Rule::xo_tern_else, Assoc::Right))
+        .op(Op::infix(Rule::xo_null_coalesce, Assoc::Right))
         .op(Op::infix(Rule::xo_bool_and, Assoc::Left) | Op::infix(Rule::xo_bool_or, Assoc::Left))
         .op(Op::infix(Rule::xo_add, Assoc::Left) | Op::infix(Rule::xo_sub, Assoc::Left))
         .op(Op::infix(Rule::xo_mul, Assoc::Left) | Op::infix(Rule::xo_div, Assoc::Left))
@@ -41,8 +48,13 @@ pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<String>) {
             | Op::infix(Rule::xo_rel_lte, Assoc::Left)
             | Op::infix(Rule::xo_rel_gt, Assoc::Left)
             | Op::infix(Rule::xo_rel_gte, Assoc::Left))
-        .op(Op::prefix(Rule::xo_bool_not));
+        .op(Op::prefix(Rule::xo_bool_not))
+}
 
+/// Returns (RIL output string, `symbolic id`s found during parse)
+/// where a `symbolic id` may be something like `self.num_clicks` or `i`
+pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<String>) {
+    let pratt = paxel_pratt_parser();
     let pairs = PaxParser::parse(Rule::expression_body, input_paxel)
         .expect(&format!("unsuccessful pratt parse {}", &input_paxel));
 
@@ -51,25 +63,150 @@ pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<String>) {
     (output, symbolic_ids.take())
 }
 
-/// Removes leading `self.` or `this.`, escapes remaining symbol to be a suitable atomic identifier
-fn convert_symbolic_binding_from_paxel_to_ril(xo_symbol: Pair<Rule>) -> String {
-    let mut pairs = xo_symbol.clone().into_inner();
-    let maybe_this_or_self = pairs.next().unwrap().as_str();
+/// The text between the holes of a template literal, with escapes like `\n` or `` \` `` resolved
+fn unescape_template_text(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some('0') => '\0',
+                Some(escaped) => escaped,
+                None => c,
+            },
+            _ => c,
+        });
+    }
+    unescaped
+}
 
-    let self_or_this_removed = if maybe_this_or_self == "this" || maybe_this_or_self == "self" {
-        let mut output = "".to_string();
+/// Compiles each argument of an `xo_function_args_list` into RIL, and each lambda among them into a closure
+fn compile_function_args(
+    args_list: Pair<Rule>,
+    pratt_parser: &PrattParser<Rule>,
+    symbolic_ids: Rc<RefCell<Vec<String>>>,
+) -> Vec<String> {
+    args_list
+        .into_inner()
+        .map(|arg| match arg.as_rule() {
+            Rule::xo_lambda => compile_lambda(arg, pratt_parser, Rc::clone(&symbolic_ids)),
+            _ => recurse_pratt_parse_to_string(
+                arg.into_inner(),
+                pratt_parser,
+                Rc::clone(&symbolic_ids),
+            ),
+        })
+        .collect()
+}
 
-        //accumulate remaining identifiers, having skipped `this` or `self` with the original `.next()`
-        pairs.for_each(|pair| output += &*(".".to_owned() + pair.as_str()));
+/// Calls the built-in function `name` with `args`, passing each the way its param expects:
+/// by value, by reference, as is for lambdas, or as one of a variadic slice
+fn builtin_function_call_to_ril(name: &str, args: Vec<String>) -> String {
+    let function = get_builtin_function(name);
+    let mut ril_args = vec![];
+    let mut variadic_args = vec![];
+    for (i, arg) in args.into_iter().enumerate() {
+        let param = function.and_then(|f| f.param(i));
+        if function.is_some_and(|f| f.is_variadic && i + 1 >= f.params.len()) {
+            variadic_args.push(format!(
+                "&({}) as &dyn {}::Printable",
+                arg, BUILTIN_FUNCTIONS_PATH
+            ));
+        } else if let Some(ValueKind::List | ValueKind::Sequence | ValueKind::Printable) = param {
+            ril_args.push(format!("&({})", arg));
+        } else if let Some(ValueKind::Lambda) = param {
+            ril_args.push(arg);
+        } else {
+            ril_args.push(format!("({}).into()", arg));
+        }
+    }
+    if function.is_some_and(|f| f.is_variadic) {
+        ril_args.push(format!("&[{}]", variadic_args.join(",")));
+    }
+    format!(
+        "{}::{}({})",
+        BUILTIN_FUNCTIONS_PATH,
+        name,
+        ril_args.join(",")
+    )
+}
 
-        //remove initial fencepost "."
-        output.replacen(".", "", 1)
-    } else {
-        //remove original binding; no self or this
-        xo_symbol.as_str().to_string()
-    };
+/// Compiles a lambda like `|i| i.count > 3` into a closure.  Symbols rooted at its params, like
+/// `i.count`, are bound inside the closure from the element it is called with, rather than from
+/// properties, and so are removed from `symbolic_ids`.
+fn compile_lambda(
+    lambda: Pair<Rule>,
+    pratt_parser: &PrattParser<Rule>,
+    symbolic_ids: Rc<RefCell<Vec<String>>>,
+) -> String {
+    /* xo_lambda = {"|" ~ identifier ~ ("," ~ identifier)* ~ "|" ~ expression_body} */
+    let mut params = vec![];
+    let mut body = String::new();
+    let first_body_symbol = symbolic_ids.borrow().len();
+    for pair in lambda.into_inner() {
+        match pair.as_rule() {
+            Rule::identifier => params.push(pair.as_str().to_string()),
+            _ => {
+                body = recurse_pratt_parse_to_string(
+                    pair.into_inner(),
+                    pratt_parser,
+                    Rc::clone(&symbolic_ids),
+                )
+            }
+        }
+    }
+
+    let body_symbols = symbolic_ids.borrow_mut().split_off(first_body_symbol);
+    let mut element_symbols: Vec<String> = vec![];
+    for symbol in body_symbols {
+        let root = symbol.split(['.', '[']).next().unwrap().trim();
+        let symbol = symbol.trim().to_string();
+        if !params.iter().any(|param| param == root) {
+            symbolic_ids.borrow_mut().push(symbol);
+        } else if !params.contains(&symbol) && !element_symbols.contains(&symbol) {
+            element_symbols.push(symbol);
+        }
+    }
 
-    escape_identifier(self_or_this_removed)
+    // numbers become `Numeric`s and `String`s `StringBox`es, as when binding properties
+    let bindings: Vec<String> = params
+        .iter()
+        .map(|param| (param.clone(), param.clone()))
+        .chain(
+            element_symbols
+                .into_iter()
+                .map(|symbol| (escape_identifier(symbol.clone()), symbol)),
+        )
+        .map(|(identifier, access)| {
+            format!(
+                "let {} = {}::PaxelValue(({}).clone()).get();",
+                identifier, BUILTIN_FUNCTIONS_PATH, access
+            )
+        })
+        .collect();
+    format!(
+        "|{}| {{ use {}::PaxelValueAsIs; {} {} }}",
+        params.join(","),
+        BUILTIN_FUNCTIONS_PATH,
+        bindings.join(" "),
+        body
+    )
+}
+
+/// Removes leading `self.` or `this.`, escapes remaining symbol to be a suitable atomic identifier
+fn convert_symbolic_binding_from_paxel_to_ril(xo_symbol: Pair<Rule>) -> String {
+    let symbol = xo_symbol.as_str().trim();
+
+    //remove any leading `this` or `self`, keeping the rest of the symbol, including any `?.`
+    let self_or_this_removed = symbol
+        .strip_prefix("this.")
+        .or_else(|| symbol.strip_prefix("self."))
+        .unwrap_or(symbol);
+
+    escape_identifier(self_or_this_removed.to_string())
 }
 
 /// Workhorse method for compiling Expressions into Rust Intermediate Language (RIL, a string of Rust)
@@ -140,6 +277,30 @@ fn recurse_pratt_parse_to_string<'a>(
                 }
 
 
+            },
+            Rule::xo_function_call => {
+                /* xo_function_call = {identifier ~ "(" ~ xo_function_args_list ~ ")"} */
+                let mut pairs = primary.into_inner();
+                let name = pairs.next().unwrap().as_str();
+                let args = compile_function_args(pairs.next().unwrap(), pratt_parser, Rc::clone(&symbolic_ids));
+                builtin_function_call_to_ril(name, args)
+            },
+            Rule::xo_method_chain => {
+                /* xo_method_chain = {xo_method_receiver ~ xo_method_call+}
+                   xo_method_call = {"." ~ identifier ~ "(" ~ xo_function_args_list ~ ")"} */
+                let mut pairs = primary.into_inner();
+                let receiver = pairs.next().unwrap();
+                let mut output = recurse_pratt_parse_to_string(receiver.into_inner(), pratt_parser, Rc::clone(&symbolic_ids));
+
+                //`a.f(b).g()` is `g(f(a, b))`
+                for method_call in pairs {
+                    let mut inner = method_call.into_inner();
+                    let name = inner.next().unwrap().as_str();
+                    let mut args = vec![output];
+                    args.extend(compile_function_args(inner.next().unwrap(), pratt_parser, Rc::clone(&symbolic_ids)));
+                    output = builtin_function_call_to_ril(name, args);
+                }
+                output
             },
             Rule::xo_range => {
                 /* { op0: (xo_literal | xo_symbol) ~ op1: (xo_range_inclusive | xo_range_exclusive) ~ op2: (xo_literal | xo_symbol)} */
@@ -310,6 +471,25 @@ fn recurse_pratt_parse_to_string<'a>(
                 }
                 format!("vec![{}]", vec.join(","))
             },
+            Rule::xo_template_literal => {
+                /* xo_template_literal = ${ "`" ~ (xo_template_text | xo_template_hole)* ~ "`" }
+                   xo_template_hole = !{ "${" ~ xo_template_expression ~ xo_template_format? ~ "}" } */
+                let pieces: Vec<String> = primary.into_inner().map(|piece| match piece.as_rule() {
+                    Rule::xo_template_text => format!("String::from({:?})", unescape_template_text(piece.as_str())),
+                    Rule::xo_template_hole => {
+                        let mut hole = piece.into_inner();
+                        let exp = recurse_pratt_parse_to_string(hole.next().unwrap().into_inner(), pratt_parser, Rc::clone(&symbolic_ids));
+                        let spec = hole.next().map(|format| &format.as_str()[1..]).unwrap_or_default();
+                        format!("{}::interpolate(&({}), {:?})", BUILTIN_FUNCTIONS_PATH, exp, spec)
+                    },
+                    _ => unreachable!(),
+                }).collect();
+                if pieces.is_empty() {
+                    "StringBox::from(\"\")".to_string()
+                } else {
+                    format!("StringBox::from([{}].concat())", pieces.join(","))
+                }
+            },
             Rule::expression_body => {
                 recurse_pratt_parse_to_string(primary.into_inner(), pratt_parser, Rc::clone(&symbolic_ids))
             },
@@ -339,6 +519,7 @@ fn recurse_pratt_parse_to_string<'a>(
             Rule::xo_rel_lte => {format!("({}<={})", lhs, rhs)},
             Rule::xo_rel_neq => {format!("({}!={})", lhs, rhs)},
             Rule::xo_sub => {format!("({}-{})", lhs, rhs)},
+            Rule::xo_null_coalesce => {format!("(({}).unwrap_or_else(|| ({}).into()))", lhs, rhs)},
             Rule::xo_tern_then => {format!("if {} {{ {} }}", lhs, rhs)},
             Rule::xo_tern_else => {format!("{} else {{ {} }}", lhs, rhs)},
             _ => unreachable!(),
@@ -403,6 +584,7 @@ fn recurse_visit_tag_pairs_for_template(
                 ),
                 settings: parse_inline_attribute_from_final_pairs_of_tag(open_tag, pax),
                 raw_comment_string: None,
+                class_bundles: None,
                 control_flow_settings: None,
             };
 
@@ -445,6 +627,7 @@ fn recurse_visit_tag_pairs_for_template(
                 type_id,
                 settings: parse_inline_attribute_from_final_pairs_of_tag(tag_pairs, pax),
                 raw_comment_string: None,
+                class_bundles: None,
                 control_flow_settings: None,
             };
             let _ = match location {
@@ -453,53 +636,12 @@ fn recurse_visit_tag_pairs_for_template(
             };
         }
         Rule::statement_control_flow => {
-            /* statement_control_flow = {(statement_if | statement_for | statement_slot)} */
+            /* statement_control_flow = {(statement_if | statement_for | statement_slot | statement_match)} */
 
             let any_tag_pair = any_tag_pair.into_inner().next().unwrap();
             let _template_node_definition = match any_tag_pair.as_rule() {
                 Rule::statement_if => {
-                    let mut statement_if = any_tag_pair.into_inner();
-                    let expression_body = statement_if.next().unwrap();
-                    let expression_body_location = span_to_location(&expression_body.as_span());
-                    let expression_body_token = Token::new(
-                        expression_body.as_str().to_string(),
-                        TokenType::IfExpression,
-                        expression_body_location,
-                        pax,
-                    );
-
-                    //`if` TemplateNodeDefinition
-                    let template_node = TemplateNodeDefinition {
-                        control_flow_settings: Some(ControlFlowSettingsDefinition {
-                            condition_expression_paxel: Some(expression_body_token),
-                            condition_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
-                            slot_index_expression_paxel: None,
-                            slot_index_expression_vtable_id: None,
-                            repeat_predicate_definition: None,
-                            repeat_source_definition: None,
-                        }),
-                        type_id: TypeId::build_if(),
-                        settings: None,
-                        raw_comment_string: None,
-                    };
-
-                    let id = match location {
-                        TreeLocation::Root => ctx.template.add_root_node_back(template_node),
-                        TreeLocation::Parent(id) => ctx.template.add_child_back(id, template_node),
-                    };
-
-                    let prospective_inner_nodes = statement_if.next();
-
-                    if let Some(inner_nodes) = prospective_inner_nodes {
-                        inner_nodes.into_inner().for_each(|sub_tag_pair| {
-                            recurse_visit_tag_pairs_for_template(
-                                ctx,
-                                sub_tag_pair,
-                                pax,
-                                TreeLocation::Parent(id.clone().get_template_node_id()),
-                            );
-                        })
-                    }
+                    visit_statement_if(ctx, any_tag_pair, pax, location, false);
                 }
                 Rule::statement_for => {
                     let mut cfavd = ControlFlowSettingsDefinition::default();
@@ -507,7 +649,21 @@ fn recurse_visit_tag_pairs_for_template(
                     let mut predicate_declaration = for_statement.next().unwrap().into_inner();
                     let source = for_statement.next().unwrap();
 
-                    let prospective_inner_nodes = for_statement.next();
+                    let mut prospective_inner_nodes = for_statement.next();
+                    if let Some(key) = prospective_inner_nodes
+                        .take_if(|pair| pair.as_rule() == Rule::statement_for_key)
+                    {
+                        /* statement_for_key = {"key" ~ expression_body} */
+                        let key_expression = key.into_inner().next().unwrap();
+                        let key_location = span_to_location(&key_expression.as_span());
+                        cfavd.repeat_key_expression_paxel = Some(Token::new(
+                            key_expression.as_str().to_string(),
+                            TokenType::ForKey,
+                            key_location,
+                            pax,
+                        ));
+                        prospective_inner_nodes = for_statement.next();
+                    }
 
                     if predicate_declaration.clone().count() > 1 {
                         //tuple, like the `elem, i` in `for (elem, i) in self.some_list`
@@ -556,7 +712,7 @@ fn recurse_visit_tag_pairs_for_template(
                         inner_source_location,
                         pax,
                     );
-                    /* statement_for_source = { xo_range | xo_symbol } */
+                    /* statement_for_source = { xo_range | xo_method_chain | xo_symbol } */
                     let repeat_source_definition = match inner_source.as_rule() {
                         Rule::xo_range => {
                             ControlFlowRepeatSourceDefinition {
@@ -565,6 +721,12 @@ fn recurse_visit_tag_pairs_for_template(
                                 symbolic_binding: None,
                             }
                         }
+                        // e.g. `self.items.filter(|i| i.visible)`, compiled like any expression
+                        Rule::xo_method_chain => ControlFlowRepeatSourceDefinition {
+                            range_expression_paxel: None,
+                            vtable_id: None,
+                            symbolic_binding: Some(inner_source_token),
+                        },
                         Rule::xo_symbol => {
                             inner_source_token.token_value =
                                 convert_symbolic_binding_from_paxel_to_ril(inner_source);
@@ -587,6 +749,7 @@ fn recurse_visit_tag_pairs_for_template(
                         control_flow_settings: Some(cfavd),
                         settings: None,
                         raw_comment_string: None,
+                        class_bundles: None,
                     };
 
                     let id = match location {
@@ -621,14 +784,21 @@ fn recurse_visit_tag_pairs_for_template(
                         control_flow_settings: Some(ControlFlowSettingsDefinition {
                             condition_expression_paxel: None,
                             condition_expression_vtable_id: None,
+                            is_else_branch: false,
                             slot_index_expression_paxel: Some(expression_body_token),
                             slot_index_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
                             repeat_predicate_definition: None,
                             repeat_source_definition: None,
+                            repeat_key_expression_paxel: None,
+                            repeat_key_expression_vtable_id: None,
+                            match_expression_paxel: None,
+                            match_expression_vtable_id: None,
+                            match_arm_pattern: None,
                         }),
                         type_id: TypeId::build_slot(),
                         settings: None,
                         raw_comment_string: None,
+                        class_bundles: None,
                     };
 
                     let id = match location {
@@ -647,6 +817,9 @@ fn recurse_visit_tag_pairs_for_template(
                         })
                     }
                 }
+                Rule::statement_match => {
+                    visit_statement_match(ctx, any_tag_pair, pax, location);
+                }
                 _ => {
                     unreachable!("Parsing error: {:?}", any_tag_pair.as_rule());
                 }
@@ -658,6 +831,7 @@ fn recurse_visit_tag_pairs_for_template(
                 type_id: TypeId::build_comment(),
                 settings: None,
                 raw_comment_string: Some(any_tag_pair.as_str().to_string()),
+                class_bundles: None,
             };
             let _ = match location {
                 TreeLocation::Root => ctx.template.add_root_node_back(template_node),
@@ -674,6 +848,288 @@ fn recurse_visit_tag_pairs_for_template(
     }
 }
 
+/// Visits an `if` statement along with its (optional) `else` clause.  An `else` clause is added as the
+/// trailing child of the node it belongs to; an `else if` is an `else` clause whose body is another `if`
+/// statement, so that a chain of `else if`s nests one level per clause.
+fn visit_statement_if(
+    ctx: &mut TemplateNodeParseContext,
+    statement_if: Pair<Rule>,
+    pax: &str,
+    location: TreeLocation,
+    is_else_branch: bool,
+) {
+    /* statement_if = {"if" ~ (statement_if_let | expression_body) ~ "{" ~ inner_nodes ~ "}" ~ statement_else?} */
+    let mut statement_if = statement_if.into_inner();
+    let expression_body = statement_if.next().unwrap();
+    if expression_body.as_rule() == Rule::statement_if_let {
+        visit_statement_if_let(
+            ctx,
+            expression_body,
+            statement_if,
+            pax,
+            location,
+            is_else_branch,
+        );
+        return;
+    }
+    let expression_body_location = span_to_location(&expression_body.as_span());
+    let expression_body_token = Token::new(
+        expression_body.as_str().to_string(),
+        TokenType::IfExpression,
+        expression_body_location,
+        pax,
+    );
+
+    //`if` TemplateNodeDefinition
+    let template_node = TemplateNodeDefinition {
+        control_flow_settings: Some(ControlFlowSettingsDefinition {
+            condition_expression_paxel: Some(expression_body_token),
+            condition_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
+            is_else_branch,
+            slot_index_expression_paxel: None,
+            slot_index_expression_vtable_id: None,
+            repeat_predicate_definition: None,
+            repeat_source_definition: None,
+            repeat_key_expression_paxel: None,
+            repeat_key_expression_vtable_id: None,
+            match_expression_paxel: None,
+            match_expression_vtable_id: None,
+            match_arm_pattern: None,
+        }),
+        type_id: TypeId::build_if(),
+        settings: None,
+        raw_comment_string: None,
+        class_bundles: None,
+    };
+
+    let id = match location {
+        TreeLocation::Root => ctx.template.add_root_node_back(template_node),
+        TreeLocation::Parent(id) => ctx.template.add_child_back(id, template_node),
+    };
+    let id = id.get_template_node_id();
+
+    if let Some(inner_nodes) = statement_if.next() {
+        inner_nodes.into_inner().for_each(|sub_tag_pair| {
+            recurse_visit_tag_pairs_for_template(
+                ctx,
+                sub_tag_pair,
+                pax,
+                TreeLocation::Parent(id.clone()),
+            );
+        })
+    }
+
+    if let Some(statement_else) = statement_if.next() {
+        /* statement_else = {"else" ~ (statement_if | ("{" ~ inner_nodes ~ "}"))} */
+        let else_body = statement_else.into_inner().next().unwrap();
+        match else_body.as_rule() {
+            Rule::statement_if => {
+                visit_statement_if(ctx, else_body, pax, TreeLocation::Parent(id), true);
+            }
+            Rule::inner_nodes => {
+                let else_id = add_bare_else(ctx, id);
+                else_body.into_inner().for_each(|sub_tag_pair| {
+                    recurse_visit_tag_pairs_for_template(
+                        ctx,
+                        sub_tag_pair,
+                        pax,
+                        TreeLocation::Parent(else_id.clone()),
+                    );
+                })
+            }
+            _ => {
+                unreachable!("Parsing error: {:?}", else_body.as_rule());
+            }
+        }
+    }
+}
+
+/// Adds a bare `else` as the trailing child of the `if` node `id`, returning its id
+fn add_bare_else(ctx: &mut TemplateNodeParseContext, id: TemplateNodeId) -> TemplateNodeId {
+    //bare `else` TemplateNodeDefinition; without a condition, it is always active once reached
+    let template_node = TemplateNodeDefinition {
+        control_flow_settings: Some(ControlFlowSettingsDefinition {
+            is_else_branch: true,
+            ..Default::default()
+        }),
+        type_id: TypeId::build_if(),
+        settings: None,
+        raw_comment_string: None,
+        class_bundles: None,
+    };
+    ctx.template
+        .add_child_back(id, template_node)
+        .get_template_node_id()
+}
+
+/// Visits an `if let`, e.g. `if let Some(user) = self.user { ... } else { ... }`, which is sugar for
+/// a `match` with an arm for its pattern and, given an `else` clause, a wildcard arm for the `else`.
+/// As an `else if let`, the `match` is the body of a bare `else`.
+fn visit_statement_if_let(
+    ctx: &mut TemplateNodeParseContext,
+    statement_if_let: Pair<Rule>,
+    mut rest: Pairs<Rule>,
+    pax: &str,
+    location: TreeLocation,
+    is_else_branch: bool,
+) {
+    /* statement_if_let = {"let" ~ statement_match_pattern ~ "=" ~ xo_symbol} */
+    let mut statement_if_let = statement_if_let.into_inner();
+    let pattern = parse_statement_match_pattern(statement_if_let.next().unwrap(), pax);
+    let xo_symbol = statement_if_let.next().unwrap();
+
+    let location = match location {
+        TreeLocation::Parent(id) if is_else_branch => TreeLocation::Parent(add_bare_else(ctx, id)),
+        location => location,
+    };
+    let id = add_match(ctx, xo_symbol, pax, location);
+
+    let arm_id = add_match_arm(ctx, id.clone(), pattern);
+    rest.next().unwrap().into_inner().for_each(|sub_tag_pair| {
+        recurse_visit_tag_pairs_for_template(
+            ctx,
+            sub_tag_pair,
+            pax,
+            TreeLocation::Parent(arm_id.clone()),
+        );
+    });
+
+    if let Some(statement_else) = rest.next() {
+        /* statement_else = {"else" ~ (statement_if | ("{" ~ inner_nodes ~ "}"))} */
+        let else_body = statement_else.into_inner().next().unwrap();
+        let else_id = add_match_arm(ctx, id, ControlFlowMatchPatternDefinition::default());
+        match else_body.as_rule() {
+            Rule::statement_if => {
+                visit_statement_if(ctx, else_body, pax, TreeLocation::Parent(else_id), false);
+            }
+            Rule::inner_nodes => else_body.into_inner().for_each(|sub_tag_pair| {
+                recurse_visit_tag_pairs_for_template(
+                    ctx,
+                    sub_tag_pair,
+                    pax,
+                    TreeLocation::Parent(else_id.clone()),
+                );
+            }),
+            _ => {
+                unreachable!("Parsing error: {:?}", else_body.as_rule());
+            }
+        }
+    }
+}
+
+/// Visits a `match` statement.  Each arm is added as a child of the `match` node, in order, holding
+/// its pattern; the arm's nodes are added as children of the arm.
+fn visit_statement_match(
+    ctx: &mut TemplateNodeParseContext,
+    statement_match: Pair<Rule>,
+    pax: &str,
+    location: TreeLocation,
+) {
+    /* statement_match = {"match" ~ xo_symbol ~ "{" ~ (statement_match_arm ~ silent_comma?)* ~ "}"} */
+    let mut statement_match = statement_match.into_inner();
+    let xo_symbol = statement_match.next().unwrap();
+    let id = add_match(ctx, xo_symbol, pax, location);
+
+    for statement_match_arm in statement_match {
+        /* statement_match_arm = {statement_match_pattern ~ "=>" ~ "{" ~ inner_nodes ~ "}"} */
+        let mut statement_match_arm = statement_match_arm.into_inner();
+        let pattern = parse_statement_match_pattern(statement_match_arm.next().unwrap(), pax);
+        let arm_id = add_match_arm(ctx, id.clone(), pattern);
+
+        let inner_nodes = statement_match_arm.next().unwrap();
+        inner_nodes.into_inner().for_each(|sub_tag_pair| {
+            recurse_visit_tag_pairs_for_template(
+                ctx,
+                sub_tag_pair,
+                pax,
+                TreeLocation::Parent(arm_id.clone()),
+            );
+        });
+    }
+}
+
+/// Adds a `match` node on the value of `xo_symbol`, returning its id
+fn add_match(
+    ctx: &mut TemplateNodeParseContext,
+    xo_symbol: Pair<Rule>,
+    pax: &str,
+    location: TreeLocation,
+) -> TemplateNodeId {
+    let xo_symbol_token = Token::new(
+        xo_symbol.as_str().to_string(),
+        TokenType::MatchExpression,
+        span_to_location(&xo_symbol.as_span()),
+        pax,
+    );
+
+    //`match` TemplateNodeDefinition
+    let template_node = TemplateNodeDefinition {
+        control_flow_settings: Some(ControlFlowSettingsDefinition {
+            match_expression_paxel: Some(xo_symbol_token),
+            match_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
+            ..Default::default()
+        }),
+        type_id: TypeId::build_match(),
+        settings: None,
+        raw_comment_string: None,
+        class_bundles: None,
+    };
+    let id = match location {
+        TreeLocation::Root => ctx.template.add_root_node_back(template_node),
+        TreeLocation::Parent(id) => ctx.template.add_child_back(id, template_node),
+    };
+    id.get_template_node_id()
+}
+
+/// Adds an arm matching `pattern` as the trailing child of the `match` node `id`, returning its id
+fn add_match_arm(
+    ctx: &mut TemplateNodeParseContext,
+    id: TemplateNodeId,
+    pattern: ControlFlowMatchPatternDefinition,
+) -> TemplateNodeId {
+    //arm TemplateNodeDefinition
+    let template_node = TemplateNodeDefinition {
+        control_flow_settings: Some(ControlFlowSettingsDefinition {
+            match_arm_pattern: Some(pattern),
+            ..Default::default()
+        }),
+        type_id: TypeId::build_match(),
+        settings: None,
+        raw_comment_string: None,
+        class_bundles: None,
+    };
+    ctx.template
+        .add_child_back(id, template_node)
+        .get_template_node_id()
+}
+
+fn parse_statement_match_pattern(
+    statement_match_pattern: Pair<Rule>,
+    pax: &str,
+) -> ControlFlowMatchPatternDefinition {
+    /* statement_match_pattern = { statement_match_wildcard | (statement_match_variant ~ ("(" ~ identifier ~ ("," ~ identifier)* ~ ")")?) } */
+    let mut pattern_pairs = statement_match_pattern.into_inner();
+    let first = pattern_pairs.next().unwrap();
+    let to_token = |pair: Pair<Rule>| {
+        Token::new(
+            pair.as_str().to_string(),
+            TokenType::MatchPattern,
+            span_to_location(&pair.as_span()),
+            pax,
+        )
+    };
+    match first.as_rule() {
+        Rule::statement_match_wildcard => ControlFlowMatchPatternDefinition::default(),
+        Rule::statement_match_variant => ControlFlowMatchPatternDefinition {
+            variant: Some(to_token(first)),
+            bindings: pattern_pairs.map(to_token).collect(),
+        },
+        _ => {
+            unreachable!("Parsing error: {:?}", first.as_rule());
+        }
+    }
+}
+
 fn parse_literal_function(literal_function_full: Pair<Rule>, pax: &str) -> Token {
     let literal_function = literal_function_full.clone().into_inner().next().unwrap();
 
@@ -730,6 +1186,32 @@ fn parse_inline_attribute_from_final_pairs_of_tag(
                         ValueDefinition::EventBindingTarget(literal_function_token),
                     )
                 }
+                Rule::class_binding => {
+                    let mut kv = attribute_key_value_pair
+                        .into_inner()
+                        .next()
+                        .unwrap()
+                        .into_inner();
+                    let class_key = kv.next().unwrap();
+                    let class_key_token = Token::new(
+                        class_key.as_str().to_string(),
+                        TokenType::SettingKey,
+                        span_to_location(&class_key.as_span()),
+                        pax,
+                    );
+                    // One or more class names, kept as written and split when matching selectors
+                    let class_list = kv.next().unwrap();
+                    let class_list_token = Token::new(
+                        class_list.as_str().to_string(),
+                        TokenType::LiteralValue,
+                        span_to_location(&class_list.as_span()),
+                        pax,
+                    );
+                    SettingElement::Setting(
+                        class_key_token,
+                        ValueDefinition::LiteralValue(class_list_token),
+                    )
+                }
                 Rule::id_binding => {
                     let mut kv = attribute_key_value_pair
                         .into_inner()
@@ -893,6 +1375,9 @@ fn derive_value_definition_from_literal_object_pair(
                                     derive_value_definition_from_literal_object_pair(value, pax),
                                 )
                             }
+                            Rule::literal_timeline => ValueDefinition::Timeline(
+                                derive_keyframes_from_literal_timeline_pair(value, pax),
+                            ),
                             // Rule::literal_enum_value => {ValueDefinition::Enum(raw_value.as_str().to_string())},
                             Rule::expression_body => {
                                 let token = Token::new_with_raw_value(
@@ -927,6 +1412,117 @@ fn derive_value_definition_from_literal_object_pair(
     }
 }
 
+/// Parses a `@timeline { 0: 0px, 60: 200px OutBack }` property value into its keyframes
+fn derive_keyframes_from_literal_timeline_pair(
+    literal_timeline: Pair<Rule>,
+    pax: &str,
+) -> Vec<KeyframeDefinition> {
+    literal_timeline
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::literal_keyframe)
+        .map(|literal_keyframe| {
+            let mut pairs = literal_keyframe.into_inner();
+            let raw_frame = pairs.next().unwrap();
+            let frame = raw_frame.as_str().parse::<usize>().unwrap_or_else(|_| {
+                panic!(
+                    "Keyframes must be non-negative frame numbers, found {}",
+                    raw_frame.as_str()
+                )
+            });
+
+            let value = pairs.next().unwrap();
+            //pratt-parse literals as for any other literal setting, to unpack `px` and `%`
+            let (output_string, _) = crate::parsing::run_pratt_parser(value.as_str());
+            let value_token = Token::new_with_raw_value(
+                output_string,
+                value.as_str().to_string(),
+                TokenType::LiteralValue,
+                span_to_location(&value.as_span()),
+                pax,
+            );
+
+            let curve = pairs.next().map(|curve| {
+                Token::new(
+                    curve.as_str().to_string(),
+                    TokenType::PascalIdentifier,
+                    span_to_location(&curve.as_span()),
+                    pax,
+                )
+            });
+
+            KeyframeDefinition {
+                frame,
+                value: value_token,
+                curve,
+            }
+        })
+        .collect()
+}
+
+/// Reads the `frames` (required), `loop` and `autoplay` keys of a `@timeline: { ... }` declaration
+fn derive_timeline_definition_from_literal_object_pair(
+    literal_object: Pair<Rule>,
+    pax: &str,
+) -> TimelineDefinition {
+    let block = derive_value_definition_from_literal_object_pair(literal_object, pax);
+    let mut frame_count = None;
+    let mut is_looping = false;
+    let mut autoplay = false;
+    for element in block.elements {
+        if let SettingElement::Setting(key, ValueDefinition::LiteralValue(value)) = element {
+            let raw_value = value.raw_value.trim();
+            match key.token_value.as_str() {
+                "frames" => {
+                    frame_count = Some(raw_value.parse::<usize>().unwrap_or_else(|_| {
+                        panic!(
+                            "@timeline frames must be a frame count, found {}",
+                            raw_value
+                        )
+                    }))
+                }
+                "loop" => is_looping = raw_value == "true",
+                "autoplay" => autoplay = raw_value == "true",
+                other => panic!("Unknown @timeline setting: {}", other),
+            }
+        }
+    }
+    TimelineDefinition {
+        frame_count: frame_count.expect("@timeline must declare its number of frames"),
+        is_looping,
+        autoplay,
+    }
+}
+
+fn derive_theme_token_definitions_from_declaration_pair(
+    theme_declaration: Pair<Rule>,
+    pax: &str,
+) -> Vec<ThemeTokenDefinition> {
+    theme_declaration
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::theme_token_declaration)
+        .map(|pair| {
+            let mut pairs = pair.into_inner();
+            let token = pairs.next().unwrap();
+            let kind = pairs.next().unwrap().as_str();
+            ThemeTokenDefinition {
+                token: Token::new(
+                    token.as_str().to_string(),
+                    TokenType::Identifier,
+                    span_to_location(&token.as_span()),
+                    pax,
+                ),
+                kind: ThemeTokenKind::parse(kind).unwrap_or_else(|| {
+                    panic!(
+                        "Unknown @theme token kind `{}` for `{}`; expected one of color, size, font or radius",
+                        kind,
+                        token.as_str()
+                    )
+                }),
+            }
+        })
+        .collect()
+}
+
 pub fn parse_settings_from_component_definition_string(pax: &str) -> Vec<SettingsBlockElement> {
     let pax_component_definition = PaxParser::parse(Rule::pax_component_definition, pax)
         .expect(&format!("unsuccessful parse from {}", &pax)) // unwrap the parse result
@@ -963,6 +1559,28 @@ pub fn parse_settings_from_component_definition_string(pax: &str) -> Vec<Setting
                                         );
                                     settings.push(handler_element);
                                 }
+                                Rule::settings_timeline_declaration => {
+                                    //component timeline in the form of `@timeline: { frames: 120 }`
+                                    let literal_object = top_level_settings_block_entity
+                                        .into_inner()
+                                        .next()
+                                        .unwrap();
+                                    settings.push(SettingsBlockElement::Timeline(
+                                        derive_timeline_definition_from_literal_object_pair(
+                                            literal_object,
+                                            pax,
+                                        ),
+                                    ));
+                                }
+                                Rule::settings_theme_declaration => {
+                                    //design tokens in the form of `@theme: { primary: color }`
+                                    settings.push(SettingsBlockElement::Theme(
+                                        derive_theme_token_definitions_from_declaration_pair(
+                                            top_level_settings_block_entity,
+                                            pax,
+                                        ),
+                                    ));
+                                }
                                 Rule::selector_block => {
                                     //selector_block => settings_key_value_pair where v is a ValueDefinition
                                     let mut selector_block_pairs =
@@ -971,11 +1589,12 @@ pub fn parse_settings_from_component_definition_string(pax: &str) -> Vec<Setting
                                     let raw_selector = selector_block_pairs.next().unwrap();
                                     let raw_value_location =
                                         span_to_location(&raw_selector.as_span());
+                                    // Whitespace separates compound selectors, so it's normalized rather than dropped
                                     let selector: String = raw_selector
                                         .as_str()
-                                        .chars()
-                                        .filter(|c| !c.is_whitespace())
-                                        .collect();
+                                        .split_whitespace()
+                                        .collect::<Vec<_>>()
+                                        .join(" ");
                                     let token = Token::new(
                                         selector,
                                         TokenType::Selector,
@@ -1442,7 +2061,8 @@ impl<T: Reflectable> Reflectable for std::option::Option<T> {
         let type_id = Self::get_type_id();
         let td = TypeDefinition {
             type_id: type_id.clone(),
-            inner_iterable_type_id: None,
+            //the `T` reached by `?.` and bound by `if let Some(...)`
+            inner_iterable_type_id: Some(T::get_type_id()),
             property_definitions: vec![],
         };
 
@@ -1532,6 +2152,23 @@ impl Reflectable for pax_runtime::api::Color {
     }
 }
 
+impl Reflectable for pax_runtime::api::BlendMode {
+    fn get_import_path() -> String {
+        "pax_engine::api::BlendMode".to_string()
+    }
+
+    fn get_self_pascal_identifier() -> String {
+        "BlendMode".to_string()
+    }
+
+    fn get_type_id() -> TypeId {
+        TypeId::build_singleton(
+            &Self::get_import_path(),
+            Some(&Self::get_self_pascal_identifier()),
+        )
+    }
+}
+
 impl Reflectable for pax_runtime::api::ColorChannel {
     fn get_import_path() -> String {
         "pax_engine::api::ColorChannel".to_string()
@@ -0,0 +1,1004 @@
+//! # Type checking
+//!
+//! Infers the type of each PAXEL expression from the component's `PropertyDefinition`s and the
+//! `TypeTable` before it is compiled to RIL, so that type errors, like binding a `Color` to a
+//! `Size` or calling a function with too few arguments, are reported against the exact span of
+//! `.pax` source at fault rather than surfacing later as rustc errors in the generated cartridge.
+//!
+//! Inference is conservative: whatever can't be typed statically, like enum variants or the
+//! fields of types missing from the type table, is `PaxelType::Unknown`, and never an error.
+
+use color_eyre::eyre::Report;
+use pax_manifest::{
+    ComponentDefinition, LocationInfo, PaxType, ThemeTokenDefinition, ThemeTokenKind, Token,
+    TypeId, TypeTable, SUPPORTED_NUMERIC_PRIMITIVES,
+};
+use pax_runtime_api::functions::{
+    get_builtin_function, BuiltinFunction, ValueKind, BUILTIN_FUNCTIONS,
+};
+use pest::iterators::{Pair, Pairs};
+use pest::pratt_parser::PrattParser;
+use pest::Parser;
+
+use crate::errors::PaxTemplateError;
+use crate::expressions::{resolve_symbol_as_theme_token, ExpressionCompilationContext};
+use crate::parsing::{paxel_pratt_parser, PaxParser, Rule};
+
+/// The static type of a PAXEL value
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaxelType {
+    /// `Numeric` and the numeric primitives, which expressions handle as `Numeric`
+    Number,
+    /// `String` and `StringBox`
+    String,
+    Bool,
+    Color,
+    Size,
+    Percent,
+    Rotation,
+    Range,
+    /// A list of elements of the given type
+    List(Box<PaxelType>),
+    /// A lambda returning the given type
+    Lambda(Box<PaxelType>),
+    /// An `Option` of the given type, as read from an `Option` property or through `?.`
+    Option(Box<PaxelType>),
+    /// Any other type known to the type table, e.g. an enum or a struct declared by a component
+    Named(TypeId),
+    Unknown,
+}
+
+impl PaxelType {
+    /// The type expressions see for a property or field of type `type_id`
+    pub fn of(type_id: &TypeId, type_table: &TypeTable) -> Self {
+        match type_id.get_pax_type() {
+            PaxType::Vector { .. } => PaxelType::List(Box::new(
+                type_table
+                    .get(type_id)
+                    .and_then(|td| td.inner_iterable_type_id.as_ref())
+                    .map(|elem| Self::of(elem, type_table))
+                    .unwrap_or(PaxelType::Unknown),
+            )),
+            PaxType::Option { .. } => PaxelType::Option(Box::new(
+                type_table
+                    .get(type_id)
+                    .and_then(|td| td.inner_iterable_type_id.as_ref())
+                    .map(|inner| Self::of(inner, type_table))
+                    .unwrap_or(PaxelType::Unknown),
+            )),
+            PaxType::Range { .. } => PaxelType::Range,
+            PaxType::Map { .. } | PaxType::Unknown => PaxelType::Unknown,
+            _ => match type_id.get_pascal_identifier().as_deref() {
+                Some("Numeric") => PaxelType::Number,
+                Some(ident) if SUPPORTED_NUMERIC_PRIMITIVES.contains(&ident) => PaxelType::Number,
+                Some("String" | "StringBox") => PaxelType::String,
+                Some("bool") => PaxelType::Bool,
+                Some("Color") => PaxelType::Color,
+                Some("Size") => PaxelType::Size,
+                Some("Percent") => PaxelType::Percent,
+                Some("Rotation") => PaxelType::Rotation,
+                _ => PaxelType::Named(type_id.clone()),
+            },
+        }
+    }
+
+    /// Whether a property of this type can be bound to a value of type `value`, i.e. whether
+    /// the `(value).into()` generated for the binding compiles
+    pub fn accepts(&self, value: &PaxelType) -> bool {
+        match (self, value) {
+            (PaxelType::Named(_) | PaxelType::Unknown, _)
+            | (_, PaxelType::Named(_) | PaxelType::Unknown) => true,
+            (PaxelType::Size, PaxelType::Number | PaxelType::Percent)
+            | (PaxelType::Rotation, PaxelType::Number | PaxelType::Percent | PaxelType::Size) => {
+                true
+            }
+            (PaxelType::List(_), PaxelType::List(_)) => true,
+            (PaxelType::Option(expected), PaxelType::Option(value)) => expected.accepts(value),
+            (PaxelType::Option(expected), value) => **expected == *value,
+            (expected, value) => expected == value,
+        }
+    }
+
+    /// Whether this is one of the built-in types whose operators and conversions are known
+    fn is_builtin(&self) -> bool {
+        match self {
+            PaxelType::Option(inner) => inner.is_builtin(),
+            _ => !matches!(
+                self,
+                PaxelType::List(_)
+                    | PaxelType::Lambda(_)
+                    | PaxelType::Named(_)
+                    | PaxelType::Unknown
+            ),
+        }
+    }
+
+    /// Whether this can fill a hole of a template literal, as a `Printable` of the runtime
+    fn is_printable(&self) -> bool {
+        matches!(
+            self,
+            PaxelType::Number
+                | PaxelType::String
+                | PaxelType::Bool
+                | PaxelType::Size
+                | PaxelType::Percent
+                | PaxelType::Rotation
+                | PaxelType::Unknown
+        )
+    }
+
+    fn value_kind(&self) -> Option<ValueKind> {
+        match self {
+            PaxelType::Number => Some(ValueKind::Number),
+            PaxelType::String => Some(ValueKind::String),
+            PaxelType::Bool => Some(ValueKind::Bool),
+            PaxelType::Color => Some(ValueKind::Color),
+            PaxelType::List(_) => Some(ValueKind::List),
+            PaxelType::Lambda(_) => Some(ValueKind::Lambda),
+            _ => None,
+        }
+    }
+
+    fn element(&self) -> PaxelType {
+        match self {
+            PaxelType::List(elem) => (**elem).clone(),
+            _ => PaxelType::Unknown,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            PaxelType::Number => "a number".to_string(),
+            PaxelType::String => "a string".to_string(),
+            PaxelType::Bool => "a bool".to_string(),
+            PaxelType::Color => "a color".to_string(),
+            PaxelType::Size => "a size".to_string(),
+            PaxelType::Percent => "a percentage".to_string(),
+            PaxelType::Rotation => "a rotation".to_string(),
+            PaxelType::Range => "a range".to_string(),
+            PaxelType::List(_) => "a list".to_string(),
+            PaxelType::Lambda(_) => "a lambda".to_string(),
+            PaxelType::Option(inner) => {
+                let inner = inner.describe();
+                // "a size" reads "an optional size"
+                let noun = inner
+                    .split_once(' ')
+                    .map_or(inner.as_str(), |(_, noun)| noun);
+                format!("an optional {}", noun)
+            }
+            PaxelType::Named(type_id) => format!(
+                "a `{}`",
+                type_id
+                    .get_pascal_identifier()
+                    .unwrap_or_else(|| type_id.get_unique_identifier())
+            ),
+            PaxelType::Unknown => "a value".to_string(),
+        }
+    }
+}
+
+fn describe_value_kind(kind: ValueKind) -> &'static str {
+    match kind {
+        ValueKind::Number => "a number",
+        ValueKind::String => "a string",
+        ValueKind::Bool => "a bool",
+        ValueKind::Color => "a color",
+        ValueKind::List => "a list",
+        ValueKind::Sequence => "a list or a string",
+        ValueKind::Printable => "a number, string or bool",
+        ValueKind::Element => "a list element",
+        ValueKind::Lambda => "a lambda",
+    }
+}
+
+/// Infers the type of `paxel`, reporting the first type error found in it
+pub fn infer_paxel_type(
+    paxel: &Token,
+    ctx: &ExpressionCompilationContext,
+) -> Result<PaxelType, Report> {
+    let Ok(pairs) = PaxParser::parse(Rule::expression_body, &paxel.token_value) else {
+        // left for the Pratt parser to report
+        return Ok(PaxelType::Unknown);
+    };
+    let checker = TypeChecker {
+        paxel,
+        ctx,
+        pratt: paxel_pratt_parser(),
+    };
+    Ok(checker.infer_body(pairs, &[])?.ty)
+}
+
+/// Checks that `paxel`, inferred to be of type `actual`, can be bound to `target`, e.g. "`width`"
+/// or "an `if` condition", of type `expected`
+pub fn check_paxel_type(
+    paxel: &Token,
+    actual: &PaxelType,
+    expected: &PaxelType,
+    target: &str,
+) -> Result<(), Report> {
+    if expected.accepts(actual) {
+        return Ok(());
+    }
+    let hint = match (expected, actual) {
+        (PaxelType::String, PaxelType::Number | PaxelType::Bool) => {
+            "  Try `str(...)` to turn it into a string."
+        }
+        (PaxelType::Bool, PaxelType::Number) => "  Try comparing it, e.g. with `> 0`.",
+        (_, PaxelType::Option(_)) => OPTIONAL_HINT,
+        _ => "",
+    };
+    Err(PaxTemplateError::new(
+        Some(format!(
+            "{} expects {}, but this expression is {}.{}",
+            target,
+            expected.describe(),
+            actual.describe(),
+            hint
+        )),
+        paxel.clone(),
+    ))
+}
+
+const OPTIONAL_HINT: &str = "  Try `??` to give it a default for when it is `None`.";
+
+/// The closest of `candidates` to `name`, if close enough to be a likely typo
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= (name.chars().count() / 2).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn did_you_mean(suggestion: Option<&str>, prefix: &str) -> String {
+    suggestion
+        .map(|s| format!("  Did you mean `{}{}`?", prefix, s))
+        .unwrap_or_default()
+}
+
+/// A typed sub-expression, and its byte span within the expression's source
+struct Typed {
+    ty: PaxelType,
+    span: (usize, usize),
+}
+
+impl Typed {
+    fn of(ty: PaxelType, pair: &Pair<Rule>) -> Self {
+        Typed {
+            ty,
+            span: (pair.as_span().start(), pair.as_span().end()),
+        }
+    }
+}
+
+/// The types of the params of the lambdas enclosing a sub-expression, innermost last
+type LambdaParams = [(String, PaxelType)];
+
+struct TypeChecker<'a, 'b> {
+    paxel: &'b Token,
+    ctx: &'b ExpressionCompilationContext<'a>,
+    pratt: PrattParser<Rule>,
+}
+
+impl<'a, 'b> TypeChecker<'a, 'b> {
+    /// An error pointing at `span` of the expression
+    fn error(&self, span: (usize, usize), message: String) -> Report {
+        PaxTemplateError::new(Some(message), self.token_at(span))
+    }
+
+    /// The token for `span` of the expression, located within the `.pax` source when the
+    /// expression's own location is known
+    fn token_at(&self, (start, end): (usize, usize)) -> Token {
+        let text = &self.paxel.token_value;
+        let Some(location) = &self.paxel.token_location else {
+            return self.paxel.clone();
+        };
+        if self.paxel.raw_value != *text {
+            return self.paxel.clone();
+        }
+        let line_col = |offset: usize| {
+            let before = &text[..offset];
+            match before.rfind('\n') {
+                None => (
+                    location.start_line_col.0,
+                    location.start_line_col.1 + before.chars().count(),
+                ),
+                Some(newline) => (
+                    location.start_line_col.0 + before.matches('\n').count(),
+                    before[newline + 1..].chars().count(),
+                ),
+            }
+        };
+        let start_line_col = line_col(start);
+        let source_line = match text[..start].rfind('\n') {
+            None => self.paxel.source_line.clone(),
+            Some(newline) => text[newline + 1..].lines().next().map(str::to_string),
+        };
+        Token {
+            token_value: text[start..end].to_string(),
+            raw_value: text[start..end].to_string(),
+            token_type: self.paxel.token_type.clone(),
+            source_line,
+            token_location: Some(LocationInfo {
+                start_line_col,
+                end_line_col: line_col(end),
+            }),
+        }
+    }
+
+    fn source(&self, span: (usize, usize)) -> &str {
+        self.paxel.token_value[span.0..span.1].trim()
+    }
+
+    fn infer_body(&self, pairs: Pairs<Rule>, params: &LambdaParams) -> Result<Typed, Report> {
+        self.pratt
+            .map_primary(|primary| self.infer_primary(primary, params))
+            .map_prefix(|op, operand| {
+                let operand = operand?;
+                let span = (op.as_span().start(), operand.span.1);
+                let ty = match (op.as_rule(), operand.ty) {
+                    (Rule::xo_bool_not, ty) => {
+                        self.expect_bool("!", &ty, operand.span)?;
+                        PaxelType::Bool
+                    }
+                    (_, ty @ (PaxelType::Number | PaxelType::Size | PaxelType::Rotation)) => ty,
+                    (_, ty) if ty.is_builtin() => {
+                        return Err(
+                            self.error(span, format!("`-` can't be applied to {}", ty.describe()))
+                        )
+                    }
+                    _ => PaxelType::Unknown,
+                };
+                Ok(Typed { ty, span })
+            })
+            .map_infix(|lhs, op, rhs| {
+                let (lhs, rhs) = (lhs?, rhs?);
+                let span = (lhs.span.0, rhs.span.1);
+                let ty = match op.as_rule() {
+                    Rule::xo_bool_and | Rule::xo_bool_or => {
+                        self.expect_bool(op.as_str(), &lhs.ty, lhs.span)?;
+                        self.expect_bool(op.as_str(), &rhs.ty, rhs.span)?;
+                        PaxelType::Bool
+                    }
+                    Rule::xo_rel_eq
+                    | Rule::xo_rel_neq
+                    | Rule::xo_rel_lt
+                    | Rule::xo_rel_lte
+                    | Rule::xo_rel_gt
+                    | Rule::xo_rel_gte => PaxelType::Bool,
+                    Rule::xo_tern_then => {
+                        self.expect_bool("?", &lhs.ty, lhs.span)?;
+                        rhs.ty
+                    }
+                    Rule::xo_tern_else => self.join(&lhs, &rhs, "the branches of `? :`")?,
+                    Rule::xo_null_coalesce => self.infer_null_coalesce(&lhs, &rhs)?,
+                    _ => self.infer_arithmetic(op.as_str(), &lhs, &rhs, span)?,
+                };
+                Ok(Typed { ty, span })
+            })
+            .parse(pairs)
+    }
+
+    fn expect_bool(&self, op: &str, ty: &PaxelType, span: (usize, usize)) -> Result<(), Report> {
+        if ty.is_builtin() && *ty != PaxelType::Bool {
+            return Err(self.error(
+                span,
+                format!(
+                    "`{}` expects a bool, but `{}` is {}",
+                    op,
+                    self.source(span),
+                    ty.describe()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The type both `a` and `b` have, for values that must share one
+    fn join(&self, a: &Typed, b: &Typed, what: &str) -> Result<PaxelType, Report> {
+        if a.ty == b.ty {
+            Ok(a.ty.clone())
+        } else if a.ty.is_builtin() && b.ty.is_builtin() {
+            Err(self.error(
+                (a.span.0, b.span.1),
+                format!(
+                    "{} must have the same type, but `{}` is {} and `{}` is {}",
+                    what,
+                    self.source(a.span),
+                    a.ty.describe(),
+                    self.source(b.span),
+                    b.ty.describe()
+                ),
+            ))
+        } else {
+            Ok(PaxelType::Unknown)
+        }
+    }
+
+    /// The type of `lhs ?? rhs`: that of the value within `lhs`, an `Option`, which `rhs` defaults
+    fn infer_null_coalesce(&self, lhs: &Typed, rhs: &Typed) -> Result<PaxelType, Report> {
+        match &lhs.ty {
+            PaxelType::Option(inner) if inner.accepts(&rhs.ty) => Ok((**inner).clone()),
+            PaxelType::Option(inner) => Err(self.error(
+                rhs.span,
+                format!(
+                    "The default of `{}` must be {}, but `{}` is {}",
+                    self.source(lhs.span),
+                    inner.describe(),
+                    self.source(rhs.span),
+                    rhs.ty.describe()
+                ),
+            )),
+            ty if ty.is_builtin() => Err(self.error(
+                lhs.span,
+                format!(
+                    "`??` expects an optional value, but `{}` is {}",
+                    self.source(lhs.span),
+                    ty.describe()
+                ),
+            )),
+            _ => Ok(PaxelType::Unknown),
+        }
+    }
+
+    /// The type of `lhs op rhs` for the arithmetic operators, per the operator impls of the runtime types
+    fn infer_arithmetic(
+        &self,
+        op: &str,
+        lhs: &Typed,
+        rhs: &Typed,
+        span: (usize, usize),
+    ) -> Result<PaxelType, Report> {
+        use PaxelType::*;
+        let ty = match (op.trim(), &lhs.ty, &rhs.ty) {
+            (_, Number, Number) => Number,
+            ("+", String, String) => String,
+            ("+" | "-", Size | Percent, Size) | ("+" | "-", Size, Percent) | ("*", Size, Size) => {
+                Size
+            }
+            ("+", Rotation, Rotation) => Rotation,
+            (op, l, r) if l.is_builtin() && r.is_builtin() => {
+                let hint = match (l, r) {
+                    (String, Number | Bool) | (Number | Bool, String) => {
+                        "  Try `str(...)` to turn the other operand into a string."
+                    }
+                    (Option(_), _) | (_, Option(_)) => OPTIONAL_HINT,
+                    _ => "",
+                };
+                return Err(self.error(
+                    span,
+                    format!(
+                        "`{}` can't be applied to {} and {}.{}",
+                        op,
+                        l.describe(),
+                        r.describe(),
+                        hint
+                    ),
+                ));
+            }
+            _ => Unknown,
+        };
+        Ok(ty)
+    }
+
+    fn infer_primary(&self, primary: Pair<Rule>, params: &LambdaParams) -> Result<Typed, Report> {
+        let ty = match primary.as_rule() {
+            Rule::expression_body => return self.infer_body(primary.into_inner(), params),
+            Rule::expression_grouped => {
+                let mut inner = primary.clone().into_inner();
+                let body = self.infer_body(inner.next().unwrap().into_inner(), params)?;
+                match inner.next() {
+                    Some(unit) => {
+                        if body.ty.is_builtin() && body.ty != PaxelType::Number {
+                            return Err(self.error(
+                                body.span,
+                                format!(
+                                    "The unit `{}` applies to numbers, but `{}` is {}",
+                                    unit.as_str(),
+                                    self.source(body.span),
+                                    body.ty.describe()
+                                ),
+                            ));
+                        }
+                        unit_type(unit.as_str())
+                    }
+                    None => body.ty,
+                }
+            }
+            Rule::xo_literal => match primary.clone().into_inner().next().unwrap() {
+                literal if literal.as_rule() == Rule::literal_number_with_unit => {
+                    unit_type(literal.into_inner().nth(1).unwrap().as_str())
+                }
+                literal => match literal.as_rule() {
+                    Rule::literal_number => PaxelType::Number,
+                    Rule::string => PaxelType::String,
+                    Rule::literal_color => PaxelType::Color,
+                    _ => PaxelType::Unknown,
+                },
+            },
+            Rule::xo_color_space_func => {
+                self.infer_nested(primary.clone(), params)?;
+                PaxelType::Color
+            }
+            Rule::xo_range => {
+                for bound in primary.clone().into_inner() {
+                    if matches!(bound.as_rule(), Rule::xo_literal | Rule::xo_symbol) {
+                        let bound = self.infer_primary(bound, params)?;
+                        if bound.ty.is_builtin() && bound.ty != PaxelType::Number {
+                            return Err(self.error(
+                                bound.span,
+                                format!(
+                                    "A range's bounds must be numbers, but `{}` is {}",
+                                    self.source(bound.span),
+                                    bound.ty.describe()
+                                ),
+                            ));
+                        }
+                    }
+                }
+                PaxelType::Range
+            }
+            Rule::xo_list => {
+                let mut elem: Option<Typed> = None;
+                for item in primary.clone().into_inner() {
+                    let item = self.infer_body(item.into_inner(), params)?;
+                    elem = Some(match elem {
+                        None => item,
+                        Some(first) => Typed {
+                            ty: self.join(&first, &item, "The elements of a list")?,
+                            span: first.span,
+                        },
+                    });
+                }
+                PaxelType::List(Box::new(elem.map(|e| e.ty).unwrap_or(PaxelType::Unknown)))
+            }
+            Rule::xo_template_literal => {
+                for hole in primary.clone().into_inner() {
+                    if hole.as_rule() != Rule::xo_template_hole {
+                        continue;
+                    }
+                    let expression = hole.into_inner().next().unwrap();
+                    let value = self.infer_body(expression.into_inner(), params)?;
+                    if !value.ty.is_printable() {
+                        return Err(self.error(
+                            value.span,
+                            format!(
+                                "`{}` is {}, which a template literal can't print.  Holes take numbers, strings, bools, sizes and rotations.",
+                                self.source(value.span),
+                                value.ty.describe()
+                            ),
+                        ));
+                    }
+                }
+                PaxelType::String
+            }
+            Rule::xo_function_call => {
+                let mut inner = primary.clone().into_inner();
+                let name = inner.next().unwrap();
+                let args = inner.next().unwrap();
+                self.infer_call(name, None, args, &primary, params)?
+            }
+            Rule::xo_method_chain => {
+                let mut inner = primary.clone().into_inner();
+                let receiver = inner.next().unwrap().into_inner().next().unwrap();
+                let mut receiver = self.infer_primary(receiver, params)?;
+                for method_call in inner {
+                    let mut call = method_call.clone().into_inner();
+                    let name = call.next().unwrap();
+                    let args = call.next().unwrap();
+                    let span = (receiver.span.0, method_call.as_span().end());
+                    receiver = Typed {
+                        ty: self.infer_call(name, Some(receiver), args, &method_call, params)?,
+                        span,
+                    };
+                }
+                receiver.ty
+            }
+            Rule::xo_symbol => self.infer_symbol(primary.clone(), params)?,
+            // enum variants, objects and tuples: only the expressions within them are checked
+            _ => {
+                self.infer_nested(primary.clone(), params)?;
+                PaxelType::Unknown
+            }
+        };
+        Ok(Typed::of(ty, &primary))
+    }
+
+    /// Checks each expression nested anywhere within `pair`
+    fn infer_nested(&self, pair: Pair<Rule>, params: &LambdaParams) -> Result<(), Report> {
+        for inner in pair.into_inner() {
+            if inner.as_rule() == Rule::expression_body {
+                self.infer_body(inner.into_inner(), params)?;
+            } else {
+                self.infer_nested(inner, params)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a call to a built-in function against its signature, with `receiver` as the first
+    /// argument of a method call, e.g. the `self.items` of `self.items.filter(|i| i.visible)`
+    fn infer_call(
+        &self,
+        name: Pair<Rule>,
+        receiver: Option<Typed>,
+        args: Pair<Rule>,
+        call: &Pair<Rule>,
+        params: &LambdaParams,
+    ) -> Result<PaxelType, Report> {
+        let name_span = (name.as_span().start(), name.as_span().end());
+        let name = name.as_str();
+        let function = get_builtin_function(name).ok_or_else(|| {
+            self.error(
+                name_span,
+                format!(
+                    "Unknown function `{}`.{}  Built-in functions are: {}",
+                    name,
+                    did_you_mean(suggest(name, BUILTIN_FUNCTIONS.iter().map(|f| f.name)), ""),
+                    BUILTIN_FUNCTIONS
+                        .iter()
+                        .map(|f| f.name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            )
+        })?;
+
+        let arg_pairs: Vec<Pair<Rule>> = args.into_inner().collect();
+        let arg_count = receiver.is_some() as usize + arg_pairs.len();
+        let required = function.params.len() - function.is_variadic as usize;
+        if arg_count < required || (!function.is_variadic && arg_count > required) {
+            return Err(self.error(
+                (call.as_span().start(), call.as_span().end()),
+                format!(
+                    "`{}` takes {}{} argument(s), but was given {}",
+                    name,
+                    if function.is_variadic {
+                        "at least "
+                    } else {
+                        ""
+                    },
+                    required,
+                    arg_count
+                ),
+            ));
+        }
+
+        let mut typed_args: Vec<Typed> = receiver.into_iter().collect();
+        for arg in arg_pairs {
+            let typed = if arg.as_rule() == Rule::xo_lambda {
+                let elem = typed_args
+                    .first()
+                    .map(|list| list.ty.element())
+                    .unwrap_or(PaxelType::Unknown);
+                self.infer_lambda(function, arg, elem, params)?
+            } else {
+                self.infer_body(arg.into_inner(), params)?
+            };
+            typed_args.push(typed);
+        }
+
+        for (i, arg) in typed_args.iter().enumerate() {
+            let param = function.param(i).unwrap();
+            if let PaxelType::Option(_) = arg.ty {
+                return Err(self.error(
+                    arg.span,
+                    format!(
+                        "`{}` can't take `{}`, which is {}.{}",
+                        name,
+                        self.source(arg.span),
+                        arg.ty.describe(),
+                        OPTIONAL_HINT
+                    ),
+                ));
+            }
+            if let Some(kind) = arg.ty.value_kind() {
+                if !param.accepts(kind) {
+                    return Err(self.error(
+                        arg.span,
+                        format!(
+                            "`{}` expects {} as argument {}, but was given {}",
+                            name,
+                            describe_value_kind(param),
+                            i + 1,
+                            describe_value_kind(kind)
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let [list, index] = typed_args.as_slice() {
+            if name == "at" {
+                self.check_constant_index(list, index)?;
+            }
+        }
+
+        let first = typed_args
+            .first()
+            .map(|arg| arg.ty.clone())
+            .unwrap_or(PaxelType::Unknown);
+        Ok(match function.returns {
+            ValueKind::Number => PaxelType::Number,
+            ValueKind::String => PaxelType::String,
+            ValueKind::Bool => PaxelType::Bool,
+            ValueKind::Color => PaxelType::Color,
+            // `map` turns elements into whatever its lambda returns, other list functions keep them
+            ValueKind::List => match typed_args.iter().find_map(|arg| match &arg.ty {
+                PaxelType::Lambda(returns) if name == "map" => Some((**returns).clone()),
+                _ => None,
+            }) {
+                Some(elem) => PaxelType::List(Box::new(elem)),
+                None if matches!(first, PaxelType::List(_)) => first,
+                None => PaxelType::List(Box::new(PaxelType::Unknown)),
+            },
+            ValueKind::Element => first.element(),
+            _ => PaxelType::Unknown,
+        })
+    }
+
+    /// Reports a constant index out of the range of a literal list, like the `3` of `[1, 2, 3].at(3)`
+    fn check_constant_index(&self, list: &Typed, index: &Typed) -> Result<(), Report> {
+        let Ok(i) = self.source(index.span).parse::<isize>() else {
+            return Ok(());
+        };
+        let Some(len) = self.literal_list_len(list.span) else {
+            return Ok(());
+        };
+        let len = len as isize;
+        if (-len..len).contains(&i) {
+            return Ok(());
+        }
+        Err(self.error(
+            index.span,
+            format!(
+                "Index {} is out of range for a list of {}, which `at` indexes from {} to {}",
+                i,
+                len,
+                -len,
+                len - 1
+            ),
+        ))
+    }
+
+    /// The number of elements of the list literal at `span`, if that is one
+    fn literal_list_len(&self, span: (usize, usize)) -> Option<usize> {
+        let source = self.source(span);
+        let body = PaxParser::parse(Rule::expression_body, source)
+            .ok()?
+            .next()
+            .filter(|body| body.as_str() == source)?;
+        let mut primaries = body.into_inner();
+        let list = primaries
+            .next()
+            .filter(|list| list.as_rule() == Rule::xo_list)?;
+        if primaries.next().is_some() {
+            return None;
+        }
+        Some(list.into_inner().count())
+    }
+
+    /// Types the body of a lambda passed to `function`, whose param is bound to each element of type `elem`
+    fn infer_lambda(
+        &self,
+        function: &BuiltinFunction,
+        lambda: Pair<Rule>,
+        elem: PaxelType,
+        params: &LambdaParams,
+    ) -> Result<Typed, Report> {
+        let span = (lambda.as_span().start(), lambda.as_span().end());
+        let mut inner: Vec<Pair<Rule>> = lambda.into_inner().collect();
+        let body = inner.pop().unwrap();
+        if inner.len() != 1 {
+            return Err(self.error(
+                span,
+                format!(
+                    "`{}` passes one argument to its lambda, but this lambda takes {}",
+                    function.name,
+                    inner.len()
+                ),
+            ));
+        }
+        let mut params = params.to_vec();
+        params.push((inner[0].as_str().to_string(), elem));
+        let body = self.infer_body(body.into_inner(), &params)?;
+        Ok(Typed {
+            ty: PaxelType::Lambda(Box::new(body.ty)),
+            span,
+        })
+    }
+
+    /// Types a design token like `theme.primary` by the kind it is declared with in a `@theme`
+    fn infer_theme_token(&self, token: &str, symbol: &Pair<Rule>) -> Result<PaxelType, Report> {
+        let declared: Vec<&ThemeTokenDefinition> = self
+            .ctx
+            .all_components
+            .values()
+            .flat_map(ComponentDefinition::get_theme_token_definitions)
+            .collect();
+        let Some(definition) = declared
+            .iter()
+            .find(|definition| definition.token.token_value == token)
+        else {
+            let suggestion = suggest(
+                token,
+                declared
+                    .iter()
+                    .map(|definition| definition.token.token_value.as_str()),
+            );
+            return Err(self.error(
+                (symbol.as_span().start(), symbol.as_span().end()),
+                format!(
+                    "Unknown design token `{}`; declare it with its kind, e.g. `@theme: {{ {}: color }}`.{}",
+                    token,
+                    token,
+                    did_you_mean(suggestion, "theme.")
+                ),
+            ));
+        };
+        Ok(match definition.kind {
+            ThemeTokenKind::Color => PaxelType::Color,
+            ThemeTokenKind::Size => PaxelType::Size,
+            ThemeTokenKind::Font => PaxelType::String,
+            ThemeTokenKind::Radius => PaxelType::Number,
+        })
+    }
+
+    /// Resolves a symbol like `self.user.name` or `i.count` through the lambda params and the
+    /// scope stack, then through the type table for each nested field.  Past a `?.`, as in
+    /// `self.user?.name`, fields are those of the value within an `Option`, and the symbol is optional.
+    fn infer_symbol(&self, symbol: Pair<Rule>, params: &LambdaParams) -> Result<PaxelType, Report> {
+        let text = symbol.as_str().trim();
+        if text.starts_with('$') || text.contains('[') {
+            // built-ins like `$container` are resolved elsewhere; indexing is typed by the runtime
+            self.infer_nested(symbol, params)?;
+            return Ok(PaxelType::Unknown);
+        }
+        if text == "true" || text == "false" {
+            return Ok(PaxelType::Bool);
+        }
+        if let Some(token) = resolve_symbol_as_theme_token(text, self.ctx) {
+            return self.infer_theme_token(&token, &symbol);
+        }
+
+        let mut segments: Vec<Pair<Rule>> = symbol.clone().into_inner().collect();
+        let prefix = match segments.first().map(|s| s.as_str()) {
+            Some(receiver @ ("self" | "this")) if segments.len() > 1 => {
+                segments.remove(0);
+                format!("{}.", receiver)
+            }
+            _ => String::new(),
+        };
+        let mut segments = segments.into_iter();
+        let root = segments.next().unwrap();
+        let root_span = (symbol.as_span().start(), root.as_span().end());
+
+        let mut type_id = if let Some((_, ty)) = params
+            .iter()
+            .rev()
+            .find(|(param, _)| param == root.as_str())
+        {
+            match ty {
+                PaxelType::Named(type_id) => type_id.clone(),
+                ty if segments.len() == 0 => return Ok(ty.clone()),
+                _ => return Ok(PaxelType::Unknown),
+            }
+        } else if let Some(pd) = self
+            .ctx
+            .scope_stack
+            .iter()
+            .find_map(|frame| frame.get(root.as_str()))
+        {
+            pd.type_id.clone()
+        } else {
+            let in_scope = self
+                .ctx
+                .scope_stack
+                .iter()
+                .flat_map(|frame| frame.keys().map(String::as_str))
+                .chain(params.iter().map(|(param, _)| param.as_str()));
+            return Err(self.error(
+                root_span,
+                format!(
+                    "Unknown symbol `{}{}`.{}",
+                    prefix,
+                    root.as_str(),
+                    did_you_mean(suggest(root.as_str(), in_scope), &prefix)
+                ),
+            ));
+        };
+
+        let mut is_chained = false;
+        for field in segments {
+            let preceding = (symbol.as_span().start(), field.as_span().start());
+            let is_option = matches!(type_id.get_pax_type(), PaxType::Option { .. });
+            if field.as_rule() == Rule::xo_optional_chain {
+                if !is_option && !matches!(type_id.get_pax_type(), PaxType::Unknown) {
+                    return Err(self.error(
+                        (preceding.0, field.as_span().end()),
+                        format!(
+                            "`?.` can only follow an optional value, but `{}` is {}",
+                            self.source(preceding),
+                            PaxelType::of(&type_id, self.ctx.type_table).describe()
+                        ),
+                    ));
+                }
+                let Some(inner) = self.ctx.resolve_option_inner_type_id(&type_id) else {
+                    return Ok(PaxelType::Unknown);
+                };
+                type_id = inner;
+                is_chained = true;
+                continue;
+            }
+            if is_option {
+                let optional = self.source((preceding.0, preceding.1 - 1));
+                return Err(self.error(
+                    (preceding.0, field.as_span().end()),
+                    format!(
+                        "`{}` is optional, so its fields are reached with `?.`, as in `{}?.{}`",
+                        optional,
+                        optional,
+                        field.as_str()
+                    ),
+                ));
+            }
+            let Some(type_def) = self.ctx.type_table.get(&type_id) else {
+                return Ok(PaxelType::Unknown);
+            };
+            let Some(pd) = type_def
+                .property_definitions
+                .iter()
+                .find(|pd| pd.name == field.as_str())
+            else {
+                return Err(self.error(
+                    (field.as_span().start(), field.as_span().end()),
+                    format!(
+                        "{} has no field `{}`.{}",
+                        PaxelType::Named(type_id.clone()).describe(),
+                        field.as_str(),
+                        did_you_mean(
+                            suggest(
+                                field.as_str(),
+                                type_def
+                                    .property_definitions
+                                    .iter()
+                                    .map(|pd| pd.name.as_str())
+                            ),
+                            ""
+                        )
+                    ),
+                ));
+            };
+            type_id = pd.type_id.clone();
+        }
+        Ok(match PaxelType::of(&type_id, self.ctx.type_table) {
+            ty @ PaxelType::Option(_) => ty,
+            ty if is_chained => PaxelType::Option(Box::new(ty)),
+            ty => ty,
+        })
+    }
+}
+
+fn unit_type(unit: &str) -> PaxelType {
+    match unit {
+        "px" => PaxelType::Size,
+        "%" => PaxelType::Percent,
+        _ => PaxelType::Rotation,
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+
+use color_eyre::eyre;
+use pax_compiler::errors::source_map::SourceMap;
+use pax_compiler::expressions::compile_all_expressions;
+use pax_compiler::parsing::{
+    parse_settings_from_component_definition_string,
+    parse_template_from_component_definition_string, TemplateNodeParseContext,
+};
+use pax_manifest::{
+    ComponentDefinition, ComponentTemplate, ExpressionSpec, HostCrateInfo, PaxManifest,
+    PropertyDefinition, TypeDefinition, TypeId,
+};
+
+fn rectangle() -> TypeId {
+    TypeId::build_singleton("pax_std::primitives::Rectangle", Some("Rectangle"))
+}
+
+fn component(type_id: TypeId, template: Option<ComponentTemplate>) -> ComponentDefinition {
+    ComponentDefinition {
+        type_id,
+        is_main_component: false,
+        is_primitive: template.is_none(),
+        is_struct_only_component: false,
+        module_path: String::new(),
+        primitive_instance_import_path: None,
+        template,
+        settings: None,
+    }
+}
+
+fn type_definition(
+    type_id: &TypeId,
+    property_definitions: Vec<PropertyDefinition>,
+) -> TypeDefinition {
+    TypeDefinition {
+        type_id: type_id.clone(),
+        inner_iterable_type_id: None,
+        property_definitions,
+    }
+}
+
+fn property(name: &str, type_id: TypeId) -> PropertyDefinition {
+    PropertyDefinition {
+        name: name.to_string(),
+        type_id,
+        ..Default::default()
+    }
+}
+
+/// The type definition of `Option<inner>`
+fn optional(inner: &TypeId) -> TypeDefinition {
+    TypeDefinition {
+        type_id: TypeId::build_option(&inner.get_unique_identifier()),
+        inner_iterable_type_id: Some(inner.clone()),
+        property_definitions: vec![],
+    }
+}
+
+/// Compiles the expressions of a `Test` component defined by `pax`, whose template may use
+/// `Rectangle` and bind its `fill: Color`, `label: Option<String>` and `count: usize`
+fn compile(pax: &str) -> eyre::Result<PaxManifest> {
+    compile_with_properties(pax, vec![], vec![])
+}
+
+/// Like [`compile`], for a `Test` component with `properties`, whose types not built into the
+/// type table are described by `type_definitions`
+fn compile_with_properties(
+    pax: &str,
+    properties: Vec<PropertyDefinition>,
+    type_definitions: Vec<TypeDefinition>,
+) -> eyre::Result<PaxManifest> {
+    let type_id = TypeId::build_singleton("crate::Test", Some("Test"));
+    let mut ctx = TemplateNodeParseContext {
+        template: ComponentTemplate::new(type_id.clone(), None),
+        pascal_identifier_to_type_id_map: HashMap::from([("Rectangle".to_string(), rectangle())]),
+    };
+    parse_template_from_component_definition_string(&mut ctx, pax);
+    let mut test = component(type_id.clone(), Some(ctx.template));
+    test.settings = Some(parse_settings_from_component_definition_string(pax));
+
+    let mut manifest = PaxManifest {
+        components: HashMap::from([
+            (type_id.clone(), test),
+            (rectangle(), component(rectangle(), None)),
+        ]),
+        main_component_type_id: type_id.clone(),
+        expression_specs: None,
+        type_table: HashMap::from([
+            (type_id.clone(), type_definition(&type_id, properties)),
+            (
+                rectangle(),
+                type_definition(
+                    &rectangle(),
+                    vec![
+                        property(
+                            "fill",
+                            TypeId::build_singleton("pax_engine::api::Color", None),
+                        ),
+                        property("label", TypeId::build_option("String")),
+                        property("count", TypeId::build_primitive("usize")),
+                    ],
+                ),
+            ),
+            (
+                TypeId::build_option("String"),
+                optional(&TypeId::build_primitive("String")),
+            ),
+        ]),
+        import_paths: HashSet::new(),
+    };
+    for type_definition in type_definitions {
+        manifest
+            .type_table
+            .insert(type_definition.type_id.clone(), type_definition);
+    }
+    let host_crate_info = HostCrateInfo {
+        name: "test".to_string(),
+        identifier: "test".to_string(),
+        import_prefix: "test::pax_reexports".to_string(),
+    };
+    compile_all_expressions(&mut manifest, &mut SourceMap::new(), &host_crate_info)?;
+    Ok(manifest)
+}
+
+#[test]
+fn test_timeline_easing_curves_are_checked() {
+    let timeline = |curve: &str| {
+        format!(
+            "<Rectangle id=rect />
+            @settings {{
+                #rect {{
+                    x: @timeline {{ 0: 0px, 60: 200px {} }}
+                }}
+            }}",
+            curve
+        )
+    };
+    assert!(compile(&timeline("OutBack")).is_ok());
+
+    let error = compile(&timeline("OutBounce")).unwrap_err().to_string();
+    assert!(
+        error.contains("Unknown easing curve `OutBounce`"),
+        "{}",
+        error
+    );
+    assert!(error.contains("InOutBack"), "{}", error);
+}
+
+#[test]
+fn test_theme_tokens_are_checked_against_their_declared_kinds() {
+    let themed = |fill: &str| {
+        format!(
+            "<Rectangle fill={{{}}} />
+            @settings {{
+                @theme: {{ primary: color, gutter: size }}
+            }}",
+            fill
+        )
+    };
+    assert!(compile(&themed("theme.primary")).is_ok());
+
+    let error = compile(&themed("theme.primery")).unwrap_err().to_string();
+    assert!(
+        error.contains("Unknown design token `primery`"),
+        "{}",
+        error
+    );
+    assert!(error.contains("Did you mean `theme.primary`?"), "{}", error);
+
+    let error = compile(&themed("theme.gutter")).unwrap_err().to_string();
+    assert!(error.contains("expects a color"), "{}", error);
+}
+
+fn without_whitespace(s: &str) -> String {
+    s.split_whitespace().collect()
+}
+
+/// The spec of the expression compiled from `input`, which the compiler records without whitespace
+fn expression_spec<'a>(manifest: &'a PaxManifest, input: &str) -> &'a ExpressionSpec {
+    manifest
+        .expression_specs
+        .as_ref()
+        .unwrap()
+        .values()
+        .find(|spec| without_whitespace(&spec.input_statement.content) == without_whitespace(input))
+        .unwrap_or_else(|| panic!("no expression was compiled from `{}`", input))
+}
+
+#[test]
+fn test_sort_by_lambda_compiles_to_a_keyed_sort() {
+    let item = TypeId::build_singleton("crate::Item", Some("Item"));
+    let items = TypeId::build_vector("crate::Item");
+    let manifest = compile_with_properties(
+        "for x in self.items.sort_by(|x| x.w) { <Rectangle /> }",
+        vec![property("items", items.clone())],
+        vec![
+            type_definition(&item, vec![property("w", TypeId::build_primitive("f64"))]),
+            TypeDefinition {
+                type_id: items,
+                inner_iterable_type_id: Some(item),
+                property_definitions: vec![],
+            },
+        ],
+    )
+    .unwrap();
+    let spec = expression_spec(&manifest, "self.items.sort_by(|x| x.w)");
+    // The lambda reads `x.w` through `PaxelValue`, which turns the `f64` into a `Numeric` key
+    assert_eq!(
+        spec.output_statement,
+        "pax_runtime::api::functions::sort_by(&(items),|x| { \
+            use pax_runtime::api::functions::PaxelValueAsIs; \
+            let x = pax_runtime::api::functions::PaxelValue((x).clone()).get(); \
+            let xPERIw = pax_runtime::api::functions::PaxelValue((x.w).clone()).get(); \
+            xPERIw \
+        })"
+    );
+    let invoked: Vec<&str> = spec
+        .invocations
+        .iter()
+        .map(|invocation| invocation.escaped_identifier.as_str())
+        .collect();
+    assert_eq!(invoked, ["items"]);
+}
+
+/// A `Test` component with `user: Option<User>`, where `User` has a `name: String`,
+/// `a: Option<A>`, where `A` has a `b: Option<B>` and `B` a `c: String`, and `opt_count: Option<usize>`
+fn compile_with_optional_properties(pax: &str) -> eyre::Result<PaxManifest> {
+    let string = TypeId::build_primitive("String");
+    let user = TypeId::build_singleton("crate::User", Some("User"));
+    let a = TypeId::build_singleton("crate::A", Some("A"));
+    let b = TypeId::build_singleton("crate::B", Some("B"));
+    let usize = TypeId::build_primitive("usize");
+    compile_with_properties(
+        pax,
+        vec![
+            property("user", TypeId::build_option("crate::User")),
+            property("a", TypeId::build_option("crate::A")),
+            property("opt_count", TypeId::build_option("usize")),
+        ],
+        vec![
+            type_definition(&user, vec![property("name", string.clone())]),
+            type_definition(&a, vec![property("b", TypeId::build_option("crate::B"))]),
+            type_definition(&b, vec![property("c", string)]),
+            optional(&user),
+            optional(&a),
+            optional(&b),
+            optional(&usize),
+        ],
+    )
+}
+
+/// Each symbol an expression invokes, with the RIL appended to it on reading its property
+fn invocation_tails(spec: &ExpressionSpec) -> Vec<(&str, &str)> {
+    spec.invocations
+        .iter()
+        .map(|invocation| {
+            (
+                invocation.escaped_identifier.as_str(),
+                invocation.nested_symbol_tail_literal.as_str(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_optional_chaining_compiles_to_invocation_tails() {
+    let manifest = compile_with_optional_properties(
+        "<Rectangle label={self.user?.name} />
+        <Rectangle label={self.a?.b?.c} />
+        <Rectangle count={self.opt_count ?? 0} />",
+    )
+    .unwrap();
+
+    // Each `?.` maps into the `Option`, and nested ones flatten into a single `Option`
+    let user_name = expression_spec(&manifest, "self.user?.name");
+    assert_eq!(user_name.output_statement, "userQUESPERIname");
+    assert_eq!(
+        invocation_tails(user_name),
+        [(
+            "userQUESPERIname",
+            ".as_ref().map(|v| v.name.clone()).map(StringBox::from)"
+        )]
+    );
+    let c = expression_spec(&manifest, "self.a?.b?.c");
+    assert_eq!(c.output_statement, "aQUESPERIbQUESPERIc");
+    assert_eq!(
+        invocation_tails(c),
+        [(
+            "aQUESPERIbQUESPERIc",
+            ".as_ref().and_then(|v| v.b.as_ref().map(|v| v.c.clone())).map(StringBox::from)"
+        )]
+    );
+
+    let count = expression_spec(&manifest, "self.opt_count ?? 0");
+    assert_eq!(
+        count.output_statement,
+        "((opt_count).unwrap_or_else(|| (Numeric::from(0)).into()))"
+    );
+    assert_eq!(
+        invocation_tails(count),
+        [("opt_count", ".map(Numeric::from)")]
+    );
+}
+
+#[test]
+fn test_if_let_binds_the_payload_of_an_option() {
+    let manifest = compile_with_optional_properties(
+        "if let Some(n) = self.opt_count { <Rectangle count={n} /> }",
+    )
+    .unwrap();
+    let matched = expression_spec(&manifest, "self.opt_count");
+    // The `Numeric` the option is read as is bound as the `usize` it is declared with
+    assert_eq!(
+        matched.output_statement,
+        "match opt_count { \
+            Some(n) => MatchArm::new(0, vec![(\"n\".to_string(), Rc::new(RefCell::new(usize::from(n))) as Rc<RefCell<dyn Any>>)]), \
+            _ => MatchArm::default() \
+        }"
+    );
+    assert_eq!(
+        invocation_tails(matched),
+        [("opt_count", ".map(Numeric::from)")]
+    );
+    assert_eq!(
+        invocation_tails(expression_spec(&manifest, "n")),
+        [("n", "")]
+    );
+
+    let error =
+        compile_with_optional_properties("if let Mode::A = self.opt_count { <Rectangle /> }")
+            .unwrap_err()
+            .to_string();
+    assert!(
+        error.contains("`Mode::A` can't match `self.opt_count`, which is optional"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+
+use pax_compiler::hot_reload::reparse_component_template;
+use pax_compiler::parsing::{
+    parse_template_from_component_definition_string, TemplateNodeParseContext,
+};
+use pax_manifest::{
+    ComponentDefinition, ComponentTemplate, PaxManifest, SettingElement, TypeId, ValueDefinition,
+};
+
+fn rectangle() -> TypeId {
+    TypeId::build_singleton("pax_std::primitives::Rectangle", Some("Rectangle"))
+}
+
+fn component(type_id: TypeId, template: Option<ComponentTemplate>) -> ComponentDefinition {
+    ComponentDefinition {
+        type_id,
+        is_main_component: false,
+        is_primitive: template.is_none(),
+        is_struct_only_component: false,
+        module_path: String::new(),
+        primitive_instance_import_path: None,
+        template,
+        settings: None,
+    }
+}
+
+/// A manifest whose `Test` component was compiled from `pax`, with its expressions numbered in
+/// template order
+fn compiled_manifest(pax: &str) -> PaxManifest {
+    let type_id = TypeId::build_blank_component("Test");
+    let mut ctx = TemplateNodeParseContext {
+        template: ComponentTemplate::new(type_id.clone(), None),
+        pascal_identifier_to_type_id_map: HashMap::from([("Rectangle".to_string(), rectangle())]),
+    };
+    parse_template_from_component_definition_string(&mut ctx, pax);
+    let mut template = ctx.template;
+
+    let mut next_vtable_id = 0;
+    for node in template.get_nodes_mut() {
+        if let Some(rsd) = node
+            .control_flow_settings
+            .as_mut()
+            .and_then(|cfs| cfs.repeat_source_definition.as_mut())
+        {
+            rsd.vtable_id = Some(next_vtable_id);
+            next_vtable_id += 1;
+        }
+        for setting in node.settings.iter_mut().flatten() {
+            if let SettingElement::Setting(_, ValueDefinition::Expression(_, vtable_id))
+            | SettingElement::Setting(_, ValueDefinition::Identifier(_, vtable_id)) = setting
+            {
+                *vtable_id = Some(next_vtable_id);
+                next_vtable_id += 1;
+            }
+        }
+    }
+
+    PaxManifest {
+        components: HashMap::from([
+            (type_id.clone(), component(type_id.clone(), Some(template))),
+            (rectangle(), component(rectangle(), None)),
+        ]),
+        main_component_type_id: type_id,
+        expression_specs: None,
+        type_table: HashMap::new(),
+        import_paths: HashSet::new(),
+    }
+}
+
+fn vtable_ids(template: &ComponentTemplate) -> HashMap<String, usize> {
+    let mut ids = HashMap::new();
+    for node in template.get_nodes() {
+        if let Some(rsd) = node
+            .control_flow_settings
+            .as_ref()
+            .and_then(|cfs| cfs.repeat_source_definition.as_ref())
+        {
+            ids.insert("for".to_string(), rsd.vtable_id.unwrap());
+        }
+        for setting in node.settings.iter().flatten() {
+            if let SettingElement::Setting(key, ValueDefinition::Expression(_, vtable_id))
+            | SettingElement::Setting(key, ValueDefinition::Identifier(_, vtable_id)) = setting
+            {
+                ids.insert(key.token_value.clone(), vtable_id.unwrap());
+            }
+        }
+    }
+    ids
+}
+
+#[test]
+fn test_reparse_reuses_compiled_expressions() {
+    let manifest = compiled_manifest(
+        "<Rectangle x={self.a + 1} /> for i in 0..3 { <Rectangle width={i * 10} /> }",
+    );
+    let type_id = manifest.main_component_type_id.clone();
+    let old_template = manifest.components[&type_id].template.as_ref().unwrap();
+
+    // Reordered, respaced and with a new literal
+    let template = reparse_component_template(
+        &manifest,
+        &type_id,
+        "for i in 0..3 { <Rectangle width={i*10} height=5px /> } <Rectangle x={ self.a+1 } />",
+    )
+    .unwrap();
+    assert_eq!(template.get_root().len(), 2);
+    assert_eq!(vtable_ids(&template), vtable_ids(old_template));
+}
+
+#[test]
+fn test_reparse_rejects_new_expressions() {
+    let manifest = compiled_manifest("for i in 0..3 { <Rectangle width={i * 10} /> }");
+    let type_id = manifest.main_component_type_id.clone();
+
+    // Moving an expression out of its `for` changes what its compiled form reads from the stack
+    assert!(
+        reparse_component_template(&manifest, &type_id, "<Rectangle width={i * 10} />").is_err()
+    );
+    assert!(reparse_component_template(
+        &manifest,
+        &type_id,
+        "for i in 0..3 { <Rectangle width={i * 20} /> }"
+    )
+    .is_err());
+    assert!(reparse_component_template(&manifest, &type_id, "<Ellipse />").is_err());
+}
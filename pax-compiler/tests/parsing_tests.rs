@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use pax_compiler::parsing::{
+    parse_settings_from_component_definition_string,
+    parse_template_from_component_definition_string, run_pratt_parser, TemplateNodeParseContext,
+};
+use pax_manifest::{
+    ComponentTemplate, PaxType, SettingElement, SettingsBlockElement, TemplateNodeId,
+    TimelineDefinition, TypeId, ValueDefinition,
+};
+
+fn parse_template(pax: &str) -> ComponentTemplate {
+    let mut pascal_identifier_to_type_id_map = HashMap::new();
+    pascal_identifier_to_type_id_map.insert(
+        "Rectangle".to_string(),
+        TypeId::build_singleton("pax_std::primitives::Rectangle", Some("Rectangle")),
+    );
+    let mut ctx = TemplateNodeParseContext {
+        template: ComponentTemplate::new(TypeId::build_blank_component("Test"), None),
+        pascal_identifier_to_type_id_map,
+    };
+    parse_template_from_component_definition_string(&mut ctx, pax);
+    ctx.template
+}
+
+fn is_else_branch(template: &ComponentTemplate, id: &TemplateNodeId) -> bool {
+    let tnd = template.get_node(id).unwrap();
+    matches!(tnd.type_id.get_pax_type(), PaxType::If)
+        && tnd.control_flow_settings.as_ref().unwrap().is_else_branch
+}
+
+#[test]
+fn test_parse_if_without_else() {
+    let template = parse_template("if self.a { <Rectangle/> }");
+    let root = template.get_root();
+    assert_eq!(root.len(), 1);
+    assert_eq!(template.get_children(&root[0]).unwrap().len(), 1);
+    assert_eq!(template.get_else_branch(&root[0]), None);
+}
+
+#[test]
+fn test_parse_else_if_chain() {
+    let template = parse_template(
+        "if self.a { <Rectangle/> } else if self.b { <Rectangle/> <Rectangle/> } else { <Rectangle/> }",
+    );
+    let root = template.get_root();
+    assert_eq!(root.len(), 1);
+
+    let else_if = template.get_else_branch(&root[0]).unwrap();
+    assert!(is_else_branch(&template, &else_if));
+    let else_if_settings = template
+        .get_node(&else_if)
+        .unwrap()
+        .control_flow_settings
+        .clone()
+        .unwrap();
+    assert_eq!(
+        else_if_settings
+            .condition_expression_paxel
+            .unwrap()
+            .token_value
+            .trim(),
+        "self.b"
+    );
+    // two `Rectangle`s plus the trailing `else`
+    assert_eq!(template.get_children(&else_if).unwrap().len(), 3);
+
+    let bare_else = template.get_else_branch(&else_if).unwrap();
+    assert!(is_else_branch(&template, &bare_else));
+    let bare_else_settings = template
+        .get_node(&bare_else)
+        .unwrap()
+        .control_flow_settings
+        .clone()
+        .unwrap();
+    assert!(bare_else_settings.condition_expression_paxel.is_none());
+    assert_eq!(template.get_children(&bare_else).unwrap().len(), 1);
+    assert_eq!(template.get_else_branch(&bare_else), None);
+}
+
+#[test]
+fn test_parse_match_arms() {
+    let template = parse_template(
+        "match self.mode {
+            Mode::Idle => { <Rectangle/> },
+            Mode::Failed(_, message) => { <Rectangle/> <Rectangle/> }
+            _ => {}
+        }",
+    );
+    let root = template.get_root();
+    assert_eq!(root.len(), 1);
+    let cfs = |id: &TemplateNodeId| {
+        let tnd = template.get_node(id).unwrap();
+        assert!(matches!(tnd.type_id.get_pax_type(), PaxType::Match));
+        tnd.control_flow_settings.clone().unwrap()
+    };
+    assert_eq!(
+        cfs(&root[0]).match_expression_paxel.unwrap().token_value,
+        "self.mode"
+    );
+
+    let arms = template.get_children(&root[0]).unwrap();
+    let patterns: Vec<_> = arms
+        .iter()
+        .map(|arm| cfs(arm).match_arm_pattern.unwrap())
+        .collect();
+    assert_eq!(
+        patterns[0].variant.as_ref().unwrap().token_value,
+        "Mode::Idle"
+    );
+    assert!(patterns[0].bindings.is_empty());
+    assert_eq!(
+        patterns[1]
+            .bindings
+            .iter()
+            .map(|binding| binding.token_value.as_str())
+            .collect::<Vec<_>>(),
+        vec!["_", "message"]
+    );
+    assert!(patterns[2].variant.is_none());
+
+    let arm_lengths: Vec<_> = arms
+        .iter()
+        .map(|arm| template.get_children(arm).unwrap_or_default().len())
+        .collect();
+    assert_eq!(arm_lengths, vec![1, 2, 0]);
+}
+
+#[test]
+fn test_parse_keyed_for() {
+    let template = parse_template(
+        "for item in self.items key item.id { <Rectangle/> } for i in 0..3 { <Rectangle/> }",
+    );
+    let root = template.get_root();
+    assert_eq!(root.len(), 2);
+    let cfs = |id: &TemplateNodeId| {
+        let tnd = template.get_node(id).unwrap();
+        assert!(matches!(tnd.type_id.get_pax_type(), PaxType::Repeat));
+        assert_eq!(template.get_children(id).unwrap().len(), 1);
+        tnd.control_flow_settings.clone().unwrap()
+    };
+    let keyed = cfs(&root[0]);
+    assert_eq!(
+        keyed
+            .repeat_key_expression_paxel
+            .unwrap()
+            .token_value
+            .trim(),
+        "item.id"
+    );
+    assert!(keyed
+        .repeat_source_definition
+        .unwrap()
+        .symbolic_binding
+        .is_some());
+    assert!(cfs(&root[1]).repeat_key_expression_paxel.is_none());
+}
+
+#[test]
+fn test_parse_settings_timeline() {
+    let settings = parse_settings_from_component_definition_string(
+        "<Rectangle id=rect/>
+        @settings {
+            @timeline: { frames: 120, loop: true },
+            #rect {
+                x: @timeline { 0: 0px, 60: 200px OutBack, 120: 0px }
+            }
+        }",
+    );
+    assert_eq!(
+        settings[0],
+        SettingsBlockElement::Timeline(TimelineDefinition {
+            frame_count: 120,
+            is_looping: true,
+            autoplay: false,
+        })
+    );
+
+    let SettingsBlockElement::SelectorBlock(_, block) = &settings[1] else {
+        panic!("expected a selector block");
+    };
+    let SettingElement::Setting(key, ValueDefinition::Timeline(keyframes)) = &block.elements[0]
+    else {
+        panic!("expected a timeline setting");
+    };
+    assert_eq!(key.token_value, "x");
+    assert_eq!(
+        keyframes.iter().map(|k| k.frame).collect::<Vec<_>>(),
+        vec![0, 60, 120]
+    );
+    assert_eq!(keyframes[1].value.raw_value, "200px");
+    assert_eq!(keyframes[1].curve.as_ref().unwrap().raw_value, "OutBack");
+    assert!(keyframes[2].curve.is_none());
+}
+
+#[test]
+fn test_parse_builtin_function_calls() {
+    let (ril, symbols) = run_pratt_parser("format(\"{} items\", len(self.items))");
+    assert_eq!(
+        ril,
+        "pax_runtime::api::functions::format((StringBox::from(\"{} items\")).into(),\
+         &[&(pax_runtime::api::functions::len(&(items))) as &dyn pax_runtime::api::functions::Printable])"
+    );
+    assert_eq!(symbols, vec!["self.items"]);
+
+    // enum variants and color functions keep their own syntax
+    let (ril, _) = run_pratt_parser("clamp(self.x, 0, Foo::max())");
+    assert!(ril.starts_with("pax_runtime::api::functions::clamp((x).into(),"));
+    assert!(ril.ends_with("(Foo::max()).into())"));
+}
+
+#[test]
+fn test_parse_lambdas_and_method_calls() {
+    let (ril, symbols) = run_pratt_parser("self.items.filter(|i| i.count > self.min).len()");
+    // `i.count` is read from each element inside the closure, not from properties
+    assert_eq!(symbols, vec!["self.items", "self.min"]);
+    assert!(ril.starts_with(
+        "pax_runtime::api::functions::len(&(pax_runtime::api::functions::filter(&(items),|i| {"
+    ));
+    assert!(ril.contains(
+        "let iPERIcount = pax_runtime::api::functions::PaxelValue((i.count).clone()).get();"
+    ));
+
+    let template = parse_template("for item in self.items.filter(|i| i.visible) { <Rectangle/> }");
+    let tnd = template.get_node(&template.get_root()[0]).unwrap();
+    let source = tnd
+        .control_flow_settings
+        .clone()
+        .unwrap()
+        .repeat_source_definition
+        .unwrap()
+        .symbolic_binding
+        .unwrap();
+    assert_eq!(
+        source.token_value.trim(),
+        "self.items.filter(|i| i.visible)"
+    );
+}
+
+#[test]
+fn test_parse_template_literals() {
+    let (ril, symbols) = run_pratt_parser("`Total: ${self.price * 2:.2}\\n`");
+    assert_eq!(
+        ril,
+        "StringBox::from([String::from(\"Total: \"),\
+         pax_runtime::api::functions::interpolate(&((price*Numeric::from(2))), \".2\"),\
+         String::from(\"\\n\")].concat())"
+    );
+    assert_eq!(symbols, vec!["self.price"]);
+
+    // a `:` followed by a space is a ternary's, not the start of a format spec
+    let (ril, _) = run_pratt_parser("`${self.on ? \"on\" : \"off\"}`");
+    assert!(ril.contains("interpolate(&(if on { StringBox::from(\"on\") } else"));
+}
+
+#[test]
+fn test_parse_optional_chaining_and_if_let() {
+    let (ril, symbols) = run_pratt_parser("self.user?.name ?? \"anonymous\"");
+    assert_eq!(
+        ril,
+        "((userQUESPERIname).unwrap_or_else(|| (StringBox::from(\"anonymous\")).into()))"
+    );
+    assert_eq!(symbols, vec!["self.user?.name"]);
+
+    // `??` is right-associative, so that each default may itself be optional
+    let (ril, _) = run_pratt_parser("self.a ?? self.b ?? 0");
+    assert!(ril.starts_with("((a).unwrap_or_else(|| (((b).unwrap_or_else("));
+
+    // `if let` is sugar for a `match` with an arm for its pattern and one for its `else`
+    let template = parse_template(
+        "if let Some(user) = self.user { <Rectangle/> } else { <Rectangle/> <Rectangle/> }",
+    );
+    let root = template.get_root();
+    let tnd = template.get_node(&root[0]).unwrap();
+    assert!(matches!(tnd.type_id.get_pax_type(), PaxType::Match));
+    let cfs = tnd.control_flow_settings.clone().unwrap();
+    assert_eq!(cfs.match_expression_paxel.unwrap().token_value, "self.user");
+    let arms = template.get_children(&root[0]).unwrap();
+    let patterns: Vec<_> = arms
+        .iter()
+        .map(|arm| {
+            let tnd = template.get_node(arm).unwrap();
+            tnd.control_flow_settings
+                .clone()
+                .unwrap()
+                .match_arm_pattern
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(patterns[0].variant.as_ref().unwrap().token_value, "Some");
+    assert_eq!(patterns[0].bindings[0].token_value, "user");
+    assert!(patterns[1].variant.is_none());
+    let arm_lengths: Vec<_> = arms
+        .iter()
+        .map(|arm| template.get_children(arm).unwrap().len())
+        .collect();
+    assert_eq!(arm_lengths, vec![1, 2]);
+
+    // as an `else if let`, the `match` is the body of a bare `else`
+    let template =
+        parse_template("if self.a { <Rectangle/> } else if let Some(b) = self.b { <Rectangle/> }");
+    let bare_else = template.get_else_branch(&template.get_root()[0]).unwrap();
+    assert!(is_else_branch(&template, &bare_else));
+    let children = template.get_children(&bare_else).unwrap();
+    let tnd = template.get_node(&children[0]).unwrap();
+    assert!(matches!(tnd.type_id.get_pax_type(), PaxType::Match));
+}
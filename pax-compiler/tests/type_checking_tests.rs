@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use pax_compiler::expressions::ExpressionCompilationContext;
+use pax_compiler::type_checking::{check_paxel_type, infer_paxel_type, PaxelType};
+use pax_manifest::{
+    ComponentDefinition, ComponentTemplate, HostCrateInfo, LocationInfo, PropertyDefinition, Token,
+    TokenType, TypeDefinition, TypeId, TypeTable,
+};
+
+fn property(name: &str, type_id: TypeId) -> PropertyDefinition {
+    PropertyDefinition {
+        name: name.to_string(),
+        type_id,
+        ..Default::default()
+    }
+}
+
+/// A `Test` component with `width: Size`, `name: String`, `count: usize`, `items: Vec<Item>`
+/// and `user: Option<Item>`, where `Item` has a `label: String`
+fn type_table() -> (TypeTable, Vec<PropertyDefinition>) {
+    let item = TypeId::build_singleton("crate::Item", None);
+    let items = TypeId::build_vector("crate::Item");
+    let user = TypeId::build_option("crate::Item");
+    let properties = vec![
+        property(
+            "width",
+            TypeId::build_singleton("pax_engine::api::Size", None),
+        ),
+        property("name", TypeId::build_primitive("String")),
+        property("count", TypeId::build_primitive("usize")),
+        property("items", items.clone()),
+        property("user", user.clone()),
+    ];
+    let type_table = HashMap::from([
+        (
+            item.clone(),
+            TypeDefinition {
+                type_id: item.clone(),
+                inner_iterable_type_id: None,
+                property_definitions: vec![property("label", TypeId::build_primitive("String"))],
+            },
+        ),
+        (
+            items.clone(),
+            TypeDefinition {
+                type_id: items,
+                inner_iterable_type_id: Some(item.clone()),
+                property_definitions: vec![],
+            },
+        ),
+        (
+            user.clone(),
+            TypeDefinition {
+                type_id: user,
+                inner_iterable_type_id: Some(item),
+                property_definitions: vec![],
+            },
+        ),
+    ]);
+    (type_table, properties)
+}
+
+/// `paxel` as the expression of `<Rectangle fill={...} />`, on the template's first line
+fn expression(paxel: &str) -> Token {
+    let pax = format!("<Rectangle fill={{{}}} />", paxel);
+    let start = "<Rectangle fill={".len();
+    Token::new(
+        paxel.to_string(),
+        TokenType::Expression,
+        LocationInfo {
+            start_line_col: (0, start),
+            end_line_col: (0, start + paxel.len()),
+        },
+        &pax,
+    )
+}
+
+#[test]
+fn test_type_checking_expressions() {
+    let (type_table, properties) = type_table();
+    let type_id = TypeId::build_blank_component("Test");
+    let component_def = ComponentDefinition {
+        type_id: type_id.clone(),
+        is_main_component: true,
+        is_primitive: false,
+        is_struct_only_component: false,
+        module_path: String::new(),
+        primitive_instance_import_path: None,
+        template: None,
+        settings: None,
+    };
+    let mut template = ComponentTemplate::new(type_id, None);
+    let mut expression_specs = HashMap::new();
+    let host_crate_info = HostCrateInfo {
+        name: "test".to_string(),
+        identifier: "test".to_string(),
+        import_prefix: "test::pax_reexports".to_string(),
+    };
+    let ctx = ExpressionCompilationContext {
+        component_def: &component_def,
+        template: &mut template,
+        scope_stack: vec![properties
+            .into_iter()
+            .map(|pd| (pd.name.clone(), pd))
+            .collect()],
+        vtable_uid_gen: 0..,
+        expression_specs: &mut expression_specs,
+        active_node_id: None,
+        all_components: HashMap::new(),
+        type_table: &type_table,
+        host_crate_info: &host_crate_info,
+    };
+    let infer = |paxel: &str| infer_paxel_type(&expression(paxel), &ctx);
+    let error = |paxel: &str| infer(paxel).unwrap_err().to_string();
+
+    assert_eq!(infer("self.width + 10px").unwrap(), PaxelType::Size);
+    assert_eq!(
+        infer("self.count > 2 ? 50% : 10%").unwrap(),
+        PaxelType::Percent
+    );
+    assert_eq!(
+        infer("self.items.filter(|i| i.label == \"a\").len()").unwrap(),
+        PaxelType::Number
+    );
+    assert_eq!(
+        infer("self.items.map(|i| i.label)").unwrap(),
+        PaxelType::List(Box::new(PaxelType::String))
+    );
+    assert_eq!(infer("`${self.width:.1} wide`").unwrap(), PaxelType::String);
+    assert_eq!(
+        infer("self.user?.label").unwrap(),
+        PaxelType::Option(Box::new(PaxelType::String))
+    );
+    assert_eq!(
+        infer("self.user?.label ?? \"anonymous\"").unwrap(),
+        PaxelType::String
+    );
+
+    // The error points at `self.nme`, 4 columns into the expression
+    let unknown = error("1 + self.nme");
+    assert!(unknown.contains("Unknown symbol `self.nme`.  Did you mean `self.name`?"));
+    assert!(unknown.contains(&format!("Col {}", "<Rectangle fill={".len() + 4)));
+    assert!(error("self.items.map(|i| i.lable)").contains("Did you mean `label`?"));
+    assert!(error("clamp(self.count, 0)").contains("`clamp` takes 3 argument(s), but was given 2"));
+    assert!(
+        error("self.count && true").contains("`&&` expects a bool, but `self.count` is a number")
+    );
+    assert!(error("self.name * 2").contains("`*` can't be applied to a string and a number"));
+    assert!(error("`Items: ${self.items}`").contains("which a template literal can't print"));
+    assert!(error("self.count > 2 ? 10px : rgb(255, 0, 0)").contains("must have the same type"));
+    assert!(error("self.user.label").contains(
+        "`self.user` is optional, so its fields are reached with `?.`, as in `self.user?.label`"
+    ));
+    assert!(error("self.name?.len")
+        .contains("`?.` can only follow an optional value, but `self.name` is a string"));
+    assert!(error("self.count ?? 0").contains("`??` expects an optional value"));
+    assert!(error("self.user?.label + \"!\"").contains("Try `??` to give it a default"));
+    assert_eq!(infer("[1, 2, 3].at(-3)").unwrap(), PaxelType::Number);
+    assert!(error("[1, 2, 3].at(3)")
+        .contains("Index 3 is out of range for a list of 3, which `at` indexes from -3 to 2"));
+    assert!(error("at([1, 2], -3)").contains("Index -3 is out of range"));
+
+    let color = PaxelType::of(
+        &TypeId::build_singleton("pax_engine::api::Color", None),
+        &type_table,
+    );
+    let check = |paxel: &str, expected: &PaxelType, target: &str| {
+        let paxel = expression(paxel);
+        check_paxel_type(&paxel, &infer_paxel_type(&paxel, &ctx)?, expected, target)
+    };
+    assert!(check("self.width * 2px", &PaxelType::Size, "`width`").is_ok());
+    let mismatch = check("self.width", &color, "`fill`")
+        .unwrap_err()
+        .to_string();
+    assert!(mismatch.contains("`fill` expects a color, but this expression is a size"));
+}
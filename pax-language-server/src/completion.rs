@@ -281,6 +281,14 @@ lazy_static! {
         let events = [
             ("scroll", "Set Scroll event handler"),
             ("checkbox_change", "Set Changed event handler"),
+            ("dropdown_change", "Set Dropdown Change event handler"),
+            ("slider_change", "Set Slider Change event handler"),
+            (
+                "virtual_range_change",
+                "Set Virtual Range Change event handler",
+            ),
+            ("image_load", "Set Image Load event handler"),
+            ("image_error", "Set Image Error event handler"),
             ("clap", "Set Clap event handler"),
             ("touch_start", "Set Touch Start event handler"),
             ("touch_move", "Set Touch Move event handler"),
@@ -526,6 +526,17 @@ impl Backend {
                                         "transform",
                                         "width",
                                         "height",
+                                        "flex_grow",
+                                        "flex_shrink",
+                                        "min_width",
+                                        "max_width",
+                                        "min_height",
+                                        "max_height",
+                                        "grid_row",
+                                        "grid_column",
+                                        "grid_row_span",
+                                        "grid_column_span",
+                                        "opacity",
                                     ];
 
                                     if let Some(struct_ident) = struct_name {
@@ -91,11 +91,18 @@ pub fn extract_positional_nodes(
             });
         }
         Rule::selector_block => {
-            let selector = &inner.clone().next().unwrap().as_str().to_string();
-            if selector.starts_with(".") {
-                classes.insert(selector.replace(".", ""));
-            } else if selector.starts_with("#") {
-                ids.insert(selector.replace("#", ""));
+            let selector = inner.clone().next().unwrap();
+            for qualifier in selector
+                .into_inner()
+                .flat_map(|compound| compound.into_inner())
+                .filter(|p| p.as_rule() == Rule::selector_qualifier)
+            {
+                let qualifier = qualifier.as_str();
+                if let Some(class) = qualifier.strip_prefix('.') {
+                    classes.insert(class.to_string());
+                } else if let Some(id) = qualifier.strip_prefix('#') {
+                    ids.insert(id.to_string());
+                }
             }
             nodes.push(PositionalNode {
                 start,
@@ -226,7 +233,7 @@ pub fn extract_positional_nodes(
                 if key == "id" {
                     ids.insert(value.to_string());
                 } else if key == "class" {
-                    classes.insert(value.to_string());
+                    classes.extend(value.split_whitespace().map(str::to_string));
                 }
                 nodes.push(PositionalNode {
                     start,
@@ -178,15 +178,45 @@ fn recurse_visit_tag_pairs_for_pascal_identifiers(
         Rule::statement_control_flow => {
             let matched_tag = any_tag_pair.into_inner().next().unwrap();
 
+            if matched_tag.as_rule() == Rule::statement_match {
+                // each arm (after the matched symbol) is a pattern followed by its inner nodes
+                matched_tag
+                    .into_inner()
+                    .skip(1)
+                    .for_each(|statement_match_arm| {
+                        let inner_nodes = statement_match_arm.into_inner().nth(1).unwrap();
+                        inner_nodes.into_inner().for_each(|sub_tag_pair| {
+                            recurse_visit_tag_pairs_for_pascal_identifiers(
+                                sub_tag_pair,
+                                Rc::clone(&pascal_identifiers),
+                            );
+                        })
+                    });
+                return;
+            }
+
             let n = match matched_tag.as_rule() {
                 Rule::statement_if => 1,
-                Rule::statement_for => 2,
+                // an optional `key` clause precedes the body
+                Rule::statement_for => matched_tag
+                    .clone()
+                    .into_inner()
+                    .position(|pair| pair.as_rule() == Rule::inner_nodes)
+                    .unwrap(),
                 Rule::statement_slot => 0,
                 _ => {
                     unreachable!("Parsing error 944491032: {:?}", matched_tag.as_rule());
                 }
             };
 
+            if matched_tag.as_rule() == Rule::statement_if {
+                // `else` / `else if` clauses trail the `if` body and may declare their own nodes
+                visit_statement_else_for_pascal_identifiers(
+                    matched_tag.clone(),
+                    Rc::clone(&pascal_identifiers),
+                );
+            }
+
             let prospective_inner_nodes = matched_tag.into_inner().nth(n).expect("WRONG nth");
             match prospective_inner_nodes.as_rule() {
                 Rule::inner_nodes => {
@@ -216,3 +246,40 @@ fn recurse_visit_tag_pairs_for_pascal_identifiers(
         }
     }
 }
+
+fn visit_statement_else_for_pascal_identifiers(
+    statement_if: Pair<Rule>,
+    pascal_identifiers: Rc<RefCell<HashSet<String>>>,
+) {
+    let Some(statement_else) = statement_if
+        .into_inner()
+        .find(|pair| pair.as_rule() == Rule::statement_else)
+    else {
+        return;
+    };
+    let else_body = statement_else.into_inner().next().unwrap();
+    match else_body.as_rule() {
+        Rule::statement_if => {
+            visit_statement_else_for_pascal_identifiers(
+                else_body.clone(),
+                Rc::clone(&pascal_identifiers),
+            );
+            let inner_nodes = else_body.into_inner().nth(1).expect("WRONG nth");
+            inner_nodes.into_inner().for_each(|sub_tag_pair| {
+                recurse_visit_tag_pairs_for_pascal_identifiers(
+                    sub_tag_pair,
+                    Rc::clone(&pascal_identifiers),
+                );
+            })
+        }
+        Rule::inner_nodes => else_body.into_inner().for_each(|sub_tag_pair| {
+            recurse_visit_tag_pairs_for_pascal_identifiers(
+                sub_tag_pair,
+                Rc::clone(&pascal_identifiers),
+            );
+        }),
+        _ => {
+            unreachable!("Parsing error 5520983: {:?}", else_body.as_rule());
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     constants::{COMMON_PROPERTIES, COMMON_PROPERTIES_TYPE},
-    PaxManifest, PropertyDefinition, SettingElement, SettingsBlockElement, TemplateNodeDefinition,
-    Token, TypeId, ValueDefinition,
+    selectors::{Selector, SelectorSubject},
+    ClassBundleDefinition, PaxManifest, PropertyDefinition, SettingElement, SettingsBlockElement,
+    TemplateNodeDefinition, TimelineDefinition, TypeId, ValueDefinition,
 };
 
 #[derive(Serialize, Debug)]
@@ -49,6 +50,17 @@ impl PaxManifest {
         handlers
     }
 
+    pub fn get_component_timeline(&self, type_id: &TypeId) -> Option<TimelineDefinition> {
+        let settings = self.components.get(type_id)?.settings.as_ref()?;
+        settings.iter().find_map(|setting| {
+            if let SettingsBlockElement::Timeline(timeline) = setting {
+                Some(timeline.clone())
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn event_to_args_map(&self) -> HashMap<String, Option<String>> {
         let mut map = HashMap::new();
         let mut add = |from: &str, to: &str| {
@@ -63,6 +75,11 @@ impl PaxManifest {
         add("key_up", "KeyUp");
         add("key_press", "KeyPress");
         add("checkbox_change", "CheckboxChange");
+        add("dropdown_change", "DropdownChange");
+        add("slider_change", "SliderChange");
+        add("virtual_range_change", "VirtualRangeChange");
+        add("image_load", "ImageLoad");
+        add("image_error", "ImageError");
         add("button_click", "ButtonClick");
         add("textbox_change", "TextboxChange");
         add("text_input", "TextInput");
@@ -166,8 +183,10 @@ impl PaxManifest {
         tnd: &TemplateNodeDefinition,
     ) -> HashMap<String, ValueDefinition> {
         let component = self.components.get(&containing_component_type_id).unwrap();
+        // Nodes are compiled with their settings already merged, including the blocks matching
+        // through their ancestors, so the node alone suffices here
         let settings =
-            Self::merge_inline_settings_with_settings_block(&tnd.settings, &component.settings);
+            Self::merge_inline_settings_with_settings_block(tnd, &[], &component.settings);
         let mut map = HashMap::new();
         if let Some(settings) = &settings {
             for setting in settings {
@@ -176,7 +195,8 @@ impl PaxManifest {
                         ValueDefinition::LiteralValue(_)
                         | ValueDefinition::Block(_)
                         | ValueDefinition::Expression(_, _)
-                        | ValueDefinition::Identifier(_, _) => {
+                        | ValueDefinition::Identifier(_, _)
+                        | ValueDefinition::Timeline(_) => {
                             map.insert(key.token_value.clone(), value.clone());
                         }
                         _ => {}
@@ -193,8 +213,10 @@ impl PaxManifest {
         tnd: &TemplateNodeDefinition,
     ) -> HashMap<String, ValueDefinition> {
         let component = self.components.get(containing_component_type_id).unwrap();
+        // Nodes are compiled with their settings already merged, including the blocks matching
+        // through their ancestors, so the node alone suffices here
         let settings =
-            Self::merge_inline_settings_with_settings_block(&tnd.settings, &component.settings);
+            Self::merge_inline_settings_with_settings_block(tnd, &[], &component.settings);
         let mut map = HashMap::new();
         if let Some(settings) = &settings {
             for setting in settings {
@@ -203,7 +225,8 @@ impl PaxManifest {
                         ValueDefinition::LiteralValue(_)
                         | ValueDefinition::Block(_)
                         | ValueDefinition::Expression(_, _)
-                        | ValueDefinition::Identifier(_, _) => {
+                        | ValueDefinition::Identifier(_, _)
+                        | ValueDefinition::Timeline(_) => {
                             if CommonProperty::get_common_properties().contains(&key.token_value) {
                                 map.insert(key.token_value.clone(), value.clone());
                             }
@@ -236,94 +259,40 @@ impl PaxManifest {
         handlers
     }
 
-    fn pull_matched_identifiers_from_inline(
-        inline_settings: &Option<Vec<SettingElement>>,
-        s: String,
-    ) -> Vec<Token> {
-        let mut ret = Vec::new();
-        if let Some(val) = inline_settings {
-            let matched_settings = val.iter().filter(|e| match e {
-                SettingElement::Setting(token, _) => token.token_value == s.as_str(),
-                _ => false,
-            });
-            for e in matched_settings {
-                if let SettingElement::Setting(_, value) = e {
-                    match value {
-                        ValueDefinition::LiteralValue(s) => ret.push(s.clone()),
-                        ValueDefinition::Identifier(s, _) => ret.push(s.clone()),
-                        _ => {}
-                    };
-                }
-            }
-        }
-        ret
-    }
-
-    fn pull_settings_with_selector(
-        settings: &Option<Vec<SettingsBlockElement>>,
-        selector: String,
-    ) -> Option<Vec<SettingElement>> {
-        settings.as_ref().and_then(|val| {
-            let mut merged_setting = Vec::new();
-            for settings_value in val.iter() {
-                match settings_value {
-                    SettingsBlockElement::SelectorBlock(token, value) => {
-                        if token.token_value == selector {
-                            merged_setting.extend(value.elements.clone());
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            (!merged_setting.is_empty()).then(|| merged_setting)
-        })
-    }
-
+    /// `node`'s inline settings, merged with those of each `@settings` block whose selector matches
+    /// it given its `ancestors` (from the root down).  Inline settings win over any block, and more
+    /// specific blocks over less specific ones, with later blocks winning ties, as in CSS.
     pub fn merge_inline_settings_with_settings_block(
-        inline_settings: &Option<Vec<SettingElement>>,
+        node: &TemplateNodeDefinition,
+        ancestors: &[SelectorSubject],
         settings_block: &Option<Vec<SettingsBlockElement>>,
     ) -> Option<Vec<SettingElement>> {
-        // collect id settings
-        let ids = Self::pull_matched_identifiers_from_inline(&inline_settings, "id".to_string());
-
-        let mut id_settings = Vec::new();
-        if ids.len() == 1 {
-            if let Some(settings) = Self::pull_settings_with_selector(
-                &settings_block,
-                format!("#{}", ids[0].token_value),
-            ) {
-                id_settings.extend(settings.clone());
-            }
-        } else if ids.len() > 1 {
-            panic!("Specified more than one id inline!");
-        }
-
-        // collect all class settings
-        let classes =
-            Self::pull_matched_identifiers_from_inline(&inline_settings, "class".to_string());
-
-        let mut class_settings = Vec::new();
-        for class in classes {
-            if let Some(settings) = Self::pull_settings_with_selector(
-                &settings_block,
-                format!(".{}", class.token_value),
-            ) {
-                class_settings.extend(settings.clone());
-            }
-        }
+        let inline_settings = &node.settings;
+        let subject = SelectorSubject::of(node);
+        let mut matched_blocks: Vec<_> = settings_block
+            .iter()
+            .flatten()
+            .filter_map(|element| match element {
+                SettingsBlockElement::SelectorBlock(token, value) => {
+                    let selector = Selector::parse(&token.token_value).ok()?;
+                    selector
+                        .matches(&subject, ancestors)
+                        .then(|| (selector.specificity(), &value.elements))
+                }
+                _ => None,
+            })
+            .collect();
+        // Stable, so that blocks of equal specificity keep their order
+        matched_blocks.sort_by_key(|(specificity, _)| *specificity);
 
         let mut map = HashMap::new();
 
-        // Iterate in reverse order of priority (class, then id, then inline)
-        for e in class_settings.into_iter() {
-            if let SettingElement::Setting(key, _) = e.clone() {
-                map.insert(key, e);
-            }
-        }
-
-        for e in id_settings.into_iter() {
-            if let SettingElement::Setting(key, _) = e.clone() {
-                map.insert(key, e);
+        // Iterate in increasing order of priority, each block overriding those before it
+        for (_, elements) in matched_blocks {
+            for e in elements {
+                if let SettingElement::Setting(key, _) = e {
+                    map.insert(key.clone(), e.clone());
+                }
             }
         }
 
@@ -346,6 +315,84 @@ impl PaxManifest {
             None
         }
     }
+
+    /// For a node whose `class` is an expression, the blocks of `settings_block` that apply to it
+    /// for some value of that expression, from least to most specific, each with the classes it
+    /// needs the node to have.  Only the settings that switching classes can change are kept:
+    /// those that some block needing a class sets, and that are not set inline.
+    pub fn build_class_bundles(
+        node: &TemplateNodeDefinition,
+        ancestors: &[SelectorSubject],
+        settings_block: &Option<Vec<SettingsBlockElement>>,
+    ) -> Vec<ClassBundleDefinition> {
+        if !Self::has_class_expression(node) {
+            return Vec::new();
+        }
+        let subject = SelectorSubject::of(node);
+        let mut bundles: Vec<_> = settings_block
+            .iter()
+            .flatten()
+            .filter_map(|element| match element {
+                SettingsBlockElement::SelectorBlock(token, value) => {
+                    let selector = Selector::parse(&token.token_value).ok()?;
+                    let classes = selector.missing_classes(&subject, ancestors)?;
+                    Some((
+                        selector.specificity(),
+                        ClassBundleDefinition {
+                            classes,
+                            settings: value.elements.clone(),
+                        },
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        bundles.sort_by_key(|(specificity, _)| *specificity);
+
+        let key_of = |setting: &SettingElement| match setting {
+            SettingElement::Setting(key, _) => Some(key.token_value.clone()),
+            _ => None,
+        };
+        let inline_keys: HashSet<String> =
+            node.settings.iter().flatten().filter_map(key_of).collect();
+        let switchable_keys: HashSet<String> = bundles
+            .iter()
+            .filter(|(_, bundle)| !bundle.classes.is_empty())
+            .flat_map(|(_, bundle)| bundle.settings.iter().filter_map(key_of))
+            .filter(|key| !inline_keys.contains(key))
+            .collect();
+
+        bundles
+            .into_iter()
+            .filter_map(|(_, mut bundle)| {
+                bundle.settings.retain(|setting| {
+                    key_of(setting).is_some_and(|k| switchable_keys.contains(&k))
+                });
+                (!bundle.settings.is_empty()).then_some(bundle)
+            })
+            .collect()
+    }
+
+    fn has_class_expression(node: &TemplateNodeDefinition) -> bool {
+        node.settings.iter().flatten().any(|setting| {
+            matches!(setting, SettingElement::Setting(key, ValueDefinition::Expression(..)) if key.token_value == "class")
+        })
+    }
+
+    /// The vtable id of `tnd`'s `class` expression, once compiled
+    pub fn get_class_expression_vtable_id(tnd: &TemplateNodeDefinition) -> Option<usize> {
+        tnd.settings
+            .iter()
+            .flatten()
+            .find_map(|setting| match setting {
+                SettingElement::Setting(key, ValueDefinition::Expression(_, id))
+                    if key.token_value == "class" =>
+                {
+                    *id
+                }
+                _ => None,
+            })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -4,22 +4,24 @@ use std::fmt::Display;
 use std::hash::Hasher;
 use std::{cmp::Ordering, hash::Hash};
 
-use constants::{TYPE_ID_COMMENT, TYPE_ID_IF, TYPE_ID_REPEAT, TYPE_ID_SLOT};
+use constants::{TYPE_ID_COMMENT, TYPE_ID_IF, TYPE_ID_MATCH, TYPE_ID_REPEAT, TYPE_ID_SLOT};
 use pax_message::serde::{Deserialize, Serialize};
 use pax_runtime_api::Interpolatable;
+use selectors::SelectorSubject;
 
 #[cfg(feature = "parsing")]
 pub mod utils;
 
 pub mod cartridge_generation;
 pub mod constants;
+pub mod selectors;
 
 #[cfg(feature = "parsing")]
 pub mod deserializer;
 
 /// Definition container for an entire Pax cartridge
 #[serde_with::serde_as]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(crate = "pax_message::serde")]
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct PaxManifest {
@@ -193,6 +195,11 @@ pub struct ExpressionSpecInvocation {
     /// Flag describing whether the nested symbolic invocation, e.g. `foo.bar`, ultimately
     /// resolves to a numeric type (as opposed to `is_numeric`, which represents the root of a nested type)
     pub is_nested_numeric: bool,
+
+    /// For invocations of design tokens, like `theme.primary`: the token's name, looked up in the
+    /// runtime's current theme rather than on the stack
+    #[serde(default)]
+    pub theme_token: Option<String>,
 }
 
 pub const SUPPORTED_NUMERIC_PRIMITIVES: [&str; 13] = [
@@ -259,6 +266,18 @@ impl ComponentDefinition {
     pub fn get_property_definitions<'a>(&self, tt: &'a TypeTable) -> &'a Vec<PropertyDefinition> {
         &tt.get(&self.type_id).unwrap().property_definitions
     }
+
+    /// The design tokens declared by this component's `@theme`, if any
+    pub fn get_theme_token_definitions(&self) -> impl Iterator<Item = &ThemeTokenDefinition> {
+        self.settings
+            .iter()
+            .flatten()
+            .filter_map(|setting| match setting {
+                SettingsBlockElement::Theme(tokens) => Some(tokens),
+                _ => None,
+            })
+            .flatten()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -266,9 +285,60 @@ impl ComponentDefinition {
 pub enum SettingsBlockElement {
     SelectorBlock(Token, LiteralBlockDefinition),
     Handler(Token, Vec<Token>),
+    Timeline(TimelineDefinition),
+    Theme(Vec<ThemeTokenDefinition>),
     Comment(String),
 }
 
+/// A component's `@timeline` declaration, e.g. `@timeline: { frames: 120, loop: true, autoplay: true }`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "pax_message::serde")]
+pub struct TimelineDefinition {
+    pub frame_count: usize,
+    pub is_looping: bool,
+    pub autoplay: bool,
+}
+
+/// A design token declared in a component's `@theme`, e.g. `primary: color`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "pax_message::serde")]
+pub struct ThemeTokenDefinition {
+    pub token: Token,
+    pub kind: ThemeTokenKind,
+}
+
+/// The kinds of design tokens, mirroring the tables of the runtime's `Theme`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "pax_message::serde")]
+pub enum ThemeTokenKind {
+    Color,
+    Size,
+    Font,
+    Radius,
+}
+
+impl ThemeTokenKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "color" => Some(ThemeTokenKind::Color),
+            "size" => Some(ThemeTokenKind::Size),
+            "font" => Some(ThemeTokenKind::Font),
+            "radius" => Some(ThemeTokenKind::Radius),
+            _ => None,
+        }
+    }
+}
+
+/// A single keyframe of a `@timeline { ... }` property value, e.g. `60: 200px OutBack`
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(crate = "pax_message::serde")]
+pub struct KeyframeDefinition {
+    pub frame: usize,
+    pub value: Token,
+    /// Name of the easing curve into this keyframe from the previous one; linear if absent
+    pub curve: Option<Token>,
+}
+
 #[derive(Serialize, Default, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 #[serde(crate = "pax_message::serde")]
 pub struct TemplateNodeId(usize);
@@ -295,6 +365,7 @@ pub enum PaxType {
     If,
     Slot,
     Repeat,
+    Match,
     Comment,
     BlankComponent {
         pascal_identifier: String,
@@ -328,6 +399,7 @@ impl Display for PaxType {
             PaxType::If => write!(f, "If"),
             PaxType::Slot => write!(f, "Slot"),
             PaxType::Repeat => write!(f, "Repeat"),
+            PaxType::Match => write!(f, "Match"),
             PaxType::Comment => write!(f, "Comment"),
             PaxType::BlankComponent { pascal_identifier } => write!(f, "{}", pascal_identifier),
             PaxType::Primitive { pascal_identifier } => write!(f, "{}", pascal_identifier),
@@ -399,6 +471,16 @@ impl TypeId {
         }
     }
 
+    pub fn build_match() -> Self {
+        TypeId {
+            pax_type: PaxType::Match,
+            import_path: None,
+            is_intoable_downstream_type: false,
+            _type_id: "Match".to_string(),
+            _type_id_escaped: "Match".to_string(),
+        }
+    }
+
     pub fn build_comment() -> Self {
         TypeId {
             pax_type: PaxType::Comment,
@@ -525,7 +607,7 @@ impl TypeId {
             PaxType::Primitive { pascal_identifier }
             | PaxType::Singleton { pascal_identifier }
             | PaxType::BlankComponent { pascal_identifier } => Some(pascal_identifier.clone()),
-            PaxType::If | PaxType::Slot | PaxType::Repeat | PaxType::Comment => {
+            PaxType::If | PaxType::Slot | PaxType::Repeat | PaxType::Match | PaxType::Comment => {
                 Some(self.pax_type.to_string())
             }
             _ => None,
@@ -558,6 +640,7 @@ impl TypeId {
         primitives_set.insert(TYPE_ID_IF);
         primitives_set.insert(TYPE_ID_REPEAT);
         primitives_set.insert(TYPE_ID_SLOT);
+        primitives_set.insert(TYPE_ID_MATCH);
         primitives_set.insert(TYPE_ID_COMMENT);
 
         let ret = id.replace("crate::", "").to_string();
@@ -1003,6 +1086,37 @@ impl ComponentTemplate {
         None
     }
 
+    /// The nodes containing `id`, from the root of the template down to its parent
+    pub fn get_ancestors(&self, id: &TemplateNodeId) -> Vec<&TemplateNodeDefinition> {
+        let mut ancestors = Vec::new();
+        let mut current = id;
+        while let Some((parent, _)) = self
+            .children
+            .iter()
+            .find(|(_, children)| children.contains(current))
+        {
+            ancestors.extend(self.nodes.get(parent));
+            current = parent;
+        }
+        ancestors.reverse();
+        ancestors
+    }
+
+    /// For an `if` / `else if` node, returns the id of its `else` clause, if one is declared.
+    /// The `else` clause is always the last non-comment child of the node it belongs to.
+    pub fn get_else_branch(&self, id: &TemplateNodeId) -> Option<TemplateNodeId> {
+        let last_child = self.get_children(id)?.into_iter().rev().find(|child_id| {
+            self.get_node(child_id)
+                .is_some_and(|tnd| tnd.raw_comment_string.is_none())
+        })?;
+        let is_else_branch = self
+            .get_node(&last_child)?
+            .control_flow_settings
+            .as_ref()
+            .is_some_and(|cfsd| cfsd.is_else_branch);
+        is_else_branch.then_some(last_child)
+    }
+
     pub fn get_node(&self, id: &TemplateNodeId) -> Option<&TemplateNodeDefinition> {
         self.nodes.get(id)
     }
@@ -1255,12 +1369,33 @@ impl ComponentTemplate {
         self.children.clone()
     }
 
+    /// Merges the settings of each node (other than control flow and comments) with the blocks of
+    /// `settings_block` whose selectors match it
     pub fn merge_with_settings(&mut self, settings_block: &Option<Vec<SettingsBlockElement>>) {
-        for node in self.get_nodes_mut() {
-            node.settings = PaxManifest::merge_inline_settings_with_settings_block(
-                &mut node.settings,
-                settings_block,
-            );
+        let merged: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| {
+                node.control_flow_settings.is_none() && node.raw_comment_string.is_none()
+            })
+            .map(|(id, node)| {
+                let ancestors: Vec<_> = self
+                    .get_ancestors(id)
+                    .into_iter()
+                    .map(SelectorSubject::of)
+                    .collect();
+                let settings = PaxManifest::merge_inline_settings_with_settings_block(
+                    node,
+                    &ancestors,
+                    settings_block,
+                );
+                (id.clone(), settings)
+            })
+            .collect();
+        for (id, settings) in merged {
+            if let Some(node) = self.nodes.get_mut(&id) {
+                node.settings = settings;
+            }
         }
     }
 }
@@ -1280,6 +1415,33 @@ pub struct TemplateNodeDefinition {
     pub settings: Option<Vec<SettingElement>>,
     /// IFF this TND is a comment node: raw comment string
     pub raw_comment_string: Option<String>,
+    /// IFF this TND binds `class` to an expression: the `@settings` blocks that apply to it
+    /// depending on the expression's value, written during expression compilation
+    #[serde(default)]
+    pub class_bundles: Option<Vec<ClassBundleDefinition>>,
+}
+
+/// Settings from one `@settings` block, applied at runtime while a node whose `class` is an
+/// expression has all of `classes`.  A bundle with no `classes` always applies.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(crate = "pax_message::serde")]
+pub struct ClassBundleDefinition {
+    pub classes: Vec<String>,
+    pub settings: Vec<SettingElement>,
+}
+
+impl ClassBundleDefinition {
+    pub fn get_settings_map(&self) -> HashMap<String, ValueDefinition> {
+        self.settings
+            .iter()
+            .filter_map(|setting| match setting {
+                SettingElement::Setting(key, value) => {
+                    Some((key.token_value.clone(), value.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl TemplateNodeDefinition {
@@ -1370,6 +1532,8 @@ pub struct PropertyDefinitionFlags {
     pub is_binding_repeat_i: bool,
     /// Does this property represent `elem` in `for (elem, i)` OR `for elem in 0..5` ?
     pub is_binding_repeat_elem: bool,
+    /// Does this property represent a payload destructured by a `match` arm, like `x` in `Mode::B(x) => { ... }` ?
+    pub is_binding_match_arm: bool,
 
     // // //
     // Source axis
@@ -1411,7 +1575,7 @@ pub struct TypeDefinition {
     pub type_id: TypeId,
 
     /// Statically known type_id for this Property's iterable TypeDefinition, that is,
-    /// T for some Property<Vec<T>>
+    /// T for some Property<Vec<T>>, or for some Property<Option<T>>
     pub inner_iterable_type_id: Option<TypeId>,
 
     /// A vec of PropertyType, describing known addressable (sub-)properties of this PropertyType
@@ -1458,6 +1622,7 @@ pub enum ValueDefinition {
     /// (Expression contents, vtable id binding)
     Identifier(Token, Option<usize>),
     EventBindingTarget(Token),
+    Timeline(Vec<KeyframeDefinition>),
 }
 
 impl Hash for ValueDefinition {
@@ -1481,6 +1646,9 @@ impl Hash for ValueDefinition {
             ValueDefinition::EventBindingTarget(t) => {
                 t.hash(state);
             }
+            ValueDefinition::Timeline(keyframes) => {
+                keyframes.hash(state);
+            }
         }
     }
 }
@@ -1530,6 +1698,13 @@ impl PartialEq for ValueDefinition {
                     false
                 }
             }
+            ValueDefinition::Timeline(keyframes) => {
+                if let ValueDefinition::Timeline(okeyframes) = other {
+                    keyframes == okeyframes
+                } else {
+                    false
+                }
+            }
         }
     }
 }
@@ -1561,10 +1736,24 @@ pub enum ControlFlowRepeatPredicateDefinition {
 pub struct ControlFlowSettingsDefinition {
     pub condition_expression_paxel: Option<Token>,
     pub condition_expression_vtable_id: Option<usize>,
+    /// Marks the `else if` / `else` clause of an `if` statement.  Each such clause is stored as the
+    /// trailing child of the `if` (or `else if`) node that precedes it; a bare `else` has no condition.
+    pub is_else_branch: bool,
     pub slot_index_expression_paxel: Option<Token>,
     pub slot_index_expression_vtable_id: Option<usize>,
     pub repeat_predicate_definition: Option<ControlFlowRepeatPredicateDefinition>,
     pub repeat_source_definition: Option<ControlFlowRepeatSourceDefinition>,
+    /// The key of a keyed `for`, e.g. the `item.id` in `for item in self.items key item.id { ... }`,
+    /// evaluated once per element with that element in scope.
+    pub repeat_key_expression_paxel: Option<Token>,
+    pub repeat_key_expression_vtable_id: Option<usize>,
+    /// The value matched by a `match` statement, e.g. the `self.mode` in `match self.mode { ... }`.
+    /// Its vtable entry evaluates to the active arm, together with that arm's bound payloads.
+    pub match_expression_paxel: Option<Token>,
+    pub match_expression_vtable_id: Option<usize>,
+    /// Marks an arm of a `match` statement.  Each arm is stored as a child of the `match` node,
+    /// in order, with the arm's nodes as its own children.
+    pub match_arm_pattern: Option<ControlFlowMatchPatternDefinition>,
 }
 
 impl PartialEq for ControlFlowRepeatSourceDefinition {
@@ -1584,9 +1773,13 @@ impl Hash for ControlFlowRepeatSourceDefinition {
 impl PartialEq for ControlFlowSettingsDefinition {
     fn eq(&self, other: &Self) -> bool {
         self.condition_expression_paxel == other.condition_expression_paxel
+            && self.is_else_branch == other.is_else_branch
             && self.slot_index_expression_paxel == other.slot_index_expression_paxel
             && self.repeat_predicate_definition == other.repeat_predicate_definition
             && self.repeat_source_definition == other.repeat_source_definition
+            && self.repeat_key_expression_paxel == other.repeat_key_expression_paxel
+            && self.match_expression_paxel == other.match_expression_paxel
+            && self.match_arm_pattern == other.match_arm_pattern
     }
 }
 
@@ -1595,9 +1788,13 @@ impl Eq for ControlFlowSettingsDefinition {}
 impl Hash for ControlFlowSettingsDefinition {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.condition_expression_paxel.hash(state);
+        self.is_else_branch.hash(state);
         self.slot_index_expression_paxel.hash(state);
         self.repeat_predicate_definition.hash(state);
         self.repeat_source_definition.hash(state);
+        self.repeat_key_expression_paxel.hash(state);
+        self.match_expression_paxel.hash(state);
+        self.match_arm_pattern.hash(state);
     }
 }
 
@@ -1611,6 +1808,15 @@ pub struct ControlFlowRepeatSourceDefinition {
     pub symbolic_binding: Option<Token>,
 }
 
+/// Pattern of a `match` arm: an enum variant, like `Mode::Failed` in `Mode::Failed(_, message)`,
+/// with identifiers binding its payloads in order (`_` discards one), or `None` for the wildcard `_`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(crate = "pax_message::serde")]
+pub struct ControlFlowMatchPatternDefinition {
+    pub variant: Option<Token>,
+    pub bindings: Vec<Token>,
+}
+
 /// Container for a parsed Literal object
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 #[serde(crate = "pax_message::serde")]
@@ -1692,7 +1898,10 @@ pub enum TokenType {
     IfExpression,
     ForPredicate,
     ForSource,
+    ForKey,
     SlotExpression,
+    MatchExpression,
+    MatchPattern,
     EventId,
     Handler,
     SettingKey,
@@ -1832,6 +2041,7 @@ pub fn escape_identifier(input: String) -> String {
         .replace("\\", "BSLA")
         .replace("#", "HASH")
         .replace("-", "HYPH")
+        .replace("?", "QUES")
 }
 
 /// Pulled from host Cargo.toml
@@ -1845,7 +2055,7 @@ pub struct HostCrateInfo {
 }
 
 //Effectively our `Prelude` types
-pub const IMPORTS_BUILTINS: [&str; 29] = [
+pub const IMPORTS_BUILTINS: [&str; 34] = [
     "std::any::Any",
     "std::cell::RefCell",
     "std::collections::HashMap",
@@ -1854,8 +2064,11 @@ pub const IMPORTS_BUILTINS: [&str; 29] = [
     "std::rc::Rc",
     "pax_runtime::RepeatItem",
     "pax_runtime::RepeatProperties",
+    "pax_runtime::RepeatKey",
     "pax_runtime::ConditionalProperties",
     "pax_runtime::SlotProperties",
+    "pax_runtime::MatchArm",
+    "pax_runtime::MatchProperties",
     "pax_runtime::get_numeric_from_wrapped_properties",
     "pax_runtime::api::PropertyInstance",
     "pax_runtime::api::PropertyLiteral",
@@ -1864,6 +2077,7 @@ pub const IMPORTS_BUILTINS: [&str; 29] = [
     "pax_runtime::ComponentInstance",
     "pax_runtime::InstanceNodePtr",
     "pax_runtime::api::expressions::PropertyExpression",
+    "pax_runtime::api::timelines::PropertyTimeline",
     "pax_runtime::InstanceNodePtrList",
     "pax_runtime::ExpressionContext",
     "pax_runtime::PaxEngine",
@@ -1872,6 +2086,7 @@ pub const IMPORTS_BUILTINS: [&str; 29] = [
     "pax_runtime::InstantiationArgs",
     "pax_runtime::ConditionalInstance",
     "pax_runtime::SlotInstance",
+    "pax_runtime::MatchInstance",
     "pax_runtime::properties::RuntimePropertiesStackFrame",
     "pax_runtime::repeat::RepeatInstance",
     "piet_common::RenderContext",
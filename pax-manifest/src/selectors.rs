@@ -0,0 +1,162 @@
+//! CSS-style selectors, joining the blocks of a component's `@settings` to the nodes of its template.
+//!
+//! A selector is a list of compound selectors separated by whitespace, e.g. `.card Text.title`.
+//! Each compound names any of a type (`Text`), classes (`.card`) and an id (`#title`), all of which
+//! a node must have to match it.  The last compound must match the node itself and each one before
+//! it some ancestor of the node, in order (CSS's descendant combinator).
+
+use crate::{SettingElement, TemplateNodeDefinition, ValueDefinition};
+
+/// A run of type, class and id selectors, none of them separated by whitespace, e.g. `Text.card.selected`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CompoundSelector {
+    pub type_name: Option<String>,
+    pub ids: Vec<String>,
+    pub classes: Vec<String>,
+}
+
+impl CompoundSelector {
+    fn matches(&self, node: &SelectorSubject) -> bool {
+        (self.type_name.is_none() || self.type_name == node.type_name)
+            && self.ids.iter().all(|id| node.id.as_ref() == Some(id))
+            && self
+                .classes
+                .iter()
+                .all(|class| node.classes.contains(class))
+    }
+}
+
+/// A parsed selector, such as the `.card Text` of a `@settings` block `.card Text { ... }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    /// From the outermost ancestor to the node itself
+    pub compounds: Vec<CompoundSelector>,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, String> {
+        let compounds = selector
+            .split_whitespace()
+            .map(parse_compound)
+            .collect::<Result<Vec<_>, _>>()?;
+        if compounds.is_empty() {
+            return Err("empty selector".to_string());
+        }
+        Ok(Self { compounds })
+    }
+
+    /// The number of id, class and type selectors in this selector, compared in that order:
+    /// when several blocks set the same property of a node, the most specific one wins
+    pub fn specificity(&self) -> (usize, usize, usize) {
+        self.compounds
+            .iter()
+            .fold((0, 0, 0), |(ids, classes, types), c| {
+                (
+                    ids + c.ids.len(),
+                    classes + c.classes.len(),
+                    types + c.type_name.is_some() as usize,
+                )
+            })
+    }
+
+    /// Whether this selector applies to `node`, whose ancestors are `ancestors` (from the root down)
+    pub fn matches(&self, node: &SelectorSubject, ancestors: &[SelectorSubject]) -> bool {
+        let Some((last, rest)) = self.compounds.split_last() else {
+            return false;
+        };
+        if !last.matches(node) {
+            return false;
+        }
+        // Matching each remaining compound to the nearest ancestor it can match leaves the most
+        // ancestors for the compounds before it
+        let mut ancestors = ancestors.iter().rev();
+        rest.iter()
+            .rev()
+            .all(|compound| ancestors.any(|ancestor| compound.matches(ancestor)))
+    }
+
+    /// The classes `node` lacks for this selector to apply to it, if having them would be enough,
+    /// e.g. `["active"]` for `Rectangle.active` and a `Rectangle` with no classes.  Used for nodes
+    /// whose classes are only known at runtime.
+    pub fn missing_classes(
+        &self,
+        node: &SelectorSubject,
+        ancestors: &[SelectorSubject],
+    ) -> Option<Vec<String>> {
+        let last = self.compounds.last()?;
+        let missing: Vec<String> = last
+            .classes
+            .iter()
+            .filter(|class| !node.classes.contains(class))
+            .cloned()
+            .collect();
+        let mut completed = node.clone();
+        completed.classes.extend(missing.iter().cloned());
+        self.matches(&completed, ancestors).then_some(missing)
+    }
+}
+
+fn parse_compound(compound: &str) -> Result<CompoundSelector, String> {
+    let mut parsed = CompoundSelector::default();
+    let type_end = compound.find(['.', '#']).unwrap_or(compound.len());
+    if type_end > 0 {
+        parsed.type_name = Some(compound[..type_end].to_string());
+    }
+    let mut rest = &compound[type_end..];
+    while let Some(prefix) = rest.chars().next() {
+        let name_end = rest[1..].find(['.', '#']).map_or(rest.len(), |end| end + 1);
+        let name = rest[1..name_end].to_string();
+        if name.is_empty() {
+            return Err(format!("selector `{}` has an empty name", compound));
+        }
+        match prefix {
+            '.' => parsed.classes.push(name),
+            _ => parsed.ids.push(name),
+        }
+        rest = &rest[name_end..];
+    }
+    Ok(parsed)
+}
+
+/// What selectors can match on a template node: its type, its `id` and its `class`es
+#[derive(Debug, Clone, Default)]
+pub struct SelectorSubject {
+    pub type_name: Option<String>,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+}
+
+impl SelectorSubject {
+    pub fn of(node: &TemplateNodeDefinition) -> Self {
+        let mut subject = Self {
+            type_name: node.type_id.get_pascal_identifier(),
+            ..Default::default()
+        };
+        for setting in node.settings.iter().flatten() {
+            let SettingElement::Setting(key, value) = setting else {
+                continue;
+            };
+            let value = match value {
+                ValueDefinition::LiteralValue(token) | ValueDefinition::Identifier(token, _) => {
+                    &token.token_value
+                }
+                // e.g. `class={self.is_active ? "active" : ""}`, known only at runtime
+                _ => continue,
+            };
+            match key.token_value.as_str() {
+                "id" => {
+                    if subject.id.is_some() {
+                        panic!("Specified more than one id inline!");
+                    }
+                    subject.id = Some(value.clone());
+                }
+                // `class=card selected` gives a node several classes
+                "class" => subject
+                    .classes
+                    .extend(value.split_whitespace().map(str::to_string)),
+                _ => {}
+            }
+        }
+        subject
+    }
+}
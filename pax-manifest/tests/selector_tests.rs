@@ -0,0 +1,176 @@
+use pax_manifest::selectors::{Selector, SelectorSubject};
+use pax_manifest::{
+    LiteralBlockDefinition, PaxManifest, SettingElement, SettingsBlockElement,
+    TemplateNodeDefinition, Token, TokenType, TypeId, ValueDefinition,
+};
+
+fn setting(key: &str, value: &str) -> SettingElement {
+    SettingElement::Setting(
+        Token::new_only_raw(key.to_string(), TokenType::SettingKey),
+        ValueDefinition::LiteralValue(Token::new_only_raw(
+            value.to_string(),
+            TokenType::LiteralValue,
+        )),
+    )
+}
+
+fn node(type_name: &str, settings: Vec<SettingElement>) -> TemplateNodeDefinition {
+    TemplateNodeDefinition {
+        type_id: TypeId::build_singleton(&format!("pax_std::primitives::{}", type_name), None),
+        settings: Some(settings),
+        ..Default::default()
+    }
+}
+
+fn block(selector: &str, settings: Vec<SettingElement>) -> SettingsBlockElement {
+    SettingsBlockElement::SelectorBlock(
+        Token::new_only_raw(selector.to_string(), TokenType::Selector),
+        LiteralBlockDefinition {
+            explicit_type_pascal_identifier: None,
+            elements: settings,
+        },
+    )
+}
+
+fn value_of<'a>(settings: &'a [SettingElement], key: &str) -> Option<&'a str> {
+    settings.iter().find_map(|s| match s {
+        SettingElement::Setting(k, ValueDefinition::LiteralValue(v)) if k.token_value == key => {
+            Some(v.token_value.as_str())
+        }
+        _ => None,
+    })
+}
+
+#[test]
+fn test_compound_and_descendant_selectors() {
+    let card = SelectorSubject::of(&node("Group", vec![setting("class", "card selected")]));
+    let text = SelectorSubject::of(&node("Text", vec![setting("id", "title")]));
+    let ancestors = [card.clone()];
+
+    let matches = |selector: &str, subject: &SelectorSubject, ancestors: &[SelectorSubject]| {
+        Selector::parse(selector)
+            .unwrap()
+            .matches(subject, ancestors)
+    };
+    assert!(matches(".card.selected", &card, &[]));
+    assert!(!matches(".card.pressed", &card, &[]));
+    assert!(matches(".card Text", &text, &ancestors));
+    assert!(matches(".selected #title", &text, &ancestors));
+    assert!(!matches(".card Text", &text, &[]));
+    // Each compound before the last needs an ancestor of its own
+    assert!(!matches(".card .selected Text", &text, &ancestors));
+
+    assert_eq!(
+        Selector::parse("Group.card Text#title")
+            .unwrap()
+            .specificity(),
+        (1, 1, 2)
+    );
+}
+
+#[test]
+fn test_merge_orders_by_specificity_then_inline() {
+    let settings_block = Some(vec![
+        block("#label", vec![setting("x", "id"), setting("y", "id")]),
+        block(
+            ".card Text",
+            vec![setting("x", "descendant"), setting("y", "descendant")],
+        ),
+        block(
+            "Text",
+            vec![
+                setting("x", "type"),
+                setting("y", "type"),
+                setting("width", "type"),
+            ],
+        ),
+        block(
+            ".big",
+            vec![setting("x", "class"), setting("height", "class")],
+        ),
+    ]);
+    let ancestors = [SelectorSubject::of(&node(
+        "Group",
+        vec![setting("class", "card")],
+    ))];
+    let text = node(
+        "Text",
+        vec![
+            setting("id", "label"),
+            setting("class", "big"),
+            setting("y", "inline"),
+        ],
+    );
+
+    let merged =
+        PaxManifest::merge_inline_settings_with_settings_block(&text, &ancestors, &settings_block)
+            .unwrap();
+    assert_eq!(value_of(&merged, "y"), Some("inline"));
+    assert_eq!(value_of(&merged, "x"), Some("id"));
+    assert_eq!(value_of(&merged, "height"), Some("class"));
+    assert_eq!(value_of(&merged, "width"), Some("type"));
+}
+
+#[test]
+fn test_class_bundles_for_class_expression() {
+    let settings_block = Some(vec![
+        block(
+            "Rectangle",
+            vec![setting("fill", "type"), setting("x", "type")],
+        ),
+        block(
+            ".active",
+            vec![setting("fill", "active"), setting("y", "active")],
+        ),
+        block(".card .active.big", vec![setting("height", "big")]),
+        block(".pressed Rectangle", vec![setting("width", "pressed")]),
+    ]);
+    let ancestors = [SelectorSubject::of(&node(
+        "Group",
+        vec![setting("class", "card")],
+    ))];
+    let class_expression = SettingElement::Setting(
+        Token::new_only_raw("class".to_string(), TokenType::SettingKey),
+        ValueDefinition::Expression(
+            Token::new_only_raw(
+                "self.is_active ? \"active\" : \"\"".to_string(),
+                TokenType::Expression,
+            ),
+            None,
+        ),
+    );
+    let rectangle = node("Rectangle", vec![class_expression, setting("y", "inline")]);
+
+    let bundles = PaxManifest::build_class_bundles(&rectangle, &ancestors, &settings_block);
+    let summary: Vec<(Vec<String>, Vec<&str>)> = bundles
+        .iter()
+        .map(|bundle| {
+            let mut keys: Vec<&str> = bundle
+                .settings
+                .iter()
+                .filter_map(|s| match s {
+                    SettingElement::Setting(k, _) => Some(k.token_value.as_str()),
+                    _ => None,
+                })
+                .collect();
+            keys.sort();
+            (bundle.classes.clone(), keys)
+        })
+        .collect();
+    // The type block only keeps what a class can override, inline `y` wins over `.active`, and
+    // `.pressed` needs an ancestor's class, which can't change
+    assert_eq!(
+        summary,
+        vec![
+            (vec![], vec!["fill"]),
+            (vec!["active".to_string()], vec!["fill"]),
+            (
+                vec!["active".to_string(), "big".to_string()],
+                vec!["height"]
+            ),
+        ]
+    );
+
+    let static_node = node("Rectangle", vec![setting("class", "active")]);
+    assert!(PaxManifest::build_class_bundles(&static_node, &[], &settings_block).is_empty());
+}
@@ -25,12 +25,16 @@ pub enum NativeMessage {
     ButtonCreate(AnyCreatePatch),
     ButtonUpdate(ButtonPatch),
     ButtonDelete(Vec<u32>),
+    DropdownCreate(AnyCreatePatch),
+    DropdownUpdate(DropdownPatch),
+    DropdownDelete(Vec<u32>),
     ScrollerCreate(AnyCreatePatch),
     ScrollerUpdate(ScrollerPatch),
     ScrollerDelete(Vec<u32>),
     ImageLoad(ImagePatch),
     LayerAdd(LayerAddPatch), //FUTURE: native form controls
     OcclusionUpdate(OcclusionPatch),
+    EffectsUpdate(EffectsPatch),
 }
 
 #[derive(Deserialize)]
@@ -60,6 +64,7 @@ pub enum NativeInterrupt {
     FormTextboxChange(FormTextboxChangeArgs),
     FormTextboxInput(FormTextboxInputArgs),
     FormButtonClick(FormButtonClickArgs),
+    FormDropdownChange(FormDropdownChangeArgs),
 }
 
 #[derive(Deserialize)]
@@ -96,6 +101,13 @@ pub struct FormButtonClickArgs {
     pub id_chain: Vec<u32>,
 }
 
+#[derive(Deserialize)]
+#[repr(C)]
+pub struct FormDropdownChangeArgs {
+    pub id_chain: Vec<u32>,
+    pub selected_id: u32,
+}
+
 #[derive(Deserialize)]
 #[repr(C)]
 pub struct ClapInterruptArgs {
@@ -106,6 +118,7 @@ pub struct ClapInterruptArgs {
 #[derive(Deserialize)]
 #[repr(C)]
 pub struct ScrollInterruptArgs {
+    pub id_chain: Vec<u32>,
     pub delta_x: f64,
     pub delta_y: f64,
 }
@@ -267,6 +280,7 @@ pub struct ContextMenuInterruptArgs {
 pub enum ImageLoadInterruptArgs {
     Reference(ImagePointerArgs),
     Data(ImageDataArgs),
+    Error(ImageErrorArgs),
 }
 #[derive(Deserialize)]
 #[repr(C)]
@@ -288,6 +302,15 @@ pub struct ImageDataArgs {
     pub height: usize,
 }
 
+/// A chassis could not load the image at `path` for the `Image` at `id_chain`
+#[derive(Deserialize)]
+#[repr(C)]
+pub struct ImageErrorArgs {
+    pub id_chain: Vec<u32>,
+    pub path: String,
+    pub message: String,
+}
+
 #[repr(C)]
 pub struct InterruptBuffer {
     pub data_ptr: *const u8,
@@ -361,6 +384,19 @@ pub struct ButtonPatch {
     pub style: Option<TextStyleMessage>,
 }
 
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Default, Serialize, Clone)]
+#[repr(C)]
+pub struct DropdownPatch {
+    pub id_chain: Vec<u32>,
+    pub transform: Option<Vec<f64>>,
+    pub size_x: Option<f64>,
+    pub size_y: Option<f64>,
+    pub options: Option<Vec<String>>,
+    pub selected_id: Option<u32>,
+    pub style: Option<TextStyleMessage>,
+}
+
 #[derive(Default, Serialize)]
 #[repr(C)]
 pub struct CheckboxStyleMessage {
@@ -375,6 +411,16 @@ pub struct OcclusionPatch {
     pub z_index: u32,
 }
 
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Default, Serialize)]
+#[repr(C)]
+pub struct EffectsPatch {
+    pub id_chain: Vec<u32>,
+    pub opacity: f64,
+    /// Radius, in px, by which the element is blurred
+    pub blur: f64,
+}
+
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[derive(Default, Serialize, Clone)]
 #[repr(C)]
@@ -465,6 +511,10 @@ pub struct ScrollerPatch {
     pub transform: Option<Vec<f64>>,
     pub scroll_x: Option<bool>,
     pub scroll_y: Option<bool>,
+    /// Set when the engine moves the content (momentum or `scroll_to`), for the chassis to
+    /// move its native scroll position to match
+    pub scroll_offset_x: Option<f64>,
+    pub scroll_offset_y: Option<f64>,
     pub subtree_depth: u32,
 }
 
@@ -16,9 +16,10 @@ pub const COLOR_CHANNEL: &str = "ColorChannel";
 pub const TYPE_ID_IF: &str = "IF";
 pub const TYPE_ID_REPEAT: &str = "REPEAT";
 pub const TYPE_ID_SLOT: &str = "SLOT";
+pub const TYPE_ID_MATCH: &str = "MATCH";
 pub const TYPE_ID_COMMENT: &str = "COMMENT";
 
-pub const COMMON_PROPERTIES: [&str; 13] = [
+pub const COMMON_PROPERTIES: [&str; 24] = [
     "id",
     "x",
     "y",
@@ -32,6 +33,17 @@ pub const COMMON_PROPERTIES: [&str; 13] = [
     "transform",
     "width",
     "height",
+    "flex_grow",
+    "flex_shrink",
+    "min_width",
+    "max_width",
+    "min_height",
+    "max_height",
+    "grid_row",
+    "grid_column",
+    "grid_row_span",
+    "grid_column_span",
+    "opacity",
 ];
 
 pub fn is_intoable_downstream_type(type_to_check: &str) -> bool {
@@ -50,7 +62,7 @@ const blessed_intoable_downstream_types: [&'static str; 5] = [
     "pax_std::types::Fill",
 ];
 
-pub const COMMON_PROPERTIES_TYPE: [(&str, &str); 13] = [
+pub const COMMON_PROPERTIES_TYPE: [(&str, &str); 24] = [
     ("id", "String"),
     ("x", "pax_engine::api::Size"),
     ("y", "pax_engine::api::Size"),
@@ -64,4 +76,15 @@ pub const COMMON_PROPERTIES_TYPE: [(&str, &str); 13] = [
     ("transform", "pax_engine::api::Transform2D"),
     ("width", "pax_engine::api::Size"),
     ("height", "pax_engine::api::Size"),
+    ("flex_grow", "pax_engine::api::Numeric"),
+    ("flex_shrink", "pax_engine::api::Numeric"),
+    ("min_width", "pax_engine::api::Size"),
+    ("max_width", "pax_engine::api::Size"),
+    ("min_height", "pax_engine::api::Size"),
+    ("max_height", "pax_engine::api::Size"),
+    ("grid_row", "pax_engine::api::Numeric"),
+    ("grid_column", "pax_engine::api::Numeric"),
+    ("grid_row_span", "pax_engine::api::Numeric"),
+    ("grid_column_span", "pax_engine::api::Numeric"),
+    ("opacity", "pax_engine::api::Numeric"),
 ];
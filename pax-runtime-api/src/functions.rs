@@ -0,0 +1,485 @@
+//! Functions callable by name from any PAXEL expression, e.g. `{clamp(self.progress, 0, 1)}` or
+//! `{mix(theme.primary, WHITE, 0.2)}`.  The compiler checks each call against [`BUILTIN_FUNCTIONS`]
+//! and compiles it into a call to the function of the same name in this module.
+//!
+//! Any of them may also be called as a method of its first argument, which reads better for the
+//! ones that take lists and lambdas: `{self.items.filter(|i| i.visible).len()}`.
+
+use crate::{Color, Numeric, Percent, Rotation, Size, StringBox};
+
+/// The kinds of value built-in functions take and return, for checking calls at compile time
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Number,
+    String,
+    Bool,
+    Color,
+    List,
+    /// A list or a string
+    Sequence,
+    /// Anything printable: a number, a string or a bool
+    Printable,
+    /// An element of a list
+    Element,
+    /// A lambda, e.g. `|i| i.visible`
+    Lambda,
+}
+
+impl ValueKind {
+    /// Whether a param of this kind accepts an argument of kind `arg`
+    pub fn accepts(self, arg: ValueKind) -> bool {
+        // elements of lists are of whatever kind the list holds
+        if self == ValueKind::Element || arg == ValueKind::Element {
+            return true;
+        }
+        match self {
+            ValueKind::Sequence => matches!(arg, ValueKind::List | ValueKind::String),
+            ValueKind::Printable => {
+                matches!(arg, ValueKind::Number | ValueKind::String | ValueKind::Bool)
+            }
+            _ => self == arg,
+        }
+    }
+}
+
+pub struct BuiltinFunction {
+    pub name: &'static str,
+    pub params: &'static [ValueKind],
+    /// Whether any number of further arguments of the last param's kind may follow
+    pub is_variadic: bool,
+    pub returns: ValueKind,
+    pub doc: &'static str,
+}
+
+impl BuiltinFunction {
+    /// The kind of the `i`th argument, or `None` past the last param of a non-variadic function
+    pub fn param(&self, i: usize) -> Option<ValueKind> {
+        match self.params.get(i) {
+            Some(kind) => Some(*kind),
+            None if self.is_variadic => self.params.last().copied(),
+            None => None,
+        }
+    }
+}
+
+macro_rules! builtin {
+    ($name:literal, [$($param:ident),*] $(, $variadic:literal)? -> $returns:ident, $doc:literal) => {
+        BuiltinFunction {
+            name: $name,
+            params: &[$(ValueKind::$param),*],
+            is_variadic: false $(|| $variadic)?,
+            returns: ValueKind::$returns,
+            doc: $doc,
+        }
+    };
+}
+
+pub const BUILTIN_FUNCTIONS: &[BuiltinFunction] = &[
+    builtin!("min", [Number, Number] -> Number, "The smaller of two numbers"),
+    builtin!("max", [Number, Number] -> Number, "The larger of two numbers"),
+    builtin!("clamp", [Number, Number, Number] -> Number, "`value`, limited to `[low, high]`"),
+    builtin!("lerp", [Number, Number, Number] -> Number, "The number `t` of the way from `a` to `b`"),
+    builtin!("abs", [Number] -> Number, "The absolute value of a number"),
+    builtin!("floor", [Number] -> Number, "The largest integer less than or equal to a number"),
+    builtin!("ceil", [Number] -> Number, "The smallest integer greater than or equal to a number"),
+    builtin!("round", [Number] -> Number, "The nearest integer to a number, rounding half away from zero"),
+    builtin!("sqrt", [Number] -> Number, "The square root of a number"),
+    builtin!("pow", [Number, Number] -> Number, "`base` raised to the power `exponent`"),
+    builtin!("sin", [Number] -> Number, "The sine of an angle in radians"),
+    builtin!("cos", [Number] -> Number, "The cosine of an angle in radians"),
+    builtin!("tan", [Number] -> Number, "The tangent of an angle in radians"),
+    builtin!("upper", [String] -> String, "A string in upper case"),
+    builtin!("lower", [String] -> String, "A string in lower case"),
+    builtin!("trim", [String] -> String, "A string without leading and trailing whitespace"),
+    builtin!("str", [Printable] -> String, "A number, string or bool as a string"),
+    builtin!("format", [String, Printable], true -> String, "`template` with each `{}` replaced by the next argument"),
+    builtin!("len", [Sequence] -> Number, "The number of elements of a list, or of characters of a string"),
+    builtin!("at", [List, Number] -> Element, "The element of a list at an index, counting from the end if negative, or the default element if out of range"),
+    builtin!("filter", [List, Lambda] -> List, "The elements of a list for which `predicate` is true"),
+    builtin!("map", [List, Lambda] -> List, "The results of `transform` for each element of a list"),
+    builtin!("sort", [List] -> List, "A list in ascending order"),
+    builtin!("sort_by", [List, Lambda] -> List, "A list in ascending order of the `key` of each element"),
+    builtin!("sum", [List] -> Number, "The sum of a list of numbers"),
+    builtin!("mix", [Color, Color, Number] -> Color, "The color `t` of the way from `a` to `b`"),
+    builtin!("lighten", [Color, Number] -> Color, "A color mixed with white by `amount`, from 0 to 1"),
+    builtin!("darken", [Color, Number] -> Color, "A color mixed with black by `amount`, from 0 to 1"),
+    builtin!("alpha", [Color, Number] -> Color, "A color with its opacity set to `alpha`, from 0 to 1"),
+];
+
+pub fn get_builtin_function(name: &str) -> Option<&'static BuiltinFunction> {
+    BUILTIN_FUNCTIONS.iter().find(|f| f.name == name)
+}
+
+pub fn min(a: Numeric, b: Numeric) -> Numeric {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+pub fn max(a: Numeric, b: Numeric) -> Numeric {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+pub fn clamp(value: Numeric, low: Numeric, high: Numeric) -> Numeric {
+    max(low, min(value, high))
+}
+
+pub fn lerp(a: Numeric, b: Numeric, t: Numeric) -> Numeric {
+    Numeric::from(a.to_float() + (b.to_float() - a.to_float()) * t.to_float())
+}
+
+pub fn abs(x: Numeric) -> Numeric {
+    match x {
+        Numeric::Integer(i) => Numeric::Integer(i.abs()),
+        Numeric::Float(f) => Numeric::Float(f.abs()),
+    }
+}
+
+pub fn floor(x: Numeric) -> Numeric {
+    Numeric::Integer(x.to_float().floor() as isize)
+}
+
+pub fn ceil(x: Numeric) -> Numeric {
+    Numeric::Integer(x.to_float().ceil() as isize)
+}
+
+pub fn round(x: Numeric) -> Numeric {
+    Numeric::Integer(x.to_float().round() as isize)
+}
+
+pub fn sqrt(x: Numeric) -> Numeric {
+    Numeric::from(x.to_float().sqrt())
+}
+
+pub fn pow(base: Numeric, exponent: Numeric) -> Numeric {
+    Numeric::from(base.to_float().powf(exponent.to_float()))
+}
+
+pub fn sin(x: Numeric) -> Numeric {
+    Numeric::from(x.to_float().sin())
+}
+
+pub fn cos(x: Numeric) -> Numeric {
+    Numeric::from(x.to_float().cos())
+}
+
+pub fn tan(x: Numeric) -> Numeric {
+    Numeric::from(x.to_float().tan())
+}
+
+pub fn upper(s: StringBox) -> StringBox {
+    StringBox::from(s.string.to_uppercase())
+}
+
+pub fn lower(s: StringBox) -> StringBox {
+    StringBox::from(s.string.to_lowercase())
+}
+
+pub fn trim(s: StringBox) -> StringBox {
+    StringBox::from(s.string.trim())
+}
+
+/// Values `str`, `format` and the holes of template literals print
+pub trait Printable {
+    fn print(&self) -> String;
+
+    /// Printed with `precision` digits after the decimal point, for those values with a number to round
+    fn print_with_precision(&self, _precision: Option<usize>) -> String {
+        self.print()
+    }
+
+    /// Numbers are signed and aligned right by a format spec, other values aligned left
+    fn is_number(&self) -> bool {
+        false
+    }
+}
+
+impl Printable for Numeric {
+    fn print(&self) -> String {
+        match self {
+            Numeric::Integer(i) => i.to_string(),
+            Numeric::Float(f) => f.to_string(),
+        }
+    }
+
+    fn print_with_precision(&self, precision: Option<usize>) -> String {
+        match precision {
+            Some(precision) => format!("{:.*}", precision, self.to_float()),
+            None => self.print(),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        true
+    }
+}
+
+impl Printable for Size {
+    fn print(&self) -> String {
+        self.print_with_precision(None)
+    }
+
+    fn print_with_precision(&self, precision: Option<usize>) -> String {
+        match self {
+            Size::Pixels(pixels) => format!("{}px", pixels.print_with_precision(precision)),
+            Size::Percent(percent) => format!("{}%", percent.print_with_precision(precision)),
+            Size::Combined(pixels, percent) => format!(
+                "{}px + {}%",
+                pixels.print_with_precision(precision),
+                percent.print_with_precision(precision)
+            ),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        true
+    }
+}
+
+impl Printable for Percent {
+    fn print(&self) -> String {
+        self.print_with_precision(None)
+    }
+
+    fn print_with_precision(&self, precision: Option<usize>) -> String {
+        format!("{}%", self.0.print_with_precision(precision))
+    }
+
+    fn is_number(&self) -> bool {
+        true
+    }
+}
+
+impl Printable for Rotation {
+    fn print(&self) -> String {
+        self.print_with_precision(None)
+    }
+
+    fn print_with_precision(&self, precision: Option<usize>) -> String {
+        match self {
+            Rotation::Degrees(degrees) => format!("{}deg", degrees.print_with_precision(precision)),
+            Rotation::Radians(radians) => format!("{}rad", radians.print_with_precision(precision)),
+            Rotation::Percent(percent) => format!("{}%", percent.print_with_precision(precision)),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        true
+    }
+}
+
+impl Printable for StringBox {
+    fn print(&self) -> String {
+        self.string.clone()
+    }
+}
+
+impl Printable for String {
+    fn print(&self) -> String {
+        self.clone()
+    }
+}
+
+impl Printable for bool {
+    fn print(&self) -> String {
+        self.to_string()
+    }
+}
+
+pub fn str(value: &dyn Printable) -> StringBox {
+    StringBox::from(value.print())
+}
+
+/// `template` with each `{}` replaced by the next of `args`; any `{}` beyond them are left as is
+pub fn format(template: StringBox, args: &[&dyn Printable]) -> StringBox {
+    let mut args = args.iter();
+    let mut pieces = template.string.split("{}");
+    let mut formatted = pieces.next().unwrap_or_default().to_string();
+    for piece in pieces {
+        match args.next() {
+            Some(arg) => formatted.push_str(&arg.print()),
+            None => formatted.push_str("{}"),
+        }
+        formatted.push_str(piece);
+    }
+    StringBox::from(formatted)
+}
+
+/// The text of a hole of a template literal, e.g. the `${self.price:.2}` of `` `Total: ${self.price:.2}` ``,
+/// formatted per its `spec` (here `.2`): an optional alignment of `<`, `^` or `>`, then `+` to sign
+/// positive numbers, then a width, padded with zeros if it starts with `0`, then a precision
+pub fn interpolate(value: &dyn Printable, spec: &str) -> String {
+    let mut spec = spec;
+    let align = match spec.chars().next() {
+        Some(align @ ('<' | '^' | '>')) => {
+            spec = &spec[1..];
+            Some(align)
+        }
+        _ => None,
+    };
+    let signed = spec.starts_with('+');
+    spec = spec.trim_start_matches('+');
+    let (width, precision) = spec.split_once('.').unwrap_or((spec, ""));
+    let zero_padded = width.len() > 1 && width.starts_with('0');
+    let width: usize = width.parse().unwrap_or(0);
+    let precision: Option<usize> = precision.parse().ok();
+
+    let mut text = value.print_with_precision(precision);
+    if signed && value.is_number() && !text.starts_with('-') {
+        text.insert(0, '+');
+    }
+    let padding = width.saturating_sub(text.chars().count());
+    if padding == 0 {
+        return text;
+    }
+    if zero_padded && align.is_none() && value.is_number() {
+        let sign_len = text.starts_with(['+', '-']) as usize;
+        text.insert_str(sign_len, &"0".repeat(padding));
+        return text;
+    }
+    let align = align.unwrap_or(if value.is_number() { '>' } else { '<' });
+    let (left, right) = match align {
+        '<' => (0, padding),
+        '^' => (padding / 2, padding - padding / 2),
+        _ => (padding, 0),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// Lists and strings, for `len`
+pub trait Sequence {
+    fn count(&self) -> usize;
+}
+
+impl<T> Sequence for Vec<T> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Sequence for StringBox {
+    fn count(&self) -> usize {
+        self.string.chars().count()
+    }
+}
+
+impl Sequence for String {
+    fn count(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+pub fn len(sequence: &dyn Sequence) -> Numeric {
+    Numeric::from(sequence.count())
+}
+
+/// Out of range, logs a warning and returns `T`'s default rather than failing the whole expression
+pub fn at<T: Clone + Default>(list: &[T], index: Numeric) -> T {
+    let i = index.to_int();
+    let resolved = if i < 0 { list.len() as isize + i } else { i };
+    match list.get(resolved as usize).filter(|_| resolved >= 0) {
+        Some(elem) => elem.clone(),
+        None => {
+            log::warn!("index {} is out of bounds for a list of {}", i, list.len());
+            T::default()
+        }
+    }
+}
+
+pub fn filter<T: Clone>(list: &[T], predicate: impl Fn(&T) -> bool) -> Vec<T> {
+    list.iter()
+        .filter(|elem| predicate(elem))
+        .cloned()
+        .collect()
+}
+
+pub fn map<T, U>(list: &[T], transform: impl Fn(&T) -> U) -> Vec<U> {
+    list.iter().map(transform).collect()
+}
+
+/// Elements that can't be compared even to themselves, like `NaN`s, sort last
+pub fn sort<T: Clone + PartialOrd>(list: &[T]) -> Vec<T> {
+    sort_by(list, |elem| elem.clone())
+}
+
+/// A stable sort, in which elements whose keys can't be compared even to themselves, like `NaN`s,
+/// sort last
+pub fn sort_by<T: Clone, K: PartialOrd>(list: &[T], key: impl Fn(&T) -> K) -> Vec<T> {
+    let mut keyed: Vec<(K, &T)> = list.iter().map(|elem| (key(elem), elem)).collect();
+    let is_incomparable = |key: &K| key.partial_cmp(key).is_none();
+    keyed.sort_by(|(a, _), (b, _)| {
+        a.partial_cmp(b)
+            .unwrap_or_else(|| is_incomparable(a).cmp(&is_incomparable(b)))
+    });
+    keyed.into_iter().map(|(_, elem)| elem.clone()).collect()
+}
+
+pub fn sum<T: Clone + Into<Numeric>>(list: &[T]) -> Numeric {
+    list.iter()
+        .fold(Numeric::from(0), |sum, elem| sum + elem.clone().into())
+}
+
+/// What a lambda reads from a list element, e.g. the `i.count` in `self.items.filter(|i| i.count > 3)`.
+/// `PaxelValue(v).get()` converts numbers to `Numeric` and `String`s to `StringBox`, as the compiler
+/// does for any property an expression reads, and otherwise returns `v` as is, through
+/// [`PaxelValueAsIs`]; Rust picks the inherent `get`s below over the trait's wherever they apply.
+pub struct PaxelValue<T>(pub T);
+
+macro_rules! impl_numeric_paxel_value {
+    ($($t:ty),*) => {
+        $(
+            impl PaxelValue<$t> {
+                pub fn get(self) -> Numeric {
+                    Numeric::from(self.0)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_paxel_value!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f64);
+
+impl PaxelValue<String> {
+    pub fn get(self) -> StringBox {
+        StringBox::from(self.0)
+    }
+}
+
+pub trait PaxelValueAsIs<T> {
+    fn get(self) -> T;
+}
+
+impl<T> PaxelValueAsIs<T> for PaxelValue<T> {
+    fn get(self) -> T {
+        self.0
+    }
+}
+
+pub fn mix(a: Color, b: Color, t: Numeric) -> Color {
+    let t = t.to_float().clamp(0.0, 1.0);
+    let (a, b) = (a.to_rgba_0_1(), b.to_rgba_0_1());
+    Color::from_rgba_0_1(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+}
+
+pub fn lighten(color: Color, amount: Numeric) -> Color {
+    let alpha = color.to_rgba_0_1()[3];
+    mix(color, Color::from_rgba_0_1([1.0, 1.0, 1.0, alpha]), amount)
+}
+
+pub fn darken(color: Color, amount: Numeric) -> Color {
+    let alpha = color.to_rgba_0_1()[3];
+    mix(color, Color::from_rgba_0_1([0.0, 0.0, 0.0, alpha]), amount)
+}
+
+pub fn alpha(color: Color, alpha: Numeric) -> Color {
+    let mut rgba = color.to_rgba_0_1();
+    rgba[3] = alpha.to_float().clamp(0.0, 1.0);
+    Color::from_rgba_0_1(rgba)
+}
@@ -4,7 +4,7 @@ use std::ops::{Add, Deref, Mul, Neg, Sub};
 
 use crate::math::Space;
 use kurbo::BezPath;
-use piet::PaintBrush;
+use piet::{PaintBrush, StrokeStyle};
 
 #[cfg(feature = "designtime")]
 use {
@@ -17,13 +17,19 @@ use std::rc::Rc;
 
 pub mod constants;
 pub mod expressions;
+pub mod functions;
 pub mod math;
 pub mod numeric;
+pub mod theme;
+pub mod timelines;
 
 pub use crate::numeric::Numeric;
+pub use crate::theme::{Theme, ThemeValue};
+pub use crate::timelines::Timeline;
 
 use crate::constants::COMMON_PROPERTIES_TYPE;
 use crate::expressions::PropertyExpression;
+use crate::timelines::PropertyTimeline;
 pub use pax_message::serde;
 use pax_message::{ColorMessage, ModifierKeyMessage, MouseButtonMessage, TouchMessage};
 use serde::{Deserialize, Serialize};
@@ -39,15 +45,95 @@ pub struct TransitionQueueEntry<T> {
 pub trait RenderContext {
     fn fill(&mut self, layer: &str, path: BezPath, brush: &PaintBrush);
     fn stroke(&mut self, layer: &str, path: BezPath, brush: &PaintBrush, width: f64);
+    fn stroke_styled(
+        &mut self,
+        layer: &str,
+        path: BezPath,
+        brush: &PaintBrush,
+        width: f64,
+        style: &StrokeStyle,
+    );
     fn save(&mut self, layer: &str);
     fn restore(&mut self, layer: &str);
     fn clip(&mut self, layer: &str, path: BezPath);
     fn load_image(&mut self, path: &str, image: &[u8], width: usize, height: usize);
     fn draw_image(&mut self, layer: &str, image_path: &str, rect: kurbo::Rect);
+    /// Draws the `src_rect` region of a loaded image, in image pixels, into `dst_rect`
+    fn draw_image_area(
+        &mut self,
+        layer: &str,
+        image_path: &str,
+        src_rect: kurbo::Rect,
+        dst_rect: kurbo::Rect,
+    );
+    /// Size in pixels of a loaded image; `None` if nothing has been loaded for `image_path`
+    fn image_size(&self, image_path: &str) -> Option<(f64, f64)>;
     fn transform(&mut self, layer: &str, affine: kurbo::Affine);
+    /// Sets the opacity of everything drawn from now on, on every layer, replacing the one set before
+    fn set_opacity(&mut self, opacity: f64);
+    /// Sets the radius, in px, by which everything drawn from now on, on every layer, is blurred,
+    /// replacing the one set before
+    fn set_blur(&mut self, radius: f64);
+    /// Fills `path` with its edges softened, as if blurred by `radius` px on top of the blur set
+    /// by `set_blur`
+    fn fill_blurred(&mut self, layer: &str, path: BezPath, brush: &PaintBrush, radius: f64);
+    /// Sets how what is drawn from now on to `layer` combines with what is already there.
+    /// Backends without compositing support keep drawing with `BlendMode::Normal`.
+    fn set_blend_mode(&mut self, layer: &str, mode: BlendMode);
     fn layers(&self) -> Vec<&str>;
 }
 
+/// How drawn content combines with the content beneath it, as in CSS `mix-blend-mode`.
+/// Honored on Web, macOS and iOS; headless rendering draws with `Normal`.
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    /// The name of this mode in CSS, also used for the canvas `globalCompositeOperation`
+    pub fn css_name(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "source-over",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen",
+            BlendMode::Overlay => "overlay",
+            BlendMode::Darken => "darken",
+            BlendMode::Lighten => "lighten",
+            BlendMode::ColorDodge => "color-dodge",
+            BlendMode::ColorBurn => "color-burn",
+            BlendMode::HardLight => "hard-light",
+            BlendMode::SoftLight => "soft-light",
+            BlendMode::Difference => "difference",
+            BlendMode::Exclusion => "exclusion",
+            BlendMode::Hue => "hue",
+            BlendMode::Saturation => "saturation",
+            BlendMode::Color => "color",
+            BlendMode::Luminosity => "luminosity",
+        }
+    }
+}
+
+impl Interpolatable for BlendMode {}
+
 #[cfg(debug_assertions)]
 impl<T: std::fmt::Debug> std::fmt::Debug for TransitionQueueEntry<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -63,6 +149,7 @@ impl<T: std::fmt::Debug> std::fmt::Debug for TransitionQueueEntry<T> {
 pub enum PropertyType {
     Literal,
     Expression,
+    Timeline,
 }
 
 /// An abstract Property that may be either: Literal,
@@ -88,6 +175,15 @@ pub trait PropertyInstance<T: Default + Clone> {
 
     fn property_type(&self) -> PropertyType;
 
+    /// Used by engine to evaluate timeline-bound values against their component's playhead
+    fn _get_timeline(&self) -> Option<&PropertyTimeline<T>> {
+        None
+    }
+
+    fn _get_timeline_mut(&mut self) -> Option<&mut PropertyTimeline<T>> {
+        None
+    }
+
     //Wishlist:
     // to_default: set back to default value
     // ease_to_default: set back to default value via interpolation
@@ -143,6 +239,12 @@ impl<T: Default + Clone + 'static> Clone for Box<dyn PropertyInstance<T>> {
                     ._get_vtable_id()
                     .expect("Cloned expression must have a v-table id"),
             )),
+            PropertyType::Timeline => Box::new(
+                self.deref()
+                    ._get_timeline()
+                    .expect("Cloned timeline property must expose its timeline")
+                    .clone(),
+            ),
         }
     }
 }
@@ -361,6 +463,41 @@ pub struct CheckboxChange {
     pub checked: bool,
 }
 
+/// The user picked an option of a `Dropdown`; `selected_id` is its index in `options`.
+#[derive(Clone)]
+pub struct DropdownChange {
+    pub selected_id: u32,
+}
+
+/// The value of a `Slider` was changed by the user.
+#[derive(Clone)]
+pub struct SliderChange {
+    pub value: f64,
+}
+
+/// An `Image` finished loading `path`, an image of `width` x `height` px, and can be drawn.
+#[derive(Clone)]
+pub struct ImageLoad {
+    pub path: String,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// An `Image` could not load `path`, for the reason given by `message`.
+#[derive(Clone)]
+pub struct ImageError {
+    pub path: String,
+    pub message: String,
+}
+
+/// The rows a `VirtualList` expands changed, as it was scrolled or resized: items `start..end`,
+/// which the list's slot children should now show in order.
+#[derive(Clone)]
+pub struct VirtualRangeChange {
+    pub start: usize,
+    pub end: usize,
+}
+
 #[derive(Clone)]
 pub struct TextInput {
     pub text: String,
@@ -535,6 +672,8 @@ impl Size {
     }
 }
 
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Axis {
     X,
     Y,
@@ -587,6 +726,26 @@ pub struct CommonProperties {
     pub transform: Box<dyn PropertyInstance<Transform2D>>,
     pub width: Box<dyn PropertyInstance<Size>>,
     pub height: Box<dyn PropertyInstance<Size>>,
+    /// Share of a `Flex` container's free space along its main axis given to this node (default 0)
+    pub flex_grow: Option<Box<dyn PropertyInstance<Numeric>>>,
+    /// Share of a `Flex` container's overflow along its main axis taken from this node,
+    /// weighted by its size (default 1)
+    pub flex_shrink: Option<Box<dyn PropertyInstance<Numeric>>>,
+    /// Bounds on the size a `Flex` container may grow, shrink or stretch this node to
+    pub min_width: Option<Box<dyn PropertyInstance<Size>>>,
+    pub max_width: Option<Box<dyn PropertyInstance<Size>>>,
+    pub min_height: Option<Box<dyn PropertyInstance<Size>>>,
+    pub max_height: Option<Box<dyn PropertyInstance<Size>>>,
+    /// Zero-based row and column of the `Grid` cell this node is placed in; when unset,
+    /// it fills the next free cell
+    pub grid_row: Option<Box<dyn PropertyInstance<Numeric>>>,
+    pub grid_column: Option<Box<dyn PropertyInstance<Numeric>>>,
+    /// Number of `Grid` rows and columns this node spans (default 1)
+    pub grid_row_span: Option<Box<dyn PropertyInstance<Numeric>>>,
+    pub grid_column_span: Option<Box<dyn PropertyInstance<Numeric>>>,
+    /// Opacity from 0 (invisible) to 1 (the default), multiplied by that of each ancestor.
+    /// Each node is drawn at its own resulting opacity, so overlapping children show through one another.
+    pub opacity: Option<Box<dyn PropertyInstance<Numeric>>>,
 }
 
 impl CommonProperties {
@@ -831,6 +990,30 @@ impl EasingEvaluators {
 }
 
 impl EasingCurve {
+    /// Names of the built-in curves, as accepted by [`EasingCurve::from_name`]
+    pub const NAMES: [&'static str; 6] = [
+        "Linear",
+        "InQuad",
+        "OutQuad",
+        "InBack",
+        "OutBack",
+        "InOutBack",
+    ];
+
+    /// Looks up a built-in curve by name, as written after a keyframe in a `@timeline`,
+    /// e.g. `60: 100.0 OutBack`
+    pub fn from_name(name: &str) -> Option<EasingCurve> {
+        match name {
+            "Linear" => Some(EasingCurve::Linear),
+            "InQuad" => Some(EasingCurve::InQuad),
+            "OutQuad" => Some(EasingCurve::OutQuad),
+            "InBack" => Some(EasingCurve::InBack),
+            "OutBack" => Some(EasingCurve::OutBack),
+            "InOutBack" => Some(EasingCurve::InOutBack),
+            _ => None,
+        }
+    }
+
     //for a time on the unit interval `t ∈ [0,1]`, given a value `t`,
     // find the interpolated value `vt` between `v0` and `v1` given the self-contained easing curve
     pub fn interpolate<T: Interpolatable>(&self, v0: &T, v1: &T, t: f64) -> T /*vt*/ {
@@ -956,12 +1139,6 @@ impl Interpolatable for i32 {
 
 impl Interpolatable for String {}
 
-pub struct Timeline {
-    pub playhead_position: usize,
-    pub frame_count: usize,
-    pub is_playing: bool,
-}
-
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[derive(Clone, Copy, PartialEq)]
 pub enum Layer {
@@ -1024,7 +1201,7 @@ impl OcclusionLayerGen {
 impl Interpolatable for StringBox {}
 
 #[cfg_attr(debug_assertions, derive(Debug))]
-#[derive(Clone, Default, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
 #[serde(crate = "crate::serde")]
 pub struct StringBox {
     pub string: String,
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use crate::{Color, Numeric, Size, StringBox};
+
+/// A table of design tokens, the named colors, sizes, fonts and corner radii that templates and
+/// expressions refer to as `theme.<token>`, e.g. `<Rectangle fill={theme.primary} />`.
+/// Set with `NodeContext#set_theme`, e.g. from the main component's `@mount` handler; setting
+/// another one (say, a dark theme) at runtime updates every property bound to its tokens.
+/// Components declare the tokens they read with their kinds, e.g. `@theme: { primary: color }`
+/// in `@settings`, so that the compiler can check each `theme.<token>` against its property.
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone, Default)]
+pub struct Theme {
+    /// e.g. `light` or `dark`
+    pub name: String,
+    pub colors: HashMap<String, Color>,
+    pub sizes: HashMap<String, Size>,
+    /// Font family names
+    pub fonts: HashMap<String, String>,
+    /// Corner radii, in pixels
+    pub radii: HashMap<String, Numeric>,
+}
+
+impl Theme {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_color(mut self, token: &str, color: Color) -> Self {
+        self.colors.insert(token.to_string(), color);
+        self
+    }
+
+    pub fn with_size(mut self, token: &str, size: Size) -> Self {
+        self.sizes.insert(token.to_string(), size);
+        self
+    }
+
+    pub fn with_font(mut self, token: &str, family: &str) -> Self {
+        self.fonts.insert(token.to_string(), family.to_string());
+        self
+    }
+
+    pub fn with_radius(mut self, token: &str, radius: impl Into<Numeric>) -> Self {
+        self.radii.insert(token.to_string(), radius.into());
+        self
+    }
+
+    /// The value of `token`, looked up among colors, sizes, fonts and radii in that order
+    pub fn get(&self, token: &str) -> Option<ThemeValue> {
+        if let Some(color) = self.colors.get(token) {
+            Some(ThemeValue::Color(color.clone()))
+        } else if let Some(size) = self.sizes.get(token) {
+            Some(ThemeValue::Size(*size))
+        } else if let Some(family) = self.fonts.get(token) {
+            Some(ThemeValue::Font(family.clone()))
+        } else {
+            self.radii
+                .get(token)
+                .map(|radius| ThemeValue::Radius(*radius))
+        }
+    }
+
+    /// The value of `token`, for an expression bound to it.  If this theme lacks it, logs a
+    /// warning and returns [`ThemeValue::Missing`], which converts into the property's default.
+    pub fn resolve(&self, token: &str) -> ThemeValue {
+        self.get(token).unwrap_or_else(|| {
+            log::warn!(
+                "theme `{}` has no token `{}`; declare it with `Theme#with_color` or similar",
+                self.name,
+                token
+            );
+            ThemeValue::Missing
+        })
+    }
+}
+
+/// The value of one design token, converted into the type of the property it is bound to
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub enum ThemeValue {
+    Color(Color),
+    Size(Size),
+    Font(String),
+    Radius(Numeric),
+    /// A token the theme lacks
+    Missing,
+}
+
+impl ThemeValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            ThemeValue::Color(_) => "color",
+            ThemeValue::Size(_) => "size",
+            ThemeValue::Font(_) => "font",
+            ThemeValue::Radius(_) => "radius",
+            ThemeValue::Missing => "missing",
+        }
+    }
+
+    /// The default of the property type `T`, after logging a warning unless the token was
+    /// already reported missing
+    fn mismatch<T: Default>(&self, expected: &str) -> T {
+        if !matches!(self, ThemeValue::Missing) {
+            log::warn!(
+                "a theme {} token can't be used as {}",
+                self.kind(),
+                expected
+            );
+        }
+        T::default()
+    }
+}
+
+impl From<ThemeValue> for Color {
+    fn from(value: ThemeValue) -> Self {
+        match value {
+            ThemeValue::Color(color) => color,
+            _ => value.mismatch("a color"),
+        }
+    }
+}
+
+impl From<ThemeValue> for Size {
+    fn from(value: ThemeValue) -> Self {
+        match value {
+            ThemeValue::Size(size) => size,
+            ThemeValue::Radius(radius) => Size::Pixels(radius),
+            _ => value.mismatch("a size"),
+        }
+    }
+}
+
+impl From<ThemeValue> for Numeric {
+    fn from(value: ThemeValue) -> Self {
+        match value {
+            ThemeValue::Radius(radius) => radius,
+            ThemeValue::Size(Size::Pixels(pixels)) => pixels,
+            _ => value.mismatch("a number"),
+        }
+    }
+}
+
+impl From<ThemeValue> for StringBox {
+    fn from(value: ThemeValue) -> Self {
+        match value {
+            ThemeValue::Font(family) => StringBox::from(family),
+            _ => value.mismatch("a string"),
+        }
+    }
+}
+
+impl From<ThemeValue> for String {
+    fn from(value: ThemeValue) -> Self {
+        StringBox::from(value).into()
+    }
+}
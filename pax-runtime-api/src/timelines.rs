@@ -0,0 +1,239 @@
+use std::rc::Rc;
+
+use crate::{EasingCurve, Interpolatable, PropertyInstance, PropertyType, TransitionManager};
+
+/// A playhead shared by the timeline-bound properties of a component's template.
+/// Declared in a component's settings, e.g. `@timeline: { frames: 120, loop: true, autoplay: true }`,
+/// and controlled from that component's handlers through `NodeContext#timeline`.
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub struct Timeline {
+    /// Current frame, in `[0, frame_count]`
+    pub playhead_position: usize,
+    pub frame_count: usize,
+    pub is_playing: bool,
+    /// Whether the playhead wraps around when it reaches either end, rather than pausing there
+    pub is_looping: bool,
+    /// Whether the playhead moves from `frame_count` towards `0`
+    pub is_reversed: bool,
+    last_advanced_frame: Option<usize>,
+}
+
+impl Timeline {
+    pub fn new(frame_count: usize) -> Self {
+        Self {
+            playhead_position: 0,
+            frame_count,
+            is_playing: false,
+            is_looping: false,
+            is_reversed: false,
+            last_advanced_frame: None,
+        }
+    }
+
+    /// Resumes playback.  A non-looping timeline that already sits at the end of its
+    /// playback direction starts over from the other end.
+    pub fn play(&mut self) {
+        if !self.is_looping {
+            if self.is_reversed && self.playhead_position == 0 {
+                self.playhead_position = self.frame_count;
+            } else if !self.is_reversed && self.playhead_position == self.frame_count {
+                self.playhead_position = 0;
+            }
+        }
+        self.is_playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.is_playing = false;
+    }
+
+    /// Moves the playhead to `frame`, clamped to `[0, frame_count]`, without affecting playback
+    pub fn seek(&mut self, frame: usize) {
+        self.playhead_position = frame.min(self.frame_count);
+    }
+
+    pub fn set_looping(&mut self, is_looping: bool) {
+        self.is_looping = is_looping;
+    }
+
+    /// Flips the direction of playback
+    pub fn reverse(&mut self) {
+        self.is_reversed = !self.is_reversed;
+    }
+
+    /// Steps the playhead by one frame if playing.  Called by the engine for every update of the
+    /// owning component; repeated calls within the same engine frame (`frames_elapsed`) are no-ops.
+    pub fn advance(&mut self, frames_elapsed: usize) {
+        let last_advanced_frame = self.last_advanced_frame.replace(frames_elapsed);
+        if !self.is_playing
+            || self.frame_count == 0
+            || last_advanced_frame.is_none()
+            || last_advanced_frame == Some(frames_elapsed)
+        {
+            return;
+        }
+
+        if self.is_reversed {
+            if self.playhead_position > 0 {
+                self.playhead_position -= 1;
+            } else if self.is_looping {
+                self.playhead_position = self.frame_count - 1;
+            }
+            if self.playhead_position == 0 && !self.is_looping {
+                self.is_playing = false;
+            }
+        } else {
+            self.playhead_position += 1;
+            if self.playhead_position >= self.frame_count {
+                if self.is_looping {
+                    self.playhead_position = 0;
+                } else {
+                    self.playhead_position = self.frame_count;
+                    self.is_playing = false;
+                }
+            }
+        }
+    }
+}
+
+/// The portion of a `PropertyTimeline` that ends at a keyframe, easing into
+/// `ending_value` from the previous keyframe along `curve`
+pub struct TimelineSegment<T> {
+    pub ending_frame_inclusive: usize,
+    pub ending_value: T,
+    pub curve: Rc<EasingCurve>,
+}
+
+impl<T: Clone> Clone for TimelineSegment<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ending_frame_inclusive: self.ending_frame_inclusive,
+            ending_value: self.ending_value.clone(),
+            curve: Rc::clone(&self.curve),
+        }
+    }
+}
+
+/// The Timeline form of a Property: a value described by keyframes, evaluated against the
+/// playhead of the nearest enclosing component's `Timeline`
+#[derive(Clone)]
+pub struct PropertyTimeline<T> {
+    pub starting_frame: usize,
+    pub starting_value: T,
+    pub timeline_segments: Vec<TimelineSegment<T>>,
+    cached_evaluated_value: T,
+}
+
+impl<T: Default + Clone + 'static> From<PropertyTimeline<T>> for Box<dyn PropertyInstance<T>> {
+    fn from(property: PropertyTimeline<T>) -> Self {
+        Box::new(property)
+    }
+}
+
+impl<T: Clone> PropertyTimeline<T> {
+    pub fn new(
+        starting_frame: usize,
+        starting_value: T,
+        timeline_segments: Vec<TimelineSegment<T>>,
+    ) -> Self {
+        PropertyTimeline {
+            starting_frame,
+            cached_evaluated_value: starting_value.clone(),
+            starting_value,
+            timeline_segments,
+        }
+    }
+
+    /// Builds a timeline from `(frame, value, curve)` keyframes, where `curve` eases into that
+    /// keyframe from the previous one.  Keyframes are sorted by frame; the curve of the first
+    /// keyframe is unused.
+    pub fn from_keyframes(mut keyframes: Vec<(usize, T, EasingCurve)>) -> Self {
+        assert!(
+            !keyframes.is_empty(),
+            "Timeline properties must define at least one keyframe."
+        );
+        keyframes.sort_by_key(|(frame, _, _)| *frame);
+        let mut keyframes = keyframes.into_iter();
+        let (starting_frame, starting_value, _) = keyframes.next().unwrap();
+        let timeline_segments = keyframes
+            .map(|(frame, value, curve)| TimelineSegment {
+                ending_frame_inclusive: frame,
+                ending_value: value,
+                curve: Rc::new(curve),
+            })
+            .collect();
+        Self::new(starting_frame, starting_value, timeline_segments)
+    }
+}
+
+impl<T: Interpolatable> PropertyTimeline<T> {
+    /// Recomputes the value of this property for the provided playhead position
+    pub fn evaluate(&mut self, playhead_position: usize) {
+        let mut starting_frame = self.starting_frame;
+        let mut starting_value = &self.starting_value;
+
+        //Scan through our list of timeline segments to find the active segment.  Before the first
+        //keyframe the starting value holds; after the last one, the last keyframe's value holds.
+        for segment in self.timeline_segments.iter() {
+            if playhead_position <= starting_frame {
+                break;
+            }
+            if playhead_position <= segment.ending_frame_inclusive {
+                // How far along the playhead is between starting_frame and the segment's ending
+                // frame: the ratio in [0,1] that our easing curve expects
+                let progress = (playhead_position - starting_frame) as f64
+                    / (segment.ending_frame_inclusive - starting_frame) as f64;
+                self.cached_evaluated_value =
+                    segment
+                        .curve
+                        .interpolate(starting_value, &segment.ending_value, progress);
+                return;
+            }
+            starting_frame = segment.ending_frame_inclusive;
+            starting_value = &segment.ending_value;
+        }
+        self.cached_evaluated_value = starting_value.clone();
+    }
+}
+
+impl<T: Default + Clone + 'static> PropertyInstance<T> for PropertyTimeline<T> {
+    fn get(&self) -> &T {
+        &self.cached_evaluated_value
+    }
+
+    fn get_mut(&mut self) -> &mut T {
+        &mut self.cached_evaluated_value
+    }
+
+    fn _get_vtable_id(&self) -> Option<usize> {
+        None
+    }
+
+    /// Overrides the value until the next evaluation against the playhead
+    fn set(&mut self, value: T) {
+        self.cached_evaluated_value = value;
+    }
+
+    fn _get_transition_manager(&mut self) -> Option<&mut TransitionManager<T>> {
+        None
+    }
+
+    /// Timeline-bound values are driven by their playhead, so they cannot be eased independently;
+    /// control the `Timeline` instead.
+    fn ease_to(&mut self, _new_value: T, _duration_frames: u64, _curve: EasingCurve) {}
+
+    fn ease_to_later(&mut self, _new_value: T, _duration_frames: u64, _curve: EasingCurve) {}
+
+    fn property_type(&self) -> PropertyType {
+        PropertyType::Timeline
+    }
+
+    fn _get_timeline(&self) -> Option<&PropertyTimeline<T>> {
+        Some(self)
+    }
+
+    fn _get_timeline_mut(&mut self) -> Option<&mut PropertyTimeline<T>> {
+        Some(self)
+    }
+}
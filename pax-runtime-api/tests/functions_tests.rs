@@ -0,0 +1,136 @@
+use pax_runtime_api::functions::*;
+use pax_runtime_api::{Color, Numeric, Rotation, Size, StringBox};
+
+#[test]
+fn test_math_and_string_functions() {
+    assert_eq!(clamp(15.into(), 0.into(), 10.into()).to_int(), 10);
+    assert_eq!(lerp(0.into(), 10.into(), 0.25.into()).to_float(), 2.5);
+    assert_eq!(round(Numeric::from(2.5)).to_int(), 3);
+    assert_eq!(upper(StringBox::from("pax")).string, "PAX");
+    assert_eq!(
+        format(
+            StringBox::from("{} of {}: {}"),
+            &[&Numeric::from(2), &StringBox::from("3")]
+        )
+        .string,
+        "2 of 3: {}"
+    );
+    assert_eq!(len(&StringBox::from("héllo")).to_int(), 5);
+    assert_eq!(at(&[1, 2, 3], Numeric::from(-1)), 3);
+    // Out of range, the element type's default
+    assert_eq!(at(&[1, 2, 3], Numeric::from(3)), 0);
+    assert_eq!(at(&[1, 2, 3], Numeric::from(-4)), 0);
+}
+
+#[test]
+fn test_color_functions() {
+    let gray = mix(Color::BLACK, Color::WHITE, 0.5.into()).to_rgba_0_1();
+    assert_eq!(gray, [0.5, 0.5, 0.5, 1.0]);
+    assert_eq!(
+        lighten(Color::BLACK, 1.into()).to_rgba_0_1(),
+        [1.0, 1.0, 1.0, 1.0]
+    );
+    assert_eq!(alpha(Color::WHITE, 0.25.into()).to_rgba_0_1()[3], 0.25);
+}
+
+#[test]
+fn test_builtin_signatures() {
+    let format = get_builtin_function("format").unwrap();
+    assert_eq!(format.param(5), Some(ValueKind::Printable));
+    assert_eq!(get_builtin_function("clamp").unwrap().param(3), None);
+    assert!(ValueKind::Sequence.accepts(ValueKind::String));
+    assert!(!ValueKind::Number.accepts(ValueKind::Color));
+    assert!(get_builtin_function("nope").is_none());
+}
+
+#[derive(Clone)]
+struct Item {
+    visible: bool,
+    count: usize,
+    name: String,
+}
+
+#[test]
+fn test_list_methods_with_lambdas() {
+    let items = vec![
+        Item {
+            visible: true,
+            count: 3,
+            name: "c".to_string(),
+        },
+        Item {
+            visible: false,
+            count: 5,
+            name: "a".to_string(),
+        },
+        Item {
+            visible: true,
+            count: 1,
+            name: "b".to_string(),
+        },
+    ];
+    let min = Numeric::from(2);
+
+    // What the compiler generates for `self.items.filter(|i| i.visible && i.count >= self.min).len()`,
+    // less its clones of `Copy` fields and with escaped names like `iPERIcount` renamed
+    let shown = len(&(filter(&(items), |i| {
+        let i = PaxelValue(i.clone()).get();
+        let i_visible = PaxelValue(i.visible).get();
+        let i_count = PaxelValue(i.count).get();
+        i_visible && (i_count >= min)
+    })));
+    assert_eq!(shown.to_int(), 1);
+
+    let names = map(
+        &sort_by(&items, |i| PaxelValue(i.name.clone()).get()),
+        |i| PaxelValue(i.name.clone()).get(),
+    );
+    assert_eq!(
+        names.iter().map(|n| n.string.as_str()).collect::<Vec<_>>(),
+        ["a", "b", "c"]
+    );
+    let counts = map(&items, |i| PaxelValue(i.count).get());
+    assert_eq!(sum(&counts).to_int(), 9);
+    assert_eq!(sort(&[3, 1, 2]), [1, 2, 3]);
+}
+
+#[test]
+fn test_interpolate_format_specs() {
+    assert_eq!(interpolate(&Numeric::from(3), ""), "3");
+    assert_eq!(interpolate(&Numeric::from(2.5), ".2"), "2.50");
+    assert_eq!(interpolate(&Numeric::from(7), "+04"), "+007");
+    assert_eq!(interpolate(&StringBox::from("ab"), "^6"), "  ab  ");
+    assert_eq!(interpolate(&Numeric::from(-1.25), ">7.1"), "   -1.2");
+    assert_eq!(interpolate(&Size::Pixels(12.into()), ""), "12px");
+    assert_eq!(
+        interpolate(&Size::Combined(10.into(), 50.5.into()), ".0"),
+        "10px + 50%"
+    );
+    assert_eq!(interpolate(&Rotation::Degrees(45.into()), "<8"), "45deg   ");
+}
+
+#[derive(Clone)]
+struct Bar {
+    w: f64,
+}
+
+#[test]
+fn test_sort_by_puts_nan_keys_last() {
+    let bars: Vec<Bar> = [2.0, f64::NAN, 1.0, f64::NAN, 0.5]
+        .into_iter()
+        .map(|w| Bar { w })
+        .collect();
+
+    // What the compiler generates for `self.bars.sort_by(|x| x.w)`, less its clone of the `f64`
+    let sorted = sort_by(&(bars), |x| {
+        let x = PaxelValue((x).clone()).get();
+        PaxelValue(x.w).get()
+    });
+    let widths: Vec<f64> = sorted.iter().map(|bar| bar.w).collect();
+    assert_eq!(widths[..3], [0.5, 1.0, 2.0]);
+    assert!(widths[3..].iter().all(|w| w.is_nan()));
+
+    let sorted = sort(&[Numeric::from(f64::NAN), 3.into(), 1.into()]);
+    assert_eq!(sorted[0].to_float(), 1.0);
+    assert!(sorted[2].to_float().is_nan());
+}
@@ -1,10 +1,14 @@
+use crate::api::math::Point2;
+use crate::node_interface::NodeLocal;
 use crate::RuntimeContext;
 pub use pax_runtime_api::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
 #[cfg(feature = "designtime")]
 use {
-    crate::api::math::Point2, crate::node_interface::NodeInterface,
-    pax_designtime::DesigntimeManager, pax_manifest::UniqueTemplateNodeIdentifier,
-    std::cell::RefCell, std::rc::Rc,
+    crate::node_interface::NodeInterface, pax_designtime::DesigntimeManager,
+    pax_manifest::UniqueTemplateNodeIdentifier,
 };
 
 #[derive(Clone)]
@@ -18,11 +22,98 @@ pub struct NodeContext<'a> {
     pub bounds_self: (f64, f64),
     /// Borrow of the RuntimeContext, used at least for exposing raycasting to userland
     pub(crate) runtime_context: &'a RuntimeContext,
+    /// Id of the expanded component whose handler is running, for looking it up in the node cache
+    pub(crate) node_id: u32,
+    /// The timeline declared by the component whose handler is running, if any.
+    /// Use it to play, pause, seek, loop or reverse that component's keyframed properties.
+    pub timeline: Option<Rc<RefCell<Timeline>>>,
 
     #[cfg(feature = "designtime")]
     pub designtime: Rc<RefCell<DesigntimeManager>>,
 }
 
+impl NodeContext<'_> {
+    /// Converts a point in window space, like the position of a mouse or touch event, into the
+    /// local space of the component whose handler is running. `None` if it has not been laid out.
+    pub fn local_point(&self, point: Point2<Window>) -> Option<Point2<NodeLocal>> {
+        let node = self.runtime_context.node_cache.get(&self.node_id)?;
+        let layout_properties = node.layout_properties.borrow();
+        let tab = &layout_properties.as_ref()?.computed_tab;
+        Some(tab.transform.inverse() * point)
+    }
+
+    /// Whether the node most recently pressed is the component whose handler is running, or
+    /// inside it.  Pressing another node, or unmounting this one, blurs it.
+    pub fn is_focused(&self) -> bool {
+        let (Some(focused), Some(node)) = (
+            self.runtime_context.get_focused_node(),
+            self.runtime_context.node_cache.get(&self.node_id),
+        ) else {
+            return false;
+        };
+        focused.id_chain == node.id_chain || focused.is_descendant_of(&node.id_chain)
+    }
+
+    /// Fires the `@slider_change` handlers bound to the component whose handler is running.
+    pub fn dispatch_slider_change(&self, args: SliderChange) {
+        if let Some(node) = self.runtime_context.node_cache.get(&self.node_id) {
+            node.dispatch_slider_change(args, self.runtime_context.globals(), self.runtime_context);
+        }
+    }
+
+    /// Fires the `@virtual_range_change` handlers bound to the component whose handler is running.
+    pub fn dispatch_virtual_range_change(&self, args: VirtualRangeChange) {
+        if let Some(node) = self.runtime_context.node_cache.get(&self.node_id) {
+            node.dispatch_virtual_range_change(
+                args,
+                self.runtime_context.globals(),
+                self.runtime_context,
+            );
+        }
+    }
+
+    /// Supplies an image from memory, e.g. one generated by the app: `Image`s whose `path` is
+    /// `path` show these `width` x `height` straight RGBA8 pixels, row-major.  Loading again
+    /// under the same `path` replaces the pixels.
+    pub fn load_image(&self, path: &str, width: usize, height: usize, pixels: Vec<u8>) {
+        self.runtime_context
+            .load_image_buffer(path, width, height, pixels);
+    }
+
+    /// The current design tokens, as read by expressions like `{theme.primary}`
+    pub fn theme(&self) -> Rc<Theme> {
+        self.runtime_context.expression_table().get_theme()
+    }
+
+    /// Swaps in another theme, e.g. a dark one: every property bound to one of its tokens
+    /// updates on the next frame
+    pub fn set_theme(&self, theme: Theme) {
+        self.runtime_context.expression_table().set_theme(theme);
+    }
+
+    /// Scrolls every scroll container (e.g. `Scroller`) with the given `id` so that `(x, y)` of
+    /// its content sits at its top-left, stopping any momentum.
+    pub fn scroll_to(&self, id: &str, x: f64, y: f64) {
+        for node in self.runtime_context.get_expanded_nodes_by_id(id) {
+            node.instance_node.borrow().scroll_to(&node, x, y);
+        }
+    }
+
+    /// How far the content of the outermost scroll container (e.g. `Scroller`) inside the
+    /// component whose handler is running has scrolled, as `(x, y)`.  `None` if it has none.
+    pub fn scroll_offset(&self) -> Option<(f64, f64)> {
+        let node = self.runtime_context.node_cache.get(&self.node_id)?;
+        let mut queue = VecDeque::from(node.children.borrow().clone());
+        while let Some(node) = queue.pop_front() {
+            if let Some(offset) = node.get_scroll_offset() {
+                return Some(offset);
+            }
+            queue.extend(node.children.borrow().iter().cloned());
+        }
+        None
+    }
+}
+
 #[cfg(feature = "designtime")]
 impl NodeContext<'_> {
     pub fn raycast(&self, point: Point2<Window>) -> Vec<NodeInterface> {
@@ -1,7 +1,7 @@
+use std::iter;
 use std::rc::Rc;
-use std::{cell::RefCell, iter};
 
-use crate::api::{Layer, Timeline};
+use crate::api::Layer;
 use crate::{
     BaseInstance, ExpandedNode, ExpressionTable, Globals, InstanceFlags, InstanceNode,
     InstanceNodePtrList, InstantiationArgs, RuntimeContext,
@@ -15,7 +15,6 @@ use crate::{
 /// properties attached to each of Repeat's virtual nodes.
 pub struct ComponentInstance {
     pub template: InstanceNodePtrList,
-    pub timeline: Option<Rc<RefCell<Timeline>>>,
     pub compute_properties_fn: Box<dyn Fn(&ExpandedNode, &ExpressionTable, &Globals)>,
     base: BaseInstance,
 }
@@ -45,11 +44,17 @@ impl InstanceNode for ComponentInstance {
             template,
             compute_properties_fn: compute_properties_fn
                 .expect("must pass a compute_properties_fn to a Component instance"),
-            timeline: None,
         })
     }
 
     fn update(self: Rc<Self>, expanded_node: &Rc<ExpandedNode>, context: &mut RuntimeContext) {
+        // Advance this component's timeline, before its template evaluates keyframes against it
+        if let Some(timeline) = &expanded_node.timeline {
+            timeline
+                .borrow_mut()
+                .advance(context.globals().frames_elapsed);
+        }
+
         // Compute properties
         (*self.compute_properties_fn)(
             &expanded_node,
@@ -77,7 +82,10 @@ impl InstanceNode for ComponentInstance {
                 Some(containing_component.create_children_detached(children_with_env, context));
         }
 
-        let new_env = expanded_node.stack.push(&expanded_node.properties.borrow());
+        let new_env = expanded_node.stack.push_with_timeline(
+            &expanded_node.properties.borrow(),
+            expanded_node.timeline.clone(),
+        );
         let children = self.template.borrow();
         let children_with_envs = children.iter().cloned().zip(iter::repeat(new_env));
         expanded_node.set_children(children_with_envs, context);
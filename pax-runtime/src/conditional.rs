@@ -11,6 +11,9 @@ use crate::{
 /// based on the value of the property `boolean_expression`.
 /// The Pax compiler handles ConditionalInstance specially
 /// with the `if` syntax in templates.
+/// An `else if` / `else` clause is compiled into a nested ConditionalInstance,
+/// passed as the last instance child of the conditional it belongs to, so that
+/// exactly one branch of an `if` / `else if` / `else` chain is mounted at a time.
 pub struct ConditionalInstance {
     base: BaseInstance,
 }
@@ -19,6 +22,9 @@ pub struct ConditionalInstance {
 #[derive(Default)]
 pub struct ConditionalProperties {
     pub boolean_expression: Box<dyn crate::api::PropertyInstance<bool>>,
+    /// Whether the last instance child is an `else` clause, mounted only when
+    /// `boolean_expression` is false.
+    pub has_else_branch: bool,
     last_boolean_expression: Option<bool>,
 }
 
@@ -56,14 +62,19 @@ impl InstanceNode for ConditionalInstance {
             });
 
         if should_update {
-            if active {
-                let env = Rc::clone(&expanded_node.stack);
-                let children = self.base().get_instance_children().borrow();
-                let children_with_envs = children.iter().cloned().zip(iter::repeat(env));
-                expanded_node.set_children(children_with_envs, context);
-            } else {
-                expanded_node.set_children(iter::empty(), context);
-            }
+            let has_else_branch = expanded_node.with_properties_unwrapped(
+                |properties: &mut ConditionalProperties| properties.has_else_branch,
+            );
+            let env = Rc::clone(&expanded_node.stack);
+            let children = self.base().get_instance_children().borrow();
+            let branch_children = match (active, has_else_branch) {
+                (true, false) => &children[..],
+                (true, true) => &children[..children.len().saturating_sub(1)],
+                (false, true) => &children[children.len().saturating_sub(1)..],
+                (false, false) => &[],
+            };
+            let children_with_envs = branch_children.iter().cloned().zip(iter::repeat(env));
+            expanded_node.set_children(children_with_envs, context);
         }
     }
 
@@ -7,6 +7,11 @@ pub const KEY_DOWN_HANDLERS: &str = "key_down";
 pub const KEY_UP_HANDLERS: &str = "key_up";
 pub const KEY_PRESS_HANDLERS: &str = "key_press";
 pub const CHECKBOX_CHANGE_HANDLERS: &str = "checkbox_change";
+pub const DROPDOWN_CHANGE_HANDLERS: &str = "dropdown_change";
+pub const SLIDER_CHANGE_HANDLERS: &str = "slider_change";
+pub const VIRTUAL_RANGE_CHANGE_HANDLERS: &str = "virtual_range_change";
+pub const IMAGE_LOAD_HANDLERS: &str = "image_load";
+pub const IMAGE_ERROR_HANDLERS: &str = "image_error";
 pub const BUTTON_CLICK_HANDLERS: &str = "button_click";
 pub const TEXTBOX_CHANGE_HANDLERS: &str = "textbox_change";
 pub const TEXT_INPUT_HANDLERS: &str = "text_input";
@@ -6,7 +6,8 @@ use crate::api::PropertyInstance;
 use crate::{ExpressionTable, Globals, RuntimePropertiesStackFrame};
 
 /// Manages vtable updates (if necessary) for a given `dyn PropertyInstance`.
-/// Is a no-op for `PropertyLiteral`s, and mutates (by calling `.set`) `PropertyExpression` instances.
+/// Is a no-op for `PropertyLiteral`s, mutates (by calling `.set`) `PropertyExpression` instances,
+/// and evaluates `PropertyTimeline`s against the playhead of the timeline in `stack`.
 /// # Examples
 /// ```text
 /// handle_vtable_update!(ptc, self.height, Size);
@@ -31,7 +32,8 @@ pub fn handle_vtable_update<V: Default + Clone + Interpolatable + 'static>(
         table.compute_eased_value(property._get_transition_manager(), globals)
     {
         property.set(new_value);
-    } else {
+    } else if let Some(timeline) = property._get_timeline_mut() {
+        timeline.evaluate(stack.get_timeline_playhead_position());
     }
 }
 
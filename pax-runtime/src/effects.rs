@@ -0,0 +1,78 @@
+use kurbo::{Affine, BezPath, Shape};
+use piet::{Color, FixedGradient, PaintBrush};
+
+/// Number of scaled copies of a shape that a blurred fill is built from
+const BLUR_STEPS: usize = 8;
+
+/// `brush` with the alpha of each of its colors multiplied by `opacity`.  Gradients given in
+/// unit coordinates (`PaintBrush::Linear` / `Radial`) can't be inspected, and are returned as-is.
+pub fn fade_brush(brush: &PaintBrush, opacity: f64) -> PaintBrush {
+    match brush {
+        PaintBrush::Color(color) => PaintBrush::Color(fade_color(color, opacity)),
+        PaintBrush::Fixed(FixedGradient::Linear(linear)) => {
+            let mut linear = linear.clone();
+            for stop in linear.stops.iter_mut() {
+                stop.color = fade_color(&stop.color, opacity);
+            }
+            PaintBrush::Fixed(FixedGradient::Linear(linear))
+        }
+        PaintBrush::Fixed(FixedGradient::Radial(radial)) => {
+            let mut radial = radial.clone();
+            for stop in radial.stops.iter_mut() {
+                stop.color = fade_color(&stop.color, opacity);
+            }
+            PaintBrush::Fixed(FixedGradient::Radial(radial))
+        }
+        brush => brush.clone(),
+    }
+}
+
+fn fade_color(color: &Color, opacity: f64) -> Color {
+    let (r, g, b, a) = color.as_rgba();
+    Color::rgba(r, g, b, a * opacity)
+}
+
+/// The copies of `path` to draw with `brush` for it to look blurred by `radius` px, each paired
+/// with the factor to multiply the alpha of `brush` by; just `path` itself if `radius` is 0
+pub fn blurred_copies(path: BezPath, brush: &PaintBrush, radius: f64) -> Vec<(BezPath, f64)> {
+    if radius <= 0.0 {
+        return vec![(path, 1.0)];
+    }
+    let alpha = match brush {
+        PaintBrush::Color(color) => color.as_rgba().3,
+        _ => 1.0,
+    };
+    blur_layers(&path, radius, alpha)
+}
+
+/// Approximates `path` blurred by `radius` px as copies of it scaled about its center, from
+/// `radius` px larger to `radius` px smaller on each side, each paired with the factor to
+/// multiply the alpha of its brush by.  Filled in order with a brush of alpha `alpha`, the copies
+/// build up from transparent at the outermost edge to `alpha` at the innermost.
+pub fn blur_layers(path: &BezPath, radius: f64, alpha: f64) -> Vec<(BezPath, f64)> {
+    let bbox = path.bounding_box();
+    let center = bbox.center().to_vec2();
+    let scale = |length: f64, offset: f64| {
+        if length > 0.0 {
+            (length + 2.0 * offset) / length
+        } else {
+            1.0
+        }
+    };
+    (0..BLUR_STEPS)
+        .filter_map(|i| {
+            let offset = radius * (1.0 - (2 * i + 1) as f64 / BLUR_STEPS as f64);
+            let scale_x = scale(bbox.width(), offset);
+            let scale_y = scale(bbox.height(), offset);
+            if scale_x <= 0.0 || scale_y <= 0.0 {
+                return None;
+            }
+            let affine = Affine::translate(center)
+                * Affine::scale_non_uniform(scale_x, scale_y)
+                * Affine::translate(-center);
+            // Chosen so that the `i + 1` outermost copies together reach `alpha * (i + 1) / BLUR_STEPS`
+            let factor = 1.0 / (BLUR_STEPS as f64 - alpha * i as f64);
+            Some((affine * path.clone(), factor))
+        })
+        .collect()
+}
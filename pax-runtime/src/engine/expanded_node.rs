@@ -3,30 +3,37 @@ use pax_runtime_api::TextInput;
 use crate::api::math::Point2;
 use crate::constants::{
     BUTTON_CLICK_HANDLERS, CHECKBOX_CHANGE_HANDLERS, CLAP_HANDLERS, CLICK_HANDLERS,
-    CONTEXT_MENU_HANDLERS, DOUBLE_CLICK_HANDLERS, KEY_DOWN_HANDLERS, KEY_PRESS_HANDLERS,
-    KEY_UP_HANDLERS, MOUSE_DOWN_HANDLERS, MOUSE_MOVE_HANDLERS, MOUSE_OUT_HANDLERS,
-    MOUSE_OVER_HANDLERS, MOUSE_UP_HANDLERS, SCROLL_HANDLERS, TEXTBOX_CHANGE_HANDLERS,
+    CONTEXT_MENU_HANDLERS, DOUBLE_CLICK_HANDLERS, DROPDOWN_CHANGE_HANDLERS, IMAGE_ERROR_HANDLERS,
+    IMAGE_LOAD_HANDLERS, KEY_DOWN_HANDLERS, KEY_PRESS_HANDLERS, KEY_UP_HANDLERS,
+    MOUSE_DOWN_HANDLERS, MOUSE_MOVE_HANDLERS, MOUSE_OUT_HANDLERS, MOUSE_OVER_HANDLERS,
+    MOUSE_UP_HANDLERS, SCROLL_HANDLERS, SLIDER_CHANGE_HANDLERS, TEXTBOX_CHANGE_HANDLERS,
     TEXTBOX_INPUT_HANDLERS, TEXT_INPUT_HANDLERS, TOUCH_END_HANDLERS, TOUCH_MOVE_HANDLERS,
-    TOUCH_START_HANDLERS, WHEEL_HANDLERS,
+    TOUCH_START_HANDLERS, VIRTUAL_RANGE_CHANGE_HANDLERS, WHEEL_HANDLERS,
 };
 use crate::{properties, Globals};
 #[cfg(debug_assertions)]
 use core::fmt;
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::rc::{Rc, Weak};
 
 use crate::api::{
     Axis, ButtonClick, CheckboxChange, Clap, Click, CommonProperties, ContextMenu, DoubleClick,
-    Event, KeyDown, KeyPress, KeyUp, MouseDown, MouseMove, MouseOut, MouseOver, MouseUp,
-    NodeContext, RenderContext, Scroll, Size, TextboxChange, TextboxInput, TouchEnd, TouchMove,
-    TouchStart, Wheel, Window,
+    DropdownChange, Event, ImageError, ImageLoad, KeyDown, KeyPress, KeyUp, MouseDown, MouseMove,
+    MouseOut, MouseOver, MouseUp, NodeContext, RenderContext, Scroll, Size, SliderChange,
+    TextboxChange, TextboxInput, Timeline, TouchEnd, TouchMove, TouchStart, VirtualRangeChange,
+    Wheel, Window,
 };
 
+use crate::api::math::{Transform2, Vector2};
+use crate::api::Layer;
+use crate::node_interface::NodeLocal;
 use crate::{
-    compute_tab, ComponentInstance, HandlerLocation, InstanceNode, InstanceNodePtr,
+    compute_tab, ComponentInstance, HandlerLocation, InstanceNode, InstanceNodePtr, Layout,
     PropertiesComputable, RuntimeContext, RuntimePropertiesStackFrame, TransformAndBounds,
 };
+use pax_message::{EffectsPatch, NativeMessage};
 
 pub struct ExpandedNode {
     #[allow(dead_code)]
@@ -66,6 +73,10 @@ pub struct ExpandedNode {
     /// would be part of render pass?
     pub layout_properties: RefCell<Option<LayoutProperties>>,
 
+    /// Set for nodes placed by a layout container (e.g. `Flex`): the box the container assigned
+    /// to this node, which [`compute_tab`] uses in place of the parent's bounds and this node's size.
+    pub layout_slot: RefCell<Option<TransformAndBounds>>,
+
     /// For component instances only, tracks the expanded slot_children in it's
     /// non-collapsed form (repeat and conditionals still present). This allows
     /// repeat/conditionals to update their children (handled in component.rs
@@ -88,6 +99,18 @@ pub struct ExpandedNode {
     /// Occlusion layer for this node. Used by canvas elements to decide what canvas to draw on, and
     /// by native elements to move to the correct native layer.
     pub occlusion_id: RefCell<u32>,
+
+    /// For component instances that declare a `@timeline` only: this node's own copy of it,
+    /// which the component's template is evaluated against and its handlers control.
+    pub timeline: Option<Rc<RefCell<Timeline>>>,
+
+    /// For nodes whose `class` is an expression only: the value it had when the `@settings`
+    /// blocks matching it were last applied
+    applied_classes: RefCell<Option<String>>,
+
+    /// Frame in which `update` was last called on this node's instance, so that a node
+    /// updated ahead of its traversal by a layout container isn't updated twice
+    last_update_frame: Cell<Option<usize>>,
 }
 
 macro_rules! dispatch_event_handler {
@@ -95,11 +118,20 @@ macro_rules! dispatch_event_handler {
         pub fn $fn_name(&self, args: $arg_type, globals: &Globals, ctx: &RuntimeContext) -> bool {
             let event = Event::new(args.clone());
             if let Some(registry) = self.instance_node.borrow().base().get_handler_registry() {
-                let component_properties = if let Some(cc) = self.containing_component.upgrade() {
-                    Rc::clone(&cc.properties.borrow())
-                } else {
-                    Rc::clone(&self.properties.borrow())
-                };
+                let (component_properties, component_timeline, component_id) =
+                    if let Some(cc) = self.containing_component.upgrade() {
+                        (
+                            Rc::clone(&cc.properties.borrow()),
+                            cc.timeline.clone(),
+                            cc.id_chain[0],
+                        )
+                    } else {
+                        (
+                            Rc::clone(&self.properties.borrow()),
+                            self.timeline.clone(),
+                            self.id_chain[0],
+                        )
+                    };
 
                 let comp_props = self.layout_properties.borrow();
                 let bounds_self = comp_props.as_ref().unwrap().computed_tab.bounds;
@@ -118,6 +150,8 @@ macro_rules! dispatch_event_handler {
                     bounds_parent,
                     frames_elapsed: globals.frames_elapsed,
                     runtime_context: ctx,
+                    node_id: component_id,
+                    timeline: None,
                     #[cfg(feature = "designtime")]
                     designtime: globals.designtime.clone(),
                 };
@@ -125,10 +159,24 @@ macro_rules! dispatch_event_handler {
                 let borrowed_registry = &(*registry).borrow();
                 if let Some(handlers) = borrowed_registry.handlers.get($handler_key) {
                     handlers.iter().for_each(|handler| {
-                        let properties = if let HandlerLocation::Component = &handler.location {
-                            Rc::clone(&self.properties.borrow())
-                        } else {
-                            Rc::clone(&component_properties)
+                        let (properties, timeline, node_id) =
+                            if let HandlerLocation::Component = &handler.location {
+                                (
+                                    Rc::clone(&self.properties.borrow()),
+                                    self.timeline.clone(),
+                                    self.id_chain[0],
+                                )
+                            } else {
+                                (
+                                    Rc::clone(&component_properties),
+                                    component_timeline.clone(),
+                                    component_id,
+                                )
+                            };
+                        let context = NodeContext {
+                            timeline,
+                            node_id,
+                            ..context.clone()
                         };
                         (handler.function)(
                             Rc::clone(&properties),
@@ -169,6 +217,11 @@ impl ExpandedNode {
         let common_properties = (&template
             .base()
             .instance_prototypical_common_properties_factory)();
+        let timeline = template
+            .base()
+            .timeline
+            .clone()
+            .map(|timeline| Rc::new(RefCell::new(timeline)));
 
         Rc::new(ExpandedNode {
             id_chain: vec![context.gen_uid().0],
@@ -182,9 +235,13 @@ impl ExpandedNode {
 
             children: RefCell::new(Vec::new()),
             layout_properties: RefCell::new(None),
+            layout_slot: RefCell::new(None),
             expanded_slot_children: Default::default(),
             expanded_and_flattened_slot_children: Default::default(),
             occlusion_id: RefCell::new(0),
+            timeline,
+            applied_classes: RefCell::new(None),
+            last_update_frame: Cell::new(None),
         })
     }
 
@@ -197,6 +254,8 @@ impl ExpandedNode {
             .base()
             .instance_prototypical_common_properties_factory)(
         );
+        *self.applied_classes.borrow_mut() = None;
+        self.last_update_frame.set(None);
     }
 
     /// Returns whether this node is a descendant of the ExpandedNode described by `other_expanded_node_id` (id_chain)
@@ -245,13 +304,21 @@ impl ExpandedNode {
         context: &mut RuntimeContext,
     ) {
         let mut curr_children = self.children.borrow_mut();
-        //TODO here we could probably check intersection between old and new children (to avoid unmount + mount)
         if *self.attached.borrow() > 0 {
+            // Children present both before and after stay mounted, keeping their state
+            let curr_ptrs: HashSet<*const ExpandedNode> =
+                curr_children.iter().map(Rc::as_ptr).collect();
+            let new_ptrs: HashSet<*const ExpandedNode> =
+                new_children.iter().map(Rc::as_ptr).collect();
             for child in curr_children.iter() {
-                Rc::clone(child).recurse_unmount(context);
+                if !new_ptrs.contains(&Rc::as_ptr(child)) {
+                    Rc::clone(child).recurse_unmount(context);
+                }
             }
             for child in new_children.iter() {
-                Rc::clone(child).recurse_mount(context);
+                if !curr_ptrs.contains(&Rc::as_ptr(child)) {
+                    Rc::clone(child).recurse_mount(context);
+                }
             }
         }
         for child in new_children.iter() {
@@ -269,9 +336,37 @@ impl ExpandedNode {
         self.attach_children(new_children, context);
     }
 
+    /// For a node whose `class` is an expression, applies the `@settings` blocks matching its
+    /// current value, if that changed since they were last applied.  A value that isn't a
+    /// string leaves the classes as they were.
+    fn update_classes(&self, context: &RuntimeContext) {
+        let instance_node = self.instance_node.borrow();
+        let Some(bindings) = &instance_node.base().class_bindings else {
+            return;
+        };
+        let value = context
+            .expression_table()
+            .compute_vtable_value(&self.stack, bindings.vtable_id);
+        let Ok(value) = value.downcast::<String>() else {
+            log::warn!("`class` expression evaluated to a non-string value; classes unchanged");
+            return;
+        };
+        if self.applied_classes.borrow().as_ref() == Some(&*value) {
+            return;
+        }
+        let classes: Vec<String> = value.split_whitespace().map(str::to_string).collect();
+        bindings.apply(
+            &classes,
+            &mut *self.properties.borrow().borrow_mut(),
+            &mut self.common_properties.borrow().borrow_mut(),
+        );
+        *self.applied_classes.borrow_mut() = Some(*value);
+    }
+
     /// This method recursively updates all node properties. When dirty-dag exists, this won't
     /// need to be here since all property dependencies can be set up and removed during mount/unmount
     pub fn recurse_update(self: &Rc<Self>, context: &mut RuntimeContext) {
+        self.update_classes(context);
         self.get_common_properties()
             .borrow_mut()
             .compute_properties(&self.stack, context.expression_table(), context.globals());
@@ -282,12 +377,53 @@ impl ExpandedNode {
             .upgrade()
             .and_then(|p| {
                 let props = p.layout_properties.borrow();
-                props.as_ref().map(|c| c.computed_tab.clone())
+                let parent_tab = props.as_ref().map(|c| c.computed_tab.clone())?;
+                Some(match p.get_scroll_offset() {
+                    Some((x, y)) => {
+                        let offset: Transform2 = Transform2::translate(Vector2::new(-x, -y));
+                        TransformAndBounds {
+                            transform: parent_tab.transform * offset.cast_spaces(),
+                            bounds: parent_tab.bounds,
+                        }
+                    }
+                    None => parent_tab,
+                })
             })
             .unwrap_or(context.globals().viewport.clone());
 
+        let parent = self.parent_expanded_node.borrow().upgrade();
+        let (parent_opacity, parent_blur) = parent
+            .as_ref()
+            .and_then(|p| {
+                let props = p.layout_properties.borrow();
+                let props = props.as_ref()?;
+                Some((props.computed_opacity, props.computed_blur))
+            })
+            .unwrap_or((1.0, 0.0));
+        let own_opacity = self
+            .get_common_properties()
+            .borrow()
+            .opacity
+            .as_ref()
+            .map(|o| o.get().to_float())
+            .unwrap_or(1.0);
+        let computed_opacity = (parent_opacity * own_opacity).clamp(0.0, 1.0);
+        // Blurs compose like gaussians: their radii add in quadrature
+        let content_blur = parent
+            .map(|p| p.instance_node.borrow().get_content_blur(&p).max(0.0))
+            .unwrap_or(0.0);
+        let computed_blur = parent_blur.hypot(content_blur);
+        let last_effects = self
+            .layout_properties
+            .borrow()
+            .as_ref()
+            .map(|props| (props.computed_opacity, props.computed_blur))
+            .unwrap_or((1.0, 0.0));
+
         *self.layout_properties.borrow_mut() = Some(LayoutProperties {
             computed_tab: compute_tab(self, &viewport),
+            computed_opacity,
+            computed_blur,
         });
 
         if let Some(ref registry) = self.instance_node.borrow().base().handler_registry {
@@ -304,12 +440,29 @@ impl ExpandedNode {
                 )
             }
         }
-        Rc::clone(&self.instance_node.borrow()).update(&self, context);
+        self.update_instance_once(context);
+
+        let layout = self.instance_node.borrow().get_layout(self);
+        if let Some(layout) = layout {
+            self.layout_children(&layout, context);
+        }
 
         if *self.attached.borrow() > 0 {
             self.instance_node
                 .borrow()
                 .handle_native_patches(self, context);
+            // Native elements aren't drawn by the renderer, so their opacity and blur are sent along to the chassis
+            let is_native = matches!(
+                self.instance_node.borrow().base().flags().layer,
+                Layer::Native
+            );
+            if is_native && (computed_opacity, computed_blur) != last_effects {
+                context.enqueue_native_message(NativeMessage::EffectsUpdate(EffectsPatch {
+                    id_chain: self.id_chain.clone(),
+                    opacity: computed_opacity,
+                    blur: computed_blur,
+                }));
+            }
         }
         if let Some(ref registry) = self.instance_node.borrow().base().handler_registry {
             for handler in registry
@@ -330,9 +483,74 @@ impl ExpandedNode {
         }
     }
 
+    /// Sizes and positions this node's children, with `if`s and `for`s flattened away,
+    /// within this node's bounds according to `layout`
+    fn layout_children(self: &Rc<Self>, layout: &Layout, context: &mut RuntimeContext) {
+        let container_tab = self
+            .layout_properties
+            .borrow()
+            .as_ref()
+            .expect("layout is computed after this node's own tab")
+            .computed_tab
+            .clone();
+        let children = self.flatten_children_for_layout(context);
+        for child in &children {
+            // Sizes are read ahead of the child's own update this frame
+            child
+                .get_common_properties()
+                .borrow_mut()
+                .compute_properties(&child.stack, context.expression_table(), context.globals());
+        }
+        let boxes = layout.compute(container_tab.bounds, &children);
+        for (child, (x, y, width, height)) in children.iter().zip(boxes) {
+            let offset: Transform2 = Transform2::translate(Vector2::new(x, y));
+            let offset = offset.cast_spaces::<NodeLocal, NodeLocal>();
+            *child.layout_slot.borrow_mut() = Some(TransformAndBounds {
+                transform: container_tab.transform * offset,
+                bounds: (width, height),
+            });
+        }
+    }
+
+    /// Calls `update` on this node's instance, unless it was already called this frame
+    fn update_instance_once(self: &Rc<Self>, context: &mut RuntimeContext) {
+        let frame = context.globals().frames_elapsed;
+        if self.last_update_frame.replace(Some(frame)) != Some(frame) {
+            Rc::clone(&self.instance_node.borrow()).update(self, context);
+        }
+    }
+
+    /// Collects the children that a layout container places, looking through slot-invisible
+    /// nodes (`if` and `for`).  These are updated ahead of their usual traversal so that the
+    /// nodes they expand to this frame are laid out this frame; their traversal then skips the update.
+    fn flatten_children_for_layout(
+        self: &Rc<Self>,
+        context: &mut RuntimeContext,
+    ) -> Vec<Rc<ExpandedNode>> {
+        let mut result = vec![];
+        let children = self.children.borrow().clone();
+        for child in children {
+            if child
+                .instance_node
+                .borrow()
+                .base()
+                .flags()
+                .invisible_to_slot
+            {
+                child.update_instance_once(context);
+                result.extend(child.flatten_children_for_layout(context));
+            } else {
+                result.push(child);
+            }
+        }
+        result
+    }
+
     pub fn recurse_mount(self: Rc<Self>, context: &mut RuntimeContext) {
         if *self.attached.borrow() == 0 {
             *self.attached.borrow_mut() += 1;
+            // A node remounted this frame, e.g. by a designtime reload, is due another update
+            self.last_update_frame.set(None);
             context
                 .node_cache
                 .insert(self.id_chain[0], Rc::clone(&self));
@@ -386,6 +604,7 @@ impl ExpandedNode {
         }
         if *self.attached.borrow() == 1 {
             *self.attached.borrow_mut() -= 1;
+            self.last_update_frame.set(None);
             context.node_cache.remove(&self.id_chain[0]);
             let uni = self
                 .instance_node
@@ -399,16 +618,52 @@ impl ExpandedNode {
                 }
             }
             self.instance_node.borrow().handle_unmount(&self, context);
+            // Unmounting the focused node blurs it
+            if context.get_focused_node().is_none() {
+                context.set_focused_node(None);
+            }
         }
     }
 
+    /// Focuses this node, the topmost one pressed, then fires `@mouse_down` on it and its ancestors
+    pub fn dispatch_mouse_down(
+        &self,
+        args: MouseDown,
+        globals: &Globals,
+        ctx: &RuntimeContext,
+    ) -> bool {
+        ctx.set_focused_node(Some(self.id_chain[0]));
+        self.bubble_mouse_down(args, globals, ctx)
+    }
+
+    /// Focuses this node, the topmost one touched, then fires `@touch_start` on it and its ancestors
+    pub fn dispatch_touch_start(
+        &self,
+        args: TouchStart,
+        globals: &Globals,
+        ctx: &RuntimeContext,
+    ) -> bool {
+        ctx.set_focused_node(Some(self.id_chain[0]));
+        self.bubble_touch_start(args, globals, ctx)
+    }
+
     pub fn recurse_render(&self, ctx: &mut RuntimeContext, rcs: &mut dyn RenderContext) {
+        let (opacity, blur) = self
+            .layout_properties
+            .borrow()
+            .as_ref()
+            .map(|props| (props.computed_opacity, props.computed_blur))
+            .unwrap_or((1.0, 0.0));
+        rcs.set_opacity(opacity);
+        rcs.set_blur(blur);
         self.instance_node
             .borrow()
             .handle_pre_render(&self, ctx, rcs);
         for child in self.children.borrow().iter().rev() {
             child.recurse_render(ctx, rcs);
         }
+        rcs.set_opacity(opacity);
+        rcs.set_blur(blur);
         self.instance_node.borrow().render(&self, ctx, rcs);
         self.instance_node
             .borrow()
@@ -468,6 +723,8 @@ impl ExpandedNode {
             bounds_self,
             bounds_parent,
             runtime_context: context,
+            node_id: self.id_chain[0],
+            timeline: self.timeline.clone(),
             #[cfg(feature = "designtime")]
             designtime: globals.designtime.clone(),
         }
@@ -544,9 +801,14 @@ impl ExpandedNode {
 
     /// Returns the scroll offset from a Scroller component
     /// Used by the engine to transform its children
-    pub fn get_scroll_offset(&mut self) -> (f64, f64) {
-        // (0.0, 0.0)
-        todo!("patch into an ExpandedNode-friendly way to track this state");
+    pub fn get_scroll_offset(&self) -> Option<(f64, f64)> {
+        self.instance_node.borrow().get_scroll_offset(self)
+    }
+
+    /// Passes native scroll input to this node, if it is a scroll container.  Its `@scroll`
+    /// handlers fire on the next tick, once its content has moved
+    pub fn scroll(&self, args: Scroll) {
+        self.instance_node.borrow().handle_scroll(self, args);
     }
 
     pub fn compute_flattened_slot_children(&self) {
@@ -558,7 +820,7 @@ impl ExpandedNode {
 
     dispatch_event_handler!(dispatch_scroll, Scroll, SCROLL_HANDLERS, true);
     dispatch_event_handler!(dispatch_clap, Clap, CLAP_HANDLERS, true);
-    dispatch_event_handler!(dispatch_touch_start, TouchStart, TOUCH_START_HANDLERS, true);
+    dispatch_event_handler!(bubble_touch_start, TouchStart, TOUCH_START_HANDLERS, true);
 
     dispatch_event_handler!(dispatch_touch_move, TouchMove, TOUCH_MOVE_HANDLERS, true);
     dispatch_event_handler!(dispatch_touch_end, TouchEnd, TOUCH_END_HANDLERS, true);
@@ -571,6 +833,31 @@ impl ExpandedNode {
         CHECKBOX_CHANGE_HANDLERS,
         true
     );
+    dispatch_event_handler!(
+        dispatch_dropdown_change,
+        DropdownChange,
+        DROPDOWN_CHANGE_HANDLERS,
+        true
+    );
+    dispatch_event_handler!(
+        dispatch_slider_change,
+        SliderChange,
+        SLIDER_CHANGE_HANDLERS,
+        false
+    );
+    dispatch_event_handler!(
+        dispatch_virtual_range_change,
+        VirtualRangeChange,
+        VIRTUAL_RANGE_CHANGE_HANDLERS,
+        false
+    );
+    dispatch_event_handler!(dispatch_image_load, ImageLoad, IMAGE_LOAD_HANDLERS, false);
+    dispatch_event_handler!(
+        dispatch_image_error,
+        ImageError,
+        IMAGE_ERROR_HANDLERS,
+        false
+    );
     dispatch_event_handler!(
         dispatch_textbox_change,
         TextboxChange,
@@ -590,7 +877,7 @@ impl ExpandedNode {
         BUTTON_CLICK_HANDLERS,
         true
     );
-    dispatch_event_handler!(dispatch_mouse_down, MouseDown, MOUSE_DOWN_HANDLERS, true);
+    dispatch_event_handler!(bubble_mouse_down, MouseDown, MOUSE_DOWN_HANDLERS, true);
     dispatch_event_handler!(dispatch_mouse_up, MouseUp, MOUSE_UP_HANDLERS, true);
     dispatch_event_handler!(dispatch_mouse_move, MouseMove, MOUSE_MOVE_HANDLERS, true);
     dispatch_event_handler!(dispatch_mouse_over, MouseOver, MOUSE_OVER_HANDLERS, true);
@@ -616,6 +903,10 @@ impl ExpandedNode {
 pub struct LayoutProperties {
     /// Computed transform and size of this ExpandedNode
     pub computed_tab: TransformAndBounds,
+    /// Opacity of this node multiplied by that of each of its ancestors
+    pub computed_opacity: f64,
+    /// Radius, in px, by which the ancestors of this node blur it
+    pub computed_blur: f64,
 }
 
 /// Given some InstanceNodePtrList, distill away all "slot-invisible" nodes (namely, `if` and `for`)
@@ -1,4 +1,4 @@
-use kurbo::Affine;
+use kurbo::{Affine, Shape};
 use std::any::Any;
 use std::borrow::Borrow;
 use std::cell::RefCell;
@@ -7,14 +7,16 @@ use std::fmt::{Debug, Formatter};
 use std::iter;
 use std::rc::Rc;
 
-use pax_manifest::UniqueTemplateNodeIdentifier;
+use pax_manifest::{TypeId, UniqueTemplateNodeIdentifier};
 use pax_message::{NativeMessage, OcclusionPatch};
 
 use crate::api::{
-    CommonProperties, Interpolatable, KeyDown, KeyPress, KeyUp, Layer, NodeContext,
-    OcclusionLayerGen, RenderContext, TransitionManager,
+    BlendMode, CommonProperties, Interpolatable, KeyDown, KeyPress, KeyUp, Layer, NodeContext,
+    OcclusionLayerGen, RenderContext, Theme, TransitionManager,
 };
-use piet::InterpolationMode;
+use piet::{Image as _, InterpolationMode};
+
+use crate::effects::{blur_layers, blurred_copies, fade_brush};
 
 use crate::declarative_macros::{handle_vtable_update, handle_vtable_update_optional};
 use crate::{
@@ -82,6 +84,17 @@ impl PropertiesComputable for CommonProperties {
         handle_vtable_update_optional(table, stack, self.anchor_y.as_mut(), globals);
         handle_vtable_update_optional(table, stack, self.x.as_mut(), globals);
         handle_vtable_update_optional(table, stack, self.y.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.flex_grow.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.flex_shrink.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.min_width.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.max_width.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.min_height.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.max_height.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.grid_row.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.grid_column.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.grid_row_span.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.grid_column_span.as_mut(), globals);
+        handle_vtable_update_optional(table, stack, self.opacity.as_mut(), globals);
     }
 }
 
@@ -130,6 +143,12 @@ impl Default for HandlerRegistry {
 pub struct Renderer<R: piet::RenderContext> {
     pub backends: HashMap<String, R>,
     pub image_map: HashMap<String, R::Image>,
+    opacity: f64,
+    blur: f64,
+    /// Per layer, sets the blend mode of the backend, which piet has no API for
+    blend_hooks: HashMap<String, Box<dyn FnMut(BlendMode)>>,
+    /// Per layer, sets the alpha that the backend multiplies images by, which piet has no API for
+    alpha_hooks: HashMap<String, Box<dyn FnMut(f64)>>,
 }
 
 impl<R: piet::RenderContext> Renderer<R> {
@@ -137,6 +156,49 @@ impl<R: piet::RenderContext> Renderer<R> {
         Self {
             backends: HashMap::new(),
             image_map: HashMap::new(),
+            opacity: 1.0,
+            blur: 0.0,
+            blend_hooks: HashMap::new(),
+            alpha_hooks: HashMap::new(),
+        }
+    }
+
+    /// Draws the image at `image_path` into `dst_rect` on `layer`, blurred and faded by the current
+    /// blur and opacity.  Layers without an alpha hook draw it opaque.
+    fn draw_image_with_effects(
+        &mut self,
+        layer: &str,
+        image_path: &str,
+        src_rect: Option<kurbo::Rect>,
+        dst_rect: kurbo::Rect,
+    ) {
+        let Some(img) = self.image_map.get(image_path) else {
+            return;
+        };
+        let copies = if self.blur > 0.0 {
+            blur_layers(&dst_rect.to_path(0.1), self.blur, 1.0)
+                .into_iter()
+                .map(|(copy, factor)| (copy.bounding_box(), factor))
+                .collect()
+        } else {
+            vec![(dst_rect, 1.0)]
+        };
+        let backend = self.backends.get_mut(layer).unwrap();
+        let is_faded = self.opacity < 1.0 || copies.len() > 1;
+        let mut alpha_hook = self.alpha_hooks.get_mut(layer).filter(|_| is_faded);
+        for (rect, factor) in copies {
+            if let Some(hook) = alpha_hook.as_mut() {
+                hook(self.opacity * factor);
+            }
+            match src_rect {
+                Some(src_rect) => {
+                    backend.draw_image_area(img, src_rect, rect, InterpolationMode::Bilinear)
+                }
+                None => backend.draw_image(img, rect, InterpolationMode::Bilinear),
+            }
+        }
+        if let Some(hook) = alpha_hook {
+            hook(1.0);
         }
     }
 
@@ -144,8 +206,21 @@ impl<R: piet::RenderContext> Renderer<R> {
         self.backends.insert(id.to_owned(), context);
     }
 
+    /// Lets the layer `id` honor `RenderContext::set_blend_mode`, by calling `hook` with each mode set
+    pub fn set_blend_hook(&mut self, id: &str, hook: impl FnMut(BlendMode) + 'static) {
+        self.blend_hooks.insert(id.to_owned(), Box::new(hook));
+    }
+
+    /// Lets the layer `id` draw images with opacity and blur, by calling `hook` with the alpha
+    /// to multiply each image by, and with `1.0` once it's drawn
+    pub fn set_alpha_hook(&mut self, id: &str, hook: impl FnMut(f64) + 'static) {
+        self.alpha_hooks.insert(id.to_owned(), Box::new(hook));
+    }
+
     pub fn remove_context(&mut self, id: &str) {
         self.backends.remove(id);
+        self.blend_hooks.remove(id);
+        self.alpha_hooks.remove(id);
     }
 
     pub fn image_loaded(&self, path: &str) -> bool {
@@ -155,7 +230,7 @@ impl<R: piet::RenderContext> Renderer<R> {
 
 impl<R: piet::RenderContext> crate::api::RenderContext for Renderer<R> {
     fn fill(&mut self, layer: &str, path: kurbo::BezPath, brush: &piet_common::PaintBrush) {
-        self.backends.get_mut(layer).unwrap().fill(path, brush);
+        self.fill_blurred(layer, path, brush, 0.0);
     }
 
     fn stroke(
@@ -165,10 +240,29 @@ impl<R: piet::RenderContext> crate::api::RenderContext for Renderer<R> {
         brush: &piet_common::PaintBrush,
         width: f64,
     ) {
-        self.backends
-            .get_mut(layer)
-            .unwrap()
-            .stroke(path, brush, width);
+        let backend = self.backends.get_mut(layer).unwrap();
+        for (copy, factor) in blurred_copies(path, brush, self.blur) {
+            backend.stroke(copy, &fade_brush(brush, factor * self.opacity), width);
+        }
+    }
+
+    fn stroke_styled(
+        &mut self,
+        layer: &str,
+        path: kurbo::BezPath,
+        brush: &piet_common::PaintBrush,
+        width: f64,
+        style: &piet::StrokeStyle,
+    ) {
+        let backend = self.backends.get_mut(layer).unwrap();
+        for (copy, factor) in blurred_copies(path, brush, self.blur) {
+            backend.stroke_styled(
+                copy,
+                &fade_brush(brush, factor * self.opacity),
+                width,
+                style,
+            );
+        }
     }
 
     fn save(&mut self, layer: &str) {
@@ -205,13 +299,50 @@ impl<R: piet::RenderContext> crate::api::RenderContext for Renderer<R> {
     }
 
     fn draw_image(&mut self, layer: &str, image_path: &str, rect: kurbo::Rect) {
-        let Some(img) = self.image_map.get(image_path) else {
-            return;
-        };
-        self.backends
-            .get_mut(layer)
-            .unwrap()
-            .draw_image(img, rect, InterpolationMode::Bilinear);
+        self.draw_image_with_effects(layer, image_path, None, rect);
+    }
+
+    fn draw_image_area(
+        &mut self,
+        layer: &str,
+        image_path: &str,
+        src_rect: kurbo::Rect,
+        dst_rect: kurbo::Rect,
+    ) {
+        self.draw_image_with_effects(layer, image_path, Some(src_rect), dst_rect);
+    }
+
+    fn image_size(&self, image_path: &str) -> Option<(f64, f64)> {
+        let size = self.image_map.get(image_path)?.size();
+        Some((size.width, size.height))
+    }
+
+    fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity;
+    }
+
+    fn set_blur(&mut self, radius: f64) {
+        self.blur = radius.max(0.0);
+    }
+
+    fn fill_blurred(
+        &mut self,
+        layer: &str,
+        path: kurbo::BezPath,
+        brush: &piet_common::PaintBrush,
+        radius: f64,
+    ) {
+        let radius = radius.max(0.0).hypot(self.blur);
+        let backend = self.backends.get_mut(layer).unwrap();
+        for (copy, factor) in blurred_copies(path, brush, radius) {
+            backend.fill(copy, &fade_brush(brush, factor * self.opacity));
+        }
+    }
+
+    fn set_blend_mode(&mut self, layer: &str, mode: BlendMode) {
+        if let Some(hook) = self.blend_hooks.get_mut(layer) {
+            hook(mode);
+        }
     }
 
     fn layers(&self) -> Vec<&str> {
@@ -221,6 +352,8 @@ impl<R: piet::RenderContext> crate::api::RenderContext for Renderer<R> {
 
 pub struct ExpressionTable {
     pub table: HashMap<usize, Box<dyn Fn(ExpressionContext) -> Box<dyn Any>>>,
+    /// The design tokens that expressions read as `theme.<token>`
+    pub theme: RefCell<Rc<Theme>>,
 }
 
 #[cfg(debug_assertions)]
@@ -238,13 +371,24 @@ impl ExpressionTable {
     ) -> Box<dyn Any> {
         if let Some(evaluator) = self.table.get(&vtable_id) {
             let stack_frame = Rc::clone(stack);
-            let ec = ExpressionContext { stack_frame };
+            let theme = Rc::clone(&self.theme.borrow());
+            let ec = ExpressionContext { stack_frame, theme };
             (**evaluator)(ec)
         } else {
             panic!() //unhandled error if an invalid id is passed or if vtable is incorrectly initialized
         }
     }
 
+    pub fn get_theme(&self) -> Rc<Theme> {
+        Rc::clone(&self.theme.borrow())
+    }
+
+    /// Replaces the theme, e.g. switching from light to dark; every expression bound to one of
+    /// its tokens picks up the new value when next computed
+    pub fn set_theme(&self, theme: Theme) {
+        *self.theme.borrow_mut() = Rc::new(theme);
+    }
+
     pub fn compute_eased_value<T: Clone + Interpolatable>(
         &self,
         transition_manager: Option<&mut TransitionManager<T>>,
@@ -414,6 +558,58 @@ impl PaxEngine {
         }
     }
 
+    /// Swaps the template of every instance of the component `type_id` for one built by
+    /// `build_template`, then re-expands the templates of its mounted instances.  The components'
+    /// own properties are kept, so their state survives; only the nodes of their templates are rebuilt.
+    pub fn replace_component_template(
+        &mut self,
+        type_id: &TypeId,
+        build_template: impl Fn() -> Vec<Rc<dyn InstanceNode>>,
+    ) {
+        let main_component_instance: Rc<dyn InstanceNode> = self.main_component_instance.clone();
+        recurse_replace_template(&main_component_instance, type_id, &build_template);
+
+        let mut mounted = vec![];
+        self.root_node.recurse_visit_postorder(
+            &|node, mounted: &mut Vec<Rc<ExpandedNode>>| {
+                let instance_node = node.instance_node.borrow();
+                if instance_node.base().component_type_id.as_ref() == Some(type_id) {
+                    mounted.push(Rc::clone(node));
+                }
+            },
+            &mut mounted,
+        );
+        for node in mounted {
+            let instance_node = Rc::clone(&node.instance_node.borrow());
+            let template = instance_node.get_template().unwrap().borrow().clone();
+            let env = node
+                .stack
+                .push_with_timeline(&node.properties.borrow(), node.timeline.clone());
+            node.set_children(
+                template.into_iter().zip(iter::repeat(env)),
+                &mut self.runtime_context,
+            );
+        }
+
+        fn recurse_replace_template(
+            node: &Rc<dyn InstanceNode>,
+            type_id: &TypeId,
+            build_template: &impl Fn() -> Vec<Rc<dyn InstanceNode>>,
+        ) {
+            if let Some(template) = node.get_template() {
+                if node.base().component_type_id.as_ref() == Some(type_id) {
+                    *template.borrow_mut() = build_template();
+                }
+                for child in template.borrow().iter() {
+                    recurse_replace_template(child, type_id, build_template);
+                }
+            }
+            for child in node.base().get_instance_children().borrow().iter() {
+                recurse_replace_template(child, type_id, build_template);
+            }
+        }
+    }
+
     pub fn partial_update_expanded_node(&mut self, new_instance: Rc<dyn InstanceNode>) {
         // update the expanded nodes that just got a new instance node
         let unique_id = new_instance
@@ -490,6 +686,7 @@ impl PaxEngine {
         // This is pretty useful during debugging - left it here since I use it often. /Sam
         // crate::api::log(&format!("tree: {:#?}", self.root_node));
 
+        self.runtime_context.upload_image_buffers(rcs);
         self.root_node
             .recurse_render(&mut self.runtime_context, rcs);
     }
@@ -99,4 +99,8 @@ impl NodeInterface {
     pub fn is_descendant_of(&self, node: &NodeInterface) -> bool {
         self.inner.is_descendant_of(&node.inner.id_chain)
     }
+
+    pub(crate) fn id_chain(&self) -> Vec<u32> {
+        self.inner.id_chain.clone()
+    }
 }
@@ -1,17 +1,26 @@
 use crate::api::math::{Generic, Transform2, Vector2};
-use crate::api::{Axis, Size, Transform2D};
+use crate::api::{Axis, Numeric, PropertyInstance, Size, Transform2D};
 use crate::node_interface::NodeLocal;
 use crate::{ExpandedNode, TransformAndBounds};
+use std::rc::Rc;
 
 /// For the `current_expanded_node` attached to `ptc`, calculates and returns a new [`crate::rendering::TransformAndBounds`] a.k.a. "tab".
 /// Intended as a helper method to be called during properties computation, for creating a new tab to attach to `ptc` for downstream calculations.
 pub fn compute_tab(node: &ExpandedNode, container_tab: &TransformAndBounds) -> TransformAndBounds {
+    //a node placed by a layout container (e.g. `Flex`) is sized and positioned by the slot it was
+    //assigned, in place of its parent's bounds and its own size
+    let layout_slot = node.layout_slot.borrow().clone();
+    let container_tab = layout_slot.as_ref().unwrap_or(container_tab);
+
     //get the size of this node (calc'd or otherwise) and use
     //it as the new accumulated bounds: both for this node's children (their parent container bounds)
     //and for this node itself (e.g. for specifying the size of a Rectangle node)
 
-    let new_accumulated_bounds_and_current_node_size =
-        { node.get_size_computed(container_tab.bounds) };
+    let new_accumulated_bounds_and_current_node_size = if layout_slot.is_some() {
+        container_tab.bounds
+    } else {
+        node.get_size_computed(container_tab.bounds)
+    };
 
     let node_transform_property_computed = {
         node.get_common_properties()
@@ -211,3 +220,512 @@ impl ComputableTransform for Transform2D {
         transform * anchor_transform * previous_transform
     }
 }
+
+/// How a layout container (e.g. `Flex`, `Grid`) places its children, as returned by
+/// [`crate::InstanceNode::get_layout`]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub enum Layout {
+    Flex(FlexLayout),
+    Grid(GridLayout),
+}
+
+impl Layout {
+    /// Solves this layout for `children`, reading their sizing properties, and returns the
+    /// `(x, y, width, height)` box of each child relative to the container
+    pub fn compute(
+        &self,
+        container_bounds: (f64, f64),
+        children: &[Rc<ExpandedNode>],
+    ) -> Vec<(f64, f64, f64, f64)> {
+        match self {
+            Layout::Flex(layout) => {
+                let items: Vec<FlexItem> = children
+                    .iter()
+                    .map(|child| FlexItem::from_expanded_node(child, container_bounds))
+                    .collect();
+                compute_flex_layout(layout, container_bounds, &items)
+            }
+            Layout::Grid(layout) => {
+                let items: Vec<GridItem> = children
+                    .iter()
+                    .map(|child| GridItem::from_expanded_node(child, container_bounds))
+                    .collect();
+                compute_grid_layout(layout, container_bounds, &items)
+            }
+        }
+    }
+}
+
+/// Distribution of a flex line's leftover main-axis space among its items
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Justify {
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+/// Placement of an item along the cross axis of its flex line
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    End,
+    Center,
+    Stretch,
+}
+
+/// Settings of a layout container that places its children in flex lines
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub struct FlexLayout {
+    /// Axis along which items are laid out in a line: `X` for rows, `Y` for columns
+    pub main_axis: Axis,
+    /// Whether items that overflow the main axis start a new line, rather than shrinking
+    pub wrap: bool,
+    pub justify: Justify,
+    pub align: Align,
+    /// Spacing between adjacent items and between adjacent lines, in pixels
+    pub gap: f64,
+}
+
+/// A child of a flex container, with its sizes resolved to pixels
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub struct FlexItem {
+    /// The size the item asks for, before growing or shrinking
+    pub basis: (f64, f64),
+    pub grow: f64,
+    pub shrink: f64,
+    pub min: (f64, f64),
+    pub max: (f64, f64),
+}
+
+impl FlexItem {
+    /// Reads an item's basis and constraints from its common properties, resolving
+    /// percentages against the flex container's bounds
+    pub fn from_expanded_node(node: &ExpandedNode, container_bounds: (f64, f64)) -> Self {
+        let basis = node.get_size_computed(container_bounds);
+        let comm = node.get_common_properties();
+        let comm = comm.borrow();
+        let resolve = |size: &Option<Box<dyn PropertyInstance<Size>>>, axis: Axis, default| {
+            size.as_ref()
+                .map_or(default, |s| s.get().evaluate(container_bounds, axis))
+        };
+        FlexItem {
+            basis,
+            grow: comm.flex_grow.as_ref().map_or(0.0, |g| g.get().to_float()),
+            shrink: comm
+                .flex_shrink
+                .as_ref()
+                .map_or(1.0, |s| s.get().to_float()),
+            min: (
+                resolve(&comm.min_width, Axis::X, 0.0),
+                resolve(&comm.min_height, Axis::Y, 0.0),
+            ),
+            max: (
+                resolve(&comm.max_width, Axis::X, f64::INFINITY),
+                resolve(&comm.max_height, Axis::Y, f64::INFINITY),
+            ),
+        }
+    }
+
+    fn clamp(&self, value: f64, axis: Axis) -> f64 {
+        let (min, max) = match axis {
+            Axis::X => (self.min.0, self.max.0),
+            Axis::Y => (self.min.1, self.max.1),
+        };
+        value.min(max).max(min)
+    }
+}
+
+/// Solves a flex layout: returns the `(x, y, width, height)` box, relative to the container,
+/// of each of `items` in order.
+pub fn compute_flex_layout(
+    layout: &FlexLayout,
+    container_bounds: (f64, f64),
+    items: &[FlexItem],
+) -> Vec<(f64, f64, f64, f64)> {
+    // Work in (main, cross) coordinates, swapping back to (x, y) on the way out
+    let swap = |pair: (f64, f64)| match layout.main_axis {
+        Axis::X => pair,
+        Axis::Y => (pair.1, pair.0),
+    };
+    let main_axis = layout.main_axis;
+    let cross_axis = match main_axis {
+        Axis::X => Axis::Y,
+        Axis::Y => Axis::X,
+    };
+    let (container_main, container_cross) = swap(container_bounds);
+    let hypothetical_main: Vec<f64> = items
+        .iter()
+        .map(|item| item.clamp(swap(item.basis).0, main_axis))
+        .collect();
+
+    // Break items into lines
+    let mut lines: Vec<Vec<usize>> = vec![];
+    let mut line_main = 0.0;
+    for (i, main) in hypothetical_main.iter().enumerate() {
+        match lines.last_mut() {
+            Some(line) if !layout.wrap || line_main + layout.gap + main <= container_main => {
+                line.push(i);
+                line_main += layout.gap + main;
+            }
+            _ => {
+                lines.push(vec![i]);
+                line_main = *main;
+            }
+        }
+    }
+
+    let mut main_sizes = hypothetical_main.clone();
+    let mut cross_sizes: Vec<f64> = items
+        .iter()
+        .map(|item| item.clamp(swap(item.basis).1, cross_axis))
+        .collect();
+    let mut boxes = vec![(0.0, 0.0, 0.0, 0.0); items.len()];
+    let mut line_cross_start = 0.0;
+
+    for line in &lines {
+        resolve_flexible_lengths(layout, container_main, line, items, &mut main_sizes);
+
+        let line_cross = if lines.len() == 1 && !layout.wrap {
+            container_cross
+        } else {
+            line.iter().map(|&i| cross_sizes[i]).fold(0.0, f64::max)
+        };
+        if layout.align == Align::Stretch {
+            for &i in line {
+                cross_sizes[i] = items[i].clamp(line_cross, cross_axis);
+            }
+        }
+
+        let used: f64 =
+            line.iter().map(|&i| main_sizes[i]).sum::<f64>() + layout.gap * (line.len() - 1) as f64;
+        let leftover = (container_main - used).max(0.0);
+        let count = line.len() as f64;
+        let (mut main_pos, spacing) = match layout.justify {
+            Justify::Start => (0.0, 0.0),
+            Justify::End => (leftover, 0.0),
+            Justify::Center => (leftover / 2.0, 0.0),
+            Justify::SpaceBetween if line.len() > 1 => (0.0, leftover / (count - 1.0)),
+            Justify::SpaceBetween => (0.0, 0.0),
+            Justify::SpaceAround => (leftover / count / 2.0, leftover / count),
+            Justify::SpaceEvenly => (leftover / (count + 1.0), leftover / (count + 1.0)),
+        };
+
+        for &i in line {
+            let cross_offset = match layout.align {
+                Align::Start | Align::Stretch => 0.0,
+                Align::End => line_cross - cross_sizes[i],
+                Align::Center => (line_cross - cross_sizes[i]) / 2.0,
+            };
+            let (x, y) = swap((main_pos, line_cross_start + cross_offset));
+            let (width, height) = swap((main_sizes[i], cross_sizes[i]));
+            boxes[i] = (x, y, width, height);
+            main_pos += main_sizes[i] + layout.gap + spacing;
+        }
+        line_cross_start += line_cross + layout.gap;
+    }
+    boxes
+}
+
+/// Grows or shrinks the main sizes of a line's items to fill its container, honoring each
+/// item's min/max constraints: an item clamped by a constraint is frozen at that size and
+/// the remaining space is redistributed among the others.
+fn resolve_flexible_lengths(
+    layout: &FlexLayout,
+    container_main: f64,
+    line: &[usize],
+    items: &[FlexItem],
+    main_sizes: &mut [f64],
+) {
+    let gaps = layout.gap * (line.len() - 1) as f64;
+    let mut frozen = vec![false; items.len()];
+    loop {
+        let free = container_main - gaps - line.iter().map(|&i| main_sizes[i]).sum::<f64>();
+        let unfrozen: Vec<usize> = line.iter().copied().filter(|&i| !frozen[i]).collect();
+        let weights: Vec<f64> = unfrozen
+            .iter()
+            .map(|&i| {
+                if free > 0.0 {
+                    items[i].grow
+                } else {
+                    items[i].shrink * main_sizes[i]
+                }
+            })
+            .collect();
+        let total_weight: f64 = weights.iter().sum();
+        if free.abs() < f64::EPSILON || total_weight <= 0.0 {
+            return;
+        }
+
+        let mut clamped_any = false;
+        for (&i, weight) in unfrozen.iter().zip(weights) {
+            let target = main_sizes[i] + free * weight / total_weight;
+            let clamped = items[i].clamp(target, layout.main_axis);
+            if clamped != target {
+                frozen[i] = true;
+                clamped_any = true;
+            }
+            main_sizes[i] = clamped;
+        }
+        if !clamped_any {
+            return;
+        }
+    }
+}
+
+/// The size of a row or column of a grid
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub enum GridTrack {
+    Fixed(Size),
+    /// A share of the space left over by the other tracks, e.g. `1fr`
+    Fraction(f64),
+    /// As large as the largest item placed in this track alone
+    Auto,
+}
+
+/// Settings of a layout container that places its children in cells of a grid.
+/// Rows and columns beyond those defined are added as needed, sized `Fraction(1.0)`.
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub struct GridLayout {
+    pub columns: Vec<GridTrack>,
+    pub rows: Vec<GridTrack>,
+    /// Spacing between adjacent columns, in pixels
+    pub column_gap: f64,
+    /// Spacing between adjacent rows, in pixels
+    pub row_gap: f64,
+}
+
+/// A child of a grid container.  Items without an explicit row and column fill the
+/// next free cells in order, row by row.
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[derive(Clone)]
+pub struct GridItem {
+    /// The size the item asks for, used to size `Auto` tracks
+    pub basis: (f64, f64),
+    pub column: Option<usize>,
+    pub row: Option<usize>,
+    pub column_span: usize,
+    pub row_span: usize,
+}
+
+impl GridItem {
+    /// Reads an item's basis and placement from its common properties, resolving
+    /// percentages against the grid container's bounds
+    pub fn from_expanded_node(node: &ExpandedNode, container_bounds: (f64, f64)) -> Self {
+        let comm = node.get_common_properties();
+        let comm = comm.borrow();
+        let index = |value: &Option<Box<dyn PropertyInstance<Numeric>>>| {
+            value.as_ref().map(|v| v.get().to_float().max(0.0) as usize)
+        };
+        GridItem {
+            basis: node.get_size_computed(container_bounds),
+            column: index(&comm.grid_column),
+            row: index(&comm.grid_row),
+            column_span: index(&comm.grid_column_span).unwrap_or(1).max(1),
+            row_span: index(&comm.grid_row_span).unwrap_or(1).max(1),
+        }
+    }
+}
+
+/// Solves a grid layout: returns the `(x, y, width, height)` box, relative to the container,
+/// of each of `items` in order.  Items fill the full area of the cells they span.
+pub fn compute_grid_layout(
+    layout: &GridLayout,
+    container_bounds: (f64, f64),
+    items: &[GridItem],
+) -> Vec<(f64, f64, f64, f64)> {
+    let mut column_count = items
+        .iter()
+        .filter_map(|item| item.column.map(|column| column + item.column_span))
+        .chain([layout.columns.len(), 1])
+        .max()
+        .unwrap();
+
+    // Place pinned items first, then fill the remaining cells in order
+    let mut occupied: Vec<Vec<bool>> = vec![];
+    let mut placements = vec![(0, 0, 1, 1); items.len()];
+    let is_free = |occupied: &Vec<Vec<bool>>,
+                   column_count: usize,
+                   row: usize,
+                   column: usize,
+                   item: &GridItem| {
+        (row..row + item.row_span).all(|r| {
+            (column..(column + item.column_span).min(column_count))
+                .all(|c| !occupied.get(r).is_some_and(|cells| cells[c]))
+        })
+    };
+    let mut place = |occupied: &mut Vec<Vec<bool>>,
+                     column_count: usize,
+                     i: usize,
+                     row: usize,
+                     column: usize| {
+        let item = &items[i];
+        let column_span = item.column_span.min(column_count - column);
+        if occupied.len() < row + item.row_span {
+            occupied.resize(row + item.row_span, vec![false; column_count]);
+        }
+        for cells in &mut occupied[row..row + item.row_span] {
+            cells[column..column + column_span].fill(true);
+        }
+        placements[i] = (row, column, item.row_span, column_span);
+    };
+
+    for (i, item) in items.iter().enumerate() {
+        if let (Some(row), Some(column)) = (item.row, item.column) {
+            place(&mut occupied, column_count, i, row, column);
+        }
+    }
+    let mut cursor = (0, 0);
+    for (i, item) in items.iter().enumerate() {
+        match (item.row, item.column) {
+            (Some(_), Some(_)) => {}
+            (None, Some(column)) => {
+                let row = (0..)
+                    .find(|&row| is_free(&occupied, column_count, row, column, item))
+                    .unwrap();
+                place(&mut occupied, column_count, i, row, column);
+            }
+            (Some(row), None) => {
+                let last_column = column_count.saturating_sub(item.column_span);
+                let column = (0..=last_column)
+                    .find(|&column| is_free(&occupied, column_count, row, column, item))
+                    .unwrap_or(column_count);
+                // With no room left in its row, the item goes in implicit columns past the last
+                if column + item.column_span > column_count {
+                    column_count = column + item.column_span;
+                    for cells in &mut occupied {
+                        cells.resize(column_count, false);
+                    }
+                }
+                place(&mut occupied, column_count, i, row, column);
+            }
+            (None, None) => {
+                let span = item.column_span.min(column_count);
+                let (mut row, mut column) = cursor;
+                while column + span > column_count
+                    || !is_free(&occupied, column_count, row, column, item)
+                {
+                    column += 1;
+                    if column + span > column_count {
+                        row += 1;
+                        column = 0;
+                    }
+                }
+                place(&mut occupied, column_count, i, row, column);
+                cursor = (row, column + span);
+            }
+        }
+    }
+
+    let row_count = occupied.len().max(layout.rows.len());
+    let column_spans: Vec<(usize, usize)> = placements.iter().map(|p| (p.1, p.3)).collect();
+    let row_spans: Vec<(usize, usize)> = placements.iter().map(|p| (p.0, p.2)).collect();
+    let (column_offsets, column_sizes) = size_grid_tracks(
+        &layout.columns,
+        column_count,
+        layout.column_gap,
+        container_bounds,
+        Axis::X,
+        items,
+        &column_spans,
+    );
+    let (row_offsets, row_sizes) = size_grid_tracks(
+        &layout.rows,
+        row_count,
+        layout.row_gap,
+        container_bounds,
+        Axis::Y,
+        items,
+        &row_spans,
+    );
+
+    let extent = |sizes: &[f64], gap: f64, start: usize, span: usize| {
+        sizes[start..start + span].iter().sum::<f64>() + gap * (span - 1) as f64
+    };
+    placements
+        .iter()
+        .map(|&(row, column, row_span, column_span)| {
+            (
+                column_offsets[column],
+                row_offsets[row],
+                extent(&column_sizes, layout.column_gap, column, column_span),
+                extent(&row_sizes, layout.row_gap, row, row_span),
+            )
+        })
+        .collect()
+}
+
+/// Resolves the sizes of a grid's tracks along `axis`, given where each item starts and how
+/// many tracks it spans, and returns the offset and size of each track
+fn size_grid_tracks(
+    tracks: &[GridTrack],
+    count: usize,
+    gap: f64,
+    container_bounds: (f64, f64),
+    axis: Axis,
+    items: &[GridItem],
+    spans: &[(usize, usize)],
+) -> (Vec<f64>, Vec<f64>) {
+    let container = match axis {
+        Axis::X => container_bounds.0,
+        Axis::Y => container_bounds.1,
+    };
+    let implicit = GridTrack::Fraction(1.0);
+    let tracks: Vec<&GridTrack> = (0..count)
+        .map(|i| tracks.get(i).unwrap_or(&implicit))
+        .collect();
+
+    let mut sizes: Vec<f64> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| match track {
+            GridTrack::Fixed(size) => size.evaluate(container_bounds, axis),
+            GridTrack::Fraction(_) => 0.0,
+            GridTrack::Auto => items
+                .iter()
+                .zip(spans)
+                .filter(|(_, &(start, span))| start == i && span == 1)
+                .map(|(item, _)| match axis {
+                    Axis::X => item.basis.0,
+                    Axis::Y => item.basis.1,
+                })
+                .fold(0.0, f64::max),
+        })
+        .collect();
+
+    let total_fraction: f64 = tracks
+        .iter()
+        .map(|track| match track {
+            GridTrack::Fraction(fraction) => *fraction,
+            _ => 0.0,
+        })
+        .sum();
+    if total_fraction > 0.0 {
+        let free = (container - sizes.iter().sum::<f64>() - gap * (count - 1) as f64).max(0.0);
+        for (size, track) in sizes.iter_mut().zip(&tracks) {
+            if let GridTrack::Fraction(fraction) = track {
+                *size = free * fraction / total_fraction;
+            }
+        }
+    }
+
+    let mut offset = 0.0;
+    let offsets = sizes
+        .iter()
+        .map(|size| {
+            let start = offset;
+            offset += size + gap;
+            start
+        })
+        .collect();
+    (offsets, sizes)
+}
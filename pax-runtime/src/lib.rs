@@ -5,22 +5,30 @@ pub mod component;
 pub mod conditional;
 pub mod constants;
 pub mod declarative_macros;
+pub mod effects;
 pub mod engine;
 pub mod form_event;
 pub mod layout;
 pub mod numeric;
+pub mod pattern_match;
 pub mod properties;
 pub mod rendering;
 pub mod repeat;
+pub mod scroll;
 pub mod slot;
+#[cfg(not(feature = "designtime"))]
+pub mod testing;
 
 pub use crate::component::*;
 pub use crate::conditional::*;
+pub use crate::effects::*;
 pub use crate::engine::*;
 pub use crate::layout::*;
+pub use crate::pattern_match::*;
 pub use crate::properties::*;
 pub use crate::rendering::*;
 pub use crate::repeat::*;
+pub use crate::scroll::*;
 pub use crate::slot::*;
 
 pub static DEBUG_TEXT_GREEN_BACKGROUND: bool = false;
@@ -0,0 +1,130 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::{iter, rc::Rc};
+
+use crate::api::{Interpolatable, Layer};
+use crate::{
+    declarative_macros::handle_vtable_update, BaseInstance, ExpandedNode, InstanceFlags,
+    InstanceNode, InstantiationArgs, RuntimeContext,
+};
+
+/// A special "control-flow" primitive associated with the `match` statement.
+/// Match renders the subtree of the first arm whose pattern matches a value,
+/// typically a variant of an enum property, like `match self.mode { ... }`.
+/// The arms' subtrees are passed as the instance children of Match, one after the
+/// other, with the number of children of each arm in `arm_lengths`.
+/// The payloads destructured by the active arm are bound by name in a stack frame
+/// (a [`MatchArm`]) pushed for that arm's nodes.
+pub struct MatchInstance {
+    base: BaseInstance,
+}
+
+/// The arm of a `match` selected by its expression, along with the payloads that arm's
+/// pattern destructured, by binding name.  Also serves as the stack frame for the arm's nodes.
+#[derive(Default, Clone)]
+pub struct MatchArm {
+    pub index: Option<usize>,
+    pub bindings: HashMap<String, Rc<RefCell<dyn Any>>>,
+}
+
+impl MatchArm {
+    pub fn new(index: usize, bindings: Vec<(String, Rc<RefCell<dyn Any>>)>) -> Self {
+        Self {
+            index: Some(index),
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl Interpolatable for MatchArm {}
+
+#[derive(Default)]
+pub struct MatchProperties {
+    pub arm_expression: Box<dyn crate::api::PropertyInstance<MatchArm>>,
+    /// Number of instance children in each arm, in order
+    pub arm_lengths: Vec<usize>,
+    last_index: Option<Option<usize>>,
+    /// Frame shared by the nodes of the active arm, updated in place as its payloads change
+    arm_frame: Option<Rc<RefCell<dyn Any>>>,
+}
+
+impl InstanceNode for MatchInstance {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self>
+    where
+        Self: Sized,
+    {
+        Rc::new(Self {
+            base: BaseInstance::new(
+                args,
+                InstanceFlags {
+                    invisible_to_slot: true,
+                    invisible_to_raycasting: true,
+                    layer: Layer::DontCare,
+                    is_component: false,
+                },
+            ),
+        })
+    }
+
+    fn update(self: Rc<Self>, expanded_node: &Rc<ExpandedNode>, context: &mut RuntimeContext) {
+        let changed_arm =
+            expanded_node.with_properties_unwrapped(|properties: &mut MatchProperties| {
+                handle_vtable_update(
+                    context.expression_table(),
+                    &expanded_node.stack,
+                    &mut properties.arm_expression,
+                    context.globals(),
+                );
+                let arm = properties.arm_expression.get().clone();
+                let index = arm.index;
+                let arm_frame = properties
+                    .arm_frame
+                    .get_or_insert_with(|| Rc::new(RefCell::new(MatchArm::default())));
+                *arm_frame.borrow_mut().downcast_mut::<MatchArm>().unwrap() = arm;
+
+                let update_children = properties.last_index != Some(index);
+                properties.last_index = Some(index);
+                update_children.then(|| {
+                    let arm_children = index.map(|index| {
+                        let start: usize = properties.arm_lengths[..index].iter().sum();
+                        start..start + properties.arm_lengths[index]
+                    });
+                    (arm_children, Rc::clone(arm_frame))
+                })
+            });
+
+        if let Some((arm_children, arm_frame)) = changed_arm {
+            let env = expanded_node.stack.push(&arm_frame);
+            let children = self.base().get_instance_children().borrow();
+            let arm_children = arm_children.map(|range| &children[range]).unwrap_or(&[]);
+            let children_with_envs = arm_children.iter().cloned().zip(iter::repeat(env));
+            expanded_node.set_children(children_with_envs, context);
+        }
+    }
+
+    fn handle_mount(&self, _expanded_node: &Rc<ExpandedNode>, _context: &mut RuntimeContext) {
+        // No-op: wait with creating child-nodes until update tick, since the
+        // matched arm has then been evaluated
+    }
+
+    #[cfg(debug_assertions)]
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Match").finish()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+
+    fn get_clipping_size(
+        &self,
+        _expanded_node: &ExpandedNode,
+    ) -> Option<(crate::api::Size, crate::api::Size)> {
+        None
+    }
+}
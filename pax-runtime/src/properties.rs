@@ -1,9 +1,9 @@
 use crate::api::math::Point2;
-use crate::api::Window;
+use crate::api::{RenderContext, Theme, Timeline, Window};
 use crate::numeric::Numeric;
 use pax_manifest::UniqueTemplateNodeIdentifier;
 use pax_message::NativeMessage;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::{any::Any, collections::HashMap};
 
@@ -13,6 +13,16 @@ use crate::{ExpandedNode, ExpressionTable, Globals};
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct Uid(pub u32);
 
+/// Straight RGBA8 pixels, row-major, supplied by the app for an `Image` to show in place of a file
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ImageBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+    /// Whether these pixels have been handed to the renderer since they were loaded
+    uploaded: Cell<bool>,
+}
+
 #[derive(Default)]
 pub struct NodeCache {
     pub lookup: HashMap<u32, ExpandedNode>,
@@ -28,6 +38,9 @@ pub struct RuntimeContext {
     pub z_index_node_cache: Vec<Rc<ExpandedNode>>,
     pub node_cache: HashMap<u32, Rc<ExpandedNode>>,
     pub uni_to_eid: HashMap<UniqueTemplateNodeIdentifier, Vec<u32>>,
+    image_buffers: RefCell<HashMap<String, ImageBuffer>>,
+    /// Id of the node most recently pressed, which keyboard-driven components respond to
+    focused_node: Cell<Option<u32>>,
 }
 
 impl RuntimeContext {
@@ -40,9 +53,21 @@ impl RuntimeContext {
             z_index_node_cache: vec![],
             node_cache: HashMap::default(),
             uni_to_eid: HashMap::default(),
+            image_buffers: Default::default(),
+            focused_node: Cell::new(None),
         }
     }
 
+    /// The node most recently pressed by a mouse or touch, unless it has since been unmounted
+    pub fn get_focused_node(&self) -> Option<Rc<ExpandedNode>> {
+        self.node_cache.get(&self.focused_node.get()?).cloned()
+    }
+
+    /// Moves focus to the node with id `id`, blurring the one focused before; `None` blurs without focusing
+    pub fn set_focused_node(&self, id: Option<u32>) {
+        self.focused_node.set(id);
+    }
+
     /// Finds all ExpandedNodes with the CommonProperty#id matching the provided string
     pub fn get_expanded_nodes_by_id(&self, id: &str) -> Vec<Rc<ExpandedNode>> {
         //v0 limitation: currently an O(n) lookup cost (could be made O(1) with an id->expandednode cache)
@@ -166,12 +191,52 @@ impl RuntimeContext {
     pub fn expression_table(&self) -> &ExpressionTable {
         &self.expression_table
     }
+
+    /// Registers `pixels` (straight RGBA8, row-major) as the image at `path`, replacing any
+    /// loaded before.  Ignored, with a warning, if there are not `width * height` pixels.
+    pub fn load_image_buffer(&self, path: &str, width: usize, height: usize, pixels: Vec<u8>) {
+        if pixels.len() != width * height * 4 {
+            log::warn!(
+                "image buffer {} has {} bytes, expected {} for {}x{} RGBA pixels",
+                path,
+                pixels.len(),
+                width * height * 4,
+                width,
+                height
+            );
+            return;
+        }
+        self.image_buffers.borrow_mut().insert(
+            path.to_owned(),
+            ImageBuffer {
+                width,
+                height,
+                pixels,
+                uploaded: Cell::new(false),
+            },
+        );
+    }
+
+    /// Whether `path` names an image loaded with `load_image_buffer`, rather than one for the chassis to load
+    pub fn has_image_buffer(&self, path: &str) -> bool {
+        self.image_buffers.borrow().contains_key(path)
+    }
+
+    /// Hands the renderer any image buffers it does not have yet
+    pub fn upload_image_buffers(&self, rcs: &mut dyn RenderContext) {
+        for (path, buffer) in self.image_buffers.borrow().iter() {
+            if !buffer.uploaded.get() || rcs.image_size(path).is_none() {
+                rcs.load_image(path, &buffer.pixels, buffer.width, buffer.height);
+                buffer.uploaded.set(true);
+            }
+        }
+    }
 }
 
 /// Data structure for a single frame of our runtime stack, including
 /// a reference to its parent frame and `properties` for
 /// runtime evaluation, e.g. of Expressions.  `RuntimePropertiesStackFrame`s also track
-/// timeline playhead position.
+/// the timeline of the component that pushed them, against which timeline-bound properties are evaluated.
 ///
 /// `Component`s push `RuntimePropertiesStackFrame`s before computing properties and pop them after computing, thus providing a
 /// hierarchical store of node-relevant data that can be bound to symbols in expressions.
@@ -179,6 +244,7 @@ impl RuntimeContext {
 pub struct RuntimePropertiesStackFrame {
     properties: Rc<RefCell<dyn Any>>,
     parent: Option<Rc<RuntimePropertiesStackFrame>>,
+    timeline: Option<Rc<RefCell<Timeline>>>,
 }
 
 impl RuntimePropertiesStackFrame {
@@ -186,13 +252,25 @@ impl RuntimePropertiesStackFrame {
         Rc::new(Self {
             properties,
             parent: None,
+            timeline: None,
         })
     }
 
+    /// Pushes a frame that shares the timeline of this one, e.g. for `Repeat` items
     pub fn push(self: &Rc<Self>, properties: &Rc<RefCell<dyn Any>>) -> Rc<Self> {
+        self.push_with_timeline(properties, self.timeline.clone())
+    }
+
+    /// Pushes a frame for a component's template, bound to that component's own timeline (if any)
+    pub fn push_with_timeline(
+        self: &Rc<Self>,
+        properties: &Rc<RefCell<dyn Any>>,
+        timeline: Option<Rc<RefCell<Timeline>>>,
+    ) -> Rc<Self> {
         Rc::new(RuntimePropertiesStackFrame {
             parent: Some(Rc::clone(&self)),
             properties: Rc::clone(properties),
+            timeline,
         })
     }
 
@@ -214,6 +292,15 @@ impl RuntimePropertiesStackFrame {
     pub fn get_properties(&self) -> Rc<RefCell<dyn Any>> {
         Rc::clone(&self.properties)
     }
+
+    /// Playhead position against which timeline-bound properties in this frame are evaluated;
+    /// `0` when the enclosing component doesn't declare a timeline
+    pub fn get_timeline_playhead_position(&self) -> usize {
+        self.timeline
+            .as_ref()
+            .map(|timeline| timeline.borrow().playhead_position)
+            .unwrap_or(0)
+    }
 }
 
 pub fn get_numeric_from_wrapped_properties(wrapped: Rc<RefCell<dyn Any>>) -> Numeric {
@@ -256,4 +343,6 @@ pub fn get_numeric_from_wrapped_properties(wrapped: Rc<RefCell<dyn Any>>) -> Num
 #[cfg_attr(debug_assertions, derive(Debug))]
 pub struct ExpressionContext {
     pub stack_frame: Rc<RuntimePropertiesStackFrame>,
+    /// Resolves the `theme.<token>`s in expressions
+    pub theme: Rc<Theme>,
 }
@@ -8,12 +8,12 @@ use std::rc::Rc;
 use crate::api::math::Transform2;
 use crate::api::{CommonProperties, RenderContext, Window};
 use crate::node_interface::NodeLocal;
-use pax_manifest::UniqueTemplateNodeIdentifier;
+use pax_manifest::{TypeId, UniqueTemplateNodeIdentifier};
 use piet::{Color, StrokeStyle};
 
-use crate::api::{Layer, Scroll, Size};
+use crate::api::{Layer, Scroll, Size, Timeline};
 
-use crate::{ExpandedNode, ExpressionTable, Globals, HandlerRegistry, RuntimeContext};
+use crate::{ExpandedNode, ExpressionTable, Globals, HandlerRegistry, Layout, RuntimeContext};
 
 /// Type aliases to make it easier to work with nested Rcs and
 /// RefCells for instance nodes.
@@ -32,6 +32,50 @@ pub struct InstantiationArgs {
     pub compute_properties_fn: Option<Box<dyn Fn(&ExpandedNode, &ExpressionTable, &Globals)>>,
 
     pub template_node_identifier: Option<UniqueTemplateNodeIdentifier>,
+
+    ///used by Component instances that declare a `@timeline`; each expanded component
+    ///plays its own copy of it
+    pub timeline: Option<Timeline>,
+
+    ///used by Component instances, to find the instances of a component whose template
+    ///is swapped at runtime (see [`crate::PaxEngine::replace_component_template`])
+    pub component_type_id: Option<TypeId>,
+
+    ///used by nodes whose `class` is an expression, to reapply the `@settings` blocks
+    ///matching its value whenever that changes
+    pub class_bindings: Option<ClassBindings>,
+}
+
+/// Sets some of a node's properties and common properties, e.g. those of one `@settings` block
+pub type PropertiesPatch = Box<dyn Fn(&mut dyn Any, &mut CommonProperties)>;
+
+/// The `@settings` blocks that may apply to a node whose `class` is an expression,
+/// e.g. `<Rectangle class={self.is_active ? "active" : "inactive"} />`
+pub struct ClassBindings {
+    /// Vtable id of the `class` expression, which evaluates to whitespace-separated class names
+    pub vtable_id: usize,
+    /// Returns every property set by any of `bundles` to its value without them
+    pub reset: PropertiesPatch,
+    /// From the least to the most specific block, each with the classes the node must have
+    /// for it to apply
+    pub bundles: Vec<(Vec<String>, PropertiesPatch)>,
+}
+
+impl ClassBindings {
+    /// Applies the blocks matching `classes`, undoing those applied for any previous classes
+    pub fn apply(
+        &self,
+        classes: &[String],
+        properties: &mut dyn Any,
+        common_properties: &mut CommonProperties,
+    ) {
+        (self.reset)(properties, common_properties);
+        for (required, patch) in &self.bundles {
+            if required.iter().all(|class| classes.contains(class)) {
+                patch(properties, common_properties);
+            }
+        }
+    }
 }
 
 /// Stores the computed transform and the pre-transform bounding box (where the
@@ -152,6 +196,20 @@ pub trait InstanceNode {
         None
     }
 
+    /// Radius, in px, by which everything this node contains is blurred, e.g. by a `Frame`
+    #[allow(unused_variables)]
+    fn get_content_blur(&self, expanded_node: &ExpandedNode) -> f64 {
+        0.0
+    }
+
+    /// Layout containers (e.g. `Flex`, `Grid`) return how they place their children.  The engine then assigns
+    /// each child (with `if`s and `for`s flattened away) a box within this node's bounds, which the
+    /// child's [`crate::layout::compute_tab`] uses in place of its own size and position.
+    #[allow(unused_variables)]
+    fn get_layout(&self, expanded_node: &ExpandedNode) -> Option<Layout> {
+        None
+    }
+
     #[cfg(debug_assertions)]
     fn resolve_debug(
         &self,
@@ -236,7 +294,20 @@ pub trait InstanceNode {
     }
     /// Invoked by event interrupts to pass scroll information to render node
     #[allow(unused_variables)]
-    fn handle_scroll(&self, args_scroll: Scroll) {
+    fn handle_scroll(&self, expanded_node: &ExpandedNode, args_scroll: Scroll) {
+        //no-op default implementation
+    }
+
+    /// Scroll containers (e.g. `Scroller`) return how far their content is scrolled, in pixels.
+    /// The engine translates this node's children by the negated offset when computing their layout.
+    #[allow(unused_variables)]
+    fn get_scroll_offset(&self, expanded_node: &ExpandedNode) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// Programmatically scrolls a scroll container so that `(x, y)` of its content sits at its top-left
+    #[allow(unused_variables)]
+    fn scroll_to(&self, expanded_node: &ExpandedNode, x: f64, y: f64) {
         //no-op default implementation
     }
 
@@ -251,6 +322,9 @@ pub struct BaseInstance {
     pub instance_prototypical_common_properties_factory:
         Box<dyn Fn() -> Rc<RefCell<CommonProperties>>>,
     pub template_node_identifier: Option<UniqueTemplateNodeIdentifier>,
+    pub timeline: Option<Timeline>,
+    pub component_type_id: Option<TypeId>,
+    pub class_bindings: Option<ClassBindings>,
     instance_children: InstanceNodePtrList,
     flags: InstanceFlags,
 }
@@ -283,6 +357,9 @@ impl BaseInstance {
             instance_children: args.children.unwrap_or_default(),
             flags,
             template_node_identifier: args.template_node_identifier,
+            timeline: args.timeline,
+            component_type_id: args.component_type_id,
+            class_bindings: args.class_bindings,
         }
     }
 
@@ -1,9 +1,10 @@
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::iter;
 use std::rc::Rc;
 
-use crate::api::Layer;
+use crate::api::{Layer, Numeric, StringBox};
 use crate::declarative_macros::handle_vtable_update_optional;
 use crate::{
     BaseInstance, ExpandedNode, InstanceFlags, InstanceNode, InstantiationArgs, RuntimeContext,
@@ -13,7 +14,11 @@ use crate::{
 /// Repeat allows for nodes to be rendered dynamically per data specified in `source_expression`.
 /// That is: for a `source_expression` of length `n`, `Repeat` will render its
 /// template `n` times, each with an embedded component context (`RepeatItem`)
-/// with an index `i` and a pointer to that relevant datum `source_expression[i]`
+/// with an index `i` and a pointer to that relevant datum `source_expression[i]`.
+/// An un-keyed `for` keeps the expanded nodes of its leading elements when `source_expression`
+/// changes, pointing each at its new datum, so that a sliding window over a larger list recycles them.
+/// A keyed `for` (`for item in self.items key item.id`) retains the expanded nodes of
+/// elements whose key persists when `source_expression` changes, reordering them if moved.
 pub struct RepeatInstance {
     pub base: BaseInstance,
 }
@@ -26,8 +31,14 @@ pub struct RepeatProperties {
         Option<Box<dyn crate::api::PropertyInstance<Vec<Rc<RefCell<dyn Any>>>>>>,
     pub source_expression_range:
         Option<Box<dyn crate::api::PropertyInstance<std::ops::Range<isize>>>>,
+    /// Vtable entry computing the key of an element, for a keyed `for`
+    pub key_vtable_id: Option<usize>,
     last_len: usize,
     last_bounds: (f64, f64),
+    /// For an un-keyed `for`: the stack frame of each expanded element, in order
+    last_items: Vec<Rc<RefCell<dyn Any>>>,
+    /// For a keyed `for`: the key and stack frame of each expanded element, in order
+    last_keyed_items: Vec<(RepeatKey, Rc<RefCell<dyn Any>>)>,
 }
 
 pub struct RepeatItem {
@@ -35,6 +46,46 @@ pub struct RepeatItem {
     pub i: usize,
 }
 
+/// The identity of an element of a keyed `for`, as computed by its key expression
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum RepeatKey {
+    Integer(isize),
+    /// Bit pattern of a float key
+    Float(u64),
+    String(String),
+    Bool(bool),
+    /// Position of an element whose key expression didn't compute a key, so that it's matched
+    /// by position, as in an un-keyed `for`
+    Index(usize),
+}
+
+impl From<Numeric> for RepeatKey {
+    fn from(value: Numeric) -> Self {
+        match value {
+            Numeric::Integer(i) => RepeatKey::Integer(i),
+            Numeric::Float(f) => RepeatKey::Float(f.to_bits()),
+        }
+    }
+}
+
+impl From<StringBox> for RepeatKey {
+    fn from(value: StringBox) -> Self {
+        RepeatKey::String(value.string)
+    }
+}
+
+impl From<String> for RepeatKey {
+    fn from(value: String) -> Self {
+        RepeatKey::String(value)
+    }
+}
+
+impl From<bool> for RepeatKey {
+    fn from(value: bool) -> Self {
+        RepeatKey::Bool(value)
+    }
+}
+
 impl InstanceNode for RepeatInstance {
     fn instantiate(args: InstantiationArgs) -> Rc<Self>
     where
@@ -104,32 +155,32 @@ impl InstanceNode for RepeatInstance {
                     .as_ref()
                     .map(|t| t.computed_tab.bounds)
                     .unwrap_or_default();
-                let update_children =
-                    current_len != properties.last_len || current_bounds != properties.last_bounds;
+                // A keyed `for` compares the keys of its elements instead
+                let update_children = properties.key_vtable_id.is_some()
+                    || current_len != properties.last_len
+                    || current_bounds != properties.last_bounds;
+                if current_bounds != properties.last_bounds {
+                    properties.last_items.clear();
+                }
 
                 properties.last_len = current_len;
                 properties.last_bounds = current_bounds;
-                update_children.then_some(vec)
+                if update_children {
+                    Some((vec, properties.key_vtable_id))
+                } else {
+                    // Same length: recycle the expanded nodes, refreshing the datum each one sees
+                    for (frame, elem) in properties.last_items.iter().zip(vec) {
+                        let mut frame_borrowed = frame.borrow_mut();
+                        frame_borrowed.downcast_mut::<RepeatItem>().unwrap().elem = elem;
+                    }
+                    None
+                }
             });
 
-        if let Some(vec) = new_vec {
-            let template_children = self.base().get_instance_children();
-            let children_with_envs = iter::repeat(template_children)
-                .zip(vec.into_iter())
-                .enumerate()
-                .flat_map(|(i, (children, elem))| {
-                    let new_repeat_item = Rc::new(RefCell::new(RepeatItem {
-                        i,
-                        elem: Rc::clone(&elem),
-                    })) as Rc<RefCell<dyn Any>>;
-                    let new_env = expanded_node.stack.push(&new_repeat_item);
-                    children
-                        .borrow()
-                        .clone()
-                        .into_iter()
-                        .zip(iter::repeat(new_env))
-                });
-            expanded_node.set_children(children_with_envs, context);
+        if let Some((vec, Some(key_vtable_id))) = new_vec {
+            self.update_keyed_children(expanded_node, vec, key_vtable_id, context);
+        } else if let Some((vec, None)) = new_vec {
+            self.update_unkeyed_children(expanded_node, vec, context);
         }
     }
 
@@ -138,3 +189,165 @@ impl InstanceNode for RepeatInstance {
         // condition has then been evaluated
     }
 }
+
+impl RepeatInstance {
+    /// Resizes the children of an un-keyed `for` to `elems`.  The nodes of the leading elements
+    /// that were already expanded are kept and pointed at their new datum; only the nodes of
+    /// elements past the previous length are mounted, or those past the new length unmounted.
+    fn update_unkeyed_children(
+        &self,
+        expanded_node: &Rc<ExpandedNode>,
+        elems: impl Iterator<Item = Rc<RefCell<dyn Any>>>,
+        context: &mut RuntimeContext,
+    ) {
+        let template_children = self.base().get_instance_children().borrow().clone();
+        let last_items =
+            expanded_node.with_properties_unwrapped(|properties: &mut RepeatProperties| {
+                std::mem::take(&mut properties.last_items)
+            });
+
+        let curr_children = expanded_node.children.borrow().clone();
+        let retained: Vec<_> = if !template_children.is_empty()
+            && curr_children.len() == last_items.len() * template_children.len()
+        {
+            last_items
+                .into_iter()
+                .zip(curr_children.chunks(template_children.len()))
+                .collect()
+        } else {
+            vec![]
+        };
+        let mut retained = retained.into_iter();
+
+        let mut items = Vec::new();
+        let mut new_children = Vec::new();
+        for (i, elem) in elems.enumerate() {
+            if let Some((frame, nodes)) = retained.next() {
+                frame
+                    .borrow_mut()
+                    .downcast_mut::<RepeatItem>()
+                    .unwrap()
+                    .elem = elem;
+                new_children.extend(nodes.iter().cloned());
+                items.push(frame);
+            } else {
+                let new_repeat_item =
+                    Rc::new(RefCell::new(RepeatItem { i, elem })) as Rc<RefCell<dyn Any>>;
+                let new_env = expanded_node.stack.push(&new_repeat_item);
+                let children_with_envs =
+                    template_children.iter().cloned().zip(iter::repeat(new_env));
+                new_children
+                    .extend(expanded_node.create_children_detached(children_with_envs, context));
+                items.push(new_repeat_item);
+            }
+        }
+
+        expanded_node.attach_children(new_children, context);
+        expanded_node.with_properties_unwrapped(|properties: &mut RepeatProperties| {
+            properties.last_items = items;
+        });
+    }
+
+    /// Reconciles the children of a keyed `for` with `elems`.  While the keys of `elems` are those
+    /// last expanded, in the same order, each element's stack frame is just pointed at its new
+    /// datum.  Otherwise the nodes of an element whose key was already expanded are kept, in the
+    /// element's new position; only the nodes of inserted and removed elements are mounted or unmounted.
+    fn update_keyed_children(
+        &self,
+        expanded_node: &Rc<ExpandedNode>,
+        elems: impl Iterator<Item = Rc<RefCell<dyn Any>>>,
+        key_vtable_id: usize,
+        context: &mut RuntimeContext,
+    ) {
+        let template_children = self.base().get_instance_children().borrow().clone();
+        let last_keyed_items =
+            expanded_node.with_properties_unwrapped(|properties: &mut RepeatProperties| {
+                std::mem::take(&mut properties.last_keyed_items)
+            });
+
+        let keyed_elems: Vec<_> = elems
+            .enumerate()
+            .map(|(i, elem)| {
+                let new_repeat_item = Rc::new(RefCell::new(RepeatItem {
+                    i,
+                    elem: Rc::clone(&elem),
+                })) as Rc<RefCell<dyn Any>>;
+                let new_env = expanded_node.stack.push(&new_repeat_item);
+                let key = match context
+                    .expression_table()
+                    .compute_vtable_value(&new_env, key_vtable_id)
+                    .downcast::<RepeatKey>()
+                {
+                    Ok(key) => *key,
+                    Err(_) => {
+                        log::warn!(
+                            "key of keyed for element {} has an unexpected type; using its index",
+                            i
+                        );
+                        RepeatKey::Index(i)
+                    }
+                };
+                (key, elem, new_repeat_item, new_env)
+            })
+            .collect();
+
+        let curr_children = expanded_node.children.borrow().clone();
+        let children_match_items =
+            curr_children.len() == last_keyed_items.len() * template_children.len();
+        let keys_unchanged = last_keyed_items.len() == keyed_elems.len()
+            && last_keyed_items
+                .iter()
+                .zip(&keyed_elems)
+                .all(|((last_key, _), (key, ..))| last_key == key);
+        if children_match_items && keys_unchanged {
+            for ((_, frame), (_, elem, ..)) in last_keyed_items.iter().zip(keyed_elems) {
+                frame
+                    .borrow_mut()
+                    .downcast_mut::<RepeatItem>()
+                    .unwrap()
+                    .elem = elem;
+            }
+            expanded_node.with_properties_unwrapped(|properties: &mut RepeatProperties| {
+                properties.last_keyed_items = last_keyed_items;
+            });
+            return;
+        }
+
+        let mut retained = HashMap::new();
+        if !template_children.is_empty() && children_match_items {
+            for ((key, frame), nodes) in last_keyed_items
+                .into_iter()
+                .zip(curr_children.chunks(template_children.len()))
+            {
+                retained.insert(key, (frame, nodes));
+            }
+        }
+
+        let mut keyed_items = Vec::new();
+        let mut new_children = Vec::new();
+        for (i, (key, elem, new_repeat_item, new_env)) in keyed_elems.into_iter().enumerate() {
+            // A key repeated within the list gets fresh nodes for each repetition
+            if let Some((frame, nodes)) = retained.remove(&key) {
+                {
+                    let mut frame_borrowed = frame.borrow_mut();
+                    let repeat_item = frame_borrowed.downcast_mut::<RepeatItem>().unwrap();
+                    repeat_item.i = i;
+                    repeat_item.elem = elem;
+                }
+                new_children.extend(nodes.iter().cloned());
+                keyed_items.push((key, frame));
+            } else {
+                let children_with_envs =
+                    template_children.iter().cloned().zip(iter::repeat(new_env));
+                new_children
+                    .extend(expanded_node.create_children_detached(children_with_envs, context));
+                keyed_items.push((key, new_repeat_item));
+            }
+        }
+
+        expanded_node.attach_children(new_children, context);
+        expanded_node.with_properties_unwrapped(|properties: &mut RepeatProperties| {
+            properties.last_keyed_items = keyed_items;
+        });
+    }
+}
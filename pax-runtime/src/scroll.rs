@@ -0,0 +1,122 @@
+/// Fraction of its velocity that a coasting scroll keeps from one tick to the next
+const MOMENTUM_FRICTION: f64 = 0.92;
+
+/// Speed, in pixels per tick, below which a coasting scroll comes to rest
+const MOMENTUM_MIN_VELOCITY: f64 = 0.5;
+
+/// Scroll position of a scroll container (e.g. `Scroller`), kept by the engine.
+///
+/// Chassis report scrolling as deltas (see [`ScrollState::scroll_by`]).  When they stop arriving,
+/// the content keeps coasting at its last velocity, slowing by [`MOMENTUM_FRICTION`] each tick.
+/// Offsets are measured from the top-left of the content, and clamped so that the content
+/// never scrolls past its edges.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct ScrollState {
+    offset: (f64, f64),
+    velocity: (f64, f64),
+    max_offset: (f64, f64),
+    enabled: (bool, bool),
+    /// Whether `scroll_by` was called since the last tick; coasting waits until input stops
+    received_input: bool,
+    /// Whether the offset moved other than by native input since last taken, see `take_pending_sync`
+    pending_sync: bool,
+    /// Offset as of the last `take_movement`
+    reported_offset: (f64, f64),
+}
+
+impl ScrollState {
+    /// Sets the size of the visible area and of the scrollable content, and which axes scroll.
+    /// Re-clamps the current offset to the new extent.
+    pub fn set_extent(&mut self, visible: (f64, f64), content: (f64, f64), enabled: (bool, bool)) {
+        self.max_offset = (
+            (content.0 - visible.0).max(0.0),
+            (content.1 - visible.1).max(0.0),
+        );
+        self.enabled = enabled;
+        let clamped = self.clamp(self.offset);
+        if clamped != self.offset {
+            self.offset = clamped;
+            self.pending_sync = true;
+        }
+    }
+
+    /// Applies native scroll input: positive deltas scroll towards the bottom-right of the content
+    pub fn scroll_by(&mut self, delta_x: f64, delta_y: f64) {
+        let delta = (
+            if self.enabled.0 { delta_x } else { 0.0 },
+            if self.enabled.1 { delta_y } else { 0.0 },
+        );
+        let target = (self.offset.0 + delta.0, self.offset.1 + delta.1);
+        let clamped = self.clamp(target);
+        self.velocity = (
+            if clamped.0 == target.0 { delta.0 } else { 0.0 },
+            if clamped.1 == target.1 { delta.1 } else { 0.0 },
+        );
+        self.offset = clamped;
+        self.received_input = true;
+    }
+
+    /// Jumps to `(x, y)`, clamped to the content, stopping any momentum
+    pub fn scroll_to(&mut self, x: f64, y: f64) {
+        self.offset = self.clamp((x, y));
+        self.velocity = (0.0, 0.0);
+        self.pending_sync = true;
+    }
+
+    /// Advances momentum by one frame, returning whether the offset moved
+    pub fn tick(&mut self) -> bool {
+        if std::mem::take(&mut self.received_input) {
+            return false;
+        }
+        if self.velocity.0.abs() < MOMENTUM_MIN_VELOCITY
+            && self.velocity.1.abs() < MOMENTUM_MIN_VELOCITY
+        {
+            self.velocity = (0.0, 0.0);
+            return false;
+        }
+        self.velocity = (
+            self.velocity.0 * MOMENTUM_FRICTION,
+            self.velocity.1 * MOMENTUM_FRICTION,
+        );
+        let target = (
+            self.offset.0 + self.velocity.0,
+            self.offset.1 + self.velocity.1,
+        );
+        let clamped = self.clamp(target);
+        if clamped.0 != target.0 {
+            self.velocity.0 = 0.0;
+        }
+        if clamped.1 != target.1 {
+            self.velocity.1 = 0.0;
+        }
+        let moved = clamped != self.offset;
+        self.offset = clamped;
+        self.pending_sync |= moved;
+        moved
+    }
+
+    /// Current offset of the content, in pixels
+    pub fn offset(&self) -> (f64, f64) {
+        self.offset
+    }
+
+    /// Returns the offset if it has moved other than by native input (momentum, `scroll_to`, or a
+    /// shrinking extent) since the last call, so that a chassis can catch its native scroll position up
+    pub fn take_pending_sync(&mut self) -> Option<(f64, f64)> {
+        std::mem::take(&mut self.pending_sync).then_some(self.offset)
+    }
+
+    /// Returns how far the content moved, by any means, since the last call; `None` if it did not
+    pub fn take_movement(&mut self) -> Option<(f64, f64)> {
+        let last = std::mem::replace(&mut self.reported_offset, self.offset);
+        (last != self.offset).then_some((self.offset.0 - last.0, self.offset.1 - last.1))
+    }
+
+    fn clamp(&self, offset: (f64, f64)) -> (f64, f64) {
+        (
+            offset.0.clamp(0.0, self.max_offset.0),
+            offset.1.clamp(0.0, self.max_offset.1),
+        )
+    }
+}
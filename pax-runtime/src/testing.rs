@@ -0,0 +1,348 @@
+//! In-process harness for exercising Pax components without a chassis.
+//!
+//! A [`TestHarness`] owns a [`PaxEngine`], advances it frame by frame, and accepts the same
+//! [`NativeInterrupt`]s a chassis would send -- so that event handlers and `@tick` logic can be
+//! covered by plain `cargo test`s, asserting on node properties and on the emitted
+//! [`NativeMessage`] stream.  Nothing is rendered.
+
+use std::rc::Rc;
+
+use pax_message::{
+    ClickInterruptArgs, FormButtonClickArgs, FormCheckboxToggleArgs, FormDropdownChangeArgs,
+    FormTextboxChangeArgs, FormTextboxInputArgs, ImageLoadInterruptArgs, KeyDownInterruptArgs,
+    KeyPressInterruptArgs, KeyUpInterruptArgs, ModifierKeyMessage, MouseButtonMessage,
+    NativeInterrupt, NativeMessage, ScrollInterruptArgs,
+};
+
+use crate::api::math::Point2;
+use crate::api::{
+    ButtonClick, CheckboxChange, Clap, Click, ContextMenu, DoubleClick, DropdownChange, ImageError,
+    KeyDown, KeyPress, KeyUp, KeyboardEventArgs, ModifierKey, MouseButton, MouseDown,
+    MouseEventArgs, MouseMove, MouseOut, MouseOver, MouseUp, Scroll, TextInput, TextboxChange,
+    TextboxInput, Touch, TouchEnd, TouchMove, TouchStart, Wheel,
+};
+use crate::node_interface::NodeInterface;
+use crate::{ComponentInstance, ExpandedNode, ExpressionTable, PaxEngine};
+
+pub struct TestHarness {
+    engine: PaxEngine,
+    native_messages: Vec<NativeMessage>,
+}
+
+impl TestHarness {
+    /// Mounts `main_component_instance` as the root of a new engine with the given viewport size.
+    /// Cartridges expose `instantiate_test_harness`, which fills in the arguments for the main component.
+    pub fn new(
+        main_component_instance: Rc<ComponentInstance>,
+        expression_table: ExpressionTable,
+        viewport_size: (f64, f64),
+    ) -> Self {
+        Self {
+            engine: PaxEngine::new(main_component_instance, expression_table, viewport_size),
+            native_messages: Vec::new(),
+        }
+    }
+
+    pub fn engine(&self) -> &PaxEngine {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut PaxEngine {
+        &mut self.engine
+    }
+
+    /// Advances the engine by `frames` ticks, recording every emitted native message
+    pub fn tick(&mut self, frames: usize) {
+        for _ in 0..frames {
+            let messages = self.engine.tick();
+            self.native_messages.extend(messages);
+        }
+    }
+
+    /// Native messages emitted since the harness was created, or since the last `take_native_messages`
+    pub fn native_messages(&self) -> &[NativeMessage] {
+        &self.native_messages
+    }
+
+    pub fn take_native_messages(&mut self) -> Vec<NativeMessage> {
+        std::mem::take(&mut self.native_messages)
+    }
+
+    /// Finds all nodes with the CommonProperty#id matching the provided string.
+    /// Nodes are only mounted once the engine has ticked at least once.
+    pub fn get_nodes_by_id(&self, id: &str) -> Vec<NodeInterface> {
+        self.engine
+            .runtime_context
+            .get_expanded_nodes_by_id(id)
+            .into_iter()
+            .map(Into::<NodeInterface>::into)
+            .collect()
+    }
+
+    pub fn set_viewport_size(&mut self, viewport_size: (f64, f64)) {
+        self.engine.set_viewport_size(viewport_size);
+    }
+
+    /// Left-clicks the topmost node beneath (`x`, `y`), returning whether a handler prevented default
+    pub fn click(&mut self, x: f64, y: f64) -> bool {
+        self.interrupt(NativeInterrupt::Click(ClickInterruptArgs {
+            x,
+            y,
+            button: MouseButtonMessage::Left,
+            modifiers: vec![],
+        }))
+    }
+
+    /// Sends a key down, key press and key up for `key`, e.g. `"a"` or `"Enter"`
+    pub fn type_key(&mut self, key: &str) {
+        self.interrupt(NativeInterrupt::KeyDown(KeyDownInterruptArgs {
+            key: key.to_string(),
+            modifiers: vec![],
+            is_repeat: false,
+        }));
+        self.interrupt(NativeInterrupt::KeyPress(KeyPressInterruptArgs {
+            key: key.to_string(),
+            modifiers: vec![],
+            is_repeat: false,
+        }));
+        self.interrupt(NativeInterrupt::KeyUp(KeyUpInterruptArgs {
+            key: key.to_string(),
+            modifiers: vec![],
+            is_repeat: false,
+        }));
+    }
+
+    /// Clicks a native `Button`, as a chassis does when the platform button is pressed
+    pub fn button_click(&mut self, button: &NodeInterface) -> bool {
+        self.interrupt(NativeInterrupt::FormButtonClick(FormButtonClickArgs {
+            id_chain: button.id_chain(),
+        }))
+    }
+
+    /// Types into a native `Textbox`: fires `@textbox_input` with the full resulting `text`
+    pub fn textbox_input(&mut self, textbox: &NodeInterface, text: &str) -> bool {
+        self.interrupt(NativeInterrupt::FormTextboxInput(FormTextboxInputArgs {
+            text: text.to_string(),
+            id_chain: textbox.id_chain(),
+        }))
+    }
+
+    /// Commits the content of a native `Textbox`, firing `@textbox_change`
+    pub fn textbox_change(&mut self, textbox: &NodeInterface, text: &str) -> bool {
+        self.interrupt(NativeInterrupt::FormTextboxChange(FormTextboxChangeArgs {
+            text: text.to_string(),
+            id_chain: textbox.id_chain(),
+        }))
+    }
+
+    pub fn checkbox_toggle(&mut self, checkbox: &NodeInterface, state: bool) -> bool {
+        self.interrupt(NativeInterrupt::FormCheckboxToggle(
+            FormCheckboxToggleArgs {
+                state,
+                id_chain: checkbox.id_chain(),
+            },
+        ))
+    }
+
+    /// Picks the option at `selected_id` of a native `Dropdown`, firing `@dropdown_change`
+    pub fn dropdown_change(&mut self, dropdown: &NodeInterface, selected_id: u32) -> bool {
+        self.interrupt(NativeInterrupt::FormDropdownChange(
+            FormDropdownChangeArgs {
+                id_chain: dropdown.id_chain(),
+                selected_id,
+            },
+        ))
+    }
+
+    /// Scrolls a `Scroller` by the given deltas, as a chassis does while its native scroll container moves.
+    /// The content moves, and `@scroll` fires, on the next tick
+    pub fn scroll(&mut self, scroller: &NodeInterface, delta_x: f64, delta_y: f64) -> bool {
+        self.interrupt(NativeInterrupt::Scroll(ScrollInterruptArgs {
+            id_chain: scroller.id_chain(),
+            delta_x,
+            delta_y,
+        }))
+    }
+
+    /// Dispatches a native interrupt the same way a chassis would, returning whether
+    /// a handler prevented default.  Loaded images and layer interrupts are no-ops, since nothing is rendered.
+    pub fn interrupt(&mut self, native_interrupt: NativeInterrupt) -> bool {
+        let engine = &self.engine;
+        let globals = engine.runtime_context.globals();
+        let ctx = &engine.runtime_context;
+        let get_form_node = |id_chain: &[u32]| -> Rc<ExpandedNode> {
+            Rc::clone(
+                engine
+                    .get_expanded_node(id_chain[0])
+                    .expect("form node exists in engine"),
+            )
+        };
+        let hit = |x: f64, y: f64| ctx.get_topmost_element_beneath_ray(Point2::new(x, y));
+
+        match native_interrupt {
+            NativeInterrupt::Image(ImageLoadInterruptArgs::Error(args)) => {
+                get_form_node(&args.id_chain).dispatch_image_error(
+                    ImageError {
+                        path: args.path,
+                        message: args.message,
+                    },
+                    globals,
+                    ctx,
+                )
+            }
+            NativeInterrupt::Image(_) | NativeInterrupt::AddedLayer(_) => false,
+            NativeInterrupt::Scroll(args) => {
+                get_form_node(&args.id_chain).scroll(Scroll {
+                    delta_x: args.delta_x,
+                    delta_y: args.delta_y,
+                });
+                false
+            }
+            NativeInterrupt::FormButtonClick(args) => {
+                get_form_node(&args.id_chain).dispatch_button_click(ButtonClick {}, globals, ctx)
+            }
+            NativeInterrupt::FormTextboxInput(args) => get_form_node(&args.id_chain)
+                .dispatch_textbox_input(TextboxInput { text: args.text }, globals, ctx),
+            NativeInterrupt::FormTextboxChange(args) => get_form_node(&args.id_chain)
+                .dispatch_textbox_change(TextboxChange { text: args.text }, globals, ctx),
+            NativeInterrupt::TextInput(args) => get_form_node(&args.id_chain).dispatch_text_input(
+                TextInput { text: args.text },
+                globals,
+                ctx,
+            ),
+            NativeInterrupt::FormCheckboxToggle(args) => get_form_node(&args.id_chain)
+                .dispatch_checkbox_change(
+                    CheckboxChange {
+                        checked: args.state,
+                    },
+                    globals,
+                    ctx,
+                ),
+            NativeInterrupt::FormDropdownChange(args) => get_form_node(&args.id_chain)
+                .dispatch_dropdown_change(
+                    DropdownChange {
+                        selected_id: args.selected_id,
+                    },
+                    globals,
+                    ctx,
+                ),
+            NativeInterrupt::KeyDown(args) => {
+                engine.global_dispatch_key_down(KeyDown {
+                    keyboard: keyboard_args(args.key, &args.modifiers, args.is_repeat),
+                });
+                false
+            }
+            NativeInterrupt::KeyUp(args) => {
+                engine.global_dispatch_key_up(KeyUp {
+                    keyboard: keyboard_args(args.key, &args.modifiers, args.is_repeat),
+                });
+                false
+            }
+            NativeInterrupt::KeyPress(args) => {
+                engine.global_dispatch_key_press(KeyPress {
+                    keyboard: keyboard_args(args.key, &args.modifiers, args.is_repeat),
+                });
+                false
+            }
+            NativeInterrupt::Clap(args) => hit(args.x, args.y).is_some_and(|node| {
+                node.dispatch_clap(
+                    Clap {
+                        x: args.x,
+                        y: args.y,
+                    },
+                    globals,
+                    ctx,
+                )
+            }),
+            NativeInterrupt::Click(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_click(Click { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::DoubleClick(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_double_click(DoubleClick { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::MouseMove(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_mouse_move(MouseMove { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::MouseDown(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_mouse_down(MouseDown { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::MouseUp(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_mouse_up(MouseUp { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::MouseOver(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_mouse_over(MouseOver { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::MouseOut(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_mouse_out(MouseOut { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::ContextMenu(args) => hit(args.x, args.y).is_some_and(|node| {
+                let mouse = mouse_args(args.x, args.y, args.button, &args.modifiers);
+                node.dispatch_context_menu(ContextMenu { mouse }, globals, ctx)
+            }),
+            NativeInterrupt::Wheel(args) => hit(args.x, args.y).is_some_and(|node| {
+                let wheel = Wheel {
+                    x: args.x,
+                    y: args.y,
+                    delta_x: args.delta_x,
+                    delta_y: args.delta_y,
+                    modifiers: args.modifiers.iter().map(ModifierKey::from).collect(),
+                };
+                node.dispatch_wheel(wheel, globals, ctx)
+            }),
+            NativeInterrupt::TouchStart(args) => {
+                let first_touch = args.touches.first().expect("at least one touch");
+                hit(first_touch.x, first_touch.y).is_some_and(|node| {
+                    let touches = args.touches.iter().map(Touch::from).collect();
+                    node.dispatch_touch_start(TouchStart { touches }, globals, ctx)
+                })
+            }
+            NativeInterrupt::TouchMove(args) => {
+                let first_touch = args.touches.first().expect("at least one touch");
+                hit(first_touch.x, first_touch.y).is_some_and(|node| {
+                    let touches = args.touches.iter().map(Touch::from).collect();
+                    node.dispatch_touch_move(TouchMove { touches }, globals, ctx)
+                })
+            }
+            NativeInterrupt::TouchEnd(args) => {
+                let first_touch = args.touches.first().expect("at least one touch");
+                hit(first_touch.x, first_touch.y).is_some_and(|node| {
+                    let touches = args.touches.iter().map(Touch::from).collect();
+                    node.dispatch_touch_end(TouchEnd { touches }, globals, ctx)
+                })
+            }
+        }
+    }
+}
+
+fn mouse_args(
+    x: f64,
+    y: f64,
+    button: MouseButtonMessage,
+    modifiers: &[ModifierKeyMessage],
+) -> MouseEventArgs {
+    MouseEventArgs {
+        x,
+        y,
+        button: MouseButton::from(button),
+        modifiers: modifiers.iter().map(ModifierKey::from).collect(),
+    }
+}
+
+fn keyboard_args(
+    key: String,
+    modifiers: &[ModifierKeyMessage],
+    is_repeat: bool,
+) -> KeyboardEventArgs {
+    KeyboardEventArgs {
+        key,
+        modifiers: modifiers.iter().map(ModifierKey::from).collect(),
+        is_repeat,
+    }
+}
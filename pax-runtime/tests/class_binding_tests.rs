@@ -0,0 +1,168 @@
+use std::any::Any;
+
+use pax_runtime::api::{CommonProperties, Numeric, PropertyLiteral};
+use pax_runtime::testing::TestHarness;
+use pax_runtime::{ClassBindings, InstanceNode, PropertiesPatch};
+
+mod common;
+use common::{args, mount, nodes_by_id, peek, with_id, with_root_properties, Expression, Leaf};
+
+#[derive(Default, Clone, PartialEq, Debug)]
+struct Style {
+    fill: &'static str,
+    stroke: &'static str,
+}
+
+#[derive(Clone)]
+struct State {
+    class: String,
+}
+
+fn set_style(fill: Option<&'static str>, stroke: Option<&'static str>) -> PropertiesPatch {
+    Box::new(move |properties: &mut dyn Any, _: &mut CommonProperties| {
+        let style = properties.downcast_mut::<Style>().unwrap();
+        if let Some(fill) = fill {
+            style.fill = fill;
+        }
+        if let Some(stroke) = stroke {
+            style.stroke = stroke;
+        }
+    })
+}
+
+/// The blocks `.active { fill: blue }`, `.large { stroke: thick, opacity: 0.5 }` and
+/// `.active.large { fill: red }`, from the least to the most specific
+fn bindings() -> ClassBindings {
+    let classes = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+    ClassBindings {
+        vtable_id: 0,
+        reset: Box::new(|properties, common| {
+            *properties.downcast_mut::<Style>().unwrap() = Style::default();
+            common.opacity = None;
+        }),
+        bundles: vec![
+            (classes(&["active"]), set_style(Some("blue"), None)),
+            (
+                classes(&["large"]),
+                Box::new(|properties, common| {
+                    set_style(None, Some("thick"))(properties, common);
+                    common.opacity = Some(Box::new(PropertyLiteral::new(Numeric::from(0.5))));
+                }),
+            ),
+            (classes(&["active", "large"]), set_style(Some("red"), None)),
+        ],
+    }
+}
+
+fn apply(bindings: &ClassBindings, classes: &[&str], style: &mut Style) -> CommonProperties {
+    let classes: Vec<String> = classes.iter().map(|class| class.to_string()).collect();
+    let mut common = CommonProperties::default();
+    bindings.apply(&classes, style, &mut common);
+    common
+}
+
+#[test]
+fn test_class_bindings_apply_matching_bundles() {
+    let bindings = bindings();
+    let mut style = Style::default();
+
+    apply(&bindings, &["active"], &mut style);
+    assert_eq!(style.fill, "blue");
+    assert_eq!(style.stroke, "");
+
+    let common = apply(&bindings, &["large"], &mut style);
+    // Applying other classes reverts what `.active` set
+    assert_eq!(style.fill, "");
+    assert_eq!(style.stroke, "thick");
+    assert_eq!(common.opacity.unwrap().get().to_float(), 0.5);
+
+    let common = apply(&bindings, &[], &mut style);
+    assert_eq!(style, Style::default());
+    assert!(common.opacity.is_none());
+}
+
+#[test]
+fn test_class_bindings_apply_more_specific_bundles_last() {
+    let bindings = bindings();
+    let mut style = Style::default();
+
+    // `.active.large` wins over `.active` whichever order the classes are listed in
+    apply(&bindings, &["large", "active"], &mut style);
+    assert_eq!(style.fill, "red");
+    assert_eq!(style.stroke, "thick");
+    apply(&bindings, &["active", "large"], &mut style);
+    assert_eq!(style.fill, "red");
+}
+
+/// Mounts `<Leaf id=leaf class={self.class} />` with the blocks of [`bindings`]
+fn mount_classed(class: &str) -> TestHarness {
+    let class = class.to_string();
+    let class_expression: Expression = Box::new(|ec| {
+        let class = peek::<State>(&ec, 0).class;
+        // `!` stands in for an expression that doesn't evaluate to a string
+        if class == "!" {
+            Box::new(Numeric::from(1))
+        } else {
+            Box::new(class)
+        }
+    });
+    let mut leaf = with_id(args(Style::default), "leaf");
+    leaf.class_bindings = Some(bindings());
+    mount(
+        move || State {
+            class: class.clone(),
+        },
+        vec![Leaf::instantiate(leaf)],
+        vec![(0, class_expression)],
+    )
+}
+
+fn set_class(harness: &mut TestHarness, class: &str) {
+    with_root_properties(harness, |state: &mut State| state.class = class.to_string());
+    harness.tick(1);
+}
+
+fn leaf_style(harness: &TestHarness) -> (Style, Option<f64>) {
+    let leaf = &nodes_by_id(harness, "leaf")[0];
+    let style = leaf.with_properties_unwrapped(|style: &mut Style| style.clone());
+    let opacity = leaf
+        .get_common_properties()
+        .borrow()
+        .opacity
+        .as_ref()
+        .map(|opacity| opacity.get().to_float());
+    (style, opacity)
+}
+
+#[test]
+fn test_updating_class_expression_switches_settings() {
+    let mut harness = mount_classed("active");
+    assert_eq!(leaf_style(&harness).0.fill, "blue");
+
+    // Adding a class applies its block, and the more specific block over `.active`
+    set_class(&mut harness, "active large");
+    let (style, opacity) = leaf_style(&harness);
+    assert_eq!((style.fill, style.stroke), ("red", "thick"));
+    assert_eq!(opacity, Some(0.5));
+
+    // Removing a class reverts what its blocks set
+    set_class(&mut harness, "active");
+    let (style, opacity) = leaf_style(&harness);
+    assert_eq!((style.fill, style.stroke), ("blue", ""));
+    assert_eq!(opacity, None);
+
+    set_class(&mut harness, "");
+    assert_eq!(leaf_style(&harness), (Style::default(), None));
+}
+
+#[test]
+fn test_non_string_class_expression_keeps_classes() {
+    let mut harness = mount_classed("active large");
+    set_class(&mut harness, "!");
+    let (style, opacity) = leaf_style(&harness);
+    assert_eq!((style.fill, style.stroke), ("red", "thick"));
+    assert_eq!(opacity, Some(0.5));
+
+    set_class(&mut harness, "active");
+    assert_eq!(leaf_style(&harness).0.fill, "blue");
+}
@@ -0,0 +1,198 @@
+//! Hand-built instance trees for tests that mount nodes in a [`TestHarness`], standing in for
+//! the instances and expression table a compiled cartridge would provide.
+
+#![allow(dead_code)]
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pax_runtime::api::expressions::PropertyExpression;
+use pax_runtime::api::{Axis, CommonProperties, Layer, PropertyLiteral};
+use pax_runtime::testing::TestHarness;
+use pax_runtime::{
+    Align, BaseInstance, ComponentInstance, ExpandedNode, ExpressionContext, ExpressionTable,
+    FlexLayout, InstanceFlags, InstanceNode, InstantiationArgs, Justify, Layout, RepeatInstance,
+    RepeatProperties,
+};
+
+/// A childless node that renders nothing, standing in for a primitive like `Rectangle`
+pub struct Leaf {
+    base: BaseInstance,
+}
+
+impl InstanceNode for Leaf {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self> {
+        Rc::new(Leaf {
+            base: BaseInstance::new(
+                args,
+                InstanceFlags {
+                    invisible_to_slot: false,
+                    invisible_to_raycasting: false,
+                    layer: Layer::Canvas,
+                    is_component: false,
+                },
+            ),
+        })
+    }
+
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Leaf").finish()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
+
+/// A node that places its children side by side, standing in for a `Flex` row
+pub struct Row {
+    base: BaseInstance,
+}
+
+impl InstanceNode for Row {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self> {
+        Rc::new(Row {
+            base: BaseInstance::new(
+                args,
+                InstanceFlags {
+                    invisible_to_slot: false,
+                    invisible_to_raycasting: true,
+                    layer: Layer::DontCare,
+                    is_component: false,
+                },
+            ),
+        })
+    }
+
+    fn get_layout(&self, _expanded_node: &ExpandedNode) -> Option<Layout> {
+        Some(Layout::Flex(FlexLayout {
+            main_axis: Axis::X,
+            wrap: false,
+            justify: Justify::Start,
+            align: Align::Stretch,
+            gap: 0.0,
+        }))
+    }
+
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Row").finish()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
+
+pub type Expression = Box<dyn Fn(ExpressionContext) -> Box<dyn Any>>;
+
+/// Instantiation args for a node whose properties are built by `properties`, with no id,
+/// handlers, children or classes
+pub fn args<T: 'static>(properties: impl Fn() -> T + 'static) -> InstantiationArgs {
+    InstantiationArgs {
+        prototypical_common_properties_factory: Box::new(|| {
+            Rc::new(RefCell::new(CommonProperties::default()))
+        }),
+        prototypical_properties_factory: Box::new(move || {
+            Rc::new(RefCell::new(properties())) as Rc<RefCell<dyn Any>>
+        }),
+        handler_registry: None,
+        children: None,
+        component_template: None,
+        compute_properties_fn: None,
+        template_node_identifier: None,
+        timeline: None,
+        component_type_id: None,
+        class_bindings: None,
+    }
+}
+
+/// A `for` over the `Vec<Rc<RefCell<dyn Any>>>` computed by `source_vtable_id`, keyed by
+/// the `RepeatKey` computed by `key_vtable_id` if any, repeating `children`
+pub fn repeat(
+    source_vtable_id: usize,
+    key_vtable_id: Option<usize>,
+    children: Vec<Rc<dyn InstanceNode>>,
+) -> Rc<dyn InstanceNode> {
+    let mut args = args(move || {
+        let mut properties = RepeatProperties::default();
+        properties.source_expression_vec =
+            Some(Box::new(PropertyExpression::new(source_vtable_id)));
+        properties.key_vtable_id = key_vtable_id;
+        properties
+    });
+    args.children = Some(RefCell::new(children));
+    RepeatInstance::instantiate(args)
+}
+
+/// Sets the `id` of the nodes built from `args`, for finding them with [`TestHarness::get_nodes_by_id`]
+pub fn with_id(mut args: InstantiationArgs, id: &str) -> InstantiationArgs {
+    let id = id.to_string();
+    args.prototypical_common_properties_factory = Box::new(move || {
+        Rc::new(RefCell::new(CommonProperties {
+            id: Some(Box::new(PropertyLiteral::new(id.clone()))),
+            ..Default::default()
+        }))
+    });
+    args
+}
+
+/// Mounts `template` in a root component whose properties are built by `properties`, computing
+/// each vtable id of `expressions` with its closure, and ticks once so that the tree is expanded
+pub fn mount<T: 'static>(
+    properties: impl Fn() -> T + 'static,
+    template: Vec<Rc<dyn InstanceNode>>,
+    expressions: Vec<(usize, Expression)>,
+) -> TestHarness {
+    let mut args = args(properties);
+    args.component_template = Some(RefCell::new(template));
+    args.compute_properties_fn = Some(Box::new(|_, _, _| {}));
+    let expression_table = ExpressionTable {
+        table: expressions.into_iter().collect(),
+        theme: Default::default(),
+    };
+    let mut harness = TestHarness::new(
+        ComponentInstance::instantiate(args),
+        expression_table,
+        (800.0, 600.0),
+    );
+    harness.tick(1);
+    harness
+}
+
+/// Reads or modifies the properties of the root component mounted by [`mount`]
+pub fn with_root_properties<T: 'static, R>(
+    harness: &TestHarness,
+    callback: impl FnOnce(&mut T) -> R,
+) -> R {
+    harness
+        .engine()
+        .root_node
+        .with_properties_unwrapped(callback)
+}
+
+/// Reads the properties of frame `n` of the stack an expression sees, where `0` is the innermost
+pub fn peek<T: Clone + 'static>(ec: &ExpressionContext, n: isize) -> T {
+    let frame = ec.stack_frame.peek_nth(n).expect("stack is deep enough");
+    let borrowed = frame.borrow();
+    borrowed
+        .downcast_ref::<T>()
+        .expect("frame has the expected type")
+        .clone()
+}
+
+/// The mounted nodes whose `id` is `id`
+pub fn nodes_by_id(harness: &TestHarness, id: &str) -> Vec<Rc<ExpandedNode>> {
+    harness
+        .engine()
+        .runtime_context
+        .get_expanded_nodes_by_id(id)
+}
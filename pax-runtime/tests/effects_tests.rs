@@ -0,0 +1,162 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use kurbo::{Rect, Shape};
+use pax_message::NativeMessage;
+use pax_runtime::api::Layer;
+use pax_runtime::{
+    blur_layers, fade_brush, BaseInstance, ExpandedNode, InstanceFlags, InstanceNode,
+    InstantiationArgs,
+};
+use piet::{Color, PaintBrush};
+
+mod common;
+use common::{args, mount, nodes_by_id, with_id};
+
+/// A container blurring what it contains by 3px, standing in for a `Frame` with `blur: 3`
+struct Blurred {
+    base: BaseInstance,
+}
+
+/// A node drawn by the chassis, standing in for a `Text`
+struct Native {
+    base: BaseInstance,
+}
+
+fn flags(layer: Layer) -> InstanceFlags {
+    InstanceFlags {
+        invisible_to_slot: false,
+        invisible_to_raycasting: true,
+        layer,
+        is_component: false,
+    }
+}
+
+impl InstanceNode for Blurred {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self> {
+        Rc::new(Blurred {
+            base: BaseInstance::new(args, flags(Layer::DontCare)),
+        })
+    }
+
+    fn get_content_blur(&self, _expanded_node: &ExpandedNode) -> f64 {
+        3.0
+    }
+
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Blurred").finish()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
+
+impl InstanceNode for Native {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self> {
+        Rc::new(Native {
+            base: BaseInstance::new(args, flags(Layer::Native)),
+        })
+    }
+
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Native").finish()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
+
+fn blurred(children: Vec<Rc<dyn InstanceNode>>) -> Rc<dyn InstanceNode> {
+    let mut args = args(|| ());
+    args.children = Some(RefCell::new(children));
+    Blurred::instantiate(args)
+}
+
+#[test]
+fn test_content_blur_composes_down_to_native_elements() {
+    let text = Native::instantiate(with_id(args(|| ()), "text"));
+    let mut harness = mount(|| (), vec![blurred(vec![blurred(vec![text])])], vec![]);
+
+    let text = &nodes_by_id(&harness, "text")[0];
+    let expected = 3.0_f64.hypot(3.0);
+    let computed_blur = text
+        .layout_properties
+        .borrow()
+        .as_ref()
+        .unwrap()
+        .computed_blur;
+    assert!((computed_blur - expected).abs() < 1e-9);
+
+    let effects: Vec<_> = harness
+        .take_native_messages()
+        .into_iter()
+        .filter_map(|message| match message {
+            NativeMessage::EffectsUpdate(patch) => Some(patch),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(effects.len(), 1);
+    assert_eq!(effects[0].id_chain, text.id_chain);
+    assert_eq!(effects[0].opacity, 1.0);
+    assert!((effects[0].blur - expected).abs() < 1e-9);
+
+    // Unchanged effects aren't sent again
+    harness.tick(1);
+    assert!(!harness
+        .take_native_messages()
+        .iter()
+        .any(|message| matches!(message, NativeMessage::EffectsUpdate(_))));
+}
+
+#[test]
+fn test_blur_layers_build_up_to_alpha() {
+    let path = Rect::new(0.0, 0.0, 100.0, 50.0).to_path(0.1);
+    let alpha = 0.6;
+    let layers = blur_layers(&path, 10.0, alpha);
+    assert_eq!(layers.len(), 8);
+
+    // Filled in order, each layer covers the uncovered remainder by its own alpha
+    let composite = layers.iter().fold(0.0, |below, (_, factor)| {
+        below + (1.0 - below) * alpha * factor
+    });
+    assert!((composite - alpha).abs() < 1e-9);
+
+    // The outermost copy extends 10 * (1 - 1/8) px beyond the path on each side
+    let outermost = layers[0].0.bounding_box();
+    assert!((outermost.x0 + 8.75).abs() < 1e-9);
+    assert!((outermost.x1 - 108.75).abs() < 1e-9);
+    assert!((outermost.y1 - 58.75).abs() < 1e-9);
+}
+
+#[test]
+fn test_blur_layers_skip_copies_that_vanish() {
+    let path = Rect::new(0.0, 0.0, 4.0, 4.0).to_path(0.1);
+    let layers = blur_layers(&path, 10.0, 1.0);
+    assert!(layers.len() < 8);
+    assert!(layers
+        .iter()
+        .all(|(copy, _)| copy.bounding_box().width() > 0.0));
+}
+
+#[test]
+fn test_fade_brush_scales_alpha() {
+    let brush: PaintBrush = Color::rgba(1.0, 0.0, 0.0, 0.8).into();
+    match fade_brush(&brush, 0.5) {
+        PaintBrush::Color(color) => {
+            let (r, _, _, a) = color.as_rgba();
+            assert_eq!(r, 1.0);
+            assert!((a - 0.4).abs() < 0.01);
+        }
+        _ => panic!("a color stays a color"),
+    }
+}
@@ -0,0 +1,125 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use pax_runtime::api::Axis;
+use pax_runtime::{compute_flex_layout, Align, FlexItem, FlexLayout, InstanceNode, Justify};
+
+mod common;
+use common::{args, mount, repeat, Expression, Leaf, Row};
+
+fn item(basis: (f64, f64), grow: f64) -> FlexItem {
+    FlexItem {
+        basis,
+        grow,
+        shrink: 1.0,
+        min: (0.0, 0.0),
+        max: (f64::INFINITY, f64::INFINITY),
+    }
+}
+
+fn row(wrap: bool, justify: Justify, align: Align) -> FlexLayout {
+    FlexLayout {
+        main_axis: Axis::X,
+        wrap,
+        justify,
+        align,
+        gap: 10.0,
+    }
+}
+
+#[test]
+fn test_flex_grow_respects_max() {
+    let mut capped = item((50.0, 20.0), 1.0);
+    capped.max.0 = 100.0;
+    let items = vec![capped, item((50.0, 20.0), 1.0)];
+    let boxes = compute_flex_layout(
+        &row(false, Justify::Start, Align::Stretch),
+        (410.0, 100.0),
+        &items,
+    );
+    assert_eq!(
+        boxes,
+        vec![(0.0, 0.0, 100.0, 100.0), (110.0, 0.0, 300.0, 100.0)]
+    );
+}
+
+#[test]
+fn test_flex_shrink_weighted_by_basis() {
+    let items = vec![item((300.0, 20.0), 0.0), item((100.0, 20.0), 0.0)];
+    let boxes = compute_flex_layout(
+        &row(false, Justify::Start, Align::Start),
+        (210.0, 100.0),
+        &items,
+    );
+    assert_eq!(
+        boxes,
+        vec![(0.0, 0.0, 150.0, 20.0), (160.0, 0.0, 50.0, 20.0)]
+    );
+}
+
+#[test]
+fn test_flex_wrap_justify_and_align() {
+    let items = vec![
+        item((100.0, 20.0), 0.0),
+        item((100.0, 40.0), 0.0),
+        item((100.0, 20.0), 0.0),
+    ];
+    let boxes = compute_flex_layout(
+        &row(true, Justify::Center, Align::Center),
+        (250.0, 200.0),
+        &items,
+    );
+    assert_eq!(
+        boxes,
+        vec![
+            (20.0, 10.0, 100.0, 20.0),
+            (130.0, 0.0, 100.0, 40.0),
+            (75.0, 50.0, 100.0, 20.0),
+        ]
+    );
+}
+
+#[test]
+fn test_flex_updates_flattened_for_once_per_tick() {
+    let evaluations = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&evaluations);
+    let source: Expression = Box::new(move |_| {
+        counter.set(counter.get() + 1);
+        Box::new(vec![Rc::new(RefCell::new(1)) as Rc<RefCell<dyn Any>>])
+    });
+    let mut row = args(|| ());
+    row.children = Some(RefCell::new(vec![repeat(
+        0,
+        None,
+        vec![Leaf::instantiate(args(|| ()))],
+    )]));
+    let mut harness = mount(|| (), vec![Row::instantiate(row)], vec![(0, source)]);
+
+    // The `for` is updated ahead of the row's layout, and not again in its own traversal
+    let before = evaluations.get();
+    harness.tick(3);
+    assert_eq!(evaluations.get() - before, 3);
+}
+
+#[test]
+fn test_node_remounted_within_a_frame_is_updated_again() {
+    let evaluations = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&evaluations);
+    let source: Expression = Box::new(move |_| {
+        counter.set(counter.get() + 1);
+        Box::new(vec![Rc::new(RefCell::new(1)) as Rc<RefCell<dyn Any>>])
+    });
+    let for_loop = repeat(0, None, vec![Leaf::instantiate(args(|| ()))]);
+    let mut harness = mount(|| (), vec![for_loop], vec![(0, source)]);
+
+    // Update, remount (as a designtime reload does) and update again, all within one frame
+    let engine = harness.engine_mut();
+    let node = Rc::clone(&engine.root_node.children.borrow()[0]);
+    node.recurse_update(&mut engine.runtime_context);
+    Rc::clone(&node).recurse_unmount(&mut engine.runtime_context);
+    Rc::clone(&node).recurse_mount(&mut engine.runtime_context);
+    let before = evaluations.get();
+    node.recurse_update(&mut engine.runtime_context);
+    assert_eq!(evaluations.get() - before, 1);
+}
@@ -0,0 +1,74 @@
+use pax_runtime::api::Size;
+use pax_runtime::{compute_grid_layout, GridItem, GridLayout, GridTrack};
+
+fn item(row: Option<usize>, column: Option<usize>, column_span: usize) -> GridItem {
+    GridItem {
+        basis: (40.0, 20.0),
+        column,
+        row,
+        column_span,
+        row_span: 1,
+    }
+}
+
+#[test]
+fn test_grid_tracks_and_auto_placement() {
+    let layout = GridLayout {
+        columns: vec![
+            GridTrack::Fixed(Size::Pixels(100.into())),
+            GridTrack::Fraction(1.0),
+            GridTrack::Auto,
+            GridTrack::Fraction(3.0),
+        ],
+        rows: vec![GridTrack::Fixed(Size::Percent(25.into()))],
+        column_gap: 10.0,
+        row_gap: 10.0,
+    };
+    let items = vec![
+        // header spanning the first row
+        item(None, None, 4),
+        item(None, None, 1),
+        // pinned to the cell the previous item would otherwise flow into next
+        item(Some(1), Some(1), 1),
+        item(None, None, 1),
+    ];
+    let boxes = compute_grid_layout(&layout, (370.0, 220.0), &items);
+    assert_eq!(
+        boxes,
+        vec![
+            (0.0, 0.0, 370.0, 55.0),
+            (0.0, 65.0, 100.0, 155.0),
+            (110.0, 65.0, 50.0, 155.0),
+            (170.0, 65.0, 40.0, 155.0),
+        ]
+    );
+}
+
+#[test]
+fn test_row_only_items_fill_free_cells_of_their_row() {
+    let layout = GridLayout {
+        columns: vec![GridTrack::Fraction(1.0), GridTrack::Fraction(1.0)],
+        rows: vec![],
+        column_gap: 0.0,
+        row_gap: 0.0,
+    };
+    let items = vec![
+        item(Some(0), None, 1),
+        item(Some(0), None, 1),
+        // no room left in the row, so it extends the grid with an implicit column
+        item(Some(0), None, 1),
+        item(Some(1), Some(0), 1),
+        item(Some(1), None, 1),
+    ];
+    let boxes = compute_grid_layout(&layout, (300.0, 200.0), &items);
+    assert_eq!(
+        boxes,
+        vec![
+            (0.0, 0.0, 100.0, 100.0),
+            (100.0, 0.0, 100.0, 100.0),
+            (200.0, 0.0, 100.0, 100.0),
+            (0.0, 100.0, 100.0, 100.0),
+            (100.0, 100.0, 100.0, 100.0),
+        ]
+    );
+}
@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use pax_message::{MouseButtonMessage, MouseDownInterruptArgs, NativeInterrupt};
+use pax_runtime::api::NodeContext;
+use pax_runtime::testing::TestHarness;
+use pax_runtime::{Handler, HandlerRegistry, InstanceNode};
+
+mod common;
+use common::{args, mount, nodes_by_id, with_id, with_root_properties, Leaf, Row};
+
+#[derive(Default)]
+struct Counter {
+    clicks: usize,
+}
+
+fn increment(properties: Rc<RefCell<dyn Any>>, _ctx: &NodeContext, _args: Option<Box<dyn Any>>) {
+    properties
+        .borrow_mut()
+        .downcast_mut::<Counter>()
+        .unwrap()
+        .clicks += 1;
+}
+
+#[test]
+fn test_harness_dispatches_to_inline_handler() {
+    let mut button = with_id(args(|| ()), "button");
+    button.handler_registry = Some(Rc::new(RefCell::new(HandlerRegistry {
+        handlers: HashMap::from([(
+            "button_click".to_string(),
+            vec![Handler::new_inline_handler(increment)],
+        )]),
+    })));
+    let mut harness = mount(Counter::default, vec![Leaf::instantiate(button)], vec![]);
+
+    let buttons = harness.get_nodes_by_id("button");
+    assert_eq!(buttons.len(), 1);
+    harness.button_click(&buttons[0]);
+    harness.button_click(&buttons[0]);
+    harness.tick(1);
+
+    // The handler runs against the properties of the component containing the button
+    with_root_properties(&harness, |counter: &mut Counter| {
+        assert_eq!(counter.clicks, 2);
+    });
+    assert!(harness.get_nodes_by_id("missing").is_empty());
+}
+
+/// Whether each key down found the node focused
+#[derive(Default)]
+struct KeyLog {
+    focused: Vec<bool>,
+}
+
+fn log_focus(properties: Rc<RefCell<dyn Any>>, ctx: &NodeContext, _args: Option<Box<dyn Any>>) {
+    properties
+        .borrow_mut()
+        .downcast_mut::<KeyLog>()
+        .unwrap()
+        .focused
+        .push(ctx.is_focused());
+}
+
+fn mouse_down(harness: &mut TestHarness, x: f64, y: f64) {
+    harness.interrupt(NativeInterrupt::MouseDown(MouseDownInterruptArgs {
+        x,
+        y,
+        button: MouseButtonMessage::Left,
+        modifiers: vec![],
+    }));
+}
+
+fn key_log(harness: &TestHarness, id: &str) -> Vec<bool> {
+    nodes_by_id(harness, id)[0].with_properties_unwrapped(|log: &mut KeyLog| log.focused.clone())
+}
+
+#[test]
+fn test_pressing_a_node_focuses_it() {
+    let field = |id| {
+        let mut field = with_id(args(KeyLog::default), id);
+        field.handler_registry = Some(Rc::new(RefCell::new(HandlerRegistry {
+            handlers: HashMap::from([(
+                "key_down".to_string(),
+                vec![Handler::new_component_handler(log_focus)],
+            )]),
+        })));
+        Leaf::instantiate(field)
+    };
+    let mut row = args(|| ());
+    row.children = Some(RefCell::new(vec![field("left"), field("right")]));
+    let mut harness = mount(|| (), vec![Row::instantiate(row)], vec![]);
+
+    harness.type_key("a");
+    mouse_down(&mut harness, 100.0, 10.0);
+    harness.type_key("a");
+    // Pressing the other node blurs the first
+    mouse_down(&mut harness, 700.0, 10.0);
+    harness.type_key("a");
+
+    assert_eq!(key_log(&harness, "left"), vec![false, true, false]);
+    assert_eq!(key_log(&harness, "right"), vec![false, false, true]);
+}
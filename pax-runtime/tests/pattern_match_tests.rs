@@ -0,0 +1,88 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pax_runtime::api::expressions::PropertyExpression;
+use pax_runtime::testing::TestHarness;
+use pax_runtime::{InstanceNode, MatchArm, MatchInstance, MatchProperties};
+
+mod common;
+use common::{args, mount, nodes_by_id, peek, with_id, with_root_properties, Expression, Leaf};
+
+#[derive(Clone)]
+struct State {
+    count: Option<i64>,
+}
+
+/// Mounts `match self.count { Some(n) => <Leaf id=some />, None => <Leaf id=none /> }`
+fn mount_match(count: Option<i64>) -> TestHarness {
+    let arm: Expression = Box::new(|ec| {
+        Box::new(match peek::<State>(&ec, 0).count {
+            Some(n) => MatchArm::new(
+                0,
+                vec![(
+                    "n".to_string(),
+                    Rc::new(RefCell::new(n)) as Rc<RefCell<dyn Any>>,
+                )],
+            ),
+            None => MatchArm::new(1, vec![]),
+        })
+    });
+    let mut match_args = args(|| {
+        let mut properties = MatchProperties::default();
+        properties.arm_expression = Box::new(PropertyExpression::new(0));
+        properties.arm_lengths = vec![1, 1];
+        properties
+    });
+    match_args.children = Some(RefCell::new(vec![
+        Leaf::instantiate(with_id(args(|| ()), "some")),
+        Leaf::instantiate(with_id(args(|| ()), "none")),
+    ]));
+    mount(
+        move || State { count },
+        vec![MatchInstance::instantiate(match_args)],
+        vec![(0, arm)],
+    )
+}
+
+fn set_count(harness: &mut TestHarness, count: Option<i64>) {
+    with_root_properties(harness, |state: &mut State| state.count = count);
+    harness.tick(1);
+}
+
+/// The payload `n` bound for the nodes of the active arm
+fn bound_n(harness: &TestHarness) -> i64 {
+    let node = &nodes_by_id(harness, "some")[0];
+    let frame = node.stack.peek_nth(0).unwrap();
+    let frame = frame.borrow();
+    let payload = &frame.downcast_ref::<MatchArm>().unwrap().bindings["n"];
+    let n = *payload.borrow().downcast_ref::<i64>().unwrap();
+    n
+}
+
+#[test]
+fn test_match_switches_arms() {
+    let mut harness = mount_match(Some(1));
+    assert_eq!(nodes_by_id(&harness, "some").len(), 1);
+    assert!(nodes_by_id(&harness, "none").is_empty());
+
+    set_count(&mut harness, None);
+    assert!(nodes_by_id(&harness, "some").is_empty());
+    assert_eq!(nodes_by_id(&harness, "none").len(), 1);
+
+    set_count(&mut harness, Some(2));
+    assert_eq!(nodes_by_id(&harness, "some").len(), 1);
+    assert!(nodes_by_id(&harness, "none").is_empty());
+}
+
+#[test]
+fn test_match_rebinds_payload_of_active_arm() {
+    let mut harness = mount_match(Some(1));
+    let node = Rc::clone(&nodes_by_id(&harness, "some")[0]);
+    assert_eq!(bound_n(&harness), 1);
+
+    // Staying in the same arm keeps its nodes, which see the new payload
+    set_count(&mut harness, Some(5));
+    assert!(Rc::ptr_eq(&node, &nodes_by_id(&harness, "some")[0]));
+    assert_eq!(bound_n(&harness), 5);
+}
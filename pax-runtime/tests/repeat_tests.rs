@@ -0,0 +1,207 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pax_runtime::testing::TestHarness;
+use pax_runtime::{ExpandedNode, InstanceNode, RepeatItem, RepeatKey};
+
+mod common;
+use common::{args, mount, peek, repeat, with_root_properties, Expression, Leaf};
+
+#[derive(Clone)]
+struct Item {
+    id: isize,
+    label: &'static str,
+}
+
+#[derive(Clone)]
+struct State {
+    items: Vec<Item>,
+}
+
+fn item(id: isize, label: &'static str) -> Item {
+    Item { id, label }
+}
+
+/// Mounts `for item in self.items { <Leaf /> }`, keyed by `item.id` if `keyed`
+fn mount_repeat(items: Vec<Item>, keyed: bool) -> TestHarness {
+    let source: Expression = Box::new(|ec| {
+        let items = peek::<State>(&ec, 0).items;
+        Box::new(
+            items
+                .into_iter()
+                .map(|item| Rc::new(RefCell::new(item)) as Rc<RefCell<dyn Any>>)
+                .collect::<Vec<_>>(),
+        )
+    });
+    let key: Expression = Box::new(|ec| {
+        let frame = ec.stack_frame.peek_nth(0).unwrap();
+        let frame = frame.borrow();
+        let elem = frame.downcast_ref::<RepeatItem>().unwrap().elem.borrow();
+        let id = elem.downcast_ref::<Item>().unwrap().id;
+        // A negative id stands in for a key expression that doesn't compute a key
+        if id < 0 {
+            Box::new(id)
+        } else {
+            Box::new(RepeatKey::Integer(id))
+        }
+    });
+    let key_vtable_id = keyed.then_some(1);
+    mount(
+        move || State {
+            items: items.clone(),
+        },
+        vec![repeat(
+            0,
+            key_vtable_id,
+            vec![Leaf::instantiate(args(|| ()))],
+        )],
+        vec![(0, source), (1, key)],
+    )
+}
+
+fn set_items(harness: &mut TestHarness, items: Vec<Item>) {
+    with_root_properties(harness, |state: &mut State| state.items = items);
+    harness.tick(1);
+}
+
+/// The nodes expanded by the `for`, each with the index and label of the element it sees
+fn rows(harness: &TestHarness) -> Vec<(Rc<ExpandedNode>, usize, &'static str)> {
+    let repeat = Rc::clone(&harness.engine().root_node.children.borrow()[0]);
+    let children = repeat.children.borrow().clone();
+    children
+        .into_iter()
+        .map(|node| {
+            let frame = node.stack.peek_nth(0).unwrap();
+            let (i, label) = {
+                let frame = frame.borrow();
+                let repeat_item = frame.downcast_ref::<RepeatItem>().unwrap();
+                let elem = repeat_item.elem.borrow();
+                (repeat_item.i, elem.downcast_ref::<Item>().unwrap().label)
+            };
+            (node, i, label)
+        })
+        .collect()
+}
+
+fn labels(harness: &TestHarness) -> Vec<&'static str> {
+    rows(harness)
+        .into_iter()
+        .map(|(_, _, label)| label)
+        .collect()
+}
+
+#[test]
+fn test_keyed_for_keeps_nodes_across_reorders() {
+    let mut harness = mount_repeat(vec![item(1, "a"), item(2, "b"), item(3, "c")], true);
+    let before = rows(&harness);
+    assert_eq!(labels(&harness), vec!["a", "b", "c"]);
+
+    set_items(&mut harness, vec![item(3, "c"), item(1, "a"), item(2, "b")]);
+    let after = rows(&harness);
+    assert_eq!(labels(&harness), vec!["c", "a", "b"]);
+    assert!(Rc::ptr_eq(&after[0].0, &before[2].0));
+    assert!(Rc::ptr_eq(&after[1].0, &before[0].0));
+    assert!(Rc::ptr_eq(&after[2].0, &before[1].0));
+    assert_eq!(
+        after.iter().map(|(_, i, _)| *i).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+}
+
+#[test]
+fn test_keyed_for_mounts_only_inserted_elements() {
+    let mut harness = mount_repeat(vec![item(1, "a"), item(2, "b")], true);
+    let before = rows(&harness);
+
+    set_items(&mut harness, vec![item(2, "b"), item(4, "d"), item(1, "a")]);
+    let after = rows(&harness);
+    assert_eq!(labels(&harness), vec!["b", "d", "a"]);
+    assert!(Rc::ptr_eq(&after[0].0, &before[1].0));
+    assert!(Rc::ptr_eq(&after[2].0, &before[0].0));
+    assert!(!Rc::ptr_eq(&after[1].0, &before[0].0) && !Rc::ptr_eq(&after[1].0, &before[1].0));
+
+    set_items(&mut harness, vec![item(1, "a")]);
+    let last = rows(&harness);
+    assert_eq!(last.len(), 1);
+    assert!(Rc::ptr_eq(&last[0].0, &before[0].0));
+}
+
+#[test]
+fn test_keyed_for_refreshes_data_under_unchanged_keys() {
+    let mut harness = mount_repeat(vec![item(1, "a"), item(2, "b")], true);
+    let before = rows(&harness);
+
+    set_items(&mut harness, vec![item(1, "x"), item(2, "y")]);
+    let after = rows(&harness);
+    assert_eq!(labels(&harness), vec!["x", "y"]);
+    assert!(Rc::ptr_eq(&after[0].0, &before[0].0));
+    assert!(Rc::ptr_eq(&after[1].0, &before[1].0));
+}
+
+#[test]
+fn test_keyed_for_matches_elements_without_keys_by_position() {
+    let mut harness = mount_repeat(vec![item(-1, "a"), item(2, "b")], true);
+    let before = rows(&harness);
+
+    set_items(
+        &mut harness,
+        vec![item(-1, "x"), item(2, "b"), item(-3, "c")],
+    );
+    let after = rows(&harness);
+    assert_eq!(labels(&harness), vec!["x", "b", "c"]);
+    assert!(Rc::ptr_eq(&after[0].0, &before[0].0));
+    assert!(Rc::ptr_eq(&after[1].0, &before[1].0));
+
+    set_items(&mut harness, vec![item(2, "b"), item(-1, "x")]);
+    let last = rows(&harness);
+    assert_eq!(labels(&harness), vec!["b", "x"]);
+    assert!(Rc::ptr_eq(&last[0].0, &before[1].0));
+}
+
+#[test]
+fn test_unkeyed_for_reuses_nodes_by_position_across_reorders() {
+    let mut harness = mount_repeat(vec![item(1, "a"), item(2, "b"), item(3, "c")], false);
+    let before = rows(&harness);
+
+    set_items(&mut harness, vec![item(3, "c"), item(1, "a"), item(2, "b")]);
+    let after = rows(&harness);
+    // Each position keeps its node, which now sees the element moved there
+    assert_eq!(labels(&harness), vec!["c", "a", "b"]);
+    for (after, before) in after.iter().zip(&before) {
+        assert!(Rc::ptr_eq(&after.0, &before.0));
+    }
+    assert_eq!(
+        after.iter().map(|(_, i, _)| *i).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+}
+
+#[test]
+fn test_unkeyed_for_appends_nodes_for_inserted_elements() {
+    let mut harness = mount_repeat(vec![item(1, "a"), item(2, "b")], false);
+    let before = rows(&harness);
+
+    set_items(&mut harness, vec![item(4, "d"), item(1, "a"), item(2, "b")]);
+    let after = rows(&harness);
+    assert_eq!(labels(&harness), vec!["d", "a", "b"]);
+    assert!(Rc::ptr_eq(&after[0].0, &before[0].0));
+    assert!(Rc::ptr_eq(&after[1].0, &before[1].0));
+    assert!(!Rc::ptr_eq(&after[2].0, &before[0].0) && !Rc::ptr_eq(&after[2].0, &before[1].0));
+    assert_eq!(after[2].1, 2);
+}
+
+#[test]
+fn test_unkeyed_for_unmounts_trailing_nodes_of_removed_elements() {
+    let mut harness = mount_repeat(vec![item(1, "a"), item(2, "b"), item(3, "c")], false);
+    let before = rows(&harness);
+
+    set_items(&mut harness, vec![item(1, "a"), item(3, "c")]);
+    let after = rows(&harness);
+    assert_eq!(labels(&harness), vec!["a", "c"]);
+    assert!(Rc::ptr_eq(&after[0].0, &before[0].0));
+    assert!(Rc::ptr_eq(&after[1].0, &before[1].0));
+
+    set_items(&mut harness, vec![]);
+    assert!(rows(&harness).is_empty());
+}
@@ -0,0 +1,141 @@
+use std::rc::Rc;
+
+use pax_runtime::api::Layer;
+use pax_runtime::testing::TestHarness;
+use pax_runtime::{
+    BaseInstance, ExpandedNode, InstanceFlags, InstanceNode, InstantiationArgs, ScrollState,
+};
+
+mod common;
+use common::{args, mount, Leaf, Row};
+
+/// A scroll container whose content is always scrolled 120px down
+struct Scrolled {
+    base: BaseInstance,
+}
+
+impl InstanceNode for Scrolled {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self> {
+        Rc::new(Scrolled {
+            base: BaseInstance::new(
+                args,
+                InstanceFlags {
+                    invisible_to_slot: false,
+                    invisible_to_raycasting: true,
+                    layer: Layer::DontCare,
+                    is_component: false,
+                },
+            ),
+        })
+    }
+
+    fn get_scroll_offset(&self, _expanded_node: &ExpandedNode) -> Option<(f64, f64)> {
+        Some((0.0, 120.0))
+    }
+
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Scrolled").finish()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
+
+fn root_scroll_offset(harness: &TestHarness) -> Option<(f64, f64)> {
+    let engine = harness.engine();
+    engine
+        .root_node
+        .get_node_context(&engine.runtime_context)
+        .scroll_offset()
+}
+
+fn vertical_scroller() -> ScrollState {
+    let mut state = ScrollState::default();
+    state.set_extent((100.0, 100.0), (100.0, 500.0), (false, true));
+    state
+}
+
+#[test]
+fn test_scroll_by_is_clamped_to_content_and_enabled_axes() {
+    let mut state = vertical_scroller();
+    state.scroll_by(30.0, 50.0);
+    assert_eq!(state.offset(), (0.0, 50.0));
+
+    state.scroll_by(0.0, 1000.0);
+    assert_eq!(state.offset(), (0.0, 400.0));
+
+    state.scroll_by(0.0, -1000.0);
+    assert_eq!(state.offset(), (0.0, 0.0));
+    assert_eq!(state.take_pending_sync(), None);
+}
+
+#[test]
+fn test_momentum_coasts_after_input_stops() {
+    let mut state = vertical_scroller();
+    state.scroll_by(0.0, 20.0);
+    assert!(!state.tick());
+    assert_eq!(state.offset(), (0.0, 20.0));
+
+    assert!(state.tick());
+    let after_one = state.offset().1;
+    assert!(after_one > 20.0);
+    assert!(state.tick());
+    assert!(state.offset().1 - after_one < after_one - 20.0);
+
+    while state.tick() {}
+    let resting = state.offset();
+    assert!(resting.1 > 100.0 && resting.1 < 400.0);
+    assert!(!state.tick());
+    assert_eq!(state.offset(), resting);
+    assert_eq!(state.take_pending_sync(), Some(resting));
+}
+
+#[test]
+fn test_scroll_to_stops_momentum_and_requests_sync() {
+    let mut state = vertical_scroller();
+    state.scroll_by(0.0, 20.0);
+    state.tick();
+    state.scroll_to(0.0, 1000.0);
+    assert_eq!(state.offset(), (0.0, 400.0));
+    assert!(!state.tick());
+    assert_eq!(state.take_pending_sync(), Some((0.0, 400.0)));
+    assert_eq!(state.take_pending_sync(), None);
+
+    state.set_extent((100.0, 100.0), (100.0, 300.0), (false, true));
+    assert_eq!(state.offset(), (0.0, 200.0));
+    assert_eq!(state.take_pending_sync(), Some((0.0, 200.0)));
+}
+
+#[test]
+fn test_movement_is_reported_once_and_clamped() {
+    let mut state = vertical_scroller();
+    assert_eq!(state.take_movement(), None);
+
+    // Native input past the end of the content only moves it as far as it goes
+    state.scroll_by(0.0, 350.0);
+    state.scroll_by(0.0, 350.0);
+    assert_eq!(state.take_movement(), Some((0.0, 400.0)));
+    assert_eq!(state.take_movement(), None);
+
+    state.scroll_to(0.0, 100.0);
+    assert_eq!(state.take_movement(), Some((0.0, -300.0)));
+}
+
+#[test]
+fn test_node_context_reads_offset_of_scroll_container_in_template() {
+    let mut row = args(|| ());
+    row.children = Some(std::cell::RefCell::new(vec![
+        Leaf::instantiate(args(|| ())) as Rc<dyn InstanceNode>,
+        Scrolled::instantiate(args(|| ())),
+    ]));
+    let harness = mount(|| (), vec![Row::instantiate(row)], vec![]);
+    assert_eq!(root_scroll_offset(&harness), Some((0.0, 120.0)));
+
+    let harness = mount(|| (), vec![Leaf::instantiate(args(|| ()))], vec![]);
+    assert_eq!(root_scroll_offset(&harness), None);
+}
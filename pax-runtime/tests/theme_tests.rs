@@ -0,0 +1,63 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use pax_runtime::api::{Color, Numeric, Size, StringBox, Theme, ThemeValue};
+use pax_runtime::{ExpressionContext, ExpressionTable, RuntimePropertiesStackFrame};
+
+fn themed_expression_table() -> ExpressionTable {
+    // What the compiler generates for `{theme.primary}`
+    let evaluator = |ec: ExpressionContext| -> Box<dyn Any> {
+        let primary = ec.theme.resolve("primary");
+        let ret: Color = primary.into();
+        Box::new(ret)
+    };
+    ExpressionTable {
+        table: HashMap::from([(0, Box::new(evaluator) as Box<_>)]),
+        theme: Default::default(),
+    }
+}
+
+#[test]
+fn test_theme_tokens_convert_to_property_types() {
+    let theme = Theme::new("light")
+        .with_color("primary", Color::rgb(0.into(), 0.into(), 255.into()))
+        .with_size("gutter", Size::Pixels(12.into()))
+        .with_font("body", "Inter")
+        .with_radius("rounded", 6);
+
+    assert!(matches!(theme.get("primary"), Some(ThemeValue::Color(_))));
+    assert!(theme.get("missing").is_none());
+    let gutter: Size = theme.resolve("gutter").into();
+    assert_eq!(gutter.expect_pixels().to_float(), 12.0);
+    let body: StringBox = theme.resolve("body").into();
+    assert_eq!(body.string, "Inter");
+    let rounded: Numeric = theme.resolve("rounded").into();
+    assert_eq!(rounded.to_float(), 6.0);
+}
+
+#[test]
+fn test_switching_theme_recomputes_expressions() {
+    let table = themed_expression_table();
+    let stack = RuntimePropertiesStackFrame::new(std::rc::Rc::new(std::cell::RefCell::new(())));
+    let primary = |table: &ExpressionTable| {
+        let value = table.compute_vtable_value(&stack, 0);
+        value.downcast::<Color>().unwrap().to_rgba_0_1()
+    };
+
+    table.set_theme(Theme::new("light").with_color("primary", Color::WHITE));
+    assert_eq!(primary(&table), [1.0, 1.0, 1.0, 1.0]);
+    table.set_theme(Theme::new("dark").with_color("primary", Color::BLACK));
+    assert_eq!(primary(&table), [0.0, 0.0, 0.0, 1.0]);
+    assert_eq!(table.get_theme().name, "dark");
+}
+
+#[test]
+fn test_missing_or_mistyped_tokens_fall_back_to_defaults() {
+    let theme = Theme::new("light").with_font("body", "Inter");
+
+    assert!(matches!(theme.resolve("missing"), ThemeValue::Missing));
+    let missing: Numeric = theme.resolve("missing").into();
+    assert_eq!(missing.to_float(), 0.0);
+    let mistyped: Color = theme.resolve("body").into();
+    assert_eq!(mistyped.to_rgba_0_1(), Color::default().to_rgba_0_1());
+}
@@ -0,0 +1,43 @@
+use pax_runtime::api::timelines::PropertyTimeline;
+use pax_runtime::api::{EasingCurve, PropertyInstance, Timeline};
+
+#[test]
+fn test_property_timeline_evaluates_segments() {
+    let mut property = PropertyTimeline::from_keyframes(vec![
+        (60, 10.0, EasingCurve::Linear),
+        (10, 0.0, EasingCurve::Linear),
+        (110, 0.0, EasingCurve::Linear),
+    ]);
+
+    property.evaluate(0);
+    assert_eq!(*property.get(), 0.0);
+    property.evaluate(35);
+    assert_eq!(*property.get(), 5.0);
+    property.evaluate(85);
+    assert_eq!(*property.get(), 5.0);
+    property.evaluate(200);
+    assert_eq!(*property.get(), 0.0);
+}
+
+#[test]
+fn test_timeline_playback() {
+    let mut timeline = Timeline::new(3);
+    timeline.play();
+    for frame in 0..5 {
+        timeline.advance(frame);
+    }
+    // non-looping timelines stop at their last frame
+    assert_eq!(timeline.playhead_position, 3);
+    assert!(!timeline.is_playing);
+
+    timeline.set_looping(true);
+    timeline.reverse();
+    timeline.play();
+    timeline.advance(5);
+    timeline.advance(5);
+    assert_eq!(timeline.playhead_position, 2);
+
+    timeline.seek(0);
+    timeline.advance(6);
+    assert_eq!(timeline.playhead_position, 2);
+}
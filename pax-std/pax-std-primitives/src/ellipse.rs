@@ -1,12 +1,14 @@
 use kurbo::{Rect, Shape};
-use pax_runtime::api::{Layer, RenderContext};
+use pax_runtime::api::{BlendMode, Layer, RenderContext};
 use pax_runtime::{declarative_macros::handle_vtable_update, BaseInstance};
-use pax_std::{primitives::Ellipse, types::Fill};
+use pax_std::primitives::Ellipse;
 
 use pax_runtime::{ExpandedNode, InstanceFlags, InstanceNode, InstantiationArgs, RuntimeContext};
 
 use std::rc::Rc;
 
+use crate::{fill_with_effects, update_stroke};
+
 /// A basic 2D vector ellipse, drawn to fill the bounds specified
 /// by `size`, transformed by `transform`
 pub struct EllipseInstance {
@@ -48,29 +50,34 @@ impl InstanceNode for EllipseInstance {
             let accuracy = 0.1;
             let bez_path = ellipse.to_path(accuracy);
 
-            let transformed_bez_path = Into::<kurbo::Affine>::into(tab.transform) * bez_path;
-            let duplicate_transformed_bez_path = transformed_bez_path.clone();
-
-            let color = if let Fill::Solid(properties_color) = properties.fill.get() {
-                properties_color.to_piet_color()
-            } else {
-                unimplemented!("gradients not supported on ellipse")
-            };
+            let transformed_bez_path =
+                Into::<kurbo::Affine>::into(tab.transform) * bez_path.clone();
 
             let layer_id = format!("{}", expanded_node.occlusion_id.borrow());
-            rc.fill(&layer_id, transformed_bez_path, &color.into());
+            rc.set_blend_mode(&layer_id, *properties.blend_mode.get());
+            fill_with_effects(
+                rc,
+                &layer_id,
+                bez_path,
+                tab,
+                properties.fill.get(),
+                properties.shadow.get(),
+                properties.blur.get().to_float(),
+            );
 
             //hack to address "phantom stroke" bug on Web
             let width: f64 = *&properties.stroke.get().width.get().expect_pixels().into();
 
             if width > f64::EPSILON {
-                rc.stroke(
+                rc.stroke_styled(
                     &layer_id,
-                    duplicate_transformed_bez_path,
+                    transformed_bez_path,
                     &properties.stroke.get().color.get().to_piet_color().into(),
                     width,
+                    &properties.stroke.get().to_piet_stroke_style(),
                 );
             }
+            rc.set_blend_mode(&layer_id, BlendMode::Normal);
         });
     }
 
@@ -100,12 +107,36 @@ impl InstanceNode for EllipseInstance {
                 &mut properties.stroke,
                 context.globals(),
             );
+            update_stroke(
+                context.expression_table(),
+                &expanded_node.stack,
+                properties.stroke.get_mut(),
+                context.globals(),
+            );
             handle_vtable_update(
                 context.expression_table(),
                 &expanded_node.stack,
                 &mut properties.fill,
                 context.globals(),
             );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.shadow,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.blur,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.blend_mode,
+                context.globals(),
+            );
         });
     }
 }
@@ -0,0 +1,116 @@
+use pax_runtime::api::{Axis, Layer};
+use pax_runtime::declarative_macros::handle_vtable_update;
+use pax_runtime::{
+    Align, BaseInstance, ExpandedNode, FlexLayout, InstanceFlags, InstanceNode, InstantiationArgs,
+    Justify, Layout, RuntimeContext,
+};
+use pax_std::primitives::Flex;
+use pax_std::types::{FlexAlign, FlexDirection, FlexJustify};
+use std::rc::Rc;
+
+/// Lays out its children as flex items: the engine sizes and places each child
+/// according to the [`FlexLayout`] returned by `get_layout`.
+pub struct FlexInstance {
+    base: BaseInstance,
+}
+
+impl InstanceNode for FlexInstance {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self>
+    where
+        Self: Sized,
+    {
+        Rc::new(Self {
+            base: BaseInstance::new(
+                args,
+                InstanceFlags {
+                    invisible_to_slot: false,
+                    invisible_to_raycasting: true,
+                    layer: Layer::DontCare,
+                    is_component: false,
+                },
+            ),
+        })
+    }
+
+    fn update(self: Rc<Self>, expanded_node: &Rc<ExpandedNode>, context: &mut RuntimeContext) {
+        expanded_node.with_properties_unwrapped(|properties: &mut Flex| {
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.direction,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.wrap,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.justify,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.align,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.gap,
+                context.globals(),
+            );
+        });
+    }
+
+    fn get_layout(&self, expanded_node: &ExpandedNode) -> Option<Layout> {
+        let bounds = expanded_node
+            .layout_properties
+            .borrow()
+            .as_ref()
+            .map(|layout| layout.computed_tab.bounds)
+            .unwrap_or_default();
+        expanded_node.with_properties_unwrapped(|properties: &mut Flex| {
+            let main_axis = match properties.direction.get() {
+                FlexDirection::Row => Axis::X,
+                FlexDirection::Column => Axis::Y,
+            };
+            Some(Layout::Flex(FlexLayout {
+                main_axis,
+                wrap: *properties.wrap.get(),
+                justify: match properties.justify.get() {
+                    FlexJustify::Start => Justify::Start,
+                    FlexJustify::End => Justify::End,
+                    FlexJustify::Center => Justify::Center,
+                    FlexJustify::SpaceBetween => Justify::SpaceBetween,
+                    FlexJustify::SpaceAround => Justify::SpaceAround,
+                    FlexJustify::SpaceEvenly => Justify::SpaceEvenly,
+                },
+                align: match properties.align.get() {
+                    FlexAlign::Start => Align::Start,
+                    FlexAlign::End => Align::End,
+                    FlexAlign::Center => Align::Center,
+                    FlexAlign::Stretch => Align::Stretch,
+                },
+                gap: properties.gap.get().evaluate(bounds, main_axis),
+            }))
+        })
+    }
+
+    #[cfg(debug_assertions)]
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&pax_runtime::ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Flex").finish_non_exhaustive()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
@@ -3,11 +3,16 @@ use core::option::Option::Some;
 
 use std::rc::Rc;
 
-use kurbo::{Affine, BezPath};
+use kurbo::Affine;
 use pax_runtime::api::{Layer, RenderContext, Size};
 use pax_runtime::{
-    BaseInstance, ExpandedNode, InstanceFlags, InstanceNode, InstantiationArgs, RuntimeContext,
+    declarative_macros::handle_vtable_update, BaseInstance, ExpandedNode, InstanceFlags,
+    InstanceNode, InstantiationArgs, RuntimeContext,
 };
+use pax_std::primitives::Frame;
+use pax_std::types::ClipShape;
+
+use crate::draw_shadow;
 
 /// A primitive that gathers children underneath a single render node with a shared base transform,
 /// like [`Group`], except [`Frame`] has the option of clipping rendering outside
@@ -38,10 +43,38 @@ impl InstanceNode for FrameInstance {
         })
     }
 
+    fn update(self: Rc<Self>, expanded_node: &Rc<ExpandedNode>, context: &mut RuntimeContext) {
+        expanded_node.with_properties_unwrapped(|properties: &mut Frame| {
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.shadow,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.clip_shape,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.blur,
+                context.globals(),
+            );
+        });
+    }
+
     fn get_clipping_size(&self, expanded_node: &ExpandedNode) -> Option<(Size, Size)> {
         Some(self.get_size(expanded_node))
     }
 
+    fn get_content_blur(&self, expanded_node: &ExpandedNode) -> f64 {
+        expanded_node
+            .with_properties_unwrapped(|properties: &mut Frame| properties.blur.get().to_float())
+    }
+
     // fn handle_native_patches(
     //     &mut self,
     //     rtc: &mut RenderTreeContext<R>,
@@ -115,16 +148,29 @@ impl InstanceNode for FrameInstance {
         let comp_props = comp_props.as_ref().unwrap();
         let transform = comp_props.computed_tab.transform;
         let bounding_dimens = comp_props.computed_tab.bounds;
-        let width = bounding_dimens.0;
-        let height = bounding_dimens.1;
 
-        let mut bez_path = BezPath::new();
-        bez_path.move_to((0.0, 0.0));
-        bez_path.line_to((width, 0.0));
-        bez_path.line_to((width, height));
-        bez_path.line_to((0.0, height));
-        bez_path.line_to((0.0, 0.0));
-        bez_path.close_path();
+        // Cast outside of the clip, behind everything the frame contains
+        let layer_id = format!("{}", expanded_node.occlusion_id.borrow());
+        let bez_path = expanded_node.with_properties_unwrapped(|properties: &mut Frame| {
+            let bez_path = properties
+                .clip_shape
+                .get()
+                .to_bez_path(bounding_dimens)
+                .unwrap_or_else(|message| {
+                    log::warn!("{}", message);
+                    ClipShape::Rectangle
+                        .to_bez_path(bounding_dimens)
+                        .expect("a rectangle always has an outline")
+                });
+            draw_shadow(
+                rcs,
+                &layer_id,
+                &bez_path,
+                transform.into(),
+                properties.shadow.get(),
+            );
+            bez_path
+        });
 
         let transformed_bez_path = <Affine>::from(transform) * bez_path;
 
@@ -178,8 +224,6 @@ impl InstanceNode for FrameInstance {
         f: &mut std::fmt::Formatter,
         expanded_node: Option<&ExpandedNode>,
     ) -> std::fmt::Result {
-        use pax_std::primitives::Frame;
-
         match expanded_node {
             Some(expanded_node) => expanded_node
                 .with_properties_unwrapped(|_f: &mut Frame| f.debug_struct("Frame").finish()),
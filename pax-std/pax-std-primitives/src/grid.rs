@@ -0,0 +1,105 @@
+use pax_runtime::api::{Axis, Layer};
+use pax_runtime::declarative_macros::handle_vtable_update;
+use pax_runtime::{
+    BaseInstance, ExpandedNode, GridLayout, InstanceFlags, InstanceNode, InstantiationArgs, Layout,
+    RuntimeContext,
+};
+use pax_std::primitives::Grid;
+use pax_std::types::GridTrack;
+use std::rc::Rc;
+
+/// Lays out its children in grid cells: the engine sizes and places each child
+/// according to the [`GridLayout`] returned by `get_layout`.
+pub struct GridInstance {
+    base: BaseInstance,
+}
+
+impl InstanceNode for GridInstance {
+    fn instantiate(args: InstantiationArgs) -> Rc<Self>
+    where
+        Self: Sized,
+    {
+        Rc::new(Self {
+            base: BaseInstance::new(
+                args,
+                InstanceFlags {
+                    invisible_to_slot: false,
+                    invisible_to_raycasting: true,
+                    layer: Layer::DontCare,
+                    is_component: false,
+                },
+            ),
+        })
+    }
+
+    fn update(self: Rc<Self>, expanded_node: &Rc<ExpandedNode>, context: &mut RuntimeContext) {
+        expanded_node.with_properties_unwrapped(|properties: &mut Grid| {
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.columns,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.rows,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.column_gap,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.row_gap,
+                context.globals(),
+            );
+        });
+    }
+
+    fn get_layout(&self, expanded_node: &ExpandedNode) -> Option<Layout> {
+        let bounds = expanded_node
+            .layout_properties
+            .borrow()
+            .as_ref()
+            .map(|layout| layout.computed_tab.bounds)
+            .unwrap_or_default();
+        let tracks = |tracks: &Vec<GridTrack>| {
+            tracks
+                .iter()
+                .map(|track| match track {
+                    GridTrack::Fixed(size) => pax_runtime::GridTrack::Fixed(*size),
+                    GridTrack::Fraction(fraction) => {
+                        pax_runtime::GridTrack::Fraction(fraction.to_float())
+                    }
+                    GridTrack::Auto => pax_runtime::GridTrack::Auto,
+                })
+                .collect()
+        };
+        expanded_node.with_properties_unwrapped(|properties: &mut Grid| {
+            Some(Layout::Grid(GridLayout {
+                columns: tracks(properties.columns.get()),
+                rows: tracks(properties.rows.get()),
+                column_gap: properties.column_gap.get().evaluate(bounds, Axis::X),
+                row_gap: properties.row_gap.get().evaluate(bounds, Axis::Y),
+            }))
+        })
+    }
+
+    #[cfg(debug_assertions)]
+    fn resolve_debug(
+        &self,
+        f: &mut std::fmt::Formatter,
+        _expanded_node: Option<&pax_runtime::ExpandedNode>,
+    ) -> std::fmt::Result {
+        f.debug_struct("Grid").finish_non_exhaustive()
+    }
+
+    fn base(&self) -> &BaseInstance {
+        &self.base
+    }
+}
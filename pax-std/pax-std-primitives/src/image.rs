@@ -1,4 +1,5 @@
-use pax_runtime::{api::math::Point2, api::RenderContext};
+use kurbo::Shape;
+use pax_runtime::api::{Axis, BlendMode, ImageLoad, RenderContext};
 use pax_std::primitives::Image;
 use std::{cell::RefCell, collections::HashMap};
 
@@ -8,11 +9,24 @@ use pax_runtime::{
     InstanceNode, InstantiationArgs, RuntimeContext,
 };
 use std::rc::Rc;
-/// An Image (decoded by chassis), drawn to the bounds specified
-/// by `size`, transformed by `transform`
+
+/// Progress of the picture shown by one `Image`, for firing `@image_load`
+#[derive(Default)]
+struct LoadState {
+    path: String,
+    /// Size of the picture, once the renderer has it
+    size: Option<(f64, f64)>,
+    /// Whether `@image_load` has fired for `path`
+    reported: bool,
+}
+
+/// An Image (decoded by chassis, or supplied as pixels by the app), drawn within the bounds
+/// specified by `size` according to `object_fit`, transformed by `transform`.
+/// `@image_load` fires on the tick after the picture is first drawn.
 pub struct ImageInstance {
     base: BaseInstance,
     last_patches: RefCell<HashMap<Vec<u32>, pax_message::ImagePatch>>,
+    load_states: RefCell<HashMap<Vec<u32>, LoadState>>,
 }
 
 impl InstanceNode for ImageInstance {
@@ -31,24 +45,62 @@ impl InstanceNode for ImageInstance {
                 },
             ),
             last_patches: Default::default(),
+            load_states: Default::default(),
         })
     }
 
     fn update(self: Rc<Self>, expanded_node: &Rc<ExpandedNode>, context: &mut RuntimeContext) {
         //Doesn't need to expand any children
-        expanded_node.with_properties_unwrapped(|properties: &mut Image| {
-            handle_vtable_update(
-                context.expression_table(),
-                &expanded_node.stack,
-                &mut properties.path,
-                context.globals(),
-            );
+        let path = expanded_node.with_properties_unwrapped(|properties: &mut Image| {
+            let tbl = context.expression_table();
+            let stk = &expanded_node.stack;
+            let glb = context.globals();
+            handle_vtable_update(tbl, stk, &mut properties.path, glb);
+            handle_vtable_update(tbl, stk, &mut properties.object_fit, glb);
+            handle_vtable_update(tbl, stk, &mut properties.source_x, glb);
+            handle_vtable_update(tbl, stk, &mut properties.source_y, glb);
+            handle_vtable_update(tbl, stk, &mut properties.source_width, glb);
+            handle_vtable_update(tbl, stk, &mut properties.source_height, glb);
+            handle_vtable_update(tbl, stk, &mut properties.placeholder, glb);
+            handle_vtable_update(tbl, stk, &mut properties.blend_mode, glb);
+            properties.path.get().string.clone()
         });
+
+        let loaded = {
+            let mut load_states = self.load_states.borrow_mut();
+            let state = load_states
+                .entry(expanded_node.id_chain.clone())
+                .or_default();
+            if state.path != path {
+                *state = LoadState {
+                    path: path.clone(),
+                    ..Default::default()
+                };
+            }
+            match state.size {
+                Some((width, height)) if !state.reported => {
+                    state.reported = true;
+                    Some(ImageLoad {
+                        path,
+                        width,
+                        height,
+                    })
+                }
+                _ => None,
+            }
+        };
+        if let Some(args) = loaded {
+            expanded_node.dispatch_image_load(args, context.globals(), context);
+        }
     }
 
     fn handle_native_patches(&self, expanded_node: &ExpandedNode, rtc: &mut RuntimeContext) {
         let val =
             expanded_node.with_properties_unwrapped(|props: &mut Image| props.path.get().clone());
+        // Pixels supplied by the app go straight to the renderer; there is nothing for the chassis to load
+        if rtc.has_image_buffer(&val.string) {
+            return;
+        }
         let mut new_message: ImagePatch = Default::default();
         new_message.id_chain = expanded_node.id_chain.clone();
         let mut last_patches = self.last_patches.borrow_mut();
@@ -86,17 +138,75 @@ impl InstanceNode for ImageInstance {
         let transform = comp_props.computed_tab.transform;
         let bounding_dimens = comp_props.computed_tab.bounds;
 
-        let transformed_bounds = kurbo::Rect::new(0.0, 0.0, bounding_dimens.0, bounding_dimens.1);
+        let (path, fit, source, placeholder, blend_mode) =
+            expanded_node.with_properties_unwrapped(|props: &mut Image| {
+                (
+                    props.path.get().string.clone(),
+                    props.object_fit.get().clone(),
+                    [
+                        *props.source_x.get(),
+                        *props.source_y.get(),
+                        *props.source_width.get(),
+                        *props.source_height.get(),
+                    ],
+                    props.placeholder.get().clone(),
+                    *props.blend_mode.get(),
+                )
+            });
+
+        let image_size = rc.image_size(&path);
+        if let Some(state) = self
+            .load_states
+            .borrow_mut()
+            .get_mut(&expanded_node.id_chain)
+        {
+            if state.path == path {
+                state.size = image_size;
+            }
+        }
 
-        let path =
-            expanded_node.with_properties_unwrapped(|props: &mut Image| props.path.get().clone());
         let layer_id = format!("{}", expanded_node.occlusion_id.borrow());
         rc.save(&layer_id);
+        rc.set_blend_mode(&layer_id, blend_mode);
         rc.transform(&layer_id, transform.into());
-        rc.draw_image(&layer_id, &path.string, transformed_bounds);
+        match image_size {
+            Some(image_size) => {
+                let [x, y, width, height] = source;
+                let x = x.evaluate(image_size, Axis::X);
+                let y = y.evaluate(image_size, Axis::Y);
+                let source_rect = kurbo::Rect::new(
+                    x,
+                    y,
+                    x + width.evaluate(image_size, Axis::X),
+                    y + height.evaluate(image_size, Axis::Y),
+                )
+                .intersect(kurbo::Rect::new(0.0, 0.0, image_size.0, image_size.1));
+                for (src_rect, dst_rect) in fit.draw_rects(source_rect, bounding_dimens) {
+                    rc.draw_image_area(&layer_id, &path, src_rect, dst_rect);
+                }
+            }
+            None => {
+                let bounds = kurbo::Rect::new(0.0, 0.0, bounding_dimens.0, bounding_dimens.1);
+                rc.fill(
+                    &layer_id,
+                    bounds.to_path(0.1),
+                    &placeholder.to_piet_color().into(),
+                );
+            }
+        }
+        rc.set_blend_mode(&layer_id, BlendMode::Normal);
         rc.restore(&layer_id);
     }
 
+    fn handle_unmount(&self, expanded_node: &Rc<ExpandedNode>, _context: &mut RuntimeContext) {
+        self.last_patches
+            .borrow_mut()
+            .remove(&expanded_node.id_chain);
+        self.load_states
+            .borrow_mut()
+            .remove(&expanded_node.id_chain);
+    }
+
     fn base(&self) -> &BaseInstance {
         &self.base
     }
@@ -1,15 +1,26 @@
+pub mod button;
+pub mod checkbox;
+pub mod dropdown;
 pub mod ellipse;
+pub mod flex;
 pub mod frame;
+pub mod grid;
 pub mod group;
 pub mod image;
 pub mod path;
 pub mod rectangle;
-// pub mod scroller;
-pub mod button;
-pub mod checkbox;
+pub mod scroller;
 pub mod text;
 pub mod textbox;
 
+use std::rc::Rc;
+
+use kurbo::{Affine, BezPath, Shape};
+use pax_runtime::api::RenderContext;
+use pax_runtime::declarative_macros::handle_vtable_update;
+use pax_runtime::{ExpressionTable, Globals, RuntimePropertiesStackFrame, TransformAndBounds};
+use pax_std::types::{Fill, Shadow, Stroke};
+
 fn patch_if_needed<T: PartialEq + Clone>(
     old_state: &mut Option<T>,
     patch: &mut Option<T>,
@@ -24,3 +35,56 @@ fn patch_if_needed<T: PartialEq + Clone>(
         false
     }
 }
+
+/// Updates the nested properties of a `Stroke`, once the `Stroke` itself has been updated
+fn update_stroke(
+    table: &ExpressionTable,
+    stack: &Rc<RuntimePropertiesStackFrame>,
+    stroke: &mut Stroke,
+    globals: &Globals,
+) {
+    handle_vtable_update(table, stack, &mut stroke.color, globals);
+    handle_vtable_update(table, stack, &mut stroke.width, globals);
+    handle_vtable_update(table, stack, &mut stroke.join, globals);
+    handle_vtable_update(table, stack, &mut stroke.cap, globals);
+    handle_vtable_update(table, stack, &mut stroke.dash, globals);
+    handle_vtable_update(table, stack, &mut stroke.dash_offset, globals);
+}
+
+/// Fills `path` (in the coordinate space of the node laid out at `tab`) with `fill` blurred by
+/// `blur` px, after casting `shadow` behind it
+fn fill_with_effects(
+    rc: &mut dyn RenderContext,
+    layer_id: &str,
+    path: BezPath,
+    tab: &TransformAndBounds,
+    fill: &Fill,
+    shadow: &Shadow,
+    blur: f64,
+) {
+    let transform: Affine = tab.transform.into();
+    draw_shadow(rc, layer_id, &path, transform, shadow);
+    let transformed_path = transform * path;
+    let brush = fill.to_paint_brush(tab.bounds, transformed_path.bounding_box());
+    rc.fill_blurred(layer_id, transformed_path, &brush, blur);
+}
+
+/// Casts `shadow` behind the outline `path`, offset in its untransformed coordinate space
+fn draw_shadow(
+    rc: &mut dyn RenderContext,
+    layer_id: &str,
+    path: &BezPath,
+    transform: Affine,
+    shadow: &Shadow,
+) {
+    if !shadow.is_visible() {
+        return;
+    }
+    let offset = Affine::translate((shadow.x.to_float(), shadow.y.to_float()));
+    rc.fill_blurred(
+        layer_id,
+        transform * offset * path.clone(),
+        &shadow.color.to_piet_color().into(),
+        shadow.blur.to_float(),
+    );
+}
@@ -1,12 +1,12 @@
-use kurbo::BezPath;
 use pax_runtime::declarative_macros::handle_vtable_update;
 
-use pax_runtime::api::{Layer, RenderContext};
+use pax_runtime::api::{BlendMode, Layer, RenderContext};
 use pax_runtime::{
     BaseInstance, ExpandedNode, InstanceFlags, InstanceNode, InstantiationArgs, RuntimeContext,
 };
 use pax_std::primitives::Path;
-use pax_std::types::{PathElement, Point};
+
+use crate::{fill_with_effects, update_stroke};
 
 use std::rc::Rc;
 
@@ -38,20 +38,13 @@ impl InstanceNode for PathInstance {
             let tbl = context.expression_table();
             let stk = &expanded_node.stack;
             handle_vtable_update(tbl, stk, &mut properties.stroke, context.globals());
-            handle_vtable_update(
-                tbl,
-                stk,
-                &mut properties.stroke.get_mut().color,
-                context.globals(),
-            );
-            handle_vtable_update(
-                tbl,
-                stk,
-                &mut properties.stroke.get_mut().width,
-                context.globals(),
-            );
+            update_stroke(tbl, stk, properties.stroke.get_mut(), context.globals());
             handle_vtable_update(tbl, stk, &mut properties.fill, context.globals());
             handle_vtable_update(tbl, stk, &mut properties.elements, context.globals());
+            handle_vtable_update(tbl, stk, &mut properties.d, context.globals());
+            handle_vtable_update(tbl, stk, &mut properties.shadow, context.globals());
+            handle_vtable_update(tbl, stk, &mut properties.blur, context.globals());
+            handle_vtable_update(tbl, stk, &mut properties.blend_mode, context.globals());
         });
     }
 
@@ -64,75 +57,46 @@ impl InstanceNode for PathInstance {
         let layer_id = format!("{}", expanded_node.occlusion_id.borrow());
 
         expanded_node.with_properties_unwrapped(|properties: &mut Path| {
-            let mut bez_path = BezPath::new();
-
-            let layout_props = expanded_node.layout_properties.borrow();
-            let bounds = layout_props.as_ref().unwrap().computed_tab.bounds;
-
-            let mut itr_elems = properties.elements.get().iter();
-
-            if let Some(elem) = itr_elems.next() {
-                if let &PathElement::Point(x, y) = elem {
-                    bez_path.move_to(Point { x, y }.to_kurbo_point(bounds));
-                } else {
-                    log::warn!("path must start with point");
-                    return;
-                }
-            }
-
-            while let Some(elem) = itr_elems.next() {
-                match elem {
-                    &PathElement::Point(x, y) => {
-                        bez_path.move_to(Point { x, y }.to_kurbo_point(bounds));
-                    }
-                    &PathElement::Line => {
-                        let Some(&PathElement::Point(x, y)) = itr_elems.next() else {
-                            log::warn!("line expects to be followed by a point");
-                            return;
-                        };
-                        bez_path.line_to(Point { x, y }.to_kurbo_point(bounds));
-                    }
-                    &PathElement::Curve(h_x, h_y) => {
-                        let Some(&PathElement::Point(x, y)) = itr_elems.next() else {
-                            log::warn!("curve expects to be followed by a point");
-                            return;
-                        };
-                        bez_path.quad_to(
-                            Point { x: h_x, y: h_y }.to_kurbo_point(bounds),
-                            Point { x, y }.to_kurbo_point(bounds),
-                        );
-                    }
-                    &PathElement::Close => {
-                        bez_path.close_path();
-                    }
-                    PathElement::Empty => (), //no-op
-                }
-            }
-
             let computed_props = expanded_node.layout_properties.borrow();
             let tab = &computed_props.as_ref().unwrap().computed_tab;
 
-            let transformed_bez_path = Into::<kurbo::Affine>::into(tab.transform) * bez_path;
-            let duplicate_transformed_bez_path = transformed_bez_path.clone();
-
-            let color = properties.fill.get().to_piet_color();
-            rc.fill(&layer_id, transformed_bez_path, &color.into());
-            if properties
-                .stroke
-                .get()
-                .width
-                .get()
-                .expect_pixels()
-                .to_float()
-                > f64::EPSILON
-            {
-                rc.stroke(
+            let bez_path = match Path::build_bez_path(
+                properties.elements.get(),
+                &properties.d.get().string,
+                tab.bounds,
+            ) {
+                Ok(bez_path) => bez_path,
+                Err(message) => {
+                    log::warn!("{}", message);
+                    return;
+                }
+            };
+
+            let transformed_bez_path =
+                Into::<kurbo::Affine>::into(tab.transform) * bez_path.clone();
+
+            rc.set_blend_mode(&layer_id, *properties.blend_mode.get());
+            fill_with_effects(
+                rc,
+                &layer_id,
+                bez_path,
+                tab,
+                properties.fill.get(),
+                properties.shadow.get(),
+                properties.blur.get().to_float(),
+            );
+            let stroke = properties.stroke.get();
+            let width: f64 = stroke.width.get().expect_pixels().into();
+            if width > f64::EPSILON {
+                rc.stroke_styled(
                     &layer_id,
-                    duplicate_transformed_bez_path,
-                    &properties.stroke.get().color.get().to_piet_color().into(),
-                    properties.stroke.get().width.get().expect_pixels().into(),
+                    transformed_bez_path,
+                    &stroke.color.get().to_piet_color().into(),
+                    width,
+                    &stroke.to_piet_stroke_style(),
                 );
             }
+            rc.set_blend_mode(&layer_id, BlendMode::Normal);
         });
     }
 
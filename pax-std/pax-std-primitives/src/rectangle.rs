@@ -1,15 +1,15 @@
 use kurbo::{RoundedRect, Shape};
 use pax_runtime::{declarative_macros::handle_vtable_update, BaseInstance};
-use piet::{LinearGradient, RadialGradient};
 
 use pax_runtime::{ExpandedNode, InstanceFlags, InstanceNode, InstantiationArgs, RuntimeContext};
 use pax_std::primitives::Rectangle;
-use pax_std::types::Fill;
 
-use pax_runtime::api::{Layer, RenderContext, Size};
+use pax_runtime::api::{BlendMode, Layer, RenderContext, Size};
 
 use std::rc::Rc;
 
+use crate::{fill_with_effects, update_stroke};
+
 /// A basic 2D vector rectangle, drawn to fill the bounds specified
 /// by `size`, transformed by `transform`
 pub struct RectangleInstance {
@@ -40,28 +40,40 @@ impl InstanceNode for RectangleInstance {
                 &mut properties.stroke,
                 context.globals(),
             );
+            update_stroke(
+                context.expression_table(),
+                &expanded_node.stack,
+                properties.stroke.get_mut(),
+                context.globals(),
+            );
             handle_vtable_update(
                 context.expression_table(),
                 &expanded_node.stack,
-                &mut properties.stroke.get_mut().color,
+                &mut properties.fill,
                 context.globals(),
             );
             handle_vtable_update(
                 context.expression_table(),
                 &expanded_node.stack,
-                &mut properties.stroke.get_mut().width,
+                &mut properties.corner_radii,
                 context.globals(),
             );
             handle_vtable_update(
                 context.expression_table(),
                 &expanded_node.stack,
-                &mut properties.fill,
+                &mut properties.shadow,
                 context.globals(),
             );
             handle_vtable_update(
                 context.expression_table(),
                 &expanded_node.stack,
-                &mut properties.corner_radii,
+                &mut properties.blur,
+                context.globals(),
+            );
+            handle_vtable_update(
+                context.expression_table(),
+                &expanded_node.stack,
+                &mut properties.blend_mode,
                 context.globals(),
             );
 
@@ -95,46 +107,32 @@ impl InstanceNode for RectangleInstance {
             let rect = RoundedRect::new(0.0, 0.0, width, height, properties.corner_radii.get());
             let bez_path = rect.to_path(0.1);
 
-            let transformed_bez_path = Into::<kurbo::Affine>::into(tab.transform) * bez_path;
-            let duplicate_transformed_bez_path = transformed_bez_path.clone();
-
-            match properties.fill.get() {
-                Fill::Solid(color) => {
-                    rc.fill(
-                        &layer_id,
-                        transformed_bez_path,
-                        &color.to_piet_color().into(),
-                    );
-                }
-                Fill::LinearGradient(linear) => {
-                    let linear_gradient = LinearGradient::new(
-                        Fill::to_unit_point(linear.start, (width, height)),
-                        Fill::to_unit_point(linear.end, (width, height)),
-                        Fill::to_piet_gradient_stops(linear.stops.clone()),
-                    );
-                    rc.fill(&layer_id, transformed_bez_path, &linear_gradient.into())
-                }
-                Fill::RadialGradient(radial) => {
-                    let origin = Fill::to_unit_point(radial.start, (width, height));
-                    let center = Fill::to_unit_point(radial.end, (width, height));
-                    let gradient_stops = Fill::to_piet_gradient_stops(radial.stops.clone());
-                    let radial_gradient = RadialGradient::new(radial.radius, gradient_stops)
-                        .with_center(center)
-                        .with_origin(origin);
-                    rc.fill(&layer_id, transformed_bez_path, &radial_gradient.into());
-                }
-            }
+            let transformed_bez_path =
+                Into::<kurbo::Affine>::into(tab.transform) * bez_path.clone();
+
+            rc.set_blend_mode(&layer_id, *properties.blend_mode.get());
+            fill_with_effects(
+                rc,
+                &layer_id,
+                bez_path,
+                tab,
+                properties.fill.get(),
+                properties.shadow.get(),
+                properties.blur.get().to_float(),
+            );
 
             //hack to address "phantom stroke" bug on Web
             let width: f64 = *&properties.stroke.get().width.get().expect_pixels().into();
             if width > f64::EPSILON {
-                rc.stroke(
+                rc.stroke_styled(
                     &layer_id,
-                    duplicate_transformed_bez_path,
+                    transformed_bez_path,
                     &properties.stroke.get().color.get().to_piet_color().into(),
                     width,
+                    &properties.stroke.get().to_piet_stroke_style(),
                 );
             }
+            rc.set_blend_mode(&layer_id, BlendMode::Normal);
         });
     }
 
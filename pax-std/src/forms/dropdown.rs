@@ -0,0 +1,14 @@
+use crate::types::text::TextStyle;
+use pax_engine::api::{Property, StringBox};
+use pax_engine::*;
+
+/// A native select control listing `options`, with the one at index `selected_id` shown.
+/// Picking an option fires `@dropdown_change` with its index; as with `Checkbox`, update
+/// `selected_id` from that handler to keep the control in sync.
+#[pax]
+#[primitive("pax_std_primitives::dropdown::DropdownInstance")]
+pub struct Dropdown {
+    pub options: Property<Vec<StringBox>>,
+    pub selected_id: Property<u32>,
+    pub style: Property<TextStyle>,
+}
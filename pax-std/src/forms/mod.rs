@@ -0,0 +1,6 @@
+pub mod dropdown;
+#[allow(unused_imports)]
+pub mod slider;
+
+pub use dropdown::*;
+pub use slider::*;
@@ -0,0 +1,304 @@
+use crate::primitives::*;
+use crate::types::SliderOrientation;
+use pax_engine::api::{Color, Event, Numeric, Property};
+use pax_engine::*;
+use pax_runtime::api::math::Point2;
+use pax_runtime::api::{
+    KeyDown, MouseDown, MouseMove, MouseOut, MouseUp, NodeContext, PropertyLiteral, SliderChange,
+    TouchEnd, TouchMove, TouchStart, Window,
+};
+/// A draggable control for picking a number between `min` and `max`, snapped to multiples of `step`.
+/// Responds to mouse drags, touch, and (while focused by a press) the arrow, Page Up/Down, Home and End keys.
+///
+/// `value` is controlled: bind it to a property and update that property from `@slider_change`
+/// for two-way binding, e.g. `<Slider value={self.volume} @slider_change=self.set_volume />`.
+#[pax]
+#[custom(Default)]
+#[inlined(
+    <Ellipse
+        x={(self._thumb_x)px}
+        y={(self._thumb_y)px}
+        anchor_x=50%
+        anchor_y=50%
+        width={(self.thumb_size)px}
+        height={(self.thumb_size)px}
+        fill={self.thumb_color}
+    />
+    <Rectangle
+        x={(self._fill_x)px}
+        y={(self._fill_y)px}
+        width={(self._fill_width)px}
+        height={(self._fill_height)px}
+        fill={self.accent}
+        corner_radii={RectangleCornerRadii::radii(self._track_radius, self._track_radius, self._track_radius, self._track_radius)}
+    />
+    <Rectangle
+        x={(self._track_x)px}
+        y={(self._track_y)px}
+        width={(self._track_width)px}
+        height={(self._track_height)px}
+        fill={self.background}
+        corner_radii={RectangleCornerRadii::radii(self._track_radius, self._track_radius, self._track_radius, self._track_radius)}
+    />
+    <Rectangle
+        width=100%
+        height=100%
+        fill={Color::TRANSPARENT}
+        @mouse_down=self.handle_mouse_down
+        @mouse_move=self.handle_mouse_move
+        @mouse_up=self.handle_mouse_up
+        @mouse_out=self.handle_mouse_out
+        @touch_start=self.handle_touch_start
+        @touch_move=self.handle_touch_move
+        @touch_end=self.handle_touch_end
+    />
+
+    @settings {
+        @tick: self.handle_tick
+        @key_down: self.handle_key_down
+    }
+
+)]
+pub struct Slider {
+    pub value: Property<Numeric>,
+    pub min: Property<Numeric>,
+    pub max: Property<Numeric>,
+    /// Values snap to `min` plus a multiple of `step`; `0` allows any value
+    pub step: Property<Numeric>,
+    pub orientation: Property<SliderOrientation>,
+    /// Color of the unfilled part of the track
+    pub background: Property<Color>,
+    /// Color of the part of the track between `min` and the thumb
+    pub accent: Property<Color>,
+    pub thumb_color: Property<Color>,
+    pub track_thickness: Property<Numeric>,
+    /// Diameter of the thumb, which is also the inset of the track from either end of the slider
+    pub thumb_size: Property<Numeric>,
+
+    pub _dragging: Property<bool>,
+    pub _track_x: Property<Numeric>,
+    pub _track_y: Property<Numeric>,
+    pub _track_width: Property<Numeric>,
+    pub _track_height: Property<Numeric>,
+    pub _track_radius: Property<Numeric>,
+    pub _fill_x: Property<Numeric>,
+    pub _fill_y: Property<Numeric>,
+    pub _fill_width: Property<Numeric>,
+    pub _fill_height: Property<Numeric>,
+    pub _thumb_x: Property<Numeric>,
+    pub _thumb_y: Property<Numeric>,
+}
+
+impl Default for Slider {
+    fn default() -> Self {
+        Self {
+            value: Box::new(PropertyLiteral::new(0.into())),
+            min: Box::new(PropertyLiteral::new(0.into())),
+            max: Box::new(PropertyLiteral::new(100.into())),
+            step: Box::new(PropertyLiteral::new(1.into())),
+            orientation: Default::default(),
+            background: Box::new(PropertyLiteral::new(Color::GRAY)),
+            accent: Box::new(PropertyLiteral::new(Color::BLUE)),
+            thumb_color: Box::new(PropertyLiteral::new(Color::BLUE)),
+            track_thickness: Box::new(PropertyLiteral::new(4.into())),
+            thumb_size: Box::new(PropertyLiteral::new(16.into())),
+            _dragging: Default::default(),
+            _track_x: Default::default(),
+            _track_y: Default::default(),
+            _track_width: Default::default(),
+            _track_height: Default::default(),
+            _track_radius: Default::default(),
+            _fill_x: Default::default(),
+            _fill_y: Default::default(),
+            _fill_width: Default::default(),
+            _fill_height: Default::default(),
+            _thumb_x: Default::default(),
+            _thumb_y: Default::default(),
+        }
+    }
+}
+
+impl Slider {
+    pub fn handle_tick(&mut self, ctx: &NodeContext) {
+        let (width, height) = ctx.bounds_self;
+        let thumb = self.thumb_size.get().to_float();
+        let thickness = self.track_thickness.get().to_float();
+        let fraction = self.fraction();
+
+        self._track_radius.set((thickness / 2.0).into());
+        match self.orientation.get() {
+            SliderOrientation::Horizontal => {
+                let length = (width - thumb).max(0.0);
+                let track_y = (height - thickness) / 2.0;
+                let thumb_x = thumb / 2.0 + fraction * length;
+                self._track_x.set((thumb / 2.0).into());
+                self._track_y.set(track_y.into());
+                self._track_width.set(length.into());
+                self._track_height.set(thickness.into());
+                self._fill_x.set((thumb / 2.0).into());
+                self._fill_y.set(track_y.into());
+                self._fill_width.set((fraction * length).into());
+                self._fill_height.set(thickness.into());
+                self._thumb_x.set(thumb_x.into());
+                self._thumb_y.set((height / 2.0).into());
+            }
+            SliderOrientation::Vertical => {
+                let length = (height - thumb).max(0.0);
+                let track_x = (width - thickness) / 2.0;
+                let thumb_y = height - thumb / 2.0 - fraction * length;
+                self._track_x.set(track_x.into());
+                self._track_y.set((thumb / 2.0).into());
+                self._track_width.set(thickness.into());
+                self._track_height.set(length.into());
+                self._fill_x.set(track_x.into());
+                self._fill_y.set(thumb_y.into());
+                self._fill_width.set(thickness.into());
+                self._fill_height.set((fraction * length).into());
+                self._thumb_x.set((width / 2.0).into());
+                self._thumb_y.set(thumb_y.into());
+            }
+        }
+    }
+
+    pub fn handle_mouse_down(&mut self, ctx: &NodeContext, args: Event<MouseDown>) {
+        self.start_drag(ctx, args.mouse.x, args.mouse.y);
+    }
+
+    pub fn handle_mouse_move(&mut self, ctx: &NodeContext, args: Event<MouseMove>) {
+        if *self._dragging.get() {
+            self.set_from_point(ctx, args.mouse.x, args.mouse.y);
+        }
+    }
+
+    pub fn handle_mouse_up(&mut self, _ctx: &NodeContext, _args: Event<MouseUp>) {
+        self._dragging.set(false);
+    }
+
+    pub fn handle_mouse_out(&mut self, _ctx: &NodeContext, _args: Event<MouseOut>) {
+        self._dragging.set(false);
+    }
+
+    pub fn handle_touch_start(&mut self, ctx: &NodeContext, args: Event<TouchStart>) {
+        if let Some(touch) = args.touches.first() {
+            self.start_drag(ctx, touch.x, touch.y);
+        }
+    }
+
+    pub fn handle_touch_move(&mut self, ctx: &NodeContext, args: Event<TouchMove>) {
+        if let Some(touch) = args.touches.first() {
+            if *self._dragging.get() {
+                self.set_from_point(ctx, touch.x, touch.y);
+            }
+        }
+    }
+
+    pub fn handle_touch_end(&mut self, _ctx: &NodeContext, _args: Event<TouchEnd>) {
+        self._dragging.set(false);
+    }
+
+    pub fn handle_key_down(&mut self, ctx: &NodeContext, args: Event<KeyDown>) {
+        if !ctx.is_focused() {
+            return;
+        }
+        let Some(new_value) = Self::value_for_key(
+            &args.keyboard.key,
+            self.value.get().to_float(),
+            self.min.get().to_float(),
+            self.max.get().to_float(),
+            self.step.get().to_float(),
+        ) else {
+            return;
+        };
+        args.prevent_default();
+        self.change_value(ctx, new_value);
+    }
+
+    /// The unsnapped value that pressing `key` moves `value` to: a step for an arrow, ten for
+    /// Page Up/Down, or either end for Home and End.  A `step` of `0` moves by 1% of the range.
+    pub fn value_for_key(key: &str, value: f64, min: f64, max: f64, step: f64) -> Option<f64> {
+        let step = match step {
+            s if s > 0.0 => s,
+            _ => (max - min) / 100.0,
+        };
+        match key {
+            "ArrowRight" | "ArrowUp" => Some(value + step),
+            "ArrowLeft" | "ArrowDown" => Some(value - step),
+            "PageUp" => Some(value + step * 10.0),
+            "PageDown" => Some(value - step * 10.0),
+            "Home" => Some(min),
+            "End" => Some(max),
+            _ => None,
+        }
+    }
+
+    fn start_drag(&mut self, ctx: &NodeContext, x: f64, y: f64) {
+        self._dragging.set(true);
+        self.set_from_point(ctx, x, y);
+    }
+
+    /// Where `value` sits between `min` and `max`, from 0.0 to 1.0
+    fn fraction(&self) -> f64 {
+        let min = self.min.get().to_float();
+        let max = self.max.get().to_float();
+        if max <= min {
+            return 0.0;
+        }
+        ((self.value.get().to_float() - min) / (max - min)).clamp(0.0, 1.0)
+    }
+
+    fn set_from_point(&mut self, ctx: &NodeContext, x: f64, y: f64) {
+        let Some(local) = ctx.local_point(Point2::<Window>::new(x, y)) else {
+            return;
+        };
+        let Some(fraction) = Self::fraction_at(
+            self.orientation.get(),
+            (local.x, local.y),
+            ctx.bounds_self,
+            self.thumb_size.get().to_float(),
+        ) else {
+            return;
+        };
+        let min = self.min.get().to_float();
+        let max = self.max.get().to_float();
+        self.change_value(ctx, min + fraction * (max - min));
+    }
+
+    /// Where the point `(x, y)`, local to a slider of size `bounds` with a thumb of diameter `thumb`,
+    /// falls along its track, from 0.0 to 1.0.  `None` if the slider is too small to have a track.
+    pub fn fraction_at(
+        orientation: &SliderOrientation,
+        (x, y): (f64, f64),
+        (width, height): (f64, f64),
+        thumb: f64,
+    ) -> Option<f64> {
+        let fraction = match orientation {
+            SliderOrientation::Horizontal => (x - thumb / 2.0) / (width - thumb),
+            SliderOrientation::Vertical => (height - thumb / 2.0 - y) / (height - thumb),
+        };
+        fraction.is_finite().then(|| fraction.clamp(0.0, 1.0))
+    }
+
+    /// Snaps `value` to `min` plus a multiple of `step` (unless `step` is `0`), then clamps it to `min..=max`
+    pub fn snap(value: f64, min: f64, max: f64, step: f64) -> f64 {
+        let mut value = value;
+        if step > 0.0 {
+            value = min + ((value - min) / step).round() * step;
+        }
+        value.clamp(min, max.max(min))
+    }
+
+    /// Snaps and clamps `value`, stores it, and fires `@slider_change` if it differs from the current value
+    fn change_value(&mut self, ctx: &NodeContext, value: f64) {
+        let value = Self::snap(
+            value,
+            self.min.get().to_float(),
+            self.max.get().to_float(),
+            self.step.get().to_float(),
+        );
+        if value == self.value.get().to_float() {
+            return;
+        }
+        self.value.set(value.into());
+        ctx.dispatch_slider_change(SliderChange { value });
+    }
+}
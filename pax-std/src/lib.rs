@@ -3,29 +3,97 @@ pub mod types;
 #[allow(unused_imports)]
 pub mod stacker;
 
+#[allow(unused_imports)]
+pub mod virtual_list;
+
+pub mod forms;
+
 pub mod components {
+    pub use super::forms::*;
     pub use super::stacker::*;
+    pub use super::virtual_list::*;
 }
 
 pub mod primitives {
     use pax_engine::pax;
-    use pax_runtime::api::{Color, Property, Size, StringBox};
+    use pax_runtime::api::{Color, Property, PropertyLiteral, Size, StringBox};
     use pax_runtime::numeric::Numeric;
 
     use crate::types::text::TextStyle;
-    use crate::types::Fill;
+    use crate::types::{
+        BlendMode, ClipShape, Fill, FlexAlign, FlexDirection, FlexJustify, GridTrack, ImageFit,
+        Shadow,
+    };
 
     use crate::types::PathElement;
     use crate::types::Stroke;
 
+    /// Clips its children to `clip_shape` within its bounds, casting `shadow` behind them
+    /// and blurring them by `blur`
     #[pax]
     #[primitive("pax_std_primitives::frame::FrameInstance")]
-    pub struct Frame {}
+    pub struct Frame {
+        pub shadow: Property<Shadow>,
+        pub clip_shape: Property<ClipShape>,
+        /// Radius, in px, by which everything the frame contains is blurred
+        pub blur: Property<Numeric>,
+    }
 
     #[pax]
     #[primitive("pax_std_primitives::group::GroupInstance")]
     pub struct Group {}
 
+    /// Lays out its children in rows or columns, sizing each from its `width` and `height`
+    /// and its `flex_grow`, `flex_shrink`, `min_*` and `max_*` properties.
+    #[pax]
+    #[primitive("pax_std_primitives::flex::FlexInstance")]
+    #[custom(Default)]
+    pub struct Flex {
+        pub direction: Property<FlexDirection>,
+        /// Whether children that overflow the main axis start a new row or column, rather than shrinking
+        pub wrap: Property<bool>,
+        pub justify: Property<FlexJustify>,
+        pub align: Property<FlexAlign>,
+        /// Space between adjacent children, and between adjacent rows or columns
+        pub gap: Property<Size>,
+    }
+
+    impl Default for Flex {
+        fn default() -> Self {
+            Self {
+                direction: Default::default(),
+                wrap: Default::default(),
+                justify: Default::default(),
+                align: Default::default(),
+                gap: Box::new(PropertyLiteral::new(Size::Pixels(0.into()))),
+            }
+        }
+    }
+
+    /// Places its children in the cells of a grid, in order row by row, or at the cells given by
+    /// their `grid_row` / `grid_column` (and `grid_row_span` / `grid_column_span`) properties.
+    /// Rows and columns beyond those listed share the leftover space equally.
+    #[pax]
+    #[primitive("pax_std_primitives::grid::GridInstance")]
+    #[custom(Default)]
+    pub struct Grid {
+        pub columns: Property<Vec<GridTrack>>,
+        pub rows: Property<Vec<GridTrack>>,
+        pub column_gap: Property<Size>,
+        pub row_gap: Property<Size>,
+    }
+
+    impl Default for Grid {
+        fn default() -> Self {
+            Self {
+                columns: Default::default(),
+                rows: Default::default(),
+                column_gap: Box::new(PropertyLiteral::new(Size::Pixels(0.into()))),
+                row_gap: Box::new(PropertyLiteral::new(Size::Pixels(0.into()))),
+            }
+        }
+    }
+
     #[pax]
     #[primitive("pax_std_primitives::scroller::ScrollerInstance")]
     pub struct Scroller {
@@ -42,6 +110,10 @@ pub mod primitives {
         pub stroke: Property<Stroke>,
         pub fill: Property<Fill>,
         pub corner_radii: Property<crate::types::RectangleCornerRadii>,
+        pub shadow: Property<Shadow>,
+        /// Radius, in px, by which the fill is blurred
+        pub blur: Property<Numeric>,
+        pub blend_mode: Property<BlendMode>,
     }
 
     #[pax]
@@ -50,15 +122,26 @@ pub mod primitives {
     pub struct Ellipse {
         pub stroke: Property<Stroke>,
         pub fill: Property<Fill>,
+        pub shadow: Property<Shadow>,
+        /// Radius, in px, by which the fill is blurred
+        pub blur: Property<Numeric>,
+        pub blend_mode: Property<BlendMode>,
     }
 
+    /// A vector outline, drawn from `elements` followed by the SVG path data `d`
+    /// (like `d="M 2 2 h 20 v 20 z"`), both in the coordinate space of this node's bounds.
     #[pax]
     #[primitive("pax_std_primitives::path::PathInstance")]
     #[cfg_attr(debug_assertions, derive(Debug))]
     pub struct Path {
         pub elements: Property<Vec<PathElement>>,
+        pub d: Property<StringBox>,
         pub stroke: Property<Stroke>,
-        pub fill: Property<Color>,
+        pub fill: Property<Fill>,
+        pub shadow: Property<Shadow>,
+        /// Radius, in px, by which the fill is blurred
+        pub blur: Property<Numeric>,
+        pub blend_mode: Property<BlendMode>,
     }
 
     #[pax]
@@ -95,10 +178,38 @@ pub mod primitives {
         pub style: Property<TextStyle>,
     }
 
+    /// A picture loaded by the chassis from `path`: an asset path, a URL (including `data:`
+    /// URLs), or a name under which the app supplied pixels with `NodeContext::load_image`.
+    /// Fires `@image_load` once the picture is ready to draw, or `@image_error` if it can't be loaded.
     #[pax]
     #[primitive("pax_std_primitives::image::ImageInstance")]
+    #[custom(Default)]
     pub struct Image {
         pub path: Property<StringBox>,
+        pub object_fit: Property<ImageFit>,
+        /// Region of the picture to show, in its pixels or as a percentage of its size
+        pub source_x: Property<Size>,
+        pub source_y: Property<Size>,
+        pub source_width: Property<Size>,
+        pub source_height: Property<Size>,
+        /// Fills the bounds until the picture has loaded, or if it fails to
+        pub placeholder: Property<Color>,
+        pub blend_mode: Property<BlendMode>,
+    }
+
+    impl Default for Image {
+        fn default() -> Self {
+            Self {
+                path: Default::default(),
+                object_fit: Default::default(),
+                source_x: Box::new(PropertyLiteral::new(Size::Pixels(0.into()))),
+                source_y: Box::new(PropertyLiteral::new(Size::Pixels(0.into()))),
+                source_width: Box::new(PropertyLiteral::new(Size::Percent(100.into()))),
+                source_height: Box::new(PropertyLiteral::new(Size::Percent(100.into()))),
+                placeholder: Box::new(PropertyLiteral::new(Color::TRANSPARENT)),
+                blend_mode: Default::default(),
+            }
+        }
     }
 
     #[pax]
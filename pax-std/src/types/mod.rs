@@ -2,13 +2,17 @@ pub mod text;
 
 use crate::primitives::Path;
 pub use kurbo::RoundedRectRadii;
+use kurbo::{Arc as KurboArc, BezPath, PathEl, Shape, SvgArc};
 use pax_engine::api::PropertyLiteral;
-pub use pax_engine::api::Size;
-use pax_engine::api::{Color, Numeric};
+pub use pax_engine::api::{BlendMode, Size};
+use pax_engine::api::{Color, Numeric, ThemeValue};
 use pax_engine::*;
 
 use pax_runtime::api::IntoableLiteral;
-use piet::UnitPoint;
+use piet::{
+    FixedGradient, FixedLinearGradient, FixedRadialGradient, LineCap, LineJoin, PaintBrush,
+    StrokeStyle, UnitPoint,
+};
 
 #[pax]
 #[custom(Default)]
@@ -16,6 +20,14 @@ use piet::UnitPoint;
 pub struct Stroke {
     pub color: Property<Color>,
     pub width: Property<Size>,
+    /// How adjacent segments are joined at corners
+    pub join: Property<StrokeJoin>,
+    /// How the ends of open paths are drawn
+    pub cap: Property<StrokeCap>,
+    /// Alternating lengths, in pixels, of dashes and gaps; empty for a solid stroke
+    pub dash: Property<Vec<Numeric>>,
+    /// Distance, in pixels, into `dash` at which the stroke starts
+    pub dash_offset: Property<Numeric>,
 }
 
 impl From<IntoableLiteral> for Stroke {
@@ -24,6 +36,7 @@ impl From<IntoableLiteral> for Stroke {
             IntoableLiteral::Color(c) => Stroke {
                 color: Box::new(PropertyLiteral::new(c)),
                 width: Box::new(PropertyLiteral::new(Numeric::from(1).into())),
+                ..Default::default()
             },
             _ => {
                 unreachable!()
@@ -37,10 +50,56 @@ impl Default for Stroke {
         Self {
             color: Default::default(),
             width: Box::new(PropertyLiteral::new(Size::Pixels(0.0.into()))),
+            join: Default::default(),
+            cap: Default::default(),
+            dash: Default::default(),
+            dash_offset: Default::default(),
         }
     }
 }
 
+impl Stroke {
+    pub fn to_piet_stroke_style(&self) -> StrokeStyle {
+        let mut style = StrokeStyle::new()
+            .line_join(match self.join.get() {
+                StrokeJoin::Miter => LineJoin::Miter {
+                    limit: LineJoin::DEFAULT_MITER_LIMIT,
+                },
+                StrokeJoin::Round => LineJoin::Round,
+                StrokeJoin::Bevel => LineJoin::Bevel,
+            })
+            .line_cap(match self.cap.get() {
+                StrokeCap::Butt => LineCap::Butt,
+                StrokeCap::Round => LineCap::Round,
+                StrokeCap::Square => LineCap::Square,
+            })
+            .dash_offset(self.dash_offset.get().to_float());
+        let dash: Vec<f64> = self.dash.get().iter().map(|n| n.to_float()).collect();
+        if !dash.is_empty() {
+            style.set_dash_pattern(dash);
+        }
+        style
+    }
+}
+
+#[pax]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum StrokeJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[pax]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum StrokeCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[pax]
 pub struct StackerCell {
@@ -50,6 +109,28 @@ pub struct StackerCell {
     pub height_px: f64,
 }
 
+/// Placement of one expanded row of a `VirtualList`, within its scrollable content
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[pax]
+pub struct VirtualListRow {
+    pub y_px: f64,
+    pub height_px: f64,
+}
+
+/// The item offsets of a `VirtualList`, and the inputs its window of rows was last computed from,
+/// so that the window is only recomputed when they change
+#[pax]
+pub struct VirtualListWindow {
+    pub item_count: usize,
+    pub item_height: f64,
+    pub item_heights: Vec<Numeric>,
+    pub scroll_y: f64,
+    pub viewport_height: f64,
+    pub overscan: usize,
+    /// As from `VirtualList::item_offsets`; empty until first computed
+    pub offsets: Vec<f64>,
+}
+
 #[pax]
 pub enum StackerDirection {
     Vertical,
@@ -57,6 +138,169 @@ pub enum StackerDirection {
     Horizontal,
 }
 
+/// The shape a `Frame` clips its children to, laid out in the frame's bounds
+#[pax]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub enum ClipShape {
+    #[default]
+    Rectangle,
+    /// The ellipse filling the bounds, e.g. for round avatars
+    Ellipse,
+    /// An outline built like `Path::elements`
+    Path(Vec<PathElement>),
+}
+
+impl ClipShape {
+    pub fn rectangle() -> Self {
+        Self::Rectangle
+    }
+    pub fn ellipse() -> Self {
+        Self::Ellipse
+    }
+    pub fn path(elements: Vec<PathElement>) -> Self {
+        Self::Path(elements)
+    }
+
+    /// The outline of this shape within `bounds`
+    pub fn to_bez_path(&self, bounds: (f64, f64)) -> Result<BezPath, String> {
+        let rect = kurbo::Rect::new(0.0, 0.0, bounds.0, bounds.1);
+        match self {
+            ClipShape::Rectangle => Ok(rect.to_path(0.1)),
+            ClipShape::Ellipse => Ok(kurbo::Ellipse::from_rect(rect).to_path(0.1)),
+            ClipShape::Path(elements) => Path::build_bez_path(elements, "", bounds),
+        }
+    }
+}
+
+/// How an `Image` sizes its picture within its bounds, like CSS `object-fit`
+#[pax]
+pub enum ImageFit {
+    /// Stretched to exactly fill the bounds, regardless of aspect ratio
+    #[default]
+    Fill,
+    /// Scaled, keeping its aspect ratio, to the largest size that fits within the bounds; centered
+    Contain,
+    /// Scaled, keeping its aspect ratio, to the smallest size that covers the bounds; centered and cropped
+    Cover,
+    /// Drawn at its own size, centered and cropped
+    None,
+    /// Repeated at its own size from the top-left corner, and cropped
+    Tile,
+}
+
+impl ImageFit {
+    /// Where to draw the `source` region of a picture, in image pixels, within `bounds`: pairs of
+    /// a region of the picture and the rect, in the `Image`'s own space, to draw it into.  Regions
+    /// falling outside `bounds` are cropped away, so that every rect lies within them.
+    pub fn draw_rects(
+        &self,
+        source: kurbo::Rect,
+        bounds: (f64, f64),
+    ) -> Vec<(kurbo::Rect, kurbo::Rect)> {
+        let (width, height) = (source.width(), source.height());
+        if width <= 0.0 || height <= 0.0 || bounds.0 <= 0.0 || bounds.1 <= 0.0 {
+            return vec![];
+        }
+        let centered = |scale: f64| {
+            let size = (width * scale, height * scale);
+            kurbo::Rect::from_origin_size(
+                ((bounds.0 - size.0) / 2.0, (bounds.1 - size.1) / 2.0),
+                size,
+            )
+        };
+        let scale_x = bounds.0 / width;
+        let scale_y = bounds.1 / height;
+        let destinations = match self {
+            ImageFit::Fill => vec![kurbo::Rect::new(0.0, 0.0, bounds.0, bounds.1)],
+            ImageFit::Contain => vec![centered(scale_x.min(scale_y))],
+            ImageFit::Cover => vec![centered(scale_x.max(scale_y))],
+            ImageFit::None => vec![centered(1.0)],
+            ImageFit::Tile => {
+                let columns = scale_x.ceil() as usize;
+                let rows = scale_y.ceil() as usize;
+                (0..rows)
+                    .flat_map(|row| {
+                        (0..columns).map(move |column| {
+                            kurbo::Rect::from_origin_size(
+                                (column as f64 * width, row as f64 * height),
+                                (width, height),
+                            )
+                        })
+                    })
+                    .collect()
+            }
+        };
+
+        let visible = kurbo::Rect::new(0.0, 0.0, bounds.0, bounds.1);
+        destinations
+            .into_iter()
+            .filter_map(|dst| {
+                let clipped = dst.intersect(visible);
+                if clipped.width() <= 0.0 || clipped.height() <= 0.0 {
+                    return None;
+                }
+                // The part of `source` that lands within `clipped`
+                let scale = (width / dst.width(), height / dst.height());
+                let src = kurbo::Rect::new(
+                    source.x0 + (clipped.x0 - dst.x0) * scale.0,
+                    source.y0 + (clipped.y0 - dst.y0) * scale.1,
+                    source.x0 + (clipped.x1 - dst.x0) * scale.0,
+                    source.y0 + (clipped.y1 - dst.y0) * scale.1,
+                );
+                Some((src, clipped))
+            })
+            .collect()
+    }
+}
+
+/// Which way a `Slider`'s track runs; vertical sliders increase upward
+#[pax]
+pub enum SliderOrientation {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+#[pax]
+pub enum FlexDirection {
+    #[default]
+    Row,
+    Column,
+}
+
+/// How a `Flex` distributes leftover space along each row (or column) of children
+#[pax]
+pub enum FlexJustify {
+    #[default]
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+/// How a `Flex` places children across each row (or column)
+#[pax]
+pub enum FlexAlign {
+    Start,
+    End,
+    Center,
+    #[default]
+    Stretch,
+}
+
+/// The size of a row or column of a `Grid`
+#[pax]
+pub enum GridTrack {
+    Fixed(Size),
+    /// A share of the space left over by the other tracks
+    Fraction(Numeric),
+    /// As large as the largest child placed in this track alone
+    #[default]
+    Auto,
+}
+
 #[pax]
 pub enum SidebarDirection {
     Left,
@@ -64,6 +308,39 @@ pub enum SidebarDirection {
     Right,
 }
 
+/// A shadow cast by a shape onto whatever is behind it, offset by `x` and `y` px and softened by `blur` px
+#[pax]
+#[custom(Default)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+pub struct Shadow {
+    pub x: Numeric,
+    pub y: Numeric,
+    pub blur: Numeric,
+    pub color: Color,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self {
+            x: Default::default(),
+            y: Default::default(),
+            blur: Default::default(),
+            color: Color::TRANSPARENT,
+        }
+    }
+}
+
+impl Shadow {
+    pub fn new(x: Numeric, y: Numeric, blur: Numeric, color: Color) -> Self {
+        Self { x, y, blur, color }
+    }
+
+    /// Whether drawing this shadow would show anything
+    pub fn is_visible(&self) -> bool {
+        self.color.to_piet_color().as_rgba().3 > 0.0
+    }
+}
+
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[pax]
 #[custom(Default)]
@@ -163,6 +440,35 @@ impl Fill {
         ret
     }
 
+    /// The brush painting this fill over a shape laid out in `bounds`, whose outline once
+    /// transformed has the bounding box `bbox`.  Gradients are positioned within `bbox` as piet
+    /// positions its unit gradients, but in absolute coordinates, so that the renderer can fade them.
+    pub fn to_paint_brush(&self, bounds: (f64, f64), bbox: kurbo::Rect) -> PaintBrush {
+        match self {
+            Fill::Solid(color) => color.to_piet_color().into(),
+            Fill::LinearGradient(linear) => FixedGradient::Linear(FixedLinearGradient {
+                start: Fill::to_unit_point(linear.start, bounds).resolve(bbox),
+                end: Fill::to_unit_point(linear.end, bounds).resolve(bbox),
+                stops: Fill::to_piet_gradient_stops(linear.stops.clone()),
+            })
+            .into(),
+            Fill::RadialGradient(radial) => {
+                // Unit radial gradients are laid out in the square around `bbox` (piet's `ScaleMode::Fill`)
+                let scale_len = bbox.width().max(bbox.height());
+                let square = kurbo::Rect::from_center_size(bbox.center(), (scale_len, scale_len));
+                let center = Fill::to_unit_point(radial.end, bounds).resolve(square);
+                let origin = Fill::to_unit_point(radial.start, bounds).resolve(square);
+                FixedGradient::Radial(FixedRadialGradient {
+                    center,
+                    origin_offset: origin - center,
+                    radius: radial.radius * scale_len,
+                    stops: Fill::to_piet_gradient_stops(radial.stops.clone()),
+                })
+                .into()
+            }
+        }
+    }
+
     #[allow(non_snake_case)]
     pub fn linearGradient(
         start: (Size, Size),
@@ -175,12 +481,22 @@ impl Fill {
 
 #[pax]
 #[cfg_attr(debug_assertions, derive(Debug))]
+/// An element of a `Path`.  A path starts at a `Point`; each `Line`, `Curve`, `Cubic` and `Arc`
+/// is followed by the `Point` it ends at.  When that is a `RelativePoint` instead, the segment's
+/// end point and handles are all offset from the current point, like SVG's lowercase commands.
 pub enum PathElement {
     #[default]
     Empty,
     Point(Size, Size),
+    RelativePoint(Size, Size),
     Line,
+    /// A quadratic Bézier curve through the given handle
     Curve(Size, Size),
+    /// A cubic Bézier curve through the two given handles
+    Cubic(Size, Size, Size, Size),
+    /// An elliptical arc with radii `rx` and `ry`, its x-axis rotated by the given degrees,
+    /// followed by the `large_arc` and `sweep` flags as in SVG
+    Arc(Size, Size, Numeric, bool, bool),
     Close,
 }
 
@@ -194,9 +510,18 @@ impl PathElement {
     pub fn point(x: Size, y: Size) -> Self {
         Self::Point(x, y)
     }
+    pub fn relative_point(x: Size, y: Size) -> Self {
+        Self::RelativePoint(x, y)
+    }
     pub fn curve(x: Size, y: Size) -> Self {
         Self::Curve(x, y)
     }
+    pub fn cubic(h1_x: Size, h1_y: Size, h2_x: Size, h2_y: Size) -> Self {
+        Self::Cubic(h1_x, h1_y, h2_x, h2_y)
+    }
+    pub fn arc(rx: Size, ry: Size, rotation: Numeric, large_arc: bool, sweep: bool) -> Self {
+        Self::Arc(rx, ry, rotation, large_arc, sweep)
+    }
 }
 
 #[pax]
@@ -263,6 +588,149 @@ impl Path {
         path.push(PathElement::Point(x, y));
         path
     }
+
+    pub fn cubic_to(
+        mut path: Vec<PathElement>,
+        (h1_x, h1_y): (Size, Size),
+        (h2_x, h2_y): (Size, Size),
+        x: Size,
+        y: Size,
+    ) -> Vec<PathElement> {
+        path.push(PathElement::Cubic(h1_x, h1_y, h2_x, h2_y));
+        path.push(PathElement::Point(x, y));
+        path
+    }
+
+    pub fn arc_to(
+        mut path: Vec<PathElement>,
+        (rx, ry): (Size, Size),
+        rotation: Numeric,
+        (large_arc, sweep): (bool, bool),
+        x: Size,
+        y: Size,
+    ) -> Vec<PathElement> {
+        path.push(PathElement::Arc(rx, ry, rotation, large_arc, sweep));
+        path.push(PathElement::Point(x, y));
+        path
+    }
+
+    pub fn close(mut path: Vec<PathElement>) -> Vec<PathElement> {
+        path.push(PathElement::Close);
+        path
+    }
+
+    /// Builds the outline described by `elements` within `bounds`, against which percentages
+    /// resolve, followed by that of the SVG path data `d`
+    pub fn build_bez_path(
+        elements: &[PathElement],
+        d: &str,
+        bounds: (f64, f64),
+    ) -> Result<BezPath, String> {
+        let mut bez_path = BezPath::new();
+        let point = |x: Size, y: Size| Point { x, y }.to_kurbo_point(bounds);
+        let mut current = kurbo::Point::ZERO;
+        let mut subpath_open = false;
+        let mut itr_elems = elements.iter();
+        while let Some(elem) = itr_elems.next() {
+            let is_segment = matches!(
+                elem,
+                PathElement::Line
+                    | PathElement::Curve(..)
+                    | PathElement::Cubic(..)
+                    | PathElement::Arc(..)
+            );
+            if is_segment && !subpath_open {
+                if bez_path.elements().is_empty() {
+                    return Err("path must start with point".to_string());
+                }
+                // As in SVG, a segment after a close starts a subpath where the closed one started
+                bez_path.move_to(current);
+                subpath_open = true;
+            }
+            // The point a segment ends at, and the origin of the segment's handles
+            let mut segment_end = |name: &str| match itr_elems.next() {
+                Some(&PathElement::Point(x, y)) => Ok((point(x, y), kurbo::Point::ZERO)),
+                Some(&PathElement::RelativePoint(x, y)) => {
+                    Ok((current + point(x, y).to_vec2(), current))
+                }
+                _ => Err(format!("{} expects to be followed by a point", name)),
+            };
+            match *elem {
+                PathElement::Point(x, y) => {
+                    current = point(x, y);
+                    bez_path.move_to(current);
+                    subpath_open = true;
+                }
+                PathElement::RelativePoint(x, y) => {
+                    current += point(x, y).to_vec2();
+                    bez_path.move_to(current);
+                    subpath_open = true;
+                }
+                PathElement::Line => {
+                    let (end, _) = segment_end("line")?;
+                    bez_path.line_to(end);
+                    current = end;
+                }
+                PathElement::Curve(h_x, h_y) => {
+                    let (end, origin) = segment_end("curve")?;
+                    bez_path.quad_to(origin + point(h_x, h_y).to_vec2(), end);
+                    current = end;
+                }
+                PathElement::Cubic(h1_x, h1_y, h2_x, h2_y) => {
+                    let (end, origin) = segment_end("cubic")?;
+                    bez_path.curve_to(
+                        origin + point(h1_x, h1_y).to_vec2(),
+                        origin + point(h2_x, h2_y).to_vec2(),
+                        end,
+                    );
+                    current = end;
+                }
+                PathElement::Arc(rx, ry, rotation, large_arc, sweep) => {
+                    let (end, _) = segment_end("arc")?;
+                    let svg_arc = SvgArc {
+                        from: current,
+                        to: end,
+                        radii: kurbo::Vec2::new(
+                            rx.evaluate(bounds, api::Axis::X),
+                            ry.evaluate(bounds, api::Axis::Y),
+                        ),
+                        x_rotation: rotation.to_float().to_radians(),
+                        large_arc,
+                        sweep,
+                    };
+                    match KurboArc::from_svg_arc(&svg_arc) {
+                        Some(arc) => arc.to_cubic_beziers(0.1, |h1, h2, p| {
+                            bez_path.curve_to(h1, h2, p);
+                        }),
+                        // A degenerate arc, like one with a zero radius, is a straight line
+                        None => bez_path.line_to(end),
+                    }
+                    current = end;
+                }
+                // There's nothing to close before the first point, or right after another close
+                PathElement::Close if !subpath_open => (),
+                PathElement::Close => {
+                    bez_path.close_path();
+                    subpath_open = false;
+                    // Subsequent relative points are offset from the start of the closed subpath
+                    if let Some(PathEl::MoveTo(start)) = bez_path
+                        .elements()
+                        .iter()
+                        .rev()
+                        .find(|el| matches!(el, PathEl::MoveTo(_)))
+                    {
+                        current = *start;
+                    }
+                }
+                PathElement::Empty => (), //no-op
+            }
+        }
+        if !d.trim().is_empty() {
+            let svg_path = BezPath::from_svg(d).map_err(|e| format!("invalid path data: {}", e))?;
+            bez_path.extend(svg_path);
+        }
+        Ok(bez_path)
+    }
 }
 
 #[pax]
@@ -300,3 +768,11 @@ impl RectangleCornerRadii {
         }
     }
 }
+
+/// A theme's radius token rounds all four corners alike, e.g. `corner_radii={theme.rounded}`
+impl From<ThemeValue> for RectangleCornerRadii {
+    fn from(value: ThemeValue) -> Self {
+        let radius = Numeric::from(value);
+        Self::radii(radius, radius, radius, radius)
+    }
+}
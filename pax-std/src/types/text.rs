@@ -1,4 +1,4 @@
-use pax_engine::api::{Color, Numeric, Property, PropertyLiteral, Size, StringBox};
+use pax_engine::api::{Color, Numeric, Property, PropertyLiteral, Size, StringBox, ThemeValue};
 use pax_engine::*;
 use pax_message::{
     ColorMessage, FontPatch, FontStyleMessage, FontWeightMessage, LocalFontMessage,
@@ -371,6 +371,17 @@ impl PartialEq<FontPatch> for Font {
     }
 }
 
+/// A theme's font token names a system font family, e.g. `font: {theme.heading}`
+impl From<ThemeValue> for Font {
+    fn from(value: ThemeValue) -> Self {
+        Self::system(
+            StringBox::from(value),
+            FontStyle::default(),
+            FontWeight::default(),
+        )
+    }
+}
+
 impl Font {
     pub fn system(family: StringBox, style: FontStyle, weight: FontWeight) -> Self {
         Self::System(SystemFont {
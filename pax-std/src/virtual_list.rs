@@ -0,0 +1,167 @@
+use crate::primitives::*;
+use crate::types::{VirtualListRow, VirtualListWindow};
+use pax_engine::api::{Event, Numeric, Property};
+use pax_engine::*;
+use pax_runtime::api::{NodeContext, PropertyLiteral, Scroll, VirtualRangeChange};
+
+/// A vertically scrolling list that only expands the rows in view, for collections too large to
+/// expand in full.  Of `item_count` items, `VirtualList` fires `@virtual_range_change` with the
+/// range `start..end` that is visible (plus `overscan` items either side), and positions its slot
+/// children, in order, as the rows of those items.  Bind a `for` over just that window as its
+/// children, e.g.:
+///
+/// `<VirtualList item_count=10000 item_height=24 @virtual_range_change=self.set_window>`
+/// `    for row in self.window { <Text text={row.label} /> }`
+/// `</VirtualList>`
+///
+/// While scrolling, the rows already expanded for the window are recycled for the items that
+/// scroll into it, rather than re-created.  Rows are `item_height` tall, unless given a height in `item_heights`.
+#[pax]
+#[custom(Default)]
+#[inlined(
+    <Scroller
+        width=100%
+        height=100%
+        size_inner_pane_x=100%
+        size_inner_pane_y={(self._content_height)px}
+        scroll_enabled_x=false
+        scroll_enabled_y=true
+        @scroll=self.handle_scroll
+    >
+        for (row, i) in self._rows {
+            <Frame y={(row.y_px)px} width=100% height={(row.height_px)px}>
+                slot(i)
+            </Frame>
+        }
+    </Scroller>
+
+    @settings {
+        @tick: self.handle_tick
+    }
+
+)]
+pub struct VirtualList {
+    pub item_count: Property<Numeric>,
+    /// Height in px of each item not given one by `item_heights`
+    pub item_height: Property<Numeric>,
+    /// Height in px of each item by index, for lists whose rows differ in height
+    pub item_heights: Property<Vec<Numeric>>,
+    /// Number of items expanded beyond each edge of the visible area
+    pub overscan: Property<Numeric>,
+
+    pub _content_height: Property<Numeric>,
+    pub _start: Property<Numeric>,
+    pub _end: Property<Numeric>,
+    pub _rows: Property<Vec<VirtualListRow>>,
+    pub _window: Property<VirtualListWindow>,
+}
+
+impl Default for VirtualList {
+    fn default() -> Self {
+        Self {
+            item_count: Box::new(PropertyLiteral::new(0.into())),
+            item_height: Box::new(PropertyLiteral::new(20.into())),
+            item_heights: Box::new(PropertyLiteral::new(vec![])),
+            overscan: Box::new(PropertyLiteral::new(3.into())),
+            _content_height: Default::default(),
+            _start: Default::default(),
+            _end: Default::default(),
+            _rows: Box::new(PropertyLiteral::new(vec![])),
+            _window: Default::default(),
+        }
+    }
+}
+
+impl VirtualList {
+    pub fn handle_tick(&mut self, ctx: &NodeContext) {
+        self.update_window(ctx);
+    }
+
+    pub fn handle_scroll(&mut self, ctx: &NodeContext, _args: Event<Scroll>) {
+        self.update_window(ctx);
+    }
+
+    /// Recomputes the rows in view, if the items, scroll position or viewport changed since last time
+    fn update_window(&mut self, ctx: &NodeContext) {
+        let item_count = self.item_count.get().to_float().max(0.0) as usize;
+        let item_height = self.item_height.get().to_float();
+        let scroll_y = ctx.scroll_offset().map(|(_, y)| y).unwrap_or_default();
+        let viewport_height = ctx.bounds_self.1;
+        let overscan = self.overscan.get().to_float().max(0.0) as usize;
+
+        let window = self._window.get_mut();
+        if window.offsets.is_empty()
+            || window.item_count != item_count
+            || window.item_height != item_height
+            || window.item_heights != *self.item_heights.get()
+        {
+            window.item_count = item_count;
+            window.item_height = item_height;
+            window.item_heights = self.item_heights.get().clone();
+            window.offsets = Self::item_offsets(item_count, item_height, &window.item_heights);
+        } else if window.scroll_y == scroll_y
+            && window.viewport_height == viewport_height
+            && window.overscan == overscan
+        {
+            return;
+        }
+        window.scroll_y = scroll_y;
+        window.viewport_height = viewport_height;
+        window.overscan = overscan;
+
+        let offsets = &window.offsets;
+        let (start, end) = Self::visible_range(offsets, scroll_y, viewport_height, overscan);
+        let content_height = offsets.last().copied().unwrap_or_default();
+        let rows = (start..end)
+            .map(|i| VirtualListRow {
+                y_px: offsets[i],
+                height_px: offsets[i + 1] - offsets[i],
+            })
+            .collect();
+
+        self._content_height.set(content_height.into());
+        self._rows.set(rows);
+        if start != self._start.get().to_float() as usize
+            || end != self._end.get().to_float() as usize
+        {
+            self._start.set(start.into());
+            self._end.set(end.into());
+            ctx.dispatch_virtual_range_change(VirtualRangeChange { start, end });
+        }
+    }
+
+    /// The top edge of each of `item_count` items followed by the bottom edge of the last, in px
+    pub fn item_offsets(item_count: usize, item_height: f64, item_heights: &[Numeric]) -> Vec<f64> {
+        let mut offsets = Vec::with_capacity(item_count + 1);
+        let mut y = 0.0;
+        offsets.push(y);
+        for i in 0..item_count {
+            y += item_heights
+                .get(i)
+                .map(|height| height.to_float())
+                .unwrap_or(item_height)
+                .max(0.0);
+            offsets.push(y);
+        }
+        offsets
+    }
+
+    /// The range of items that intersect `viewport_height` px of content starting `scroll_y` px
+    /// down, widened by `overscan` items either side; `offsets` as from [`Self::item_offsets`]
+    pub fn visible_range(
+        offsets: &[f64],
+        scroll_y: f64,
+        viewport_height: f64,
+        overscan: usize,
+    ) -> (usize, usize) {
+        let item_count = offsets.len().saturating_sub(1);
+        // First item whose bottom edge is below the top of the viewport
+        let first = offsets[1..].partition_point(|bottom| *bottom <= scroll_y);
+        // First item whose top edge is at or below the bottom of the viewport
+        let last = offsets[..item_count].partition_point(|top| *top < scroll_y + viewport_height);
+        (
+            first.saturating_sub(overscan).min(item_count),
+            (last + overscan).min(item_count).max(first.min(item_count)),
+        )
+    }
+}
@@ -0,0 +1,58 @@
+use kurbo::Rect;
+use pax_std::types::ImageFit;
+
+const PICTURE: Rect = Rect::new(0.0, 0.0, 200.0, 100.0);
+
+#[test]
+fn test_contain_and_cover_keep_aspect_ratio() {
+    assert_eq!(
+        ImageFit::Contain.draw_rects(PICTURE, (100.0, 100.0)),
+        vec![(PICTURE, Rect::new(0.0, 25.0, 100.0, 75.0))]
+    );
+    // Cover scales to 200x200 px and crops the middle half of the picture's width
+    assert_eq!(
+        ImageFit::Cover.draw_rects(PICTURE, (200.0, 200.0)),
+        vec![(
+            Rect::new(50.0, 0.0, 150.0, 100.0),
+            Rect::new(0.0, 0.0, 200.0, 200.0)
+        )]
+    );
+    assert_eq!(
+        ImageFit::Fill.draw_rects(PICTURE, (50.0, 50.0)),
+        vec![(PICTURE, Rect::new(0.0, 0.0, 50.0, 50.0))]
+    );
+}
+
+#[test]
+fn test_none_and_tile_crop_to_bounds() {
+    assert_eq!(
+        ImageFit::None.draw_rects(PICTURE, (100.0, 100.0)),
+        vec![(
+            Rect::new(50.0, 0.0, 150.0, 100.0),
+            Rect::new(0.0, 0.0, 100.0, 100.0)
+        )]
+    );
+
+    let tiles = ImageFit::Tile.draw_rects(PICTURE, (300.0, 150.0));
+    assert_eq!(tiles.len(), 4);
+    assert_eq!(tiles[0], (PICTURE, PICTURE));
+    assert_eq!(
+        tiles[3],
+        (
+            Rect::new(0.0, 0.0, 100.0, 50.0),
+            Rect::new(200.0, 100.0, 300.0, 150.0)
+        )
+    );
+}
+
+#[test]
+fn test_source_rect_is_drawn_alone() {
+    let source = Rect::new(20.0, 10.0, 60.0, 30.0);
+    assert_eq!(
+        ImageFit::Contain.draw_rects(source, (80.0, 80.0)),
+        vec![(source, Rect::new(0.0, 20.0, 80.0, 60.0))]
+    );
+    assert!(ImageFit::Fill
+        .draw_rects(Rect::ZERO, (80.0, 80.0))
+        .is_empty());
+}
@@ -0,0 +1,121 @@
+use kurbo::{PathEl, Point, Shape};
+use pax_std::primitives::Path;
+use pax_std::types::{ClipShape, PathElement, Size};
+
+fn px(value: f64) -> Size {
+    Size::Pixels(value.into())
+}
+
+#[test]
+fn test_relative_elements_offset_from_current_point() {
+    let elements = vec![
+        PathElement::point(px(10.0), px(10.0)),
+        PathElement::line(),
+        PathElement::relative_point(px(20.0), px(0.0)),
+        PathElement::cubic(px(0.0), px(10.0), px(10.0), px(10.0)),
+        PathElement::relative_point(px(10.0), px(0.0)),
+        PathElement::close(),
+        PathElement::relative_point(Size::Percent(50.into()), px(0.0)),
+    ];
+    let bez_path = Path::build_bez_path(&elements, "", (100.0, 100.0)).unwrap();
+    assert_eq!(
+        bez_path.elements(),
+        &[
+            PathEl::MoveTo(Point::new(10.0, 10.0)),
+            PathEl::LineTo(Point::new(30.0, 10.0)),
+            PathEl::CurveTo(
+                Point::new(30.0, 20.0),
+                Point::new(40.0, 20.0),
+                Point::new(40.0, 10.0)
+            ),
+            PathEl::ClosePath,
+            PathEl::MoveTo(Point::new(60.0, 10.0)),
+        ]
+    );
+}
+
+#[test]
+fn test_svg_path_data() {
+    let bez_path =
+        Path::build_bez_path(&[], "M2 2 h20 a10 10 0 0 1 0 20 L2 22 z", (100.0, 100.0)).unwrap();
+    let els = bez_path.elements();
+    assert_eq!(els[0], PathEl::MoveTo(Point::new(2.0, 2.0)));
+    assert_eq!(els[1], PathEl::LineTo(Point::new(22.0, 2.0)));
+    assert!(matches!(els[2], PathEl::CurveTo(..)));
+    assert_eq!(els[els.len() - 1], PathEl::ClosePath);
+    assert!(Path::build_bez_path(&[], "M2 2 X", (100.0, 100.0)).is_err());
+}
+
+#[test]
+fn test_segment_requires_end_point() {
+    let elements = vec![
+        PathElement::point(px(0.0), px(0.0)),
+        PathElement::arc(px(5.0), px(5.0), 0.into(), false, true),
+        PathElement::close(),
+    ];
+    assert!(Path::build_bez_path(&elements, "", (100.0, 100.0)).is_err());
+}
+
+#[test]
+fn test_clip_shapes_fill_bounds() {
+    let bounds = (80.0, 40.0);
+    let ellipse = ClipShape::ellipse().to_bez_path(bounds).unwrap();
+    let bbox = ellipse.bounding_box();
+    assert!((bbox.width() - 80.0).abs() < 1e-6);
+    assert!((bbox.height() - 40.0).abs() < 1e-6);
+    // Corners of the bounds fall outside of an elliptical clip
+    assert_eq!(ellipse.winding(Point::new(1.0, 1.0)), 0);
+    assert_ne!(ellipse.winding(Point::new(40.0, 20.0)), 0);
+
+    let triangle = ClipShape::path(vec![
+        PathElement::point(px(0.0), px(0.0)),
+        PathElement::line(),
+        PathElement::point(Size::Percent(100.into()), px(0.0)),
+        PathElement::line(),
+        PathElement::point(px(0.0), Size::Percent(100.into())),
+        PathElement::close(),
+    ])
+    .to_bez_path(bounds)
+    .unwrap();
+    assert_eq!(
+        triangle.bounding_box(),
+        kurbo::Rect::new(0.0, 0.0, 80.0, 40.0)
+    );
+}
+
+#[test]
+fn test_segment_after_close_starts_where_closed_subpath_started() {
+    let elements = vec![
+        PathElement::point(px(10.0), px(10.0)),
+        PathElement::line(),
+        PathElement::point(px(30.0), px(10.0)),
+        PathElement::close(),
+        PathElement::close(),
+        PathElement::line(),
+        PathElement::point(px(10.0), px(30.0)),
+    ];
+    let bez_path = Path::build_bez_path(&elements, "", (100.0, 100.0)).unwrap();
+    assert_eq!(
+        bez_path.elements(),
+        &[
+            PathEl::MoveTo(Point::new(10.0, 10.0)),
+            PathEl::LineTo(Point::new(30.0, 10.0)),
+            PathEl::ClosePath,
+            PathEl::MoveTo(Point::new(10.0, 10.0)),
+            PathEl::LineTo(Point::new(10.0, 30.0)),
+        ]
+    );
+}
+
+#[test]
+fn test_path_must_start_with_point() {
+    let elements = vec![PathElement::line(), PathElement::point(px(10.0), px(10.0))];
+    assert!(Path::build_bez_path(&elements, "", (100.0, 100.0)).is_err());
+
+    let elements = vec![PathElement::close(), PathElement::point(px(10.0), px(10.0))];
+    let bez_path = Path::build_bez_path(&elements, "", (100.0, 100.0)).unwrap();
+    assert_eq!(
+        bez_path.elements(),
+        &[PathEl::MoveTo(Point::new(10.0, 10.0))]
+    );
+}
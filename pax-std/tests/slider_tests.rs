@@ -0,0 +1,70 @@
+use pax_std::forms::Slider;
+use pax_std::types::SliderOrientation;
+
+#[test]
+fn test_drag_maps_point_to_track() {
+    // A 216px slider with a 16px thumb has a 200px track starting 8px in
+    let horizontal = SliderOrientation::Horizontal;
+    assert_eq!(
+        Slider::fraction_at(&horizontal, (8.0, 5.0), (216.0, 20.0), 16.0),
+        Some(0.0)
+    );
+    assert_eq!(
+        Slider::fraction_at(&horizontal, (58.0, 5.0), (216.0, 20.0), 16.0),
+        Some(0.25)
+    );
+    // Dragging past either end pins the thumb there
+    assert_eq!(
+        Slider::fraction_at(&horizontal, (-40.0, 5.0), (216.0, 20.0), 16.0),
+        Some(0.0)
+    );
+    assert_eq!(
+        Slider::fraction_at(&horizontal, (400.0, 5.0), (216.0, 20.0), 16.0),
+        Some(1.0)
+    );
+
+    // Vertical sliders increase upward
+    let vertical = SliderOrientation::Vertical;
+    assert_eq!(
+        Slider::fraction_at(&vertical, (5.0, 58.0), (20.0, 216.0), 16.0),
+        Some(0.75)
+    );
+
+    // No track to drag along
+    assert_eq!(
+        Slider::fraction_at(&horizontal, (8.0, 5.0), (16.0, 20.0), 16.0),
+        None
+    );
+}
+
+#[test]
+fn test_step_snaps_and_clamps() {
+    assert_eq!(Slider::snap(42.4, 0.0, 100.0, 5.0), 40.0);
+    assert_eq!(Slider::snap(42.6, 0.0, 100.0, 5.0), 45.0);
+    // Steps count from `min`
+    assert_eq!(Slider::snap(14.0, 2.0, 100.0, 5.0), 12.0);
+    assert_eq!(Slider::snap(130.0, 0.0, 100.0, 5.0), 100.0);
+    assert_eq!(Slider::snap(-3.0, 0.0, 100.0, 5.0), 0.0);
+    // A step of 0 allows any value in range
+    assert_eq!(Slider::snap(42.42, 0.0, 100.0, 0.0), 42.42);
+    // An empty range holds `min`
+    assert_eq!(Slider::snap(5.0, 10.0, 0.0, 1.0), 10.0);
+}
+
+#[test]
+fn test_keys_move_value() {
+    let key = |key| Slider::value_for_key(key, 50.0, 0.0, 100.0, 2.0);
+    assert_eq!(key("ArrowRight"), Some(52.0));
+    assert_eq!(key("ArrowUp"), Some(52.0));
+    assert_eq!(key("ArrowLeft"), Some(48.0));
+    assert_eq!(key("PageUp"), Some(70.0));
+    assert_eq!(key("PageDown"), Some(30.0));
+    assert_eq!(key("Home"), Some(0.0));
+    assert_eq!(key("End"), Some(100.0));
+    assert_eq!(key("a"), None);
+    // Without a step, arrows move by 1% of the range
+    assert_eq!(
+        Slider::value_for_key("ArrowRight", 50.0, 0.0, 200.0, 0.0),
+        Some(52.0)
+    );
+}
@@ -0,0 +1,39 @@
+use pax_engine::api::Numeric;
+use pax_std::components::VirtualList;
+
+#[test]
+fn test_fixed_height_window_includes_overscan() {
+    let offsets = VirtualList::item_offsets(10_000, 20.0, &[]);
+    assert_eq!(offsets.len(), 10_001);
+    assert_eq!(offsets[10_000], 200_000.0);
+
+    assert_eq!(VirtualList::visible_range(&offsets, 0.0, 100.0, 3), (0, 8));
+    assert_eq!(
+        VirtualList::visible_range(&offsets, 1010.0, 100.0, 3),
+        (47, 59)
+    );
+    assert_eq!(
+        VirtualList::visible_range(&offsets, 199_900.0, 100.0, 3),
+        (9992, 10_000)
+    );
+}
+
+#[test]
+fn test_window_keeps_its_length_while_scrolling() {
+    let offsets = VirtualList::item_offsets(1000, 20.0, &[]);
+    let len = |scroll_y| {
+        let (start, end) = VirtualList::visible_range(&offsets, scroll_y, 100.0, 2);
+        end - start
+    };
+    assert_eq!(len(400.0), len(4000.0));
+    assert_eq!(len(410.0), len(4010.0));
+}
+
+#[test]
+fn test_item_heights_override_item_height() {
+    let heights: Vec<Numeric> = vec![50.into(), 10.into()];
+    let offsets = VirtualList::item_offsets(4, 20.0, &heights);
+    assert_eq!(offsets, vec![0.0, 50.0, 60.0, 80.0, 100.0]);
+    assert_eq!(VirtualList::visible_range(&offsets, 55.0, 10.0, 0), (1, 3));
+    assert_eq!(VirtualList::visible_range(&[0.0], 0.0, 100.0, 3), (0, 0));
+}